@@ -0,0 +1,344 @@
+//! Character inspection: details of the character under the cursor and
+//! detection of suspicious (invisible or unusual-space) characters.
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::editor_state::FileViewerState;
+
+/// Names for characters that are easy to miss or confuse with others.
+/// Not a full Unicode name table; other characters are reported without a name.
+const CHAR_NAMES: &[(char, &str)] = &[
+    ('\t', "CHARACTER TABULATION"),
+    (' ', "SPACE"),
+    ('\u{00A0}', "NO-BREAK SPACE"),
+    ('\u{00AD}', "SOFT HYPHEN"),
+    ('\u{034F}', "COMBINING GRAPHEME JOINER"),
+    ('\u{061C}', "ARABIC LETTER MARK"),
+    ('\u{115F}', "HANGUL CHOSEONG FILLER"),
+    ('\u{1160}', "HANGUL JUNGSEONG FILLER"),
+    ('\u{1680}', "OGHAM SPACE MARK"),
+    ('\u{180E}', "MONGOLIAN VOWEL SEPARATOR"),
+    ('\u{2000}', "EN QUAD"),
+    ('\u{2001}', "EM QUAD"),
+    ('\u{2002}', "EN SPACE"),
+    ('\u{2003}', "EM SPACE"),
+    ('\u{2004}', "THREE-PER-EM SPACE"),
+    ('\u{2005}', "FOUR-PER-EM SPACE"),
+    ('\u{2006}', "SIX-PER-EM SPACE"),
+    ('\u{2007}', "FIGURE SPACE"),
+    ('\u{2008}', "PUNCTUATION SPACE"),
+    ('\u{2009}', "THIN SPACE"),
+    ('\u{200A}', "HAIR SPACE"),
+    ('\u{200B}', "ZERO WIDTH SPACE"),
+    ('\u{200C}', "ZERO WIDTH NON-JOINER"),
+    ('\u{200D}', "ZERO WIDTH JOINER"),
+    ('\u{200E}', "LEFT-TO-RIGHT MARK"),
+    ('\u{200F}', "RIGHT-TO-LEFT MARK"),
+    ('\u{2010}', "HYPHEN"),
+    ('\u{2011}', "NON-BREAKING HYPHEN"),
+    ('\u{2012}', "FIGURE DASH"),
+    ('\u{2013}', "EN DASH"),
+    ('\u{2014}', "EM DASH"),
+    ('\u{2015}', "HORIZONTAL BAR"),
+    ('\u{2018}', "LEFT SINGLE QUOTATION MARK"),
+    ('\u{2019}', "RIGHT SINGLE QUOTATION MARK"),
+    ('\u{201C}', "LEFT DOUBLE QUOTATION MARK"),
+    ('\u{201D}', "RIGHT DOUBLE QUOTATION MARK"),
+    ('\u{2024}', "ONE DOT LEADER"),
+    ('\u{2026}', "HORIZONTAL ELLIPSIS"),
+    ('\u{2028}', "LINE SEPARATOR"),
+    ('\u{2029}', "PARAGRAPH SEPARATOR"),
+    ('\u{202A}', "LEFT-TO-RIGHT EMBEDDING"),
+    ('\u{202B}', "RIGHT-TO-LEFT EMBEDDING"),
+    ('\u{202C}', "POP DIRECTIONAL FORMATTING"),
+    ('\u{202D}', "LEFT-TO-RIGHT OVERRIDE"),
+    ('\u{202E}', "RIGHT-TO-LEFT OVERRIDE"),
+    ('\u{202F}', "NARROW NO-BREAK SPACE"),
+    ('\u{205F}', "MEDIUM MATHEMATICAL SPACE"),
+    ('\u{2060}', "WORD JOINER"),
+    ('\u{2066}', "LEFT-TO-RIGHT ISOLATE"),
+    ('\u{2067}', "RIGHT-TO-LEFT ISOLATE"),
+    ('\u{2068}', "FIRST STRONG ISOLATE"),
+    ('\u{2069}', "POP DIRECTIONAL ISOLATE"),
+    ('\u{2212}', "MINUS SIGN"),
+    ('\u{3000}', "IDEOGRAPHIC SPACE"),
+    ('\u{3164}', "HANGUL FILLER"),
+    ('\u{FEFF}', "ZERO WIDTH NO-BREAK SPACE (BOM)"),
+    ('\u{FFFC}', "OBJECT REPLACEMENT CHARACTER"),
+    ('\u{FFFD}', "REPLACEMENT CHARACTER"),
+    // Common homoglyphs of Latin letters
+    ('\u{0391}', "GREEK CAPITAL LETTER ALPHA"),
+    ('\u{0392}', "GREEK CAPITAL LETTER BETA"),
+    ('\u{0395}', "GREEK CAPITAL LETTER EPSILON"),
+    ('\u{0397}', "GREEK CAPITAL LETTER ETA"),
+    ('\u{0399}', "GREEK CAPITAL LETTER IOTA"),
+    ('\u{039A}', "GREEK CAPITAL LETTER KAPPA"),
+    ('\u{039C}', "GREEK CAPITAL LETTER MU"),
+    ('\u{039D}', "GREEK CAPITAL LETTER NU"),
+    ('\u{039F}', "GREEK CAPITAL LETTER OMICRON"),
+    ('\u{03A1}', "GREEK CAPITAL LETTER RHO"),
+    ('\u{03A4}', "GREEK CAPITAL LETTER TAU"),
+    ('\u{03A7}', "GREEK CAPITAL LETTER CHI"),
+    ('\u{03BF}', "GREEK SMALL LETTER OMICRON"),
+    ('\u{0410}', "CYRILLIC CAPITAL LETTER A"),
+    ('\u{0412}', "CYRILLIC CAPITAL LETTER VE"),
+    ('\u{0415}', "CYRILLIC CAPITAL LETTER IE"),
+    ('\u{041A}', "CYRILLIC CAPITAL LETTER KA"),
+    ('\u{041C}', "CYRILLIC CAPITAL LETTER EM"),
+    ('\u{041D}', "CYRILLIC CAPITAL LETTER EN"),
+    ('\u{041E}', "CYRILLIC CAPITAL LETTER O"),
+    ('\u{0420}', "CYRILLIC CAPITAL LETTER ER"),
+    ('\u{0421}', "CYRILLIC CAPITAL LETTER ES"),
+    ('\u{0422}', "CYRILLIC CAPITAL LETTER TE"),
+    ('\u{0425}', "CYRILLIC CAPITAL LETTER HA"),
+    ('\u{0430}', "CYRILLIC SMALL LETTER A"),
+    ('\u{0435}', "CYRILLIC SMALL LETTER IE"),
+    ('\u{043E}', "CYRILLIC SMALL LETTER O"),
+    ('\u{0440}', "CYRILLIC SMALL LETTER ER"),
+    ('\u{0441}', "CYRILLIC SMALL LETTER ES"),
+    ('\u{0443}', "CYRILLIC SMALL LETTER U"),
+    ('\u{0445}', "CYRILLIC SMALL LETTER HA"),
+    ('\u{0455}', "CYRILLIC SMALL LETTER DZE"),
+    ('\u{0456}', "CYRILLIC SMALL LETTER BYELORUSSIAN-UKRAINIAN I"),
+    ('\u{0458}', "CYRILLIC SMALL LETTER JE"),
+];
+
+/// Name of `ch` if it is in the built-in table or is a control character
+pub(crate) fn char_name(ch: char) -> Option<&'static str> {
+    if let Some((_, name)) = CHAR_NAMES.iter().find(|(c, _)| *c == ch) {
+        return Some(name);
+    }
+    if ch.is_control() {
+        return Some("CONTROL CHARACTER");
+    }
+    None
+}
+
+/// One-line description of `ch`: code point, name (if known) and display width
+pub(crate) fn describe_char(ch: char) -> String {
+    let width = ch.width().unwrap_or(0);
+    let shown = if ch.is_control() || width == 0 || ch.is_whitespace() {
+        String::new()
+    } else {
+        format!("'{}' ", ch)
+    };
+    match char_name(ch) {
+        Some(name) => format!("{}U+{:04X} {}, width {}", shown, ch as u32, name, width),
+        None => format!("{}U+{:04X}, width {}", shown, ch as u32, width),
+    }
+}
+
+/// Parse the `suspicious_chars` setting: entries are `U+XXXX` code points or a
+/// single literal character; anything else is ignored
+pub(crate) fn parse_char_set(entries: &[String]) -> Vec<char> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let entry = entry.trim();
+            if let Some(hex) = entry.strip_prefix("U+").or_else(|| entry.strip_prefix("u+")) {
+                return u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+            }
+            let mut chars = entry.chars();
+            match (chars.next(), chars.next()) {
+                (Some(ch), None) => Some(ch),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Char indices of characters from `set` in `line`
+pub(crate) fn suspicious_char_indices(line: &str, set: &[char]) -> Vec<usize> {
+    if set.is_empty() {
+        return Vec::new();
+    }
+    line.chars()
+        .enumerate()
+        .filter(|(_, ch)| set.contains(ch))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Remove all characters from `set` in the selection (or the whole buffer) as
+/// one undo step. A block selection limits this to its columns on each of its
+/// rows. Returns the number of characters removed.
+pub(crate) fn strip_suspicious_chars(
+    state: &mut FileViewerState,
+    lines: &mut [String],
+    filename: &str,
+) -> usize {
+    let set = parse_char_set(&state.settings.suspicious_chars);
+    let block = state.block_rect().filter(|_| state.has_selection());
+    let selection = state.selection_range().filter(|_| !state.block_selection);
+    let (first, last) = match (block, selection) {
+        (Some((top, bottom, _, _)), _) => (top, bottom),
+        (None, Some((start, end))) => (start.0, end.0),
+        (None, None) => (0, lines.len().saturating_sub(1)),
+    };
+
+    let pre_cursor = Some((state.absolute_line(), state.cursor_col, state.multi_cursors.clone()));
+    let cursor = state.current_position();
+    let mut edits = Vec::new();
+    let mut removed = 0;
+    let mut cursor_shift = 0;
+
+    for idx in first..=last.min(lines.len().saturating_sub(1)) {
+        let line = &lines[idx];
+        let (from, to) = match (block, selection) {
+            (Some((_, _, left, right)), _) => (left, right),
+            (None, Some((start, end))) => (
+                if idx == start.0 { start.1 } else { 0 },
+                if idx == end.0 { end.1 } else { usize::MAX },
+            ),
+            (None, None) => (0, usize::MAX),
+        };
+        let hits: Vec<usize> = suspicious_char_indices(line, &set)
+            .into_iter()
+            .filter(|&col| col >= from && col < to)
+            .collect();
+        if hits.is_empty() {
+            continue;
+        }
+        if idx == cursor.0 {
+            cursor_shift = hits.iter().filter(|&&col| col < cursor.1).count();
+        }
+        let new_line: String = line
+            .chars()
+            .enumerate()
+            .filter(|(col, _)| !hits.contains(col))
+            .map(|(_, ch)| ch)
+            .collect();
+        edits.push(crate::undo::Edit::ReplaceLine {
+            line: idx,
+            old_content: line.clone(),
+            new_content: new_line.clone(),
+        });
+        removed += hits.len();
+        lines[idx] = new_line;
+    }
+
+    if removed == 0 {
+        return 0;
+    }
+
+    state.cursor_col = cursor.1 - cursor_shift;
+    state.desired_cursor_col = state.cursor_col;
+    state.clear_selection();
+    let abs = state.absolute_line();
+    state
+        .undo_history
+        .push_composite(edits, Some((abs, state.cursor_col, Vec::new())), pre_cursor);
+    state
+        .undo_history
+        .update_state(state.top_line, abs, state.cursor_col, lines.to_vec());
+    let _ = state.undo_history.save(filename);
+    state.last_save_time = Some(std::time::Instant::now());
+    removed
+}
+
+/// Parse a code point entered as hex (`263A`, `U+263A`, `0x263A`) or as
+/// decimal with a leading `#` (`#9786`)
+pub(crate) fn parse_code_point(input: &str) -> Result<char, String> {
+    let input = input.trim();
+    let value = if let Some(decimal) = input.strip_prefix('#') {
+        decimal.parse::<u32>().ok()
+    } else {
+        let hex = ["U+", "u+", "0x", "0X"]
+            .iter()
+            .find_map(|prefix| input.strip_prefix(prefix))
+            .unwrap_or(input);
+        u32::from_str_radix(hex, 16).ok()
+    };
+    let value = value.ok_or_else(|| format!("Invalid code point: {}", input))?;
+    match char::from_u32(value) {
+        Some(ch) if ch.is_control() => Err(format!("Cannot insert control character U+{:04X}", value)),
+        Some(ch) => Ok(ch),
+        None => Err(format!("U+{:04X} is not a valid Unicode scalar value", value)),
+    }
+}
+
+/// Report the character under the cursor in the footer
+pub(crate) fn inspect_char_at_cursor(state: &mut FileViewerState, lines: &[String]) {
+    let (line, col) = state.current_position();
+    let message = match lines.get(line).and_then(|l| l.chars().nth(col)) {
+        Some(ch) => describe_char(ch),
+        None => "End of line".to_string(),
+    };
+    state.status_message = Some(message);
+    state.needs_footer_redraw = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_char_reports_code_point_name_and_width() {
+        assert_eq!(describe_char('a'), "'a' U+0061, width 1");
+        assert_eq!(describe_char('\u{200B}'), "U+200B ZERO WIDTH SPACE, width 0");
+        assert_eq!(describe_char('\u{00A0}'), "U+00A0 NO-BREAK SPACE, width 1");
+        assert_eq!(describe_char('\u{0430}'), "'а' U+0430 CYRILLIC SMALL LETTER A, width 1");
+        assert_eq!(describe_char('界'), "'界' U+754C, width 2");
+        assert_eq!(describe_char('\u{1B}'), "U+001B CONTROL CHARACTER, width 0");
+    }
+
+    #[test]
+    fn strip_suspicious_chars_is_one_undo_step() {
+        use crate::env::set_temp_home;
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let (_tmp, _guard) = set_temp_home();
+        let settings = Box::leak(Box::new(Settings::load().unwrap()));
+        let mut state = FileViewerState::new(80, UndoHistory::new(), settings);
+        let mut lines = vec!["a\u{200B}b\u{00A0}c".to_string(), "\u{FEFF}x".to_string()];
+        state.cursor_col = 4;
+
+        assert_eq!(strip_suspicious_chars(&mut state, &mut lines, "test.txt"), 3);
+        assert_eq!(lines, vec!["abc", "x"]);
+        assert_eq!(state.cursor_col, 2);
+        assert_eq!(state.undo_history.edits.len(), 1);
+
+        assert!(crate::editing::apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines, vec!["a\u{200B}b\u{00A0}c", "\u{FEFF}x"]);
+        assert_eq!(strip_suspicious_chars(&mut state, &mut ["plain".to_string()], "test.txt"), 0);
+    }
+
+    #[test]
+    fn strip_suspicious_chars_keeps_to_the_block_columns() {
+        use crate::env::set_temp_home;
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let (_tmp, _guard) = set_temp_home();
+        let settings = Box::leak(Box::new(Settings::load().unwrap()));
+        let mut state = FileViewerState::new(80, UndoHistory::new(), settings);
+        let z = '\u{200B}';
+        let mut lines = vec![format!("{z}a{z}b{z}"), format!("{z}c{z}d{z}"), format!("{z}e{z}")];
+        // Columns 1..3 of the first two rows
+        state.block_selection = true;
+        state.selection_start = Some((1, 3));
+        state.selection_end = Some((0, 1));
+
+        assert_eq!(strip_suspicious_chars(&mut state, &mut lines, "test.txt"), 2);
+        assert_eq!(lines, vec![format!("{z}ab{z}"), format!("{z}cd{z}"), format!("{z}e{z}")]);
+    }
+
+    #[test]
+    fn parse_char_set_accepts_code_points_and_literals() {
+        let entries = ["U+200B", "u+feff", "§", "bogus", "U+ZZ"].map(String::from);
+        assert_eq!(parse_char_set(&entries), vec!['\u{200B}', '\u{FEFF}', '§']);
+    }
+
+    #[test]
+    fn parse_code_point_accepts_hex_and_decimal() {
+        assert_eq!(parse_code_point("263A"), Ok('☺'));
+        assert_eq!(parse_code_point("U+263a"), Ok('☺'));
+        assert_eq!(parse_code_point("0x1F600"), Ok('😀'));
+        assert_eq!(parse_code_point("#9786"), Ok('☺'));
+        assert!(parse_code_point("D800").is_err(), "surrogates are not scalar values");
+        assert!(parse_code_point("110000").is_err());
+        assert!(parse_code_point("000A").is_err(), "control characters would break the line");
+        assert!(parse_code_point("xyz").is_err());
+    }
+}
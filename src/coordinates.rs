@@ -1,5 +1,7 @@
 use crate::editor_state::FileViewerState;
 use crate::settings::Settings;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 use unicode_width::UnicodeWidthChar;
 
 /// Unicode character for line wrap indicator (carriage return arrow)
@@ -162,18 +164,96 @@ pub(crate) fn strip_ansi_escapes(s: &str) -> String {
 // Word-wrap calculation
 // ---------------------------------------------------------------------------
 
-/// Calculate break points (character indices) for word-wrapping `line` into
+/// Break wrapped lines exactly at the text width instead of at word boundaries
+/// (`wrap_mode = "char"`). Process-wide so every caller wraps the same way.
+static CHAR_WRAP: AtomicBool = AtomicBool::new(false);
+
+/// Apply the `wrap_mode` setting: "char" breaks anywhere, everything else at words
+pub(crate) fn set_wrap_mode(mode: &str) {
+    CHAR_WRAP.store(mode == "char", Ordering::Relaxed);
+}
+
+/// Text drawn at the start of continuation rows of wrapped lines (`wrap_prefix`)
+static WRAP_PREFIX: RwLock<String> = RwLock::new(String::new());
+/// Indent continuation rows by the line's leading whitespace (`wrap_hanging_indent`)
+static HANGING_INDENT: AtomicBool = AtomicBool::new(false);
+
+/// Apply the `wrap_prefix` and `wrap_hanging_indent` settings
+pub(crate) fn set_wrap_continuation(prefix: &str, hanging_indent: bool) {
+    if let Ok(mut current) = WRAP_PREFIX.write() {
+        *current = strip_ansi_escapes(prefix);
+    }
+    HANGING_INDENT.store(hanging_indent, Ordering::Relaxed);
+}
+
+/// Text drawn at the start of continuation rows
+pub(crate) fn wrap_prefix() -> String {
+    WRAP_PREFIX.read().map(|prefix| prefix.clone()).unwrap_or_default()
+}
+
+/// Columns in front of the text on continuation rows of `line`: the wrap
+/// prefix plus, with hanging indent, the line's leading whitespace.
+pub(crate) fn continuation_indent(line: &str, text_width: usize, tab_width: usize) -> usize {
+    let prefix_width = visual_width(&wrap_prefix(), tab_width);
+    continuation_indent_with(line, text_width, tab_width, prefix_width, HANGING_INDENT.load(Ordering::Relaxed))
+}
+
+/// `continuation_indent` for explicit settings. Capped at half the usable width
+/// so deeply indented lines still get room for text on every row.
+fn continuation_indent_with(
+    line: &str,
+    text_width: usize,
+    tab_width: usize,
+    prefix_width: usize,
+    hanging_indent: bool,
+) -> usize {
+    let indent = if hanging_indent {
+        let leading: String = line.chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+        visual_width(&leading, tab_width)
+    } else {
+        0
+    };
+    (prefix_width + indent).min(text_width.saturating_sub(1) / 2)
+}
+
+/// Columns in front of the text on wrap segment `segment` of `line` (0 for the first)
+pub(crate) fn segment_indent(line: &str, segment: usize, text_width: usize, tab_width: usize) -> usize {
+    if segment == 0 {
+        0
+    } else {
+        continuation_indent(line, text_width, tab_width)
+    }
+}
+
+/// Calculate break points (character indices) for wrapping `line` into
 /// segments of at most `text_width` terminal columns (one column is reserved
-/// for the wrap indicator `↩`).
+/// for the wrap indicator `↩`), following the `wrap_mode` setting.
 ///
 /// Returns a `Vec` of character indices at which new visual lines begin.
 pub(crate) fn calculate_word_wrap_points(line: &str, text_width: usize, tab_width: usize) -> Vec<usize> {
     if text_width == 0 || line.is_empty() {
         return vec![];
     }
+    let continuation = continuation_indent(line, text_width, tab_width);
+    calculate_wrap_points(line, text_width, tab_width, !CHAR_WRAP.load(Ordering::Relaxed), continuation)
+}
+
+/// Break points for `line`; `at_words` prefers breaking after whitespace,
+/// otherwise every segment is filled up to the last column. Continuation rows
+/// are `continuation` columns narrower than the first one.
+fn calculate_wrap_points(
+    line: &str,
+    text_width: usize,
+    tab_width: usize,
+    at_words: bool,
+    continuation: usize,
+) -> Vec<usize> {
+    if text_width == 0 || line.is_empty() {
+        return vec![];
+    }
 
     // One column is consumed by the wrap indicator on every wrapped segment.
-    let usable_width = text_width.saturating_sub(1);
+    let first_width = text_width.saturating_sub(1);
     // Words longer than half the line width are broken at character boundaries.
     let max_word_length = text_width / 2;
 
@@ -182,6 +262,11 @@ pub(crate) fn calculate_word_wrap_points(line: &str, text_width: usize, tab_widt
     let mut line_start_idx = 0;
 
     while line_start_idx < chars.len() {
+        let usable_width = if line_start_idx == 0 {
+            first_width
+        } else {
+            first_width.saturating_sub(continuation)
+        };
         // How wide is the remaining tail?
         // Strip ANSI escape sequences before measuring — they contribute zero columns.
         let remaining: String = chars[line_start_idx..].iter().collect();
@@ -219,7 +304,7 @@ pub(crate) fn calculate_word_wrap_points(line: &str, text_width: usize, tab_widt
 
             if current_visual + cw > usable_width {
                 // This character would overflow the line.
-                if let Some(space_idx) = last_space_idx {
+                if at_words && let Some(space_idx) = last_space_idx {
                     let word_segment: String = chars[word_start_idx..i].iter().collect();
                     let word_plain = strip_ansi_escapes(&word_segment);
                     let word_visual = visual_width(&word_plain, tab_width);
@@ -310,7 +395,7 @@ pub(crate) fn calculate_wrapped_lines_for_line_with_wrapping(
 /// Visual line count for one logical line, respecting the wrapping flag.
 /// Shared by all the counting functions below.
 #[inline]
-fn visual_lines_for(
+pub(crate) fn visual_lines_for(
     lines: &[String],
     logical_idx: usize,
     text_width: u16,
@@ -429,15 +514,28 @@ pub(crate) fn line_number_display_width(settings: &Settings, total_lines: usize)
 
 /// Usable text width: terminal width minus the gutter and the always-visible
 /// scrollbar column (reserving the scrollbar prevents text from jumping).
+/// Zen mode also keeps a right margin matching the blank left gutter.
 pub fn calculate_text_width(
     state: &FileViewerState,
     _lines: &[String],
     _visible_lines: usize,
 ) -> u16 {
-    state
+    let right_margin = if state.zen_mode {
+        line_number_width(state.settings).saturating_sub(1)
+    } else {
+        0
+    };
+    let width = state
         .term_width
         .saturating_sub(line_number_width(state.settings))
         .saturating_sub(1) // scrollbar
+        .saturating_sub(crate::minimap::width(state))
+        .saturating_sub(right_margin);
+    // wrap_column only narrows wrapped text; horizontal scrolling keeps the full width
+    match state.settings.wrap_column {
+        column if column > 0 && state.is_line_wrapping_enabled() => width.min(column),
+        _ => width,
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -458,8 +556,8 @@ pub(crate) fn visual_to_logical_position(
     let text_width = calculate_text_width(state, lines, visible_lines);
     let tab_width = state.settings.tab_width;
 
-    // Reject clicks on the gutter or the scrollbar column.
-    if column < line_num_width || column >= state.term_width.saturating_sub(1) {
+    // Reject clicks on the gutter, the minimap or the scrollbar column.
+    if column < line_num_width || column >= crate::minimap::start_column(state) {
         return None;
     }
     let text_col = (column - line_num_width) as usize;
@@ -508,9 +606,11 @@ fn resolve_visual_col(
         if line_offset == 0 || wrap_points.is_empty() {
             text_col
         } else {
-            // Offset from the start of this segment's first character.
+            // Offset from the start of this segment's first character, past the
+            // continuation prefix/indent in front of it.
             let seg_start_char = wrap_points[line_offset - 1];
-            visual_width_up_to(line, seg_start_char, tab_width) + text_col
+            let indent = continuation_indent(line, text_width, tab_width);
+            visual_width_up_to(line, seg_start_char, tab_width) + text_col.saturating_sub(indent)
         }
     } else {
         // Horizontal-scroll mode: the screen column is offset by the scroll amount.
@@ -826,6 +926,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_wrap_points_word_vs_char_mode() {
+        // usable width is 9 columns (one for the wrap indicator)
+        let line = "hello wonderful world";
+        assert_eq!(calculate_wrap_points(line, 10, 4, true, 0), vec![6, 15]);
+        assert_eq!(calculate_wrap_points(line, 10, 4, false, 0), vec![9, 18]);
+    }
+
+    #[test]
+    fn test_continuation_rows_are_narrower_by_their_indent() {
+        // 9 usable columns on the first row, 7 on continuation rows
+        assert_eq!(calculate_wrap_points("abcdefghijklmnopqrst", 10, 4, false, 2), vec![9, 16]);
+
+        assert_eq!(continuation_indent_with("    foo", 40, 4, 2, false), 2);
+        assert_eq!(continuation_indent_with("    foo", 40, 4, 2, true), 6);
+        assert_eq!(continuation_indent_with("\tfoo", 40, 4, 0, true), 4);
+        // Capped at half the usable width
+        assert_eq!(continuation_indent_with(&" ".repeat(30), 21, 4, 2, true), 10);
+    }
+
     #[test]
     fn test_wrapped_lines_ansi_same_as_plain() {
         // Short line — no wrapping expected in either variant.
@@ -1015,6 +1135,39 @@ mod tests {
         assert_eq!(calculate_text_width(&state, &lines, 20), 79);
     }
 
+    #[test]
+    fn test_calculate_text_width_wrap_column() {
+        let settings = Settings {
+            wrap_column: 40,
+            ..Default::default()
+        };
+        let mut state = make_state(&settings);
+        state.term_width = 120;
+        let lines = vec!["word ".repeat(20)];
+        assert_eq!(calculate_text_width(&state, &lines, 20), 40);
+        assert_eq!(calculate_wrapped_lines_for_line(&lines, 0, 40, 4), 3);
+
+        // Narrower windows still wrap at their own width
+        state.term_width = 30;
+        assert_eq!(calculate_text_width(&state, &lines, 20), 25);
+
+        // Horizontal scrolling is not limited
+        state.term_width = 120;
+        state.line_wrapping_override = Some(false);
+        assert_eq!(calculate_text_width(&state, &lines, 20), 115);
+    }
+
+    #[test]
+    fn test_calculate_text_width_zen_mode_margins() {
+        let settings = Settings::default().zen_variant();
+        let mut state = make_state(&settings);
+        state.term_width = 80;
+        state.zen_mode = true;
+        let lines = vec!["line1".to_string()];
+        // 80 - left margin(8) - scrollbar(1) - right margin(7) = 64
+        assert_eq!(calculate_text_width(&state, &lines, 20), 64);
+    }
+
     // --- visual_to_logical_position ---
 
     #[test]
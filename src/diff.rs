@@ -0,0 +1,240 @@
+//! Line-based diff between two versions of a buffer.
+
+/// Above this many line pairs in the changed middle part the diff is not
+/// searched for common lines: everything there counts as removed and added
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// One line of a diff from `old` to `new`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Lines of `old` and `new` in order, marked as kept, removed or added
+/// (longest common subsequence; removals come before additions)
+pub(crate) fn diff_lines<'a>(old: &'a [String], new: &'a [String]) -> Vec<DiffLine<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut result: Vec<DiffLine> = old[..prefix].iter().map(|l| DiffLine::Same(l)).collect();
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        result.extend(old_mid.iter().map(|l| DiffLine::Removed(l)));
+        result.extend(new_mid.iter().map(|l| DiffLine::Added(l)));
+    } else {
+        // lcs[i][j]: length of the longest common subsequence of old_mid[i..] and new_mid[j..]
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i] == new_mid[j] {
+                result.push(DiffLine::Same(&old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                result.push(DiffLine::Removed(&old_mid[i]));
+                i += 1;
+            } else {
+                result.push(DiffLine::Added(&new_mid[j]));
+                j += 1;
+            }
+        }
+    }
+    result.extend(old[old.len() - suffix..].iter().map(|l| DiffLine::Same(l)));
+    result
+}
+
+/// How a buffer line differs from the version it is compared with (gutter markers)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LineChange {
+    Added,
+    Changed,
+    /// Lines of the old version were removed just above this line
+    Removed,
+}
+
+/// Marker per line of `new` against `old`: added lines that take the place of
+/// removed ones count as changed, the others as added. Removed lines left over
+/// mark the line below them (the last line for removals at the end).
+pub(crate) fn line_changes(old: &[String], new: &[String]) -> Vec<Option<LineChange>> {
+    let mut marks = vec![None; new.len()];
+    let mut new_idx = 0;
+    // Removed lines not yet paired with an added one
+    let mut removed = 0;
+    for line in diff_lines(old, new) {
+        match line {
+            DiffLine::Same(_) => {
+                if removed > 0 {
+                    marks[new_idx] = marks[new_idx].or(Some(LineChange::Removed));
+                    removed = 0;
+                }
+                new_idx += 1;
+            }
+            DiffLine::Removed(_) => removed += 1,
+            DiffLine::Added(_) => {
+                marks[new_idx] = Some(if removed > 0 {
+                    removed -= 1;
+                    LineChange::Changed
+                } else {
+                    LineChange::Added
+                });
+                new_idx += 1;
+            }
+        }
+    }
+    if removed > 0
+        && let Some(last) = marks.last_mut()
+    {
+        *last = last.or(Some(LineChange::Removed));
+    }
+    marks
+}
+
+/// A hunk of a unified diff: where it starts in each version (0-based) and its
+/// lines, with the unchanged context around the changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Hunk<'a> {
+    pub(crate) old_start: usize,
+    pub(crate) new_start: usize,
+    pub(crate) lines: Vec<DiffLine<'a>>,
+}
+
+impl<'a> Hunk<'a> {
+    /// The hunk's lines as in the old version
+    pub(crate) fn old_lines(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                DiffLine::Same(l) | DiffLine::Removed(l) => Some(l.to_string()),
+                DiffLine::Added(_) => None,
+            })
+            .collect()
+    }
+
+    /// Number of lines the hunk spans in the new version
+    pub(crate) fn new_len(&self) -> usize {
+        self.lines.iter().filter(|l| !matches!(l, DiffLine::Removed(_))).count()
+    }
+
+    /// `@@ -a,b +c,d @@` header line
+    pub(crate) fn header(&self) -> String {
+        let old_len = self.lines.iter().filter(|l| !matches!(l, DiffLine::Added(_))).count();
+        format!("@@ -{},{} +{},{} @@", self.old_start + 1, old_len, self.new_start + 1, self.new_len())
+    }
+}
+
+/// Hunks of the changes from `old` to `new`, with `context` unchanged lines
+/// around each change; changes closer than two contexts apart share a hunk
+pub(crate) fn hunks<'a>(old: &'a [String], new: &'a [String], context: usize) -> Vec<Hunk<'a>> {
+    let diff = diff_lines(old, new);
+    let changed: Vec<usize> = (0..diff.len())
+        .filter(|&i| !matches!(diff[i], DiffLine::Same(_)))
+        .collect();
+    let mut result = Vec::new();
+    let mut idx = 0;
+    while idx < changed.len() {
+        let start = changed[idx].saturating_sub(context);
+        let mut end = changed[idx];
+        while idx < changed.len() && changed[idx] <= end + 2 * context + 1 {
+            end = changed[idx];
+            idx += 1;
+        }
+        let end = (end + context + 1).min(diff.len());
+        result.push(Hunk {
+            old_start: diff[..start].iter().filter(|l| !matches!(l, DiffLine::Added(_))).count(),
+            new_start: diff[..start].iter().filter(|l| !matches!(l, DiffLine::Removed(_))).count(),
+            lines: diff[start..end].to_vec(),
+        });
+    }
+    result
+}
+
+/// Unified diff text (`@@ -a,b +c,d @@` hunks with `context` unchanged lines
+/// around each change); empty when both versions are equal
+pub(crate) fn unified(old: &[String], new: &[String], context: usize) -> Vec<String> {
+    let mut out = Vec::new();
+    for hunk in hunks(old, new, context) {
+        out.push(hunk.header());
+        out.extend(hunk.lines.iter().map(|line| match line {
+            DiffLine::Same(l) => format!(" {}", l),
+            DiffLine::Removed(l) => format!("-{}", l),
+            DiffLine::Added(l) => format!("+{}", l),
+        }));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(String::from).collect()
+    }
+
+    #[test]
+    fn diff_lines_keeps_common_lines() {
+        let old = lines("a\nb\nc\nd");
+        let new = lines("a\nx\nc\nd\ne");
+        assert_eq!(
+            diff_lines(&old, &new),
+            vec![
+                DiffLine::Same("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Added("x"),
+                DiffLine::Same("c"),
+                DiffLine::Same("d"),
+                DiffLine::Added("e"),
+            ]
+        );
+        assert!(diff_lines(&old, &old).iter().all(|l| matches!(l, DiffLine::Same(_))));
+    }
+
+    #[test]
+    fn line_changes_mark_added_changed_and_removed_lines() {
+        use LineChange::*;
+        let old = lines("a\nb\nc\nd\ne");
+        assert_eq!(line_changes(&old, &lines("a\nB\nc\nd\ne")), vec![None, Some(Changed), None, None, None]);
+        assert_eq!(line_changes(&old, &lines("a\nb\nx\ny\nc\nd\ne")), vec![None, None, Some(Added), Some(Added), None, None, None]);
+        assert_eq!(line_changes(&old, &lines("a\nd\ne")), vec![None, Some(Removed), None]);
+        assert_eq!(line_changes(&old, &lines("a\nX\ne")), vec![None, Some(Changed), Some(Removed)]);
+        assert_eq!(line_changes(&old, &lines("a\nb\nc")), vec![None, None, Some(Removed)]);
+        assert!(line_changes(&old, &old).iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn unified_groups_nearby_changes_into_hunks() {
+        let old: Vec<String> = (1..=20).map(|n| n.to_string()).collect();
+        let mut new = old.clone();
+        new[1] = "two".to_string();
+        new[3] = "four".to_string();
+        new.remove(15);
+
+        let text = unified(&old, &new, 1);
+        assert_eq!(
+            text,
+            vec![
+                "@@ -1,5 +1,5 @@", " 1", "-2", "+two", " 3", "-4", "+four", " 5",
+                "@@ -15,3 +15,2 @@", " 15", "-16", " 17",
+            ]
+        );
+        assert!(unified(&old, &old, 3).is_empty());
+    }
+}
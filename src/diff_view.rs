@@ -0,0 +1,268 @@
+//! Diff view: the changes of the buffer against its file on disk or another
+//! file, as a full-screen unified diff. `n`/`p` step through the hunks and `r`
+//! reverts the selected one, taking its lines from the other version.
+
+use crossterm::{
+    cursor, event, execute,
+    event::KeyCode,
+    style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal,
+};
+use std::io::Write;
+
+use crate::diff::{DiffLine, Hunk};
+use crate::editor_state::FileViewerState;
+use crate::settings::Settings;
+
+/// Unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+/// Kind of a diff view row, deciding its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RowKind {
+    Header,
+    Same,
+    Removed,
+    Added,
+}
+
+/// One row of the diff view and the hunk it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Row {
+    pub(crate) text: String,
+    pub(crate) kind: RowKind,
+    pub(crate) hunk: usize,
+}
+
+/// Rows of the unified diff of `hunks`
+pub(crate) fn diff_rows(hunks: &[Hunk]) -> Vec<Row> {
+    let mut rows = Vec::new();
+    for (idx, hunk) in hunks.iter().enumerate() {
+        rows.push(Row { text: hunk.header(), kind: RowKind::Header, hunk: idx });
+        rows.extend(hunk.lines.iter().map(|line| {
+            let (prefix, text, kind) = match line {
+                DiffLine::Same(l) => (' ', l, RowKind::Same),
+                DiffLine::Removed(l) => ('-', l, RowKind::Removed),
+                DiffLine::Added(l) => ('+', l, RowKind::Added),
+            };
+            Row { text: format!("{}{}", prefix, text), kind, hunk: idx }
+        }));
+    }
+    rows
+}
+
+/// Show the diff of the buffer against `other`, or against its own file on disk
+/// when None. Returns true if reverting hunks changed the buffer.
+pub(crate) fn run_diff_view(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    filename: &str,
+    other: Option<&str>,
+    visible_lines: usize,
+    settings: &Settings,
+) -> Result<bool, std::io::Error> {
+    if other.is_none() && (state.is_untitled || state.is_scratch || state.is_new) {
+        state.status_message = Some("Not saved on disk yet: nothing to compare".to_string());
+        state.alert();
+        state.needs_footer_redraw = true;
+        return Ok(false);
+    }
+    let path = other.unwrap_or(filename);
+    let base = match std::fs::read(path) {
+        // The buffer's own file is read the way it was opened
+        Ok(bytes) if other.is_none() => crate::line_ending::split_lines(&state.encoding.decode_as(&bytes)),
+        Ok(bytes) => crate::line_ending::split_lines(&crate::encoding::decode(&bytes).0),
+        Err(e) => {
+            state.status_message = Some(format!("Cannot read {}: {}", path, e));
+            state.alert();
+            state.needs_footer_redraw = true;
+            return Ok(false);
+        }
+    };
+    let name = std::path::Path::new(path)
+        .file_name()
+        .map_or_else(|| path.to_string(), |n| n.to_string_lossy().to_string());
+
+    let mut stdout = crate::no_color::stdout();
+    let mut selected = 0usize;
+    let mut scroll = 0usize;
+    let mut changed = false;
+    loop {
+        let hunks = crate::diff::hunks(&base, lines, CONTEXT_LINES);
+        if hunks.is_empty() {
+            state.status_message = Some(if changed {
+                format!("Reverted all changes against {}", name)
+            } else {
+                format!("No differences from {}", name)
+            });
+            break;
+        }
+        let rows = diff_rows(&hunks);
+        selected = selected.min(hunks.len() - 1);
+        let (_, term_height) = terminal::size()?;
+        let page = (term_height as usize).saturating_sub(1).max(1);
+        scroll = scroll.min(rows.len().saturating_sub(page));
+        let title = format!(
+            " Diff {} \u{2192} buffer  hunk {}/{}  n/p: next/previous  r: revert  q: close",
+            name,
+            selected + 1,
+            hunks.len()
+        );
+        render_diff(&mut stdout, state, &title, &rows, selected, scroll, page)?;
+
+        let event::Event::Key(key) = event::read()? else {
+            continue;
+        };
+        let key = crate::event_handlers::normalize_key_event(key, settings);
+        let header_row = |hunk: usize| rows.iter().position(|r| r.hunk == hunk).unwrap_or(0);
+        // Moving through the rows selects the hunk at the top of the view
+        let scroll_to = |target: usize, scroll: &mut usize, selected: &mut usize| {
+            *scroll = target.min(rows.len().saturating_sub(page));
+            *selected = rows.get(*scroll).map_or(*selected, |r| r.hunk);
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                state.status_message = None;
+                break;
+            }
+            KeyCode::Char('n') => {
+                selected = (selected + 1).min(hunks.len() - 1);
+                scroll = header_row(selected);
+            }
+            KeyCode::Char('p') => {
+                selected = selected.saturating_sub(1);
+                scroll = header_row(selected);
+            }
+            KeyCode::Up => scroll_to(scroll.saturating_sub(1), &mut scroll, &mut selected),
+            KeyCode::Down => scroll_to(scroll + 1, &mut scroll, &mut selected),
+            KeyCode::PageUp => scroll_to(scroll.saturating_sub(page), &mut scroll, &mut selected),
+            KeyCode::PageDown => scroll_to(scroll + page, &mut scroll, &mut selected),
+            KeyCode::Home => scroll_to(0, &mut scroll, &mut selected),
+            KeyCode::End => scroll_to(rows.len(), &mut scroll, &mut selected),
+            KeyCode::Char('r') => {
+                let hunk = &hunks[selected];
+                let (start, len, old) = (hunk.new_start, hunk.new_len(), hunk.old_lines());
+                drop(hunks);
+                changed |= crate::editing::replace_line_range(state, lines, filename, start, len, old);
+                scroll = 0;
+            }
+            _ => {}
+        }
+    }
+
+    if changed {
+        state.ensure_cursor_visible(visible_lines, lines);
+    }
+    state.needs_redraw = true;
+    Ok(changed)
+}
+
+/// Draw the diff view over the whole screen: the `title` row, then `page` rows
+/// starting at `scroll`
+fn render_diff(
+    stdout: &mut impl Write,
+    state: &FileViewerState,
+    title: &str,
+    rows: &[Row],
+    selected: usize,
+    scroll: usize,
+    page: usize,
+) -> Result<(), std::io::Error> {
+    let (term_width, _) = terminal::size()?;
+    let width = term_width as usize;
+    let chrome_bg = state.effective_theme_bg();
+    let selection_color = state.settings.theme.menu_selection_bg;
+    let tab_width = state.settings.tab_width;
+
+    let fit = |text: &str| -> String {
+        let mut text = crate::rendering::truncate_to_width(&crate::rendering::expand_tabs(text, tab_width), width);
+        let pad = width.saturating_sub(crate::coordinates::visual_width(&text, tab_width));
+        text.push_str(&" ".repeat(pad));
+        text
+    };
+
+    execute!(stdout, cursor::Hide, cursor::MoveTo(0, 0), SetBackgroundColor(chrome_bg), SetForegroundColor(Color::White))?;
+    write!(stdout, "{}", fit(title))?;
+    for screen_row in 0..page {
+        execute!(stdout, cursor::MoveTo(0, 1 + screen_row as u16), SetBackgroundColor(Color::Reset))?;
+        let Some(row) = rows.get(scroll + screen_row) else {
+            write!(stdout, "{}", fit(""))?;
+            continue;
+        };
+        let fg = match row.kind {
+            RowKind::Header => Color::Cyan,
+            RowKind::Same => Color::Reset,
+            RowKind::Removed => Color::Red,
+            RowKind::Added => Color::Green,
+        };
+        if row.kind == RowKind::Header && row.hunk == selected {
+            execute!(stdout, SetBackgroundColor(selection_color))?;
+        }
+        execute!(stdout, SetForegroundColor(fg))?;
+        write!(stdout, "{}", fit(&row.text))?;
+    }
+    execute!(stdout, ResetColor)?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::set_temp_home;
+    use crate::undo::UndoHistory;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(String::from).collect()
+    }
+
+    #[test]
+    fn rows_belong_to_their_hunks() {
+        let old: Vec<String> = (1..=20).map(|n| n.to_string()).collect();
+        let mut new = old.clone();
+        new[1] = "two".to_string();
+        new.remove(15);
+        let hunks = crate::diff::hunks(&old, &new, 1);
+        let rows = diff_rows(&hunks);
+        let summary: Vec<(&str, RowKind, usize)> = rows.iter().map(|r| (r.text.as_str(), r.kind, r.hunk)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("@@ -1,3 +1,3 @@", RowKind::Header, 0),
+                (" 1", RowKind::Same, 0),
+                ("-2", RowKind::Removed, 0),
+                ("+two", RowKind::Added, 0),
+                (" 3", RowKind::Same, 0),
+                ("@@ -15,3 +15,2 @@", RowKind::Header, 1),
+                (" 15", RowKind::Same, 1),
+                ("-16", RowKind::Removed, 1),
+                (" 17", RowKind::Same, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn reverting_hunks_restores_the_other_version() {
+        let (tmp, _guard) = set_temp_home();
+        let settings = Box::leak(Box::new(Settings::load().unwrap()));
+        let mut state = FileViewerState::new(80, UndoHistory::new(), settings);
+        let file = tmp.path().join("notes.txt").to_string_lossy().to_string();
+        let base = lines("a\nb\nc\nd\ne\nf\ng\nh\ni\nj");
+        let mut buffer = lines("a\nB\nx\nc\nd\ne\nf\ng\nj");
+
+        // Revert the later hunk (removed "h" and "i") first, then the earlier one
+        for _ in 0..2 {
+            let hunks = crate::diff::hunks(&base, &buffer, 1);
+            let hunk = hunks.last().unwrap();
+            let (start, len, old) = (hunk.new_start, hunk.new_len(), hunk.old_lines());
+            assert!(crate::editing::replace_line_range(&mut state, &mut buffer, &file, start, len, old));
+        }
+        assert_eq!(buffer, base);
+        assert!(state.modified);
+        assert!(!crate::editing::replace_line_range(&mut state, &mut buffer, &file, 0, 1, vec!["a".to_string()]));
+
+        // A buffer keeps at least one line
+        let mut single = lines("only");
+        assert!(crate::editing::replace_line_range(&mut state, &mut single, &file, 0, 1, Vec::new()));
+        assert_eq!(single, vec![String::new()]);
+    }
+}
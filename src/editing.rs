@@ -2,7 +2,7 @@ use crate::editor_state::{FileViewerState, Position};
 use crate::undo::Edit;
 use std::fs;
 use std::sync::{Mutex, OnceLock};
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
 
 static GLOBAL_CLIPBOARD: OnceLock<Mutex<Option<arboard::Clipboard>>> = OnceLock::new();
 fn get_clipboard() -> &'static Mutex<Option<arboard::Clipboard>> {
@@ -68,9 +68,8 @@ pub(crate) fn handle_copy(state: &FileViewerState, lines: &[String]) -> Result<(
 
     if let (Some(sel_start), Some(sel_end)) = (state.selection_start, state.selection_end) {
         let lines_refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
-        let selected_text = if state.block_selection {
-            let (start, end) = normalize_selection(sel_start, sel_end);
-            extract_block_selection(&lines_refs, start.0, start.1, end.0, end.1)
+        let selected_text = if let Some((top, bottom, left, right)) = state.block_rect() {
+            extract_block_selection(&lines_refs, top, bottom, left, right)
         } else {
             extract_selection(&lines_refs, sel_start, sel_end)
         };
@@ -102,10 +101,32 @@ pub(crate) fn handle_paste(
     if text.is_empty() {
         return false;
     }
+    paste_text(state, lines, &text, filename)
+}
 
+/// Paste `text` over the selection or at the cursor(s); one undo step
+pub(crate) fn paste_text(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    text: &str,
+    filename: &str,
+) -> bool {
+    if paste_columns(state, lines, text, filename) {
+        return true;
+    }
     // We'll accumulate all edits to push as one composite for proper undo behavior
     let mut edits: Vec<Edit> = Vec::new();
 
+    // A block the text does not fit row by row is cleared first; the text then
+    // goes in at its top-left corner
+    if let Some((top, bottom, left, right)) = state.block_rect().filter(|_| state.has_selection()) {
+        remove_block_columns(lines, top, bottom, left, right, &mut edits);
+        state.clear_selection();
+        state.clear_multi_cursors();
+        state.cursor_line = top.saturating_sub(state.top_line);
+        state.set_cursor_col(left, lines);
+    }
+
     // If there's a selection, delete it first (inline, without creating separate undo entry)
     if state.has_selection() {
         let (sel_start, sel_end) = {
@@ -198,12 +219,209 @@ pub(crate) fn handle_paste(
     // Used to restore cursor position on Ctrl+Z so a second paste doesn't panic.
     let pre_paste_cursor = Some((state.absolute_line(), state.cursor_col, state.multi_cursors.clone()));
 
+    if !insert_text_at_cursor(state, lines, text, &mut edits) && edits.is_empty() {
+        return false;
+    }
+    state.modified = true;
+
+    // Push all edits as a single composite so Ctrl+Z undoes the entire operation.
+    // pre_cursor = where cursor was before paste (for undo restoration).
+    // undo_cursor = where cursor is after paste (for redo restoration).
+    let absolute_line = state.absolute_line();
+    let undo_cursor = Some((absolute_line, state.cursor_col, state.multi_cursors.clone()));
+    state.undo_history.push_composite(edits, undo_cursor, pre_paste_cursor);
+
+    state.undo_history.update_state(
+        state.top_line,
+        absolute_line,
+        state.cursor_col,
+        lines.clone(),
+    );
+    save_undo_with_timestamp(state, filename);
+
+    // Validate cursor after paste operation (debug only)
+    state.validate_cursor_invariants(lines);
+
+    true
+}
+
+/// Insert a copy of the selected text right after the selection and select the
+/// copy, so repeated calls stack copies. A block selection copies the rows it
+/// spans below itself. Without a selection the cursor line is copied below it
+/// and the cursor moves onto the copy. One undo step; the clipboard is untouched.
+pub(crate) fn duplicate_selection(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    filename: &str,
+) -> bool {
+    if state.block_selection {
+        return duplicate_block_rows(state, lines, filename);
+    }
+    let (start, end) = match state.selection_range() {
+        Some((start, end)) if start != end => (start, end),
+        _ => return duplicate_line(state, lines, filename),
+    };
+    if end.0 >= lines.len() {
+        return false;
+    }
+    let text = {
+        let lines_refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+        extract_selection(&lines_refs, start, end)
+    };
+
+    let pre_cursor = Some((state.absolute_line(), state.cursor_col, state.multi_cursors.clone()));
+    state.cursor_line = end.0.saturating_sub(state.top_line);
+    state.cursor_col = end.1;
+    let mut edits = Vec::new();
+    if !insert_text_at_cursor(state, lines, &text, &mut edits) {
+        return false;
+    }
+
+    let copy_end = state.current_position();
+    state.selection_start = Some(end);
+    state.selection_anchor = Some(end);
+    state.selection_end = Some(copy_end);
+
+    let undo_cursor = Some((copy_end.0, copy_end.1, state.multi_cursors.clone()));
+    state.undo_history.push_composite(edits, undo_cursor, pre_cursor);
+    state
+        .undo_history
+        .update_state(state.top_line, copy_end.0, copy_end.1, lines.clone());
+    save_undo_with_timestamp(state, filename);
+    state.validate_cursor_invariants(lines);
+    true
+}
+
+/// Copy the rows of a block selection below it and move the block and the
+/// cursor onto the copy, at the same columns
+fn duplicate_block_rows(state: &mut FileViewerState, lines: &mut Vec<String>, filename: &str) -> bool {
+    let Some((top, bottom, _, _)) = state.block_rect() else {
+        return false;
+    };
+    if bottom >= lines.len() {
+        return false;
+    }
+    let height = bottom - top + 1;
+    let (line, col) = state.current_position();
+    let pre_cursor = Some((line, col, state.multi_cursors.clone()));
+    let mut edits = Vec::with_capacity(height);
+    for offset in 0..height {
+        let content = lines[top + offset].clone();
+        lines.insert(bottom + 1 + offset, content.clone());
+        edits.push(Edit::InsertLine { line: bottom + 1 + offset, content });
+    }
+
+    let shift = |pos: Option<Position>| pos.map(|(l, c)| (l + height, c));
+    state.selection_start = shift(state.selection_start);
+    state.selection_end = shift(state.selection_end);
+    state.selection_anchor = shift(state.selection_anchor);
+    state.cursor_line = (line + height).saturating_sub(state.top_line);
+
+    let undo_cursor = Some((line + height, col, state.multi_cursors.clone()));
+    state.undo_history.push_composite(edits, undo_cursor, pre_cursor);
+    state.undo_history.update_state(state.top_line, line + height, col, lines.clone());
+    save_undo_with_timestamp(state, filename);
+    state.validate_cursor_invariants(lines);
+    true
+}
+
+/// Copy the cursor line below itself and put the cursor on the copy, at the
+/// same column
+fn duplicate_line(state: &mut FileViewerState, lines: &mut Vec<String>, filename: &str) -> bool {
+    let (line, col) = state.current_position();
+    let Some(content) = lines.get(line).cloned() else {
+        return false;
+    };
+    state.clear_selection();
+    let pre_cursor = Some((line, col, state.multi_cursors.clone()));
+    lines.insert(line + 1, content.clone());
+    state.cursor_line = (line + 1).saturating_sub(state.top_line);
+    state.cursor_col = col;
+    state.desired_cursor_col = col;
+
+    let undo_cursor = Some((line + 1, col, state.multi_cursors.clone()));
+    state.undo_history.push_composite(vec![Edit::InsertLine { line: line + 1, content }], undo_cursor, pre_cursor);
+    state.undo_history.update_state(state.top_line, line + 1, col, lines.clone());
+    save_undo_with_timestamp(state, filename);
+    state.validate_cursor_invariants(lines);
+    true
+}
+
+/// Move the cursor line, or every line the selection touches, one line up or
+/// down, keeping the cursor and selection on the moved text. One undo step.
+pub(crate) fn move_lines(
+    state: &mut FileViewerState,
+    lines: &mut [String],
+    filename: &str,
+    up: bool,
+    visible_lines: usize,
+) -> bool {
+    if state.block_selection {
+        return false;
+    }
+    let cursor = state.current_position();
+    let (first, last) = match state.selection_range() {
+        // A selection ending at the start of a line does not take that line along
+        Some((start, end)) if end.1 == 0 && end.0 > start.0 => (start.0, end.0 - 1),
+        Some((start, end)) => (start.0, end.0),
+        None => (cursor.0, cursor.0),
+    };
+    if last >= lines.len() || (up && first == 0) || (!up && last + 1 >= lines.len()) {
+        return false;
+    }
+    let start = first;
+    let len = last - first + 1;
+    move_line_block(lines, start, len, up);
+
+    state.clear_multi_cursors();
+    let shift = |line: usize| if up { line - 1 } else { line + 1 };
+    let pre_cursor = Some((cursor.0, cursor.1, Vec::new()));
+    for pos in [&mut state.selection_start, &mut state.selection_end, &mut state.selection_anchor] {
+        if let Some((line, _)) = pos.as_mut() {
+            *line = shift(*line);
+        }
+    }
+    let moved = (shift(cursor.0), cursor.1);
+    state.set_cursor_position(moved.0, moved.1, lines, visible_lines);
+    state.undo_history.push_composite(
+        vec![Edit::MoveLines { start, len, up }],
+        Some((moved.0, moved.1, Vec::new())),
+        pre_cursor,
+    );
+    state.undo_history.update_state(state.top_line, moved.0, moved.1, lines.to_vec());
+    save_undo_with_timestamp(state, filename);
+    state.validate_cursor_invariants(lines);
+    true
+}
+
+/// Move the `len` lines from `start` one line up or down, swapping them with the
+/// line next to them. False when there is no such line.
+fn move_line_block(lines: &mut [String], start: usize, len: usize, up: bool) -> bool {
+    if up && start > 0 && start + len <= lines.len() {
+        lines[start - 1..start + len].rotate_left(1);
+        true
+    } else if !up && start + len < lines.len() {
+        lines[start..start + len + 1].rotate_right(1);
+        true
+    } else {
+        false
+    }
+}
+
+/// Insert `text` (possibly spanning several lines) at the cursor, recording the
+/// edits into `edits` and leaving the cursor after the inserted text
+fn insert_text_at_cursor(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    text: &str,
+    edits: &mut Vec<Edit>,
+) -> bool {
     let idx = state.absolute_line();
     if idx >= lines.len() {
         return false;
     }
 
-    // Check if the pasted text ends with a newline (indicating complete lines)
+    // Check if the text ends with a newline (indicating complete lines)
     let text_ends_with_newline = text.ends_with('\n');
 
     let mut paste_lines: Vec<&str> = text.lines().collect();
@@ -264,28 +482,130 @@ pub(crate) fn handle_paste(
         state.modified = true;
     }
 
-    // Push all edits as a single composite so Ctrl+Z undoes the entire operation.
-    // pre_cursor = where cursor was before paste (for undo restoration).
-    // undo_cursor = where cursor is after paste (for redo restoration).
-    let absolute_line = state.absolute_line();
-    let undo_cursor = Some((absolute_line, state.cursor_col, state.multi_cursors.clone()));
-    state.undo_history.push_composite(edits, undo_cursor, pre_paste_cursor);
+    true
+}
 
-    state.undo_history.update_state(
-        state.top_line,
-        absolute_line,
-        state.cursor_col,
-        lines.clone(),
-    );
-    save_undo_with_timestamp(state, filename);
+/// Paste into every row of a column edit: over a block selection (replacing its
+/// columns) or at each multi-cursor. One-line text goes into every row; text with
+/// exactly one line per row is spread row by row. Each row keeps a cursor right
+/// after its pasted text. Returns false, changing nothing, when neither applies.
+fn paste_columns(
+    state: &mut FileViewerState,
+    lines: &mut [String],
+    text: &str,
+    filename: &str,
+) -> bool {
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    let rows: Vec<&str> = text
+        .split('\n')
+        .map(|row| row.strip_suffix('\r').unwrap_or(row))
+        .collect();
+    let rect = state.block_rect().filter(|_| state.has_selection());
+    let positions: Vec<Position> = match rect {
+        Some((top, bottom, left, _)) if bottom < lines.len() => (top..=bottom).map(|line| (line, left)).collect(),
+        Some(_) => return false,
+        None if state.has_multi_cursors() => state.all_cursor_positions(),
+        None => return false,
+    };
+    if positions.iter().any(|&(line, _)| line >= lines.len())
+        || (rows.len() != 1 && rows.len() != positions.len())
+    {
+        return false;
+    }
 
-    // Validate cursor after paste operation (debug only)
-    state.validate_cursor_invariants(lines);
+    let pre_cursor = Some((state.absolute_line(), state.cursor_col, state.multi_cursors.clone()));
+    let mut edits = Vec::new();
+    if let Some((top, bottom, left, right)) = rect {
+        remove_block_columns(lines, top, bottom, left, right, &mut edits);
+    }
+
+    // Without virtual space a cursor past a short line's end pastes at the line end
+    let virtual_space = state.settings.virtual_space;
+    let positions: Vec<Position> = positions
+        .into_iter()
+        .map(|(line, col)| if virtual_space { (line, col) } else { (line, col.min(char_len(&lines[line]))) })
+        .collect();
+    let row_text = |i: usize| if rows.len() == 1 { rows[0] } else { rows[i] };
+
+    // Right to left, bottom to top, so positions not yet handled stay valid
+    for (i, &(line_idx, col)) in positions.iter().enumerate().rev() {
+        for pad_col in char_len(&lines[line_idx])..col {
+            lines[line_idx].push(' ');
+            edits.push(Edit::InsertChar { line: line_idx, col: pad_col, ch: ' ' });
+        }
+        for (offset, ch) in row_text(i).chars().enumerate() {
+            edits.push(Edit::InsertChar { line: line_idx, col: col + offset, ch });
+        }
+        let byte_idx = char_index_to_byte_index(&lines[line_idx], col);
+        lines[line_idx].insert_str(byte_idx, row_text(i));
+    }
+
+    // Cursors end up after their text, shifted by earlier pastes on the same line
+    let mut cursors = Vec::with_capacity(positions.len());
+    let mut shift = (usize::MAX, 0);
+    for (i, &(line, col)) in positions.iter().enumerate() {
+        if shift.0 != line {
+            shift = (line, 0);
+        }
+        shift.1 += row_text(i).chars().count();
+        cursors.push((line, col + shift.1));
+    }
+    let (main_line, main_col) = cursors.remove(0);
+    state.clear_selection();
+    state.cursor_line = main_line.saturating_sub(state.top_line);
+    state.cursor_col = main_col;
+    state.desired_cursor_col = main_col;
+    state.multi_cursors = cursors;
 
+    let undo_cursor = Some((main_line, main_col, state.multi_cursors.clone()));
+    state.undo_history.push_composite(edits, undo_cursor, pre_cursor);
+    state.modified = true;
+    state
+        .undo_history
+        .update_state(state.top_line, main_line, main_col, lines.to_vec());
+    save_undo_with_timestamp(state, filename);
+    state.needs_redraw = true;
     true
 }
 
-// ...existing code...
+/// Remove columns `left..right` from lines `top..=bottom`, recording the deletes
+fn remove_block_columns(
+    lines: &mut [String],
+    top: usize,
+    bottom: usize,
+    left: usize,
+    right: usize,
+    edits: &mut Vec<Edit>,
+) {
+    for (line_idx, line) in lines.iter_mut().enumerate().take(bottom + 1).skip(top) {
+        let chars: Vec<char> = line.chars().collect();
+        let line_start = left.min(chars.len());
+        let line_end = right.min(chars.len());
+        if line_start >= line_end {
+            continue;
+        }
+        // Record deletes in reverse order
+        for (i, &ch) in chars[line_start..line_end].iter().enumerate().rev() {
+            edits.push(Edit::DeleteChar { line: line_idx, col: line_start + i, ch });
+        }
+        *line = chars[..line_start].iter().chain(chars[line_end..].iter()).collect();
+    }
+}
+
+/// Delete the columns of a block selection and continue with one cursor per row
+/// at its left edge, so the next keystroke or paste edits the same rows
+fn remove_block_to_cursors(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    filename: &str,
+) -> bool {
+    let Some((top, bottom, left, _)) = state.block_rect() else {
+        return false;
+    };
+    let removed = remove_selection(state, lines, filename);
+    activate_multi_cursor_from_block(state, lines, (top, left), (bottom, left));
+    removed
+}
 
 pub(crate) fn handle_cut(
     state: &mut FileViewerState,
@@ -295,13 +615,21 @@ pub(crate) fn handle_cut(
     if state.has_selection() {
         let (sel_start, sel_end) = (state.selection_start.unwrap(), state.selection_end.unwrap());
         let lines_refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
-        let selected_text = extract_selection(&lines_refs, sel_start, sel_end);
+        let block = state.block_rect();
+        let selected_text = match block {
+            Some((top, bottom, left, right)) => extract_block_selection(&lines_refs, top, bottom, left, right),
+            None => extract_selection(&lines_refs, sel_start, sel_end),
+        };
         let mut clipboard_guard = get_clipboard().lock().unwrap();
         if let Some(ref mut cb) = *clipboard_guard {
             let _ = cb.set_text(selected_text);
         }
-        let removed = remove_selection(state, lines, filename);
-        return removed;
+        drop(clipboard_guard);
+        return if block.is_some() {
+            remove_block_to_cursors(state, lines, filename)
+        } else {
+            remove_selection(state, lines, filename)
+        };
     }
     let abs = state.absolute_line();
     if abs >= lines.len() {
@@ -370,45 +698,18 @@ pub(crate) fn remove_selection(
         return false;
     }
 
-    if state.block_selection {
+    if let Some((top, bottom, left, right)) = state.block_rect() {
         // Block selection deletion - remove column range from each line
         let mut edits = Vec::new();
+        remove_block_columns(lines, top, bottom, left, right, &mut edits);
 
-        for line_idx in s_line..=e_line {
-            if line_idx >= lines.len() {
-                break;
-            }
-            let line = &mut lines[line_idx];
-            let chars: Vec<char> = line.chars().collect();
-            let line_start = s_col.min(chars.len());
-            let line_end = e_col.min(chars.len());
-
-            if line_start < line_end {
-                let removed: Vec<char> = chars[line_start..line_end].to_vec();
-                // Record deletes in reverse order
-                for (i, ch) in removed.into_iter().enumerate().rev() {
-                    edits.push(Edit::DeleteChar {
-                        line: line_idx,
-                        col: line_start + i,
-                        ch,
-                    });
-                }
-                // Rebuild line without the removed range
-                let new_line: String = chars[..line_start]
-                    .iter()
-                    .chain(chars[line_end..].iter())
-                    .collect();
-                *line = new_line;
-            }
-        }
-
-        let undo_cursor = Some((s_line, s_col, state.multi_cursors.clone()));
+        let undo_cursor = Some((top, left, state.multi_cursors.clone()));
         // Push all deletes as a single composite edit
         state.undo_history.push_composite(edits, undo_cursor, None);
 
-        // Position cursor at start of selection
-        state.cursor_line = s_line.saturating_sub(state.top_line);
-        state.cursor_col = s_col;
+        // Position cursor at the block's top-left corner
+        state.cursor_line = top.saturating_sub(state.top_line);
+        state.cursor_col = left;
         state.clear_selection();
         state.modified = true;
         let absolute_line = state.absolute_line();
@@ -530,13 +831,26 @@ pub(crate) fn insert_char(
         let line_char_len = char_len(&lines[idx]);
         if state.cursor_col <= line_char_len {
             let byte_idx = char_index_to_byte_index(&lines[idx], state.cursor_col);
-            lines[idx].insert(byte_idx, c);
-
-            state.undo_history.push(Edit::InsertChar {
+            let insert = Edit::InsertChar {
                 line: idx,
                 col: state.cursor_col,
                 ch: c,
-            });
+            };
+            // In overwrite mode the character under the cursor is replaced;
+            // at the end of the line typing appends as usual
+            if state.overwrite_mode && state.cursor_col < line_char_len {
+                let old = lines[idx][byte_idx..].chars().next().unwrap();
+                lines[idx].replace_range(byte_idx..byte_idx + old.len_utf8(), c.encode_utf8(&mut [0; 4]));
+                let delete = Edit::DeleteChar {
+                    line: idx,
+                    col: state.cursor_col,
+                    ch: old,
+                };
+                state.undo_history.push_composite(vec![delete, insert], None, None);
+            } else {
+                lines[idx].insert(byte_idx, c);
+                state.undo_history.push_typing(insert);
+            }
             state.cursor_col += 1;
             state.cursor_at_wrap_end = false; // Clear wrap end flag after typing
             state.desired_cursor_col = state.cursor_col;
@@ -558,73 +872,6 @@ pub(crate) fn insert_char(
     }
 }
 
-// Silence dead_code warning for insert_char_block used only in tests
-#[allow(dead_code)]
-/// Insert character on multiple lines for zero-width block selection
-fn insert_char_block(
-    state: &mut FileViewerState,
-    lines: &mut [String],
-    c: char,
-    filename: &str,
-) -> bool {
-    if !state.has_selection() || !state.block_selection {
-        return false;
-    }
-
-    let (sel_start, sel_end) = {
-        let s = state.selection_start.unwrap();
-        let e = state.selection_end.unwrap();
-        if s.0 < e.0 || (s.0 == e.0 && s.1 <= e.1) {
-            (s, e)
-        } else {
-            (e, s)
-        }
-    };
-
-    let (s_line, s_col) = sel_start;
-    let (e_line, e_col) = sel_end;
-
-    // Only handle zero-width selections (multi-cursor)
-    if s_col != e_col {
-        return false;
-    }
-
-    // Insert character on each line in the block
-    let mut inserted = false;
-    for line_idx in s_line..=e_line {
-        if line_idx < lines.len() {
-            let line = &mut lines[line_idx];
-            let insert_col = s_col.min(line.len());
-            line.insert(insert_col, c);
-            state.undo_history.push(Edit::InsertChar {
-                line: line_idx,
-                col: insert_col,
-                ch: c,
-            });
-            inserted = true;
-        }
-    }
-
-    if inserted {
-        // Move cursor and selection one column to the right
-        state.cursor_col = s_col + 1;
-        state.selection_start = Some((s_line, s_col + 1));
-        state.selection_end = Some((e_line, e_col + 1));
-
-        let absolute_line = state.absolute_line();
-        state.undo_history.update_state(
-            state.top_line,
-            absolute_line,
-            state.cursor_col,
-            lines.to_vec(),
-        );
-        save_undo_with_timestamp(state, filename);
-        true
-    } else {
-        false
-    }
-}
-
 pub(crate) fn split_line(
     state: &mut FileViewerState,
     lines: &mut Vec<String>,
@@ -639,6 +886,22 @@ pub(crate) fn split_line(
     let split_at_byte = char_index_to_byte_index(&lines[idx], split_at_char);
     let line_clone = lines[idx].clone();
     let (before, after) = line_clone.split_at(split_at_byte);
+    if state.settings.electric_indent
+        && is_bracket_pair(before.chars().last(), after.chars().next())
+    {
+        return split_bracket_pair(state, lines, visible_lines, filename, split_at_char);
+    }
+    // Splitting inside the leading indentation stays a plain split
+    if state.settings.electric_indent && !before.trim().is_empty() {
+        let trimmed = before.trim_end();
+        let deeper = after.trim().is_empty()
+            && crate::syntax::indent_after_tokens()
+                .iter()
+                .any(|t| trimmed.ends_with(t.as_str()));
+        if deeper || !leading_indent(before).is_empty() {
+            return split_line_indented(state, lines, visible_lines, filename, split_at_char, deeper);
+        }
+    }
     state.undo_history.push(Edit::SplitLine {
         line: idx,
         col: split_at_char,
@@ -665,31 +928,226 @@ pub(crate) fn split_line(
     true
 }
 
-pub(crate) fn delete_backward(
+/// Open a blank line below (or above) the current one without splitting it and
+/// move the cursor there, indented like the current line (one level deeper below
+/// a line ending in an indent-increasing token). One undo step.
+pub(crate) fn open_line(
     state: &mut FileViewerState,
     lines: &mut Vec<String>,
+    above: bool,
+    visible_lines: usize,
     filename: &str,
 ) -> bool {
     let idx = state.absolute_line();
     if idx >= lines.len() {
         return false;
     }
-    if state.cursor_col > 0 && state.cursor_col <= char_len(&lines[idx]) {
-        let ch = lines[idx].chars().nth(state.cursor_col - 1).unwrap();
-        let byte_idx = char_index_to_byte_index(&lines[idx], state.cursor_col - 1);
-        lines[idx].remove(byte_idx);
-        state.undo_history.push(Edit::DeleteChar {
-            line: idx,
-            col: state.cursor_col - 1,
-            ch,
-        });
-        state.cursor_col -= 1;
-        state.desired_cursor_col = state.cursor_col;
-        state
-            .undo_history
-            .update_state(state.top_line, idx, state.cursor_col, lines.to_vec());
-        save_undo_with_timestamp(state, filename);
-        true
+    let current = &lines[idx];
+    let mut indent = leading_indent(current);
+    if !above && state.settings.electric_indent {
+        let trimmed = current.trim_end();
+        if crate::syntax::indent_after_tokens()
+            .iter()
+            .any(|t| trimmed.ends_with(t.as_str()))
+        {
+            indent.push_str(&indent_unit(&indent, state.settings.indent_size()));
+        }
+    }
+    let new_idx = if above { idx } else { idx + 1 };
+
+    state.clear_selection();
+    state.clear_multi_cursors();
+    if state.saved_absolute_cursor.is_some() {
+        // Cursor scrolled out of view: bring it back before moving it
+        state.set_cursor_position(idx, state.cursor_col, lines, visible_lines);
+    }
+    let pre_cursor = Some((idx, state.cursor_col, Vec::new()));
+    lines.insert(new_idx, indent.clone());
+    if !above {
+        if state.cursor_line + 1 < visible_lines {
+            state.cursor_line += 1;
+        } else {
+            state.top_line += 1;
+        }
+    }
+    state.cursor_col = char_len(&indent);
+    state.desired_cursor_col = state.cursor_col;
+    let absolute_line = state.absolute_line();
+    let undo_cursor = Some((absolute_line, state.cursor_col, Vec::new()));
+    state.undo_history.push_composite(
+        vec![Edit::InsertLine { line: new_idx, content: indent }],
+        undo_cursor,
+        pre_cursor,
+    );
+    state.undo_history.update_state(
+        state.top_line,
+        absolute_line,
+        state.cursor_col,
+        lines.clone(),
+    );
+    save_undo_with_timestamp(state, filename);
+    true
+}
+
+fn leading_indent(line: &str) -> String {
+    line.chars().take_while(|c| *c == ' ' || *c == '\t').collect()
+}
+
+/// One indentation level, matching the style (tabs or spaces) of `indent`
+fn indent_unit(indent: &str, indent_size: usize) -> String {
+    if indent.starts_with('\t') {
+        "\t".to_string()
+    } else {
+        " ".repeat(indent_size)
+    }
+}
+
+/// Enter with electric indent: the new line keeps the indentation of the current one,
+/// one level deeper at the end of a line ending in an indent-increasing token (see
+/// `indent_after|` in the syntax files).
+fn split_line_indented(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    visible_lines: usize,
+    filename: &str,
+    split_at_char: usize,
+    deeper: bool,
+) -> bool {
+    let idx = state.absolute_line();
+    let original = lines[idx].clone();
+    let split_at_byte = char_index_to_byte_index(&original, split_at_char);
+    let (before, after) = original.split_at(split_at_byte);
+    let mut indent = leading_indent(&original);
+    if deeper {
+        indent.push_str(&indent_unit(&indent, state.settings.indent_size()));
+    }
+    let new_line = format!("{}{}", indent, after.trim_start());
+
+    let pre_cursor = Some((idx, state.cursor_col, state.multi_cursors.clone()));
+    let edits = vec![
+        Edit::SplitLine {
+            line: idx,
+            col: split_at_char,
+            before: before.to_string(),
+            after: after.to_string(),
+        },
+        Edit::ReplaceLine {
+            line: idx + 1,
+            old_content: after.to_string(),
+            new_content: new_line.clone(),
+        },
+    ];
+    lines[idx] = before.to_string();
+    lines.insert(idx + 1, new_line.clone());
+
+    if state.cursor_line + 1 < visible_lines {
+        state.cursor_line += 1;
+    } else {
+        state.top_line += 1;
+    }
+    state.cursor_col = char_len(&indent);
+    state.desired_cursor_col = state.cursor_col;
+    let absolute_line = state.absolute_line();
+    let undo_cursor = Some((absolute_line, state.cursor_col, state.multi_cursors.clone()));
+    state.undo_history.push_composite(edits, undo_cursor, pre_cursor);
+    state.undo_history.update_state(
+        state.top_line,
+        absolute_line,
+        state.cursor_col,
+        lines.clone(),
+    );
+    save_undo_with_timestamp(state, filename);
+    true
+}
+
+fn is_bracket_pair(open: Option<char>, close: Option<char>) -> bool {
+    matches!((open, close), (Some('{'), Some('}')) | (Some('('), Some(')')) | (Some('['), Some(']')))
+}
+
+/// Enter between an opening and closing bracket: put the closer on its own line with the
+/// current indentation and leave the cursor on an indented blank line in between.
+fn split_bracket_pair(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    visible_lines: usize,
+    filename: &str,
+    split_at_char: usize,
+) -> bool {
+    let idx = state.absolute_line();
+    let original = lines[idx].clone();
+    let split_at_byte = char_index_to_byte_index(&original, split_at_char);
+    let (before, after) = original.split_at(split_at_byte);
+    let indent = leading_indent(&original);
+    let inner = format!("{}{}", indent, indent_unit(&indent, state.settings.indent_size()));
+    let closer = format!("{}{}", indent, after);
+
+    let pre_cursor = Some((idx, state.cursor_col, state.multi_cursors.clone()));
+    let edits = vec![
+        Edit::SplitLine {
+            line: idx,
+            col: split_at_char,
+            before: before.to_string(),
+            after: after.to_string(),
+        },
+        Edit::ReplaceLine {
+            line: idx + 1,
+            old_content: after.to_string(),
+            new_content: closer.clone(),
+        },
+        Edit::InsertLine {
+            line: idx + 1,
+            content: inner.clone(),
+        },
+    ];
+    lines[idx] = before.to_string();
+    lines.insert(idx + 1, inner.clone());
+    lines.insert(idx + 2, closer);
+
+    if state.cursor_line + 1 < visible_lines {
+        state.cursor_line += 1;
+    } else {
+        state.top_line += 1;
+    }
+    state.cursor_col = char_len(&inner);
+    state.desired_cursor_col = state.cursor_col;
+    let absolute_line = state.absolute_line();
+    let undo_cursor = Some((absolute_line, state.cursor_col, state.multi_cursors.clone()));
+    state.undo_history.push_composite(edits, undo_cursor, pre_cursor);
+    state.undo_history.update_state(
+        state.top_line,
+        absolute_line,
+        state.cursor_col,
+        lines.clone(),
+    );
+    save_undo_with_timestamp(state, filename);
+    true
+}
+
+pub(crate) fn delete_backward(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    filename: &str,
+) -> bool {
+    let idx = state.absolute_line();
+    if idx >= lines.len() {
+        return false;
+    }
+    if state.cursor_col > 0 && state.cursor_col <= char_len(&lines[idx]) {
+        let ch = lines[idx].chars().nth(state.cursor_col - 1).unwrap();
+        let byte_idx = char_index_to_byte_index(&lines[idx], state.cursor_col - 1);
+        lines[idx].remove(byte_idx);
+        state.undo_history.push_typing(Edit::DeleteChar {
+            line: idx,
+            col: state.cursor_col - 1,
+            ch,
+        });
+        state.cursor_col -= 1;
+        state.desired_cursor_col = state.cursor_col;
+        state
+            .undo_history
+            .update_state(state.top_line, idx, state.cursor_col, lines.to_vec());
+        save_undo_with_timestamp(state, filename);
+        true
     } else if idx > 0 {
         let current = lines.remove(idx);
         let prev_len = char_len(&lines[idx - 1]);
@@ -739,7 +1197,7 @@ pub(crate) fn delete_forward(
         let ch = lines[idx].chars().nth(state.cursor_col).unwrap();
         let byte_idx = char_index_to_byte_index(&lines[idx], state.cursor_col);
         lines[idx].remove(byte_idx);
-        state.undo_history.push(Edit::DeleteChar {
+        state.undo_history.push_typing(Edit::DeleteChar {
             line: idx,
             col: state.cursor_col,
             ch,
@@ -788,30 +1246,23 @@ pub(crate) fn delete_word_backward(
         return delete_backward(state, lines, filename);
     }
 
-    let line = &lines[idx];
-    let start_col = state.cursor_col;
+    // Collect once: indexing with chars().nth() per step is quadratic on long lines
+    let chars: Vec<char> = lines[idx].chars().collect();
+    let start_col = state.cursor_col.min(chars.len());
     let mut end_col = start_col;
 
     // Find the start of the word to delete
     // First skip any non-word characters (whitespace/punctuation)
-    while end_col > 0 {
-        let c = line.chars().nth(end_col - 1).unwrap_or(' ');
-        if is_word_char(c) {
-            break;
-        }
+    while end_col > 0 && !is_word_char(chars[end_col - 1]) {
         end_col -= 1;
     }
     // Then skip word characters
-    while end_col > 0 {
-        let c = line.chars().nth(end_col - 1).unwrap_or(' ');
-        if !is_word_char(c) {
-            break;
-        }
+    while end_col > 0 && is_word_char(chars[end_col - 1]) {
         end_col -= 1;
     }
 
     // Delete characters from end_col to start_col
-    let deleted_text: String = line.chars().skip(end_col).take(start_col - end_col).collect();
+    let deleted_text: String = chars[end_col..start_col].iter().collect();
     
     // Create single undo entry for the entire word deletion
     state.undo_history.push(Edit::DeleteWord {
@@ -845,10 +1296,10 @@ pub(crate) fn delete_word_forward(
         return false;
     }
 
-    let line = &lines[idx];
+    let chars: Vec<char> = lines[idx].chars().collect();
     let start_col = state.cursor_col;
 
-    if start_col >= line.len() {
+    if start_col >= chars.len() {
         // At end of line, behave like regular delete (merge with next line)
         return delete_forward(state, lines, filename);
     }
@@ -857,25 +1308,16 @@ pub(crate) fn delete_word_forward(
 
     // Find the end of the word to delete
     // First skip any non-word characters (whitespace/punctuation)
-    while end_col < line.len() {
-        let c = line.chars().nth(end_col).unwrap_or(' ');
-        if is_word_char(c) {
-            break;
-        }
+    while end_col < chars.len() && !is_word_char(chars[end_col]) {
         end_col += 1;
     }
     // Then skip word characters
-    let line_char_len = char_len(line);
-    while end_col < line_char_len {
-        let c = line.chars().nth(end_col).unwrap_or(' ');
-        if !is_word_char(c) {
-            break;
-        }
+    while end_col < chars.len() && is_word_char(chars[end_col]) {
         end_col += 1;
     }
 
     // Delete characters from start_col to end_col
-    let deleted_text: String = line.chars().skip(start_col).take(end_col - start_col).collect();
+    let deleted_text: String = chars[start_col..end_col].iter().collect();
     
     // Create single undo entry for the entire word deletion
     state.undo_history.push(Edit::DeleteWord {
@@ -901,25 +1343,47 @@ fn is_word_char(c: char) -> bool {
     c.is_alphanumeric() || c == '_'
 }
 
+/// Text Tab inserts at char column `col`: `tab_leading` decides within the line's
+/// leading whitespace, `tab_inline` everywhere else. "indent" fills up to the next
+/// indentation stop (a tab on tab-indented lines), "spaces" inserts `indent_size`
+/// spaces and "tab" a tab character.
+fn tab_text(line: &str, col: usize, settings: &crate::settings::Settings) -> String {
+    let indent = leading_indent(line);
+    let action = if col <= indent.chars().count() {
+        settings.tab_leading.as_str()
+    } else {
+        settings.tab_inline.as_str()
+    };
+    let indent_size = settings.indent_size();
+    match action {
+        "tab" => "\t".to_string(),
+        "indent" if indent.starts_with('\t') => "\t".to_string(),
+        "indent" => {
+            let visual_col = crate::coordinates::visual_width_up_to(line, col, settings.tab_width);
+            " ".repeat(indent_size - visual_col % indent_size)
+        }
+        _ => " ".repeat(indent_size),
+    }
+}
+
 pub(crate) fn insert_tab(
     state: &mut FileViewerState,
     lines: &mut [String],
     filename: &str,
 ) -> bool {
     let idx = state.absolute_line();
-    let tab_width = state.settings.tab_width;
     if idx < lines.len() && state.cursor_col <= char_len(&lines[idx]) {
+        let text = tab_text(&lines[idx], state.cursor_col, state.settings);
         let byte_idx = char_index_to_byte_index(&lines[idx], state.cursor_col);
-        let spaces = " ".repeat(tab_width);
-        lines[idx].insert_str(byte_idx, &spaces);
-        for (i, _) in spaces.chars().enumerate() {
+        lines[idx].insert_str(byte_idx, &text);
+        for (i, ch) in text.chars().enumerate() {
             state.undo_history.push(Edit::InsertChar {
                 line: idx,
                 col: state.cursor_col + i,
-                ch: ' ',
+                ch,
             });
         }
-        state.cursor_col += tab_width;
+        state.cursor_col += text.chars().count();
         state.desired_cursor_col = state.cursor_col;
         state
             .undo_history
@@ -931,9 +1395,19 @@ pub(crate) fn insert_tab(
     }
 }
 
-/// Delete the undo history file for the given file path and remove empty parent directories
-pub fn delete_file_history(file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let history_path = crate::undo::UndoHistory::history_path_for(file_path)?;
+/// Delete the undo history file for the given file path (or untitled buffer)
+/// and remove it from the recent files
+pub fn delete_file_history(file_path: &str, untitled: bool) -> Result<(), Box<dyn std::error::Error>> {
+    delete_history_file(file_path, untitled)?;
+    // Also remove from recent files list to keep both in sync
+    let _ = crate::recent::remove_recent_file(file_path);
+    Ok(())
+}
+
+/// Delete only the undo history file for the given file path (or untitled buffer)
+/// and remove empty parent directories
+pub(crate) fn delete_history_file(file_path: &str, untitled: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = crate::undo::UndoHistory::history_path_for(file_path, untitled)?;
     if history_path.exists() {
         fs::remove_file(&history_path)?;
     }
@@ -959,54 +1433,183 @@ pub fn delete_file_history(file_path: &str) -> Result<(), Box<dyn std::error::Er
             }
         }
     }
-    // Also remove from recent files list to keep both in sync
-    let _ = crate::recent::remove_recent_file(file_path);
     Ok(())
 }
 
-/// Save file content to disk
-pub(crate) fn save_file(path: &str, lines: &[String]) -> Result<(), std::io::Error> {
+/// Move the file `from` to `to` on disk, taking its undo history and recent
+/// files entry along
+pub(crate) fn rename_file(from: &str, to: &str) -> Result<(), std::io::Error> {
+    // Recent files hold canonical paths, which cannot be resolved after the move
+    let canonical_from = fs::canonicalize(from).map(|p| p.to_string_lossy().to_string());
+    fs::rename(from, to)?;
+    if let (Ok(old), Ok(new)) = (
+        crate::undo::UndoHistory::history_path_for(from, false),
+        crate::undo::UndoHistory::history_path_for(to, false),
+    ) && old.exists()
+    {
+        if let Some(parent) = new.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let _ = fs::rename(&old, &new);
+    }
+    // Clean up the emptied history directories
+    let _ = delete_history_file(from, false);
+    let _ = crate::recent::remove_recent_file(canonical_from.as_deref().unwrap_or(from));
+    let _ = crate::recent::update_recent_file(to);
+    Ok(())
+}
+
+/// Save file content to disk in the given encoding, with `line_ending` between lines
+pub(crate) fn save_file(
+    path: &str,
+    lines: &[String],
+    encoding: crate::encoding::FileEncoding,
+    line_ending: crate::line_ending::LineEnding,
+) -> Result<(), std::io::Error> {
     // Construct content with newlines preserved; assume lines vector does not include trailing newline for last line
     let mut content = String::new();
     for (i, line) in lines.iter().enumerate() {
         content.push_str(line);
         if i + 1 < lines.len() {
-            content.push('\n');
+            content.push_str(line_ending.as_str());
         }
     }
-    fs::write(path, content)?;
+    fs::write(path, encoding.encode(&content))?;
     Ok(())
 }
 
-pub(crate) fn apply_undo(
+/// Write a modified buffer for `autosave_seconds`. Untitled buffers have no file
+/// yet, so their content goes to the undo history, which restores them after a
+/// crash. A new file whose directory is missing is left alone rather than
+/// prompting. Returns whether the file was written.
+pub(crate) fn autosave(
     state: &mut FileViewerState,
-    lines: &mut Vec<String>,
+    lines: &[String],
     filename: &str,
-    visible_lines: usize,
-) -> bool {
-    if let Some(edit) = state.undo_history.undo() {
-        let result = match &edit {
-            Edit::CompositeEdit { edits, undo_cursor, pre_cursor } => {
-                // Undo composite edit: apply all edits in reverse order
-                let mut success = true;
-                for e in edits.iter().rev() {
-                    if !apply_single_undo_edit(state, lines, e) {
-                        success = false;
-                    }
+) -> Result<bool, std::io::Error> {
+    if !state.modified || state.is_read_only || state.is_scratch || state.follow.is_some() {
+        return Ok(false);
+    }
+    if state.is_untitled {
+        let abs = state.absolute_line();
+        state.undo_history.update_state(state.top_line, abs, state.cursor_col, lines.to_vec());
+        save_undo_with_timestamp(state, filename);
+        return Ok(false);
+    }
+    let parent_exists = std::path::Path::new(filename)
+        .parent()
+        .is_none_or(|p| p.as_os_str().is_empty() || p.is_dir());
+    if !parent_exists {
+        return Ok(false);
+    }
+    save_file(filename, lines, state.encoding, state.line_ending)?;
+    state.disk_stamp = crate::file_watch::DiskStamp::read(filename);
+    state.modified = false;
+    state.is_new = false;
+    state.undo_history.clear_unsaved_state();
+    save_undo_with_timestamp(state, filename);
+    Ok(true)
+}
+
+/// Tracked files other than `current` whose undo history holds unsaved changes
+pub(crate) fn unsaved_files_except(current: &str) -> Vec<String> {
+    let current_canonical = std::path::Path::new(current)
+        .canonicalize()
+        .unwrap_or_else(|_| std::path::PathBuf::from(current));
+    crate::recent::get_recent_files()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| p.canonicalize().unwrap_or_else(|_| p.clone()) != current_canonical)
+        .filter(|p| crate::menu::check_file_has_unsaved_changes(p))
+        .map(|p| p.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Write the unsaved content kept in a file's undo history to disk.
+/// Untitled buffers have nowhere to go and are left untouched.
+pub(crate) fn save_unsaved_from_history(path: &str) -> Result<(), std::io::Error> {
+    if crate::ui::is_untitled(path) {
+        return Ok(());
+    }
+    let mut history = crate::undo::UndoHistory::load(path, false)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    if let Some(content) = history.file_content.clone() {
+        // Keep whatever encoding and line ending the file currently has on disk
+        let (encoding, line_ending) = crate::encoding::read_file(path)
+            .map(|(text, encoding)| (encoding, crate::line_ending::LineEnding::detect(&text)))
+            .unwrap_or_default();
+        save_file(path, &content, encoding, line_ending)?;
+    }
+    history.clear_unsaved_state();
+    history.save(path).map_err(|e| std::io::Error::other(e.to_string()))
+}
+
+/// Throw away the unsaved changes kept in a file's undo history
+pub(crate) fn discard_unsaved_from_history(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut history = crate::undo::UndoHistory::load(path, crate::ui::is_untitled(path))?;
+    history.discard_unsaved_state();
+    history.save(path)
+}
+
+/// Revert one undo step in the buffer without persisting anything.
+/// None when there is nothing left to undo.
+fn undo_step(state: &mut FileViewerState, lines: &mut Vec<String>) -> Option<bool> {
+    let edit = state.undo_history.undo()?;
+    Some(match &edit {
+        Edit::CompositeEdit { edits, undo_cursor, pre_cursor } => {
+            // Undo composite edit: apply all edits in reverse order
+            let mut success = true;
+            for e in edits.iter().rev() {
+                if !apply_single_undo_edit(state, lines, e) {
+                    success = false;
                 }
-                // Restore cursor: prefer pre_cursor (pre-edit position) if set,
-                // otherwise fall back to undo_cursor for backward compatibility.
-                let cursor_to_restore = pre_cursor.as_ref().or(undo_cursor.as_ref());
-                if let Some((line, col, multi)) = cursor_to_restore {
-                    state.cursor_line = line.saturating_sub(state.top_line);
-                    state.cursor_col = *col;
-                    state.multi_cursors = multi.clone();
+            }
+            // Restore cursor: prefer pre_cursor (pre-edit position) if set,
+            // otherwise fall back to undo_cursor for backward compatibility.
+            let cursor_to_restore = pre_cursor.as_ref().or(undo_cursor.as_ref());
+            if let Some((line, col, multi)) = cursor_to_restore {
+                state.cursor_line = line.saturating_sub(state.top_line);
+                state.cursor_col = *col;
+                state.multi_cursors = multi.clone();
+            }
+            success
+        }
+        _ => apply_single_undo_edit(state, lines, &edit),
+    })
+}
+
+/// Reapply one undone step in the buffer without persisting anything.
+/// None when there is nothing left to redo.
+fn redo_step(state: &mut FileViewerState, lines: &mut Vec<String>) -> Option<bool> {
+    let edit = state.undo_history.redo()?;
+    Some(match &edit {
+        Edit::CompositeEdit { edits, undo_cursor, .. } => {
+            // Redo composite edit: apply all edits in forward order
+            let mut success = true;
+            for e in edits.iter() {
+                if !apply_single_redo_edit(state, lines, e) {
+                    success = false;
                 }
-                success
             }
-            _ => apply_single_undo_edit(state, lines, &edit),
-        };
+            // Restore post-edit cursor (undo_cursor stores the post-edit position for redo)
+            if let Some((line, col, multi)) = undo_cursor {
+                state.cursor_line = line.saturating_sub(state.top_line);
+                state.cursor_col = *col;
+                state.multi_cursors = multi.clone();
+            }
+            success
+        }
+        _ => apply_single_redo_edit(state, lines, &edit),
+    })
+}
 
+pub(crate) fn apply_undo(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    filename: &str,
+    visible_lines: usize,
+) -> bool {
+    if let Some(result) = undo_step(state, lines) {
         if result {
             state.ensure_cursor_visible(visible_lines, lines);
             let absolute_line = state.absolute_line();
@@ -1113,6 +1716,11 @@ fn apply_single_undo_edit(
                 false
             }
         }
+        Edit::MoveLines { start, len, up } => {
+            // Undo move: move the block back from where it ended up
+            let moved_to = if *up { start.saturating_sub(1) } else { start + 1 };
+            move_line_block(lines, moved_to, *len, !*up)
+        }
         Edit::DragBlock { before, .. } => {
             *lines = before.clone();
             // Cursor remains; ensure visibility
@@ -1162,27 +1770,7 @@ pub(crate) fn apply_redo(
     filename: &str,
     visible_lines: usize,
 ) -> bool {
-    if let Some(edit) = state.undo_history.redo() {
-        let result = match &edit {
-            Edit::CompositeEdit { edits, undo_cursor, .. } => {
-                // Redo composite edit: apply all edits in forward order
-                let mut success = true;
-                for e in edits.iter() {
-                    if !apply_single_redo_edit(state, lines, e) {
-                        success = false;
-                    }
-                }
-                // Restore post-edit cursor (undo_cursor stores the post-edit position for redo)
-                if let Some((line, col, multi)) = undo_cursor {
-                    state.cursor_line = line.saturating_sub(state.top_line);
-                    state.cursor_col = *col;
-                    state.multi_cursors = multi.clone();
-                }
-                success
-            }
-            _ => apply_single_redo_edit(state, lines, &edit),
-        };
-
+    if let Some(result) = redo_step(state, lines) {
         if result {
             state.ensure_cursor_visible(visible_lines, lines);
             let absolute_line = state.absolute_line();
@@ -1198,6 +1786,69 @@ pub(crate) fn apply_redo(
     }
 }
 
+/// Parse a time offset for `undo_to_time`: a number with an optional unit
+/// (`s`, `m`, `h`, `d`; minutes when omitted). Plain or `-` goes back in time,
+/// `+` forward. Returns signed seconds.
+pub(crate) fn parse_time_offset(input: &str) -> Option<i64> {
+    let input = input.trim();
+    let (sign, rest) = match input.strip_prefix('+') {
+        Some(rest) => (1, rest),
+        None => (-1, input.strip_prefix('-').unwrap_or(input)),
+    };
+    let rest = rest.trim();
+    let unit_at = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    let amount: i64 = rest[..unit_at].parse().ok()?;
+    let unit = match rest[unit_at..].trim() {
+        "s" | "sec" => 1,
+        "" | "m" | "min" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        _ => return None,
+    };
+    amount.checked_mul(unit).map(|secs| sign * secs)
+}
+
+/// Undo or redo until the buffer is as it was `offset_secs` away from the
+/// current state: from now when nothing is undone, otherwise from when the
+/// current state was reached. Returns the number of steps redone (negative:
+/// undone).
+pub(crate) fn undo_to_time(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    filename: &str,
+    visible_lines: usize,
+    offset_secs: i64,
+) -> isize {
+    let history = &state.undo_history;
+    let base = if history.current == history.edits.len() {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs())
+    } else {
+        history.current_time()
+    };
+    let target = history.position_at_time(base.saturating_add_signed(offset_secs));
+
+    let mut steps: isize = 0;
+    while state.undo_history.current > target && undo_step(state, lines).is_some() {
+        steps -= 1;
+    }
+    while state.undo_history.current < target && redo_step(state, lines).is_some() {
+        steps += 1;
+    }
+    if steps != 0 {
+        state.clamp_cursor_to_line_bounds(lines);
+        state.ensure_cursor_visible(visible_lines, lines);
+        let absolute_line = state.absolute_line();
+        state
+            .undo_history
+            .update_state(state.top_line, absolute_line, state.cursor_col, lines.clone());
+        state.modified = state.undo_history.modified;
+        save_undo_with_timestamp(state, filename);
+    }
+    steps
+}
+
 fn apply_single_redo_edit(
     state: &mut FileViewerState,
     lines: &mut Vec<String>,
@@ -1280,6 +1931,7 @@ fn apply_single_redo_edit(
                 false
             }
         }
+        Edit::MoveLines { start, len, up } => move_line_block(lines, *start, *len, *up),
         Edit::DragBlock { after, .. } => {
             *lines = after.clone();
             true
@@ -1327,20 +1979,29 @@ fn apply_single_redo_edit(
 /// Convert a block selection into multi-cursor mode at the block's start column
 fn activate_multi_cursor_from_block(
     state: &mut FileViewerState,
+    lines: &[String],
     start: Position,
     end: Position,
 ) {
     let (start_line, start_col) = start;
     let end_line = end.0.max(start_line);
+    // Without virtual space, cursors on lines shorter than the block snap to the line end
+    let virtual_space = state.settings.virtual_space;
+    let col_on = |line: usize| -> usize {
+        match lines.get(line) {
+            Some(text) if !virtual_space => start_col.min(char_len(text)),
+            _ => start_col,
+        }
+    };
 
     // Position main cursor on the first line of the block
     state.cursor_line = start_line.saturating_sub(state.top_line);
-    state.cursor_col = start_col;
+    state.cursor_col = col_on(start_line);
 
     // Populate multi-cursors for remaining lines in the block
     state.multi_cursors.clear();
     for line in start_line + 1..=end_line {
-        state.multi_cursors.push((line, start_col));
+        state.multi_cursors.push((line, col_on(line)));
     }
 
     // Exit block selection mode
@@ -1351,43 +2012,405 @@ fn activate_multi_cursor_from_block(
     state.needs_redraw = true;
 }
 
-pub(crate) fn handle_editing_keys(
+/// Split a multi-line selection into one cursor per selected line ("add cursors to line ends").
+/// Each line gets a cursor at its end, except the last line where the cursor sits at the
+/// selection end. A selection ending at column 0 does not claim that last line.
+/// The main cursor goes on the last line; the others become multi-cursors.
+pub(crate) fn split_selection_into_cursors(
     state: &mut FileViewerState,
-    lines: &mut Vec<String>,
-    code: &crossterm::event::KeyCode,
-    modifiers: &crossterm::event::KeyModifiers,
+    lines: &[String],
     visible_lines: usize,
-    filename: &str,
 ) -> bool {
-    use crossterm::event::{KeyCode, KeyModifiers};
+    if state.block_selection {
+        return false;
+    }
+    let Some((start, end)) = state.selection_range() else {
+        return false;
+    };
+    if start.0 == end.0 {
+        return false;
+    }
 
+    let last_line = if end.1 == 0 { end.0 - 1 } else { end.0 };
+    let mut positions: Vec<Position> = (start.0..=last_line.min(lines.len().saturating_sub(1)))
+        .map(|line| {
+            let col = if line == end.0 { end.1 } else { char_len(&lines[line]) };
+            (line, col)
+        })
+        .collect();
+    let Some((main_line, main_col)) = positions.pop() else {
+        return false;
+    };
 
-    // If a zero-width block selection is active, convert it to multi-cursors for editing keys
-    if state.block_selection
-        && let Some((start, end)) = state.selection_range() {
-            let is_zero_width_block = start.1 == end.1 && start.0 != end.0;
-            if is_zero_width_block
-                && matches!(code, KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Delete)
-            {
-                activate_multi_cursor_from_block(state, start, end);
-            }
-        }
+    state.clear_selection();
+    state.multi_cursors = positions;
+    state.set_cursor_position(main_line, main_col, lines, visible_lines);
+    state.desired_cursor_col = state.cursor_col;
+    state.needs_redraw = true;
+    true
+}
 
-    // Handle multi-cursor typing
-    if state.has_multi_cursors() {
-        match code {
-            KeyCode::Char(c)
-                if !modifiers.contains(KeyModifiers::CONTROL)
-                    && !modifiers.contains(KeyModifiers::ALT) =>
-            {
-                return insert_char_multi_cursor(state, lines, *c, filename);
+/// Toggle comments on the selected lines (or the cursor line) using the file's syntax:
+/// line comments when the syntax has them, otherwise the selection (or the cursor
+/// line's text) is wrapped in block-comment delimiters, or unwrapped when already
+/// commented. One undo step. Errors are footer messages.
+pub(crate) fn toggle_comment(
+    state: &mut FileViewerState,
+    lines: &mut [String],
+    filename: &str,
+) -> Result<bool, String> {
+    if lines.is_empty() {
+        return Ok(false);
+    }
+    let style = crate::syntax::comment_style();
+    let last_idx = lines.len() - 1;
+    let selection = state.selection_range().filter(|_| !state.block_selection);
+    let cursor = state.current_position();
+
+    let (first, replacement) = if let Some(token) = &style.line {
+        let (first, last) = match selection {
+            // A selection ending at column 0 does not include that line
+            Some((start, end)) if end.1 == 0 && end.0 > start.0 => (start.0, end.0 - 1),
+            Some((start, end)) => (start.0, end.0),
+            None => (cursor.0, cursor.0),
+        };
+        let last = last.min(last_idx);
+        (first, toggle_line_comments(&lines[first..=last], token))
+    } else if let Some((open, close)) = &style.block {
+        let (start, end) = match selection {
+            Some(range) => range,
+            None => {
+                let line = &lines[cursor.0.min(last_idx)];
+                (
+                    (cursor.0, leading_whitespace_chars(line)),
+                    (cursor.0, char_len(line)),
+                )
             }
-            KeyCode::Backspace => {
-                return delete_backward_multi_cursor(state, lines, filename);
+        };
+        let end = if end.0 > last_idx { (last_idx, char_len(&lines[last_idx])) } else { end };
+        let block = toggle_block_comment(&lines[start.0..=end.0], start.1, end.1, open, close)?;
+        (start.0, block)
+    } else {
+        return Err("No comment syntax for this file".to_string());
+    };
+
+    let pre_cursor = Some((state.absolute_line(), state.cursor_col, state.multi_cursors.clone()));
+    let mut edits = Vec::new();
+    for (offset, new_line) in replacement.into_iter().enumerate() {
+        let idx = first + offset;
+        if lines[idx] == new_line {
+            continue;
+        }
+        // Keep cursor and selection on the same text; positions within the
+        // indentation stay where they are
+        let indent = leading_whitespace_chars(&lines[idx]);
+        let old_line = lines[idx].clone();
+        let shift = |(line, col): Position| -> Position {
+            if line != idx || col <= indent {
+                return (line, col);
             }
-            KeyCode::Delete => {
+            (line, map_column_after_toggle(&old_line, &new_line, col))
+        };
+        if cursor.0 == idx {
+            state.cursor_col = shift((idx, state.cursor_col)).1;
+        }
+        state.selection_start = state.selection_start.map(shift);
+        state.selection_end = state.selection_end.map(shift);
+        state.selection_anchor = state.selection_anchor.map(shift);
+        edits.push(Edit::ReplaceLine {
+            line: idx,
+            old_content: std::mem::replace(&mut lines[idx], new_line.clone()),
+            new_content: new_line,
+        });
+    }
+    if edits.is_empty() {
+        return Ok(false);
+    }
+
+    state.desired_cursor_col = state.cursor_col;
+    let abs = state.absolute_line();
+    state
+        .undo_history
+        .push_composite(edits, Some((abs, state.cursor_col, state.multi_cursors.clone())), pre_cursor);
+    state
+        .undo_history
+        .update_state(state.top_line, abs, state.cursor_col, lines.to_vec());
+    save_undo_with_timestamp(state, filename);
+    Ok(true)
+}
+
+/// Replace the `len` lines starting at `start` with `replacement` as one undo
+/// step and put the cursor on the first of them. The buffer keeps at least one
+/// line. Returns false when nothing changed.
+pub(crate) fn replace_line_range(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    filename: &str,
+    start: usize,
+    len: usize,
+    replacement: Vec<String>,
+) -> bool {
+    let start = start.min(lines.len());
+    let len = len.min(lines.len() - start);
+    if lines[start..start + len] == replacement[..] {
+        return false;
+    }
+    let pre_cursor = Some((state.absolute_line(), state.cursor_col, state.multi_cursors.clone()));
+    let mut edits = Vec::new();
+    let common = len.min(replacement.len());
+    for (offset, new_line) in replacement.iter().enumerate() {
+        let idx = start + offset;
+        if offset < common {
+            if lines[idx] != *new_line {
+                edits.push(Edit::ReplaceLine {
+                    line: idx,
+                    old_content: std::mem::replace(&mut lines[idx], new_line.clone()),
+                    new_content: new_line.clone(),
+                });
+            }
+        } else {
+            lines.insert(idx, new_line.clone());
+            edits.push(Edit::InsertLine { line: idx, content: new_line.clone() });
+        }
+    }
+    for _ in common..len {
+        let idx = start + common;
+        if lines.len() == 1 {
+            edits.push(Edit::ReplaceLine {
+                line: 0,
+                old_content: std::mem::take(&mut lines[0]),
+                new_content: String::new(),
+            });
+        } else {
+            edits.push(Edit::DeleteLine { line: idx, content: lines.remove(idx) });
+        }
+    }
+
+    state.clear_selection();
+    state.clear_multi_cursors();
+    let target = start.min(lines.len().saturating_sub(1));
+    if target < state.top_line {
+        state.top_line = target;
+    }
+    state.cursor_line = target - state.top_line;
+    state.cursor_col = 0;
+    state.desired_cursor_col = 0;
+    state.undo_history.push_composite(edits, Some((target, 0, Vec::new())), pre_cursor);
+    state.undo_history.update_state(state.top_line, target, 0, lines.to_vec());
+    save_undo_with_timestamp(state, filename);
+    state.modified = true;
+    true
+}
+
+/// Column in `new` of the text at `col` in `old`, where `new` differs from `old`
+/// only by inserted (or only by removed) comment delimiters
+fn map_column_after_toggle(old: &str, new: &str, col: usize) -> usize {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+    let col = col.min(old.len());
+    let mut j = 0;
+    if new.len() >= old.len() {
+        // Insertions: skip inserted characters until the next original one
+        for &ch in old.iter().take(col + 1) {
+            while j < new.len() && new[j] != ch {
+                j += 1;
+            }
+            j += 1;
+        }
+        if col < old.len() { j - 1 } else { new.len() }
+    } else {
+        // Removals: count the surviving characters before `col`
+        for &ch in old.iter().take(col) {
+            if j < new.len() && new[j] == ch {
+                j += 1;
+            }
+        }
+        j
+    }
+}
+
+fn leading_whitespace_chars(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+/// Comment every non-blank line with `token` at their common indentation, or
+/// remove the token (and one following space) when all of them are commented
+fn toggle_line_comments(lines: &[String], token: &str) -> Vec<String> {
+    let non_blank: Vec<&String> = lines.iter().filter(|l| !l.trim().is_empty()).collect();
+    if non_blank.is_empty() {
+        return lines.to_vec();
+    }
+
+    if non_blank.iter().all(|l| l.trim_start().starts_with(token)) {
+        return lines
+            .iter()
+            .map(|line| {
+                let body = line.trim_start();
+                let Some(rest) = body.strip_prefix(token) else {
+                    return line.clone();
+                };
+                let indent = &line[..line.len() - body.len()];
+                format!("{}{}", indent, rest.strip_prefix(' ').unwrap_or(rest))
+            })
+            .collect();
+    }
+
+    let indent = non_blank.iter().map(|l| leading_whitespace_chars(l)).min().unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| {
+            if line.trim().is_empty() {
+                return line.clone();
+            }
+            let byte = char_index_to_byte_index(line, indent);
+            format!("{}{} {}", &line[..byte], token, &line[byte..])
+        })
+        .collect()
+}
+
+/// Wrap the text between (first line, `start_col`) and (last line, `end_col`) in
+/// `open`/`close`, ignoring surrounding whitespace. Text that is already wrapped
+/// is unwrapped instead, removing the delimiters of adjacent comments too.
+fn toggle_block_comment(
+    lines: &[String],
+    start_col: usize,
+    end_col: usize,
+    open: &str,
+    close: &str,
+) -> Result<Vec<String>, String> {
+    let text = lines.join("\n");
+    let last = lines.len() - 1;
+    let start = char_index_to_byte_index(&lines[0], start_col);
+    let end = lines[..last].iter().map(|l| l.len() + 1).sum::<usize>()
+        + char_index_to_byte_index(&lines[last], end_col);
+    if start >= end {
+        return Ok(lines.to_vec());
+    }
+    let region = &text[start..end];
+    let inner_start = start + (region.len() - region.trim_start().len());
+    let inner_end = end - (region.len() - region.trim_end().len());
+    if inner_start >= inner_end {
+        return Ok(lines.to_vec());
+    }
+    let inner = &text[inner_start..inner_end];
+
+    let replaced = if inner.len() >= open.len() + close.len()
+        && inner.starts_with(open)
+        && inner.ends_with(close)
+    {
+        strip_block_comments(inner, open, close)
+    } else if inner.contains(close) {
+        return Err(format!(
+            "Selection contains '{}'; block comments cannot be nested",
+            close
+        ));
+    } else {
+        format!("{} {} {}", open, inner, close)
+    };
+
+    let new_text = format!("{}{}{}", &text[..inner_start], replaced, &text[inner_end..]);
+    Ok(new_text.split('\n').map(String::from).collect())
+}
+
+/// Remove every `open`/`close` delimiter in `text` along with the single space
+/// that `toggle_block_comment` puts next to it
+fn strip_block_comments(text: &str, open: &str, close: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(ch) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix(open) {
+            rest = after.strip_prefix(' ').unwrap_or(after);
+        } else if let Some(after) = rest.strip_prefix(close) {
+            if out.ends_with(' ') {
+                out.pop();
+            }
+            rest = after;
+        } else {
+            out.push(ch);
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+    out
+}
+
+/// Move the secondary cursors for a plain arrow/Home/End key; the main cursor is
+/// moved by the regular navigation. Returns false for keys that do not move cursors.
+pub(crate) fn move_multi_cursors(
+    state: &mut FileViewerState,
+    lines: &[String],
+    code: &crossterm::event::KeyCode,
+) -> bool {
+    use crossterm::event::KeyCode;
+
+    let last_line = lines.len().saturating_sub(1);
+    let line_len = |line: usize| lines.get(line).map_or(0, |l| char_len(l));
+    let step = |(line, col): Position| -> Position {
+        match code {
+            KeyCode::Left if col > 0 => (line, col - 1),
+            KeyCode::Left if line > 0 => (line - 1, line_len(line - 1)),
+            KeyCode::Right if col < line_len(line) => (line, col + 1),
+            KeyCode::Right if line < last_line => (line + 1, 0),
+            KeyCode::Up if line > 0 => (line - 1, col.min(line_len(line - 1))),
+            KeyCode::Down if line < last_line => (line + 1, col.min(line_len(line + 1))),
+            KeyCode::Home => (line, 0),
+            KeyCode::End => (line, line_len(line)),
+            _ => (line, col),
+        }
+    };
+    if !matches!(
+        code,
+        KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down | KeyCode::Home | KeyCode::End
+    ) {
+        return false;
+    }
+
+    let mut moved: Vec<Position> = state.multi_cursors.iter().map(|&p| step(p)).collect();
+    moved.sort_unstable();
+    moved.dedup();
+    state.multi_cursors = moved;
+    true
+}
+
+pub(crate) fn handle_editing_keys(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    code: &crossterm::event::KeyCode,
+    modifiers: &crossterm::event::KeyModifiers,
+    visible_lines: usize,
+    filename: &str,
+) -> bool {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+
+    // If a zero-width block selection is active, convert it to multi-cursors for editing keys
+    if state.block_selection
+        && let Some((start, end)) = state.selection_range() {
+            let is_zero_width_block = start.1 == end.1 && start.0 != end.0;
+            if is_zero_width_block
+                && matches!(code, KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Delete)
+            {
+                activate_multi_cursor_from_block(state, lines, start, end);
+            }
+        }
+
+    // Handle multi-cursor typing
+    if state.has_multi_cursors() {
+        match code {
+            KeyCode::Char(c)
+                if !modifiers.contains(KeyModifiers::CONTROL)
+                    && !modifiers.contains(KeyModifiers::ALT) =>
+            {
+                return insert_char_multi_cursor(state, lines, *c, filename);
+            }
+            KeyCode::Backspace => {
+                return delete_backward_multi_cursor(state, lines, filename);
+            }
+            KeyCode::Delete => {
                 return delete_forward_multi_cursor(state, lines, filename);
             }
+            // Plain cursor movement moves every cursor (see move_multi_cursors)
+            KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down | KeyCode::Home | KeyCode::End
+                if modifiers.is_empty() => {}
             // Any other key clears multi-cursors
             _ => {
                 state.clear_multi_cursors();
@@ -1397,16 +2420,7 @@ pub(crate) fn handle_editing_keys(
 
     match code {
         KeyCode::Backspace | KeyCode::Delete if state.has_selection() && state.block_selection => {
-            if let Some((start, end)) = state.selection_range() {
-                let start_col = start.1;
-                let start_line = start.0;
-                let end_line = end.0;
-                let removed = remove_selection(state, lines, filename);
-                activate_multi_cursor_from_block(state, (start_line, start_col), (end_line, start_col));
-                removed
-            } else {
-                false
-            }
+            remove_block_to_cursors(state, lines, filename)
         }
         KeyCode::Backspace | KeyCode::Delete if state.has_selection() => {
             remove_selection(state, lines, filename)
@@ -1416,17 +2430,9 @@ pub(crate) fn handle_editing_keys(
                 && !modifiers.contains(KeyModifiers::ALT) =>
         {
             if state.has_selection() && state.block_selection {
-                if let Some((start, end)) = state.selection_range()
-                    && start.1 != end.1 {
-                        let start_col = start.1;
-                        let start_line = start.0;
-                        let end_line = end.0;
-                        let _ = remove_selection(state, lines, filename);
-                        activate_multi_cursor_from_block(
-                            state,
-                            (start_line, start_col),
-                            (end_line, start_col),
-                        );
+                if let Some((_, _, left, right)) = state.block_rect()
+                    && left != right {
+                        let _ = remove_block_to_cursors(state, lines, filename);
                         return insert_char_multi_cursor(state, lines, *c, filename);
                     }
             } else if state.has_selection() {
@@ -1448,10 +2454,25 @@ pub(crate) fn handle_editing_keys(
         }
         KeyCode::Backspace => delete_backward(state, lines, filename),
         KeyCode::Delete => delete_forward(state, lines, filename),
+        KeyCode::Insert if modifiers.is_empty() => {
+            state.overwrite_mode = !state.overwrite_mode;
+            state.needs_redraw = true;
+            false
+        }
         _ => false,
     }
 }
 
+/// Move cursors left in virtual space back to the end of their line; deleting
+/// there has nothing to remove
+fn snap_cursors_to_line_ends(state: &mut FileViewerState, lines: &[String]) {
+    let line_len = |line: usize| lines.get(line).map_or(0, |text| char_len(text));
+    state.cursor_col = state.cursor_col.min(line_len(state.absolute_line()));
+    for cursor in &mut state.multi_cursors {
+        cursor.1 = cursor.1.min(line_len(cursor.0));
+    }
+}
+
 /// Insert character at all cursor positions for multi-cursor mode
 fn insert_char_multi_cursor(
     state: &mut FileViewerState,
@@ -1467,12 +2488,24 @@ fn insert_char_multi_cursor(
     let undo_cursor = Some((state.absolute_line(), state.cursor_col, state.multi_cursors.clone()));
 
     for &(line_idx, col) in positions.iter().rev() {
-        if line_idx < lines.len() && col <= char_len(&lines[line_idx]) {
-            let byte_idx = char_index_to_byte_index(&lines[line_idx], col);
-            lines[line_idx].insert(byte_idx, c);
-            edits.push(Edit::InsertChar { line: line_idx, col, ch: c });
-            inserted = true;
+        if line_idx >= lines.len() {
+            continue;
+        }
+        // A cursor past the end of the line (virtual space) pads it with spaces first
+        let line_len = char_len(&lines[line_idx]);
+        if col > line_len {
+            if !state.settings.virtual_space {
+                continue;
+            }
+            for pad_col in line_len..col {
+                lines[line_idx].push(' ');
+                edits.push(Edit::InsertChar { line: line_idx, col: pad_col, ch: ' ' });
+            }
         }
+        let byte_idx = char_index_to_byte_index(&lines[line_idx], col);
+        lines[line_idx].insert(byte_idx, c);
+        edits.push(Edit::InsertChar { line: line_idx, col, ch: c });
+        inserted = true;
     }
 
     if inserted {
@@ -1495,6 +2528,7 @@ fn delete_backward_multi_cursor(
     lines: &mut [String],
     filename: &str,
 ) -> bool {
+    snap_cursors_to_line_ends(state, lines);
     let mut positions = state.all_cursor_positions();
     let mut deleted = false;
     let mut edits = Vec::new();
@@ -1540,6 +2574,7 @@ fn delete_forward_multi_cursor(
     lines: &mut [String],
     filename: &str,
 ) -> bool {
+    snap_cursors_to_line_ends(state, lines);
     let mut positions = state.all_cursor_positions();
     let mut deleted = false;
     let mut edits = Vec::new();
@@ -1555,7 +2590,8 @@ fn delete_forward_multi_cursor(
             if col < chars.len() {
                 let removed_char = chars[col];
                 edits.push(Edit::DeleteChar { line: line_idx, col, ch: removed_char });
-                line.remove(col);
+                let byte_idx = char_index_to_byte_index(line, col);
+                line.remove(byte_idx);
                 deleted = true;
             }
         }
@@ -1781,6 +2817,306 @@ mod tests {
         assert_eq!(state.cursor_col, 3);
     }
 
+    #[test]
+    fn duplicate_selection_stacks_copies_and_undoes_in_one_step() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = vec!["ab[cd".to_string(), "ef]gh".to_string()];
+        state.selection_start = Some((0, 2));
+        state.selection_end = Some((1, 3));
+
+        assert!(duplicate_selection(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines, vec!["ab[cd", "ef][cd", "ef]gh"]);
+        assert_eq!(state.selection_range(), Some(((1, 3), (2, 3))));
+
+        assert!(duplicate_selection(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines, vec!["ab[cd", "ef][cd", "ef][cd", "ef]gh"]);
+        assert_eq!(state.current_position(), (3, 3));
+
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines, vec!["ab[cd", "ef][cd", "ef]gh"]);
+
+        // Single-line selections stay on the line
+        let mut lines = vec!["xy".to_string()];
+        state.clear_selection();
+        state.top_line = 0;
+        state.cursor_line = 0;
+        state.selection_start = Some((0, 0));
+        state.selection_end = Some((0, 2));
+        assert!(duplicate_selection(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines, vec!["xyxy"]);
+        assert_eq!(state.selection_range(), Some(((0, 2), (0, 4))));
+    }
+
+    #[test]
+    fn duplicate_block_copies_its_rows_below_and_moves_the_block() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines: Vec<String> = ["abc", "def", "ghi"].map(String::from).to_vec();
+        state.block_selection = true;
+        state.selection_start = Some((0, 1));
+        state.selection_end = Some((1, 2));
+        state.cursor_line = 1;
+        state.cursor_col = 2;
+
+        assert!(duplicate_selection(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines, vec!["abc", "def", "abc", "def", "ghi"]);
+        assert_eq!(state.block_rect(), Some((2, 3, 1, 2)));
+        assert_eq!(state.current_position(), (3, 2));
+
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines, vec!["abc", "def", "ghi"]);
+    }
+
+    #[test]
+    fn duplicate_without_selection_copies_the_cursor_line() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines: Vec<String> = ["first", "second"].map(String::from).to_vec();
+        state.cursor_line = 0;
+        state.cursor_col = 3;
+
+        assert!(duplicate_selection(&mut state, &mut lines, "test.txt"));
+        assert!(duplicate_selection(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines, vec!["first", "first", "first", "second"]);
+        assert_eq!(state.current_position(), (2, 3));
+
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines, vec!["first", "first", "second"]);
+        assert_eq!(state.current_position(), (1, 3));
+    }
+
+    #[test]
+    fn move_lines_carries_cursor_and_selection_and_undoes_in_one_step() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines: Vec<String> = ["a", "b", "c", "d", "e"].map(String::from).to_vec();
+        state.cursor_line = 1;
+        state.cursor_col = 1;
+        assert!(move_lines(&mut state, &mut lines, "test.txt", true, 10));
+        assert_eq!(lines, vec!["b", "a", "c", "d", "e"]);
+        assert_eq!(state.current_position(), (0, 1));
+        assert!(!move_lines(&mut state, &mut lines, "test.txt", true, 10), "already at the top");
+
+        // A selection ending at the start of a line leaves that line in place
+        state.selection_start = Some((1, 0));
+        state.selection_end = Some((3, 0));
+        state.cursor_line = 3;
+        state.cursor_col = 0;
+        assert!(move_lines(&mut state, &mut lines, "test.txt", false, 10));
+        assert_eq!(lines, vec!["b", "d", "a", "c", "e"]);
+        assert_eq!(state.selection_range(), Some(((2, 0), (4, 0))));
+        assert_eq!(state.current_position(), (4, 0));
+
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines, vec!["b", "a", "c", "d", "e"]);
+        assert_eq!(state.current_position(), (3, 0));
+        assert!(apply_redo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines, vec!["b", "d", "a", "c", "e"]);
+
+        state.clear_selection();
+        state.cursor_line = 4;
+        assert!(!move_lines(&mut state, &mut lines, "test.txt", false, 10), "already at the bottom");
+    }
+
+    #[test]
+    fn toggle_comment_uses_line_comments_at_common_indent() {
+        let (_tmp, _guard) = set_temp_home();
+        crate::syntax::set_current_file("test.rs");
+        let mut state = create_test_state();
+        let mut lines = vec!["    a();".to_string(), "".to_string(), "  b();".to_string()];
+        state.selection_start = Some((0, 0));
+        state.selection_end = Some((2, 6));
+
+        assert_eq!(toggle_comment(&mut state, &mut lines, "test.rs"), Ok(true));
+        assert_eq!(lines, vec!["  //   a();", "", "  // b();"]);
+        assert_eq!(toggle_comment(&mut state, &mut lines, "test.rs"), Ok(true));
+        assert_eq!(lines, vec!["    a();", "", "  b();"]);
+
+        assert_eq!(toggle_comment(&mut state, &mut lines, "test.rs"), Ok(true));
+        assert!(apply_undo(&mut state, &mut lines, "test.rs", 10));
+        assert_eq!(lines, vec!["    a();", "", "  b();"]);
+    }
+
+    /// Zero-width block selection at column 4 over lines 0..=2 of ["abcdef", "ab", "abcdef"]
+    fn type_into_block_over_short_line(state: &mut FileViewerState) -> Vec<String> {
+        let mut lines = vec!["abcdef".to_string(), "ab".to_string(), "abcdef".to_string()];
+        state.block_selection = true;
+        state.selection_start = Some((0, 4));
+        state.selection_end = Some((2, 4));
+        state.cursor_col = 4;
+        let none = crossterm::event::KeyModifiers::NONE;
+        handle_editing_keys(state, &mut lines, &crossterm::event::KeyCode::Char('|'), &none, 10, "t.txt");
+        lines
+    }
+
+    #[test]
+    fn block_insert_on_short_line_goes_to_line_end_without_virtual_space() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        assert!(!state.settings.virtual_space);
+
+        let lines = type_into_block_over_short_line(&mut state);
+        assert_eq!(lines, vec!["abcd|ef", "ab|", "abcd|ef"]);
+        assert_eq!(state.multi_cursors, vec![(1, 3), (2, 5)]);
+        state.validate_cursor_invariants(&lines);
+    }
+
+    #[test]
+    fn consecutive_block_edits_keep_the_rows() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = vec!["a123b".to_string(), "a45b".to_string(), "a678b".to_string()];
+        // Block over columns 1..4, extended leftwards from the bottom-right corner
+        state.block_selection = true;
+        state.selection_start = Some((2, 4));
+        state.selection_end = Some((0, 1));
+        let none = crossterm::event::KeyModifiers::NONE;
+        let key = |state: &mut FileViewerState, lines: &mut Vec<String>, code| {
+            handle_editing_keys(state, lines, &code, &none, 10, "t.txt")
+        };
+
+        assert!(key(&mut state, &mut lines, crossterm::event::KeyCode::Delete));
+        assert_eq!(lines, vec!["ab", "a", "ab"]);
+        assert_eq!((state.absolute_line(), state.cursor_col), (0, 1));
+        assert_eq!(state.multi_cursors, vec![(1, 1), (2, 1)]);
+
+        // Typing right away goes into the same rows
+        key(&mut state, &mut lines, crossterm::event::KeyCode::Char('x'));
+        key(&mut state, &mut lines, crossterm::event::KeyCode::Char('y'));
+        assert_eq!(lines, vec!["axyb", "axy", "axyb"]);
+
+        // One-line text pastes into every row, a line per row spreads over them
+        assert!(paste_text(&mut state, &mut lines, "-", "t.txt"));
+        assert_eq!(lines, vec!["axy-b", "axy-", "axy-b"]);
+        assert!(paste_text(&mut state, &mut lines, "1\n22\n333\n", "t.txt"));
+        assert_eq!(lines, vec!["axy-1b", "axy-22", "axy-333b"]);
+        assert_eq!((state.absolute_line(), state.cursor_col), (0, 5));
+        assert_eq!(state.multi_cursors, vec![(1, 6), (2, 7)]);
+        state.validate_cursor_invariants(&lines);
+
+        assert!(apply_undo(&mut state, &mut lines, "t.txt", 10));
+        assert_eq!(lines, vec!["axy-b", "axy-", "axy-b"]);
+    }
+
+    #[test]
+    fn paste_over_block_replaces_its_columns() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = vec!["one".to_string(), "two".to_string(), "six".to_string()];
+        state.block_selection = true;
+        state.selection_start = Some((0, 0));
+        state.selection_end = Some((2, 1));
+
+        assert!(paste_text(&mut state, &mut lines, "T\nT\nS", "t.txt"));
+        assert_eq!(lines, vec!["Tne", "Two", "Six"]);
+        assert!(!state.has_selection());
+        assert_eq!(state.multi_cursors, vec![(1, 1), (2, 1)]);
+
+        // Text that does not fit the rows clears the block and pastes at its corner
+        state.clear_multi_cursors();
+        state.block_selection = true;
+        state.selection_start = Some((0, 1));
+        state.selection_end = Some((2, 3));
+        assert!(paste_text(&mut state, &mut lines, "a\nb", "t.txt"));
+        assert_eq!(lines, vec!["Ta", "b", "T", "S"]);
+
+        // One undo brings back the cleared block as well
+        assert!(apply_undo(&mut state, &mut lines, "t.txt", 10));
+        assert_eq!(lines, vec!["Tne", "Two", "Six"]);
+    }
+
+    #[test]
+    fn block_insert_on_short_line_pads_with_virtual_space() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut settings = Settings::load().unwrap();
+        settings.virtual_space = true;
+        let settings = Box::leak(Box::new(settings));
+        let mut state = FileViewerState::new(80, UndoHistory::new(), settings);
+
+        let mut lines = type_into_block_over_short_line(&mut state);
+        assert_eq!(lines, vec!["abcd|ef", "ab  |", "abcd|ef"]);
+        assert_eq!(state.multi_cursors, vec![(1, 5), (2, 5)]);
+
+        // Padding and character are undone together
+        apply_undo(&mut state, &mut lines, "t.txt", 10);
+        assert_eq!(lines, vec!["abcdef", "ab", "abcdef"]);
+    }
+
+    #[test]
+    fn toggle_comment_wraps_block_comments() {
+        let (_tmp, _guard) = set_temp_home();
+        crate::syntax::set_current_file("style.css");
+        let mut state = create_test_state();
+        let mut lines = vec!["  color: red;".to_string()];
+        state.cursor_col = 4;
+
+        assert_eq!(toggle_comment(&mut state, &mut lines, "style.css"), Ok(true));
+        assert_eq!(lines, vec!["  /* color: red; */"]);
+        assert_eq!(state.cursor_col, 7);
+        assert_eq!(toggle_comment(&mut state, &mut lines, "style.css"), Ok(true));
+        assert_eq!(lines, vec!["  color: red;"]);
+
+        // Multi-line selection, then adjacent comments are all unwrapped
+        let mut lines = vec!["a {".to_string(), "}".to_string()];
+        state.selection_start = Some((0, 0));
+        state.selection_end = Some((1, 1));
+        assert_eq!(toggle_comment(&mut state, &mut lines, "style.css"), Ok(true));
+        assert_eq!(lines, vec!["/* a {", "} */"]);
+
+        let mut lines = vec!["/* a */ /* b */".to_string()];
+        state.clear_selection();
+        assert_eq!(toggle_comment(&mut state, &mut lines, "style.css"), Ok(true));
+        assert_eq!(lines, vec!["a b"]);
+
+        // Wrapping text that contains a comment end would nest comments
+        let mut lines = vec!["a /* b */ c".to_string()];
+        assert!(toggle_comment(&mut state, &mut lines, "style.css").is_err());
+        assert_eq!(lines, vec!["a /* b */ c"]);
+
+        crate::syntax::set_current_file("notes.txt");
+        assert!(toggle_comment(&mut state, &mut lines, "notes.txt").is_err());
+    }
+
+    #[test]
+    fn move_multi_cursors_steps_and_merges() {
+        use crossterm::event::KeyCode;
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let lines = vec!["ab".to_string(), "".to_string(), "xyz".to_string()];
+        state.multi_cursors = vec![(0, 2), (2, 0)];
+
+        assert!(move_multi_cursors(&mut state, &lines, &KeyCode::Right));
+        assert_eq!(state.multi_cursors, vec![(1, 0), (2, 1)]);
+        assert!(move_multi_cursors(&mut state, &lines, &KeyCode::Up));
+        assert_eq!(state.multi_cursors, vec![(0, 0), (1, 0)]);
+        assert!(move_multi_cursors(&mut state, &lines, &KeyCode::Home));
+        assert!(move_multi_cursors(&mut state, &lines, &KeyCode::Up));
+        assert_eq!(state.multi_cursors, vec![(0, 0)]);
+        assert!(!move_multi_cursors(&mut state, &lines, &KeyCode::PageDown));
+    }
+
+    #[test]
+    fn insert_char_overwrite_replaces_until_end_of_line() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = vec!["häy".to_string()];
+        state.overwrite_mode = true;
+        state.cursor_col = 1;
+
+        for c in "ello".chars() {
+            assert!(insert_char(&mut state, &mut lines, c, "test.txt", 10));
+        }
+        assert_eq!(lines[0], "hello");
+        assert_eq!(state.cursor_col, 5);
+
+        // Each replaced character is a single undo step; the appended "lo" is one
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines[0], "hel");
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines[0], "hey");
+    }
+
     #[test]
     fn delete_backward_char() {
         let (_tmp, _guard) = set_temp_home();
@@ -1847,6 +3183,81 @@ mod tests {
         assert_eq!(state.cursor_col, 0);
     }
 
+    #[test]
+    fn split_line_between_braces_indents_block() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = vec!["  fn x() {}".to_string()];
+        state.cursor_col = 10;
+
+        assert!(split_line(&mut state, &mut lines, 10, "test.txt"));
+        assert_eq!(lines, vec!["  fn x() {", "      ", "  }"]);
+        assert_eq!(state.absolute_line(), 1);
+        assert_eq!(state.cursor_col, 6);
+
+        // A single undo restores the original line
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines, vec!["  fn x() {}"]);
+        assert!(apply_redo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines, vec!["  fn x() {", "      ", "  }"]);
+    }
+
+    #[test]
+    fn split_line_after_colon_indents_for_python() {
+        let (_tmp, _guard) = set_temp_home();
+        crate::syntax::set_current_file("test.py");
+        let mut state = create_test_state();
+        let mut lines = vec!["    if x:".to_string()];
+        state.cursor_col = 9;
+
+        assert!(split_line(&mut state, &mut lines, 10, "test.py"));
+        assert_eq!(lines, vec!["    if x:", "        "]);
+        assert_eq!(state.cursor_col, 8);
+        assert!(apply_undo(&mut state, &mut lines, "test.py", 10));
+        assert_eq!(lines, vec!["    if x:"]);
+    }
+
+    #[test]
+    fn split_line_after_colon_keeps_plain_split_for_rust() {
+        let (_tmp, _guard) = set_temp_home();
+        crate::syntax::set_current_file("test.rs");
+        let mut state = create_test_state();
+        let mut lines = vec!["label:".to_string()];
+        state.cursor_col = 6;
+
+        assert!(split_line(&mut state, &mut lines, 10, "test.rs"));
+        assert_eq!(lines, vec!["label:", ""]);
+        assert_eq!(state.cursor_col, 0);
+    }
+
+    #[test]
+    fn split_line_keeps_indentation_of_plain_line() {
+        let (_tmp, _guard) = set_temp_home();
+        crate::syntax::set_current_file("test.txt");
+        let mut state = create_test_state();
+        let mut lines = vec!["    let x = 1;".to_string()];
+        state.cursor_col = 14;
+
+        assert!(split_line(&mut state, &mut lines, 10, "test.txt"));
+        assert_eq!(lines, vec!["    let x = 1;", "    "]);
+        assert_eq!(state.cursor_col, 4);
+
+        // Text after the cursor moves down onto the indented line
+        state.cursor_line = 0;
+        state.cursor_col = 9;
+        assert!(split_line(&mut state, &mut lines, 10, "test.txt"));
+        assert_eq!(lines, vec!["    let x", "    = 1;", "    "]);
+        assert_eq!(state.cursor_col, 4);
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines, vec!["    let x = 1;", "    "]);
+
+        // Enter inside the indentation is a plain split
+        state.cursor_line = 0;
+        state.cursor_col = 2;
+        assert!(split_line(&mut state, &mut lines, 10, "test.txt"));
+        assert_eq!(lines[..2], ["  ", "  let x = 1;"]);
+    }
+
     #[test]
     fn insert_tab_adds_spaces() {
         let (_tmp, _guard) = set_temp_home();
@@ -1859,6 +3270,81 @@ mod tests {
         assert_eq!(state.cursor_col, 4);
     }
 
+    #[test]
+    fn insert_tab_uses_indent_size_not_tab_width() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut settings = Settings::load().expect("Failed to load test settings");
+        settings.tab_width = 8;
+        settings.indent_size = Some(2);
+        let settings = Box::leak(Box::new(settings));
+        let mut state = FileViewerState::new(80, UndoHistory::new(), settings);
+        let mut lines = vec!["hello".to_string()];
+
+        assert!(insert_tab(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines[0], "  hello");
+        assert_eq!(state.cursor_col, 2);
+    }
+
+    #[test]
+    fn tab_depends_on_leading_whitespace() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut settings = Settings::load().expect("Failed to load test settings");
+        settings.indent_size = Some(4);
+        settings.tab_leading = "indent".to_string();
+        settings.tab_inline = "tab".to_string();
+        let settings = Box::leak(Box::new(settings));
+        let mut state = FileViewerState::new(80, UndoHistory::new(), settings);
+
+        // Within leading whitespace: up to the next indentation stop
+        let mut lines = vec!["  x = 1".to_string(), "\tfoo".to_string()];
+        state.cursor_col = 2;
+        assert!(insert_tab(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines[0], "    x = 1");
+        assert_eq!(state.cursor_col, 4);
+
+        // After the text: a literal tab for alignment
+        state.cursor_col = 5;
+        assert!(insert_tab(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines[0], "    x\t = 1");
+
+        // Tab-indented lines keep indenting with tabs
+        state.cursor_line = 1;
+        state.cursor_col = 0;
+        assert!(insert_tab(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines[1], "\t\tfoo");
+    }
+
+    #[test]
+    fn open_line_keeps_current_line_and_indents() {
+        let (_tmp, _guard) = set_temp_home();
+        crate::syntax::set_current_file("test.rs");
+        let mut state = create_test_state();
+        let mut lines = vec!["    fn main() {".to_string(), "    }".to_string()];
+        state.cursor_col = 7;
+
+        // Below a line ending in `{`: one level deeper
+        assert!(open_line(&mut state, &mut lines, false, 10, "test.rs"));
+        assert_eq!(lines, vec!["    fn main() {", "        ", "    }"]);
+        assert_eq!(state.current_position(), (1, 8));
+
+        // Above the closing brace: same indentation as that line
+        state.cursor_line = 2;
+        state.cursor_col = 0;
+        assert!(open_line(&mut state, &mut lines, true, 10, "test.rs"));
+        assert_eq!(lines[2], "    ");
+        assert_eq!(lines[3], "    }");
+        assert_eq!(state.current_position(), (2, 4));
+
+        assert!(apply_undo(&mut state, &mut lines, "test.rs", 10));
+        assert_eq!(lines.len(), 3);
+        assert_eq!(state.current_position(), (2, 0));
+
+        // Opening below the last visible row scrolls by one line
+        state.cursor_line = 1;
+        assert!(open_line(&mut state, &mut lines, false, 2, "test.rs"));
+        assert_eq!((state.top_line, state.cursor_line), (1, 1));
+    }
+
     #[test]
     fn undo_insert_char() {
         let (_tmp, _guard) = set_temp_home();
@@ -2039,6 +3525,58 @@ mod tests {
         assert_eq!(lines[3], "after");
     }
 
+    #[test]
+    fn split_selection_places_cursor_at_each_line_end() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = vec!["one".to_string(), "three".to_string(), "xy".to_string()];
+        state.selection_start = Some((0, 1));
+        state.selection_end = Some((2, 1));
+
+        assert!(split_selection_into_cursors(&mut state, &lines, 10));
+        assert!(!state.has_selection());
+        assert_eq!(state.multi_cursors, vec![(0, 3), (1, 5)]);
+        assert_eq!(state.current_position(), (2, 1));
+
+        // Typing fans out to every cursor and undoes as one step
+        let code = crossterm::event::KeyCode::Char('!');
+        let mods = crossterm::event::KeyModifiers::empty();
+        assert!(handle_editing_keys(&mut state, &mut lines, &code, &mods, 10, "test.txt"));
+        assert_eq!(lines, vec!["one!", "three!", "x!y"]);
+        let bs = crossterm::event::KeyCode::Backspace;
+        assert!(handle_editing_keys(&mut state, &mut lines, &bs, &mods, 10, "test.txt"));
+        assert_eq!(lines, vec!["one", "three", "xy"]);
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines, vec!["one!", "three!", "x!y"]);
+    }
+
+    #[test]
+    fn split_selection_skips_last_line_at_column_zero() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let lines = vec!["a".to_string(), "bb".to_string(), "ccc".to_string()];
+        state.selection_start = Some((0, 0));
+        state.selection_end = Some((2, 0));
+
+        assert!(split_selection_into_cursors(&mut state, &lines, 10));
+        assert_eq!(state.multi_cursors, vec![(0, 1)]);
+        assert_eq!(state.current_position(), (1, 2));
+    }
+
+    #[test]
+    fn multi_cursor_delete_forward_handles_multibyte() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = vec!["äb".to_string(), "öc".to_string()];
+        state.cursor_col = 1;
+        state.multi_cursors = vec![(1, 1)];
+
+        let code = crossterm::event::KeyCode::Delete;
+        let mods = crossterm::event::KeyModifiers::empty();
+        assert!(handle_editing_keys(&mut state, &mut lines, &code, &mods, 10, "test.txt"));
+        assert_eq!(lines, vec!["ä", "ö"]);
+    }
+
     #[test]
     fn delete_word_backward_single_undo() {
         let (_tmp, _guard) = set_temp_home();
@@ -2092,6 +3630,195 @@ mod tests {
             panic!("Expected DeleteWord edit");
         }
     }
+
+    #[test]
+    fn delete_word_forward_at_end_of_multibyte_line_joins_next_line() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = vec!["grüße".to_string(), "next".to_string()];
+        // 5 characters but 7 bytes: the cursor is at the end of the line
+        state.cursor_col = 5;
+
+        assert!(delete_word_forward(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines, vec!["grüßenext"]);
+
+        state.cursor_col = 9;
+        assert!(delete_word_backward(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines, vec![""]);
+        assert_eq!(state.cursor_col, 0);
+    }
+
+    fn history_with_unsaved_edit(path: &str) {
+        let mut h = UndoHistory::new();
+        h.push(Edit::InsertChar { line: 0, col: 3, ch: '!' });
+        h.update_state(0, 0, 4, vec!["abc!".to_string()]);
+        h.save(path).unwrap();
+        crate::recent::update_recent_file(path).unwrap();
+    }
+
+    #[test]
+    fn unsaved_files_are_listed_and_saved_from_history() {
+        let (tmp, _guard) = set_temp_home();
+        let current = tmp.path().join("current.txt");
+        let other = tmp.path().join("other.txt");
+        fs::write(&current, "x\n").unwrap();
+        fs::write(&other, "abc\n").unwrap();
+        let current = current.to_string_lossy().to_string();
+        let other = other.to_string_lossy().to_string();
+        history_with_unsaved_edit(&current);
+        history_with_unsaved_edit(&other);
+
+        let unsaved = unsaved_files_except(&current);
+        assert_eq!(unsaved.len(), 1);
+        assert!(unsaved[0].ends_with("other.txt"));
+
+        save_unsaved_from_history(&unsaved[0]).unwrap();
+        assert_eq!(fs::read_to_string(&other).unwrap(), "abc!");
+        assert!(unsaved_files_except(&current).is_empty());
+    }
+
+    #[test]
+    fn save_file_writes_latin1_and_bom() {
+        let (tmp, _guard) = set_temp_home();
+        let path = tmp.path().join("enc.txt");
+        let path = path.to_string_lossy().to_string();
+        let lines = vec!["für".to_string(), "€".to_string()];
+
+        save_file(&path, &lines, crate::encoding::FileEncoding::Latin1, crate::line_ending::LineEnding::Lf).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"f\xFCr\n?");
+
+        save_file(&path, &lines, crate::encoding::FileEncoding::Utf8Bom, crate::line_ending::LineEnding::Lf).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), "\u{FEFF}für\n€".as_bytes());
+
+        save_file(&path, &lines, crate::encoding::FileEncoding::Utf8, crate::line_ending::LineEnding::CrLf).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), "für\r\n€".as_bytes());
+    }
+
+    #[test]
+    fn typing_a_word_is_one_undo_step() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = vec![String::new()];
+
+        for c in "hello world".chars() {
+            assert!(insert_char(&mut state, &mut lines, c, "test.txt", 10));
+        }
+        assert!(delete_backward(&mut state, &mut lines, "test.txt"));
+        assert!(delete_backward(&mut state, &mut lines, "test.txt"));
+        assert_eq!(lines[0], "hello wor");
+
+        // The backspaces, then the word after the space, then "hello "
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines[0], "hello world");
+        assert_eq!(state.cursor_col, 11);
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines[0], "hello ");
+        assert!(apply_undo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines[0], "");
+        assert!(!apply_undo(&mut state, &mut lines, "test.txt", 10));
+
+        assert!(apply_redo(&mut state, &mut lines, "test.txt", 10));
+        assert_eq!(lines[0], "hello ");
+        assert_eq!(state.cursor_col, 6);
+    }
+
+    #[test]
+    fn parse_time_offset_accepts_units_and_direction() {
+        assert_eq!(parse_time_offset("5m"), Some(-300));
+        assert_eq!(parse_time_offset("5"), Some(-300));
+        assert_eq!(parse_time_offset("-30s"), Some(-30));
+        assert_eq!(parse_time_offset("+2h"), Some(7200));
+        assert_eq!(parse_time_offset(" 1 d "), Some(-86_400));
+        assert_eq!(parse_time_offset("m"), None);
+        assert_eq!(parse_time_offset("5 weeks"), None);
+    }
+
+    #[test]
+    fn undo_to_time_walks_to_the_state_of_that_time() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = vec![String::new()];
+        for c in "ab".chars() {
+            assert!(insert_char(&mut state, &mut lines, c, "test.txt", 10));
+            state.undo_history.push(Edit::InsertLine { line: 1, content: String::new() });
+            lines.insert(1, String::new());
+        }
+        // Pretend the edits were made ten, eight, six and four minutes ago
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        state.undo_history.edit_times = vec![now - 600, now - 480, now - 360, now - 240];
+
+        assert_eq!(undo_to_time(&mut state, &mut lines, "test.txt", 10, -300), -1);
+        assert_eq!(lines, vec!["ab", ""]);
+        assert_eq!(undo_to_time(&mut state, &mut lines, "test.txt", 10, -200), -2);
+        assert_eq!(lines, vec!["a"]);
+        assert_eq!(undo_to_time(&mut state, &mut lines, "test.txt", 10, 300), 2);
+        assert_eq!(lines, vec!["ab", ""]);
+        assert_eq!(undo_to_time(&mut state, &mut lines, "test.txt", 10, -3600), -3);
+        assert_eq!(lines, vec![""]);
+    }
+
+    #[test]
+    fn autosave_writes_modified_files_only() {
+        let (tmp, _guard) = set_temp_home();
+        let path = tmp.path().join("auto.txt");
+        let path = path.to_string_lossy().to_string();
+        fs::write(&path, "old").unwrap();
+        let mut state = create_test_state();
+        let lines = vec!["new".to_string()];
+
+        assert!(!autosave(&mut state, &lines, &path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+
+        state.modified = true;
+        assert!(autosave(&mut state, &lines, &path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert!(!state.modified);
+        assert!(!state.undo_history.modified);
+
+        state.modified = true;
+        state.is_read_only = true;
+        assert!(!autosave(&mut state, &["newer".to_string()], &path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    }
+
+    #[test]
+    fn renaming_moves_history_and_recent_entry() {
+        let (tmp, _guard) = set_temp_home();
+        let old = tmp.path().join("old.txt");
+        fs::write(&old, "abc\n").unwrap();
+        let old = old.to_string_lossy().to_string();
+        let new = tmp.path().join("sub").join("new.txt");
+        fs::create_dir_all(new.parent().unwrap()).unwrap();
+        let new = new.to_string_lossy().to_string();
+        history_with_unsaved_edit(&old);
+
+        rename_file(&old, &new).unwrap();
+        assert!(!std::path::Path::new(&old).exists());
+        assert_eq!(fs::read_to_string(&new).unwrap(), "abc\n");
+        assert!(!UndoHistory::history_path_for(&old, false).unwrap().exists());
+        assert_eq!(UndoHistory::load(&new, false).unwrap().file_content, Some(vec!["abc!".to_string()]));
+        let recent = crate::recent::get_recent_files().unwrap();
+        assert_eq!(recent.first(), fs::canonicalize(&new).ok().as_ref());
+        assert_eq!(recent.len(), 1);
+
+        assert!(rename_file(&old, &new).is_err(), "nothing left to move");
+    }
+
+    #[test]
+    fn discarding_unsaved_history_keeps_disk_content() {
+        let (tmp, _guard) = set_temp_home();
+        let other = tmp.path().join("other.txt");
+        fs::write(&other, "abc\n").unwrap();
+        let other = other.to_string_lossy().to_string();
+        history_with_unsaved_edit(&other);
+
+        discard_unsaved_from_history(&other).unwrap();
+        let h = UndoHistory::load(&other, false).unwrap();
+        assert!(!h.modified);
+        assert!(h.file_content.is_none());
+        assert!(h.edits.is_empty());
+        assert_eq!(fs::read_to_string(&other).unwrap(), "abc\n");
+    }
 }
 
 
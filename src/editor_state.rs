@@ -20,6 +20,8 @@ pub struct FileViewerState<'a> {
     pub(crate) selection_anchor: Option<Position>,
     /// True if selection is block-wise (column selection), false for normal line-wise
     pub(crate) block_selection: bool,
+    /// True while typing replaces the character under the cursor (toggled by Insert)
+    pub(crate) overwrite_mode: bool,
     /// Multiple cursor positions (for Alt+Down multi-cursor mode)
     /// When non-empty, typing inserts at all cursor positions
     pub(crate) multi_cursors: Vec<Position>,
@@ -56,6 +58,12 @@ pub struct FileViewerState<'a> {
     pub(crate) find_active: bool,
     /// Find mode: true = regex, false = wildcard (* and ? only)
     pub(crate) find_regex_mode: bool,
+    /// Find mode: match letter case exactly
+    pub(crate) find_case_sensitive: bool,
+    /// Find mode: only match whole words
+    pub(crate) find_whole_word: bool,
+    /// Options each new search starts with (persisted in the undo file)
+    pub(crate) find_options_default: crate::find::FindOptions,
     /// True if find mode was entered via replace keybinding (auto-enter replace after search)
     pub(crate) find_via_replace: bool,
     /// Current find pattern being entered
@@ -73,10 +81,16 @@ pub struct FileViewerState<'a> {
     pub(crate) find_input_saved: String,
     /// Last successful search pattern (for F3/Shift+F3)
     pub(crate) last_search_pattern: Option<String>,
-    /// Whether the last search pattern used regex mode (true) or wildcard mode (false)
-    pub(crate) last_search_regex_mode: bool,
+    /// Find options the last search pattern was highlighted with
+    pub(crate) last_search_options: crate::find::FindOptions,
     /// Search pattern saved before entering find mode (to restore on Esc)
     pub(crate) saved_search_pattern: Option<String>,
+    /// View and cursor (top_line, cursor_line, cursor_col) when find mode started;
+    /// incremental search moves from there and Esc goes back to it
+    pub(crate) saved_find_position: Option<(usize, usize, usize)>,
+    /// Undo position when the search highlight was set by highlight_word; an
+    /// edit after that clears the highlight
+    pub(crate) word_highlight_edit: Option<usize>,
     /// Whether we've wrapped around in search
     pub(crate) search_wrapped: bool,
     /// Whether we're showing a wrap warning (waiting for second press to actually wrap)
@@ -97,6 +111,10 @@ pub struct FileViewerState<'a> {
     pub(crate) replace_cursor_pos: usize,
     /// Selection in replace pattern: (start_pos, end_pos) in character indices
     pub(crate) replace_selection: Option<(usize, usize)>,
+    /// Replace matches the casing of each occurrence (ALL CAPS, Capitalized, lowercase)
+    pub(crate) replace_preserve_case: bool,
+    /// Confirming replacements one match at a time: (matches left, replaced so far)
+    pub(crate) replace_confirm: Option<(usize, usize)>,
     /// Replace history (last 100 replacement strings, per-file)
     pub(crate) replace_history: Vec<String>,
     /// Current position in replace history (when navigating with Up/Down)
@@ -165,6 +183,49 @@ pub struct FileViewerState<'a> {
     pub(crate) close_all_confirmed: bool,
     /// Whether this is an untitled file that hasn't been saved to disk yet
     pub(crate) is_untitled: bool,
+    /// Whether this is the scratch buffer (never backed by a file, no undo file)
+    pub(crate) is_scratch: bool,
+    /// Name shown for buffers without a file on disk (untitled and scratch);
+    /// None for regular files, which show their path
+    pub(crate) display_name: Option<String>,
+    /// Whether the file does not exist on disk yet (shown as "[NEW]" until first saved)
+    pub(crate) is_new: bool,
+    /// Opened above `large_file_threshold_mb`: read-only, no document-wide search statistics
+    pub(crate) large_file: bool,
+    /// Read position when the file is followed (`--follow`); None otherwise
+    pub(crate) follow: Option<crate::follow::FollowState>,
+    /// Signals that the editing loop should switch to/from the scratch buffer
+    pub(crate) scratch_toggle_requested: bool,
+    /// Distraction-free mode: no header, footer, line numbers or scrollbar
+    pub(crate) zen_mode: bool,
+    /// Tabs, spaces and trailing whitespace are drawn visibly
+    pub(crate) show_invisibles: bool,
+    /// Visual bell: header and footer are drawn flashed until the next event-loop tick
+    pub(crate) bell_flash: bool,
+    /// Signals that the editing loop should toggle zen mode
+    pub(crate) zen_toggle_requested: bool,
+    /// Encoding the buffer is written in on save
+    pub(crate) encoding: crate::encoding::FileEncoding,
+    /// Line ending written between lines on save, detected when the file is read
+    pub(crate) line_ending: crate::line_ending::LineEnding,
+    /// The file on disk as we last read or wrote it; None while it does not exist
+    pub(crate) disk_stamp: Option<crate::file_watch::DiskStamp>,
+    /// The saved file the diff gutter compares against, with the stamp it was read at
+    pub(crate) saved_snapshot: Option<(Option<crate::file_watch::DiskStamp>, Vec<String>)>,
+    /// The file as committed in git HEAD, when it is tracked (`git_gutter`)
+    pub(crate) git_head: Option<Vec<String>>,
+    /// Git branch of the file's repository, shown in the header (`git_branch`)
+    pub(crate) git_branch: Option<String>,
+    /// Blame per line while the blame gutter is shown
+    pub(crate) blame: Option<Vec<crate::git::BlameLine>>,
+    /// Signals that the editing loop should show or hide the blame gutter
+    pub(crate) blame_toggle_requested: bool,
+    /// Whether the directory tree sidebar is shown
+    pub(crate) sidebar_open: bool,
+    /// Whether keys go to the sidebar instead of the text
+    pub(crate) sidebar_focused: bool,
+    /// Diff gutter markers per line, with the hash of the buffer they were computed for
+    pub(crate) diff_marks: Option<(u64, Vec<Option<crate::diff::LineChange>>)>,
     /// Whether this file is read-only (no write permission)
     /// In read-only mode, editing operations are blocked but navigation/copy/find still work
     pub(crate) is_read_only: bool,
@@ -196,6 +257,19 @@ pub struct FileViewerState<'a> {
     /// Status message to show in the footer (e.g., warnings, errors)
     #[allow(dead_code)] // Read in rendering.rs (binary)
     pub(crate) status_message: Option<String>,
+    /// Open buffers (path, unsaved changes) shown as tabs in the header; the current
+    /// one is `buffer_index`
+    pub(crate) buffers: Vec<(String, bool)>,
+    pub(crate) buffer_index: usize,
+    /// Set by next/previous/goto buffer keys; ui.rs switches to that buffer
+    pub(crate) buffer_switch_requested: Option<usize>,
+    /// File to switch to, e.g. picked from the find in files results
+    pub(crate) open_file_requested: Option<String>,
+    /// Text whose next occurrence add_next_occurrence puts a cursor at, and
+    /// whether only whole words match
+    pub(crate) occurrence_word: Option<(String, bool)>,
+    /// Waiting for the delimiter key after select_inside (false) or select_around (true)
+    pub(crate) pending_delimiter_select: Option<bool>,
     /// True when the current mouse drag was initiated by clicking on the line number area.
     /// Used to distinguish line-number drags from text-area drags that move over line numbers.
     pub(crate) line_number_drag_active: bool,
@@ -213,6 +287,7 @@ impl<'a> FileViewerState<'a> {
             selection_end: None,
             selection_anchor: None,
             block_selection: false,
+            overwrite_mode: false,
             multi_cursors: Vec::new(),
             cursor_blink_state: true,
             last_blink_time: None,
@@ -234,6 +309,9 @@ impl<'a> FileViewerState<'a> {
             last_save_time: None,
             find_active: false,
             find_regex_mode: true,
+            find_case_sensitive: false,
+            find_whole_word: false,
+            find_options_default: crate::find::FindOptions::default(),
             find_via_replace: false,
             find_pattern: String::new(),
             find_cursor_pos: 0,
@@ -242,8 +320,10 @@ impl<'a> FileViewerState<'a> {
             find_history_index: None,
             find_input_saved: String::new(),
             last_search_pattern: None,
-            last_search_regex_mode: true,
+            last_search_options: crate::find::FindOptions::default(),
             saved_search_pattern: None,
+            saved_find_position: None,
+            word_highlight_edit: None,
             search_wrapped: false,
             wrap_warning_pending: None,
             find_scope: None,
@@ -253,6 +333,8 @@ impl<'a> FileViewerState<'a> {
             replace_pattern: String::new(),
             replace_cursor_pos: 0,
             replace_selection: None,
+            replace_preserve_case: false,
+            replace_confirm: None,
             replace_history: Vec::new(),
             replace_history_index: None,
             replace_input_saved: String::new(),
@@ -283,6 +365,27 @@ impl<'a> FileViewerState<'a> {
             close_all_confirmation_active: false,
             close_all_confirmed: false,
             is_untitled: false,
+            is_scratch: false,
+            display_name: None,
+            is_new: false,
+            large_file: false,
+            follow: None,
+            scratch_toggle_requested: false,
+            zen_mode: false,
+            show_invisibles: settings.appearance.show_invisibles,
+            bell_flash: false,
+            zen_toggle_requested: false,
+            encoding: crate::encoding::FileEncoding::Utf8,
+            line_ending: crate::line_ending::LineEnding::Lf,
+            disk_stamp: None,
+            saved_snapshot: None,
+            git_head: None,
+            git_branch: None,
+            blame: None,
+            blame_toggle_requested: false,
+            sidebar_open: false,
+            sidebar_focused: false,
+            diff_marks: None,
             is_read_only: false,
             is_sudo: false,
             markdown_rendered: false,
@@ -294,6 +397,12 @@ impl<'a> FileViewerState<'a> {
             rendered_top_line: 0,
             cursor_at_wrap_end: false,
             status_message: None,
+            buffers: Vec::new(),
+            buffer_index: 0,
+            buffer_switch_requested: None,
+            open_file_requested: None,
+            occurrence_word: None,
+            pending_delimiter_select: None,
             line_number_drag_active: false,
         }
     }
@@ -315,10 +424,13 @@ impl<'a> FileViewerState<'a> {
         use crossterm::style::Color;
         let effectively_read_only = self.is_read_only || self.markdown_rendered;
         match (self.is_sudo, effectively_read_only) {
-            (false, false) => {
-                crate::settings::Settings::parse_color(&self.settings.appearance.header_bg)
-                    .unwrap_or(Color::Rgb { r: 0, g: 24, b: 72 })
-            }
+            (false, false) => self
+                .settings
+                .appearance
+                .header_bg
+                .as_deref()
+                .and_then(crate::settings::Settings::parse_color)
+                .unwrap_or(self.settings.theme.chrome_bg),
             (false, true) => Color::Rgb { r: 30, g: 77, b: 122 },
             (true, false) => Color::Rgb { r: 90, g: 0, b: 0 },
             (true, true)  => Color::Rgb { r: 120, g: 80, b: 80 },
@@ -392,6 +504,30 @@ impl<'a> FileViewerState<'a> {
         }
     }
 
+    /// Clear selection (including block mode), multi-cursors and search
+    /// highlights. Returns true if there was anything to clear
+    pub(crate) fn clear_modes(&mut self) -> bool {
+        let active = self.has_selection()
+            || self.block_selection
+            || self.has_multi_cursors()
+            || self.rendered_selection_start.is_some()
+            || self.rendered_selection_end.is_some()
+            || self.last_search_pattern.is_some();
+        if !active {
+            return false;
+        }
+        self.clear_selection();
+        self.block_selection = false;
+        self.clear_multi_cursors();
+        self.clear_rendered_selection();
+        self.last_search_pattern = None;
+        self.find_scope = None;
+        self.search_hit_count = 0;
+        self.search_current_hit = 0;
+        self.needs_redraw = true;
+        true
+    }
+
     pub(crate) fn adjust_cursor_col(&mut self, lines: &[&str]) {
         if let Some(line) = lines.get(self.absolute_line()) {
             let char_count = line.chars().count();
@@ -554,6 +690,16 @@ impl<'a> FileViewerState<'a> {
         }
     }
 
+    /// Block selection as (first_line, last_line, left_col, right_col), with the
+    /// columns ordered even when the block was extended leftwards
+    pub(crate) fn block_rect(&self) -> Option<(usize, usize, usize, usize)> {
+        if !self.block_selection {
+            return None;
+        }
+        let (s, e) = (self.selection_start?, self.selection_end?);
+        Some((s.0.min(e.0), s.0.max(e.0), s.1.min(e.1), s.1.max(e.1)))
+    }
+
     pub(crate) fn is_point_in_selection(&self, pos: Position) -> bool {
         if let Some((start, end)) = self.selection_range() {
             let (l, c) = pos;
@@ -608,6 +754,96 @@ impl<'a> FileViewerState<'a> {
 
 
 
+    /// Options of the current search
+    pub(crate) fn find_options(&self) -> crate::find::FindOptions {
+        crate::find::FindOptions {
+            regex_mode: self.find_regex_mode,
+            case_sensitive: self.find_case_sensitive,
+            whole_word: self.find_whole_word,
+        }
+    }
+
+    /// Start a new search with the saved default find options, dropping any
+    /// per-search overrides from the previous search
+    pub(crate) fn reset_find_options(&mut self) {
+        self.set_find_options(self.find_options_default);
+        self.saved_find_position = None;
+        self.word_highlight_edit = None;
+    }
+
+    /// Clear the highlight set by highlight_word once the text has been edited
+    /// (or an edit undone) since it was set
+    pub(crate) fn clear_word_highlight_if_edited(&mut self) {
+        if self.word_highlight_edit.is_some_and(|at| at != self.undo_history.current) {
+            self.word_highlight_edit = None;
+            self.last_search_pattern = None;
+            self.search_hit_count = 0;
+            self.search_current_hit = 0;
+        }
+    }
+
+    /// Refresh the gutter markers: against git HEAD for files tracked by git
+    /// (`git_gutter`), otherwise against the saved file for modified buffers
+    /// (`diff_gutter`). The saved file, its HEAD version and the branch are read
+    /// again when the file's stamp changed (on load and save, or when written
+    /// elsewhere), the diff redone only when the buffer changed.
+    pub(crate) fn update_diff_marks(&mut self, lines: &[String], file: &str) {
+        use std::hash::{Hash, Hasher};
+
+        let appearance = &self.settings.appearance;
+        if self.large_file || self.is_untitled || self.follow.is_some() || self.display_name.is_some() {
+            self.diff_marks = None;
+            return;
+        }
+        let stamp = crate::file_watch::DiskStamp::read(file);
+        if self.saved_snapshot.as_ref().is_none_or(|(known, _)| *known != stamp) {
+            let saved = if appearance.diff_gutter {
+                std::fs::read(file)
+                    .map(|bytes| crate::line_ending::split_lines(&self.encoding.decode_as(&bytes)))
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            self.saved_snapshot = Some((stamp, saved));
+            self.git_head = appearance.git_gutter.then(|| crate::git::head_lines(file, self.encoding)).flatten();
+            self.git_branch = appearance.git_branch.then(|| crate::git::current_branch(file)).flatten();
+            if self.blame.is_some() {
+                self.blame = Some(crate::git::blame(file, lines, self.encoding).unwrap_or_default());
+            }
+            self.diff_marks = None;
+        }
+        let against_head = self.git_head.is_some();
+        if !(against_head || appearance.diff_gutter && self.modified) {
+            self.diff_marks = None;
+            return;
+        }
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        lines.hash(&mut hasher);
+        against_head.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self.diff_marks.as_ref().is_some_and(|(known, _)| *known == hash) {
+            return;
+        }
+        let base = match (&self.git_head, &self.saved_snapshot) {
+            (Some(head), _) => head.as_slice(),
+            (None, Some((_, saved))) => saved.as_slice(),
+            (None, None) => &[],
+        };
+        self.diff_marks = Some((hash, crate::diff::line_changes(base, lines)));
+    }
+
+    /// Diff gutter marker of a line, if it changed since the last save
+    pub(crate) fn diff_mark(&self, line: usize) -> Option<crate::diff::LineChange> {
+        self.diff_marks.as_ref().and_then(|(_, marks)| marks.get(line).copied().flatten())
+    }
+
+    /// Use `options` for the current search
+    pub(crate) fn set_find_options(&mut self, options: crate::find::FindOptions) {
+        self.find_regex_mode = options.regex_mode;
+        self.find_case_sensitive = options.case_sensitive;
+        self.find_whole_word = options.whole_word;
+    }
+
     /// Check if multi-cursor mode is active
     pub(crate) fn has_multi_cursors(&self) -> bool {
         !self.multi_cursors.is_empty()
@@ -635,6 +871,33 @@ impl<'a> FileViewerState<'a> {
         self.line_wrapping_override.unwrap_or(self.settings.line_wrapping)
     }
 
+    /// Alert the user that an action failed, as configured by the `bell` setting
+    pub(crate) fn alert(&mut self) {
+        match self.settings.bell.as_str() {
+            "audible" => {
+                use std::io::Write;
+                let mut stdout = std::io::stdout();
+                let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+            }
+            "visual" => {
+                self.bell_flash = true;
+                self.needs_redraw = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// Alert and explain why an edit was refused
+    pub(crate) fn alert_editing_blocked(&mut self) {
+        self.status_message = Some(if self.markdown_rendered {
+            "Switch to plain view to edit".to_string()
+        } else {
+            "File is read-only".to_string()
+        });
+        self.needs_footer_redraw = true;
+        self.alert();
+    }
+
     /// Check if editing is blocked.
     /// Editing is blocked when the file is read-only OR when the rendered markdown view is
     /// active (the rendered view is intentionally read-only for now).
@@ -701,6 +964,40 @@ impl<'a> FileViewerState<'a> {
         }
     }
 
+    /// Scroll so the cursor keeps `scroll_margin` lines of context above and below
+    /// it. Fewer at the start and end of the file; at most half the view.
+    pub(crate) fn apply_scroll_margin(&mut self, lines: &[String], visible_lines: usize) {
+        let effective_visible = self.effective_visible_lines(lines, visible_lines);
+        let margin = self.settings.scroll_margin.min(effective_visible.saturating_sub(1) / 2);
+        if margin == 0 || lines.is_empty() || self.saved_absolute_cursor.is_some() {
+            return;
+        }
+        let absolute = self.absolute_line();
+
+        while self.cursor_line < margin && self.top_line > 0 {
+            self.top_line -= 1;
+            self.cursor_line += 1;
+            self.top_line_visual_offset = 0;
+        }
+
+        // Visual rows from the top of the view through the context lines below the cursor
+        let text_width = crate::coordinates::calculate_text_width(self, lines, visible_lines);
+        let tab_width = self.settings.tab_width;
+        let wrapping = self.is_line_wrapping_enabled();
+        let last = (absolute + margin).min(lines.len() - 1);
+        let rows_needed = |state: &Self| -> usize {
+            (state.top_line..=last)
+                .map(|i| crate::coordinates::visual_lines_for(lines, i, text_width, tab_width, wrapping))
+                .sum::<usize>()
+                .saturating_sub(state.top_line_visual_offset)
+        };
+        while self.cursor_line > margin && rows_needed(self) > effective_visible {
+            self.top_line += 1;
+            self.cursor_line -= 1;
+            self.top_line_visual_offset = 0;
+        }
+    }
+
     /// Update cursor blink state (toggles every 500ms)
     /// Returns true if blink state changed and redraw is needed
     pub(crate) fn update_cursor_blink(&mut self) -> bool {
@@ -1107,6 +1404,35 @@ mod tests {
         assert!(state.saved_absolute_cursor.is_none());
     }
 
+    #[test]
+    fn scroll_margin_keeps_context_around_cursor() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut settings = Settings::load().expect("Failed to load test settings");
+        settings.scroll_margin = 3;
+        let settings = Box::leak(Box::new(settings));
+        let mut state = FileViewerState::new(80, UndoHistory::new(), settings);
+        let lines: Vec<String> = vec!["test".to_string(); 30];
+
+        state.top_line = 10;
+        state.cursor_line = 8;
+        state.apply_scroll_margin(&lines, 10);
+        assert_eq!((state.top_line, state.cursor_line), (12, 6));
+
+        state.cursor_line = 1;
+        state.apply_scroll_margin(&lines, 10);
+        assert_eq!((state.top_line, state.cursor_line), (10, 3));
+
+        // No context to show past the start or end of the file
+        state.top_line = 0;
+        state.cursor_line = 1;
+        state.apply_scroll_margin(&lines, 10);
+        assert_eq!((state.top_line, state.cursor_line), (0, 1));
+        state.top_line = 20;
+        state.cursor_line = 8;
+        state.apply_scroll_margin(&lines, 10);
+        assert_eq!((state.top_line, state.cursor_line), (20, 8));
+    }
+
     #[test]
     fn ensure_cursor_visible_brings_above_cursor_to_top() {
         let (_tmp, _guard) = set_temp_home();
@@ -0,0 +1,240 @@
+//! Text encodings a buffer can be read from and written back in.
+
+use std::fs;
+use std::io;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// Characters of Windows-1252 bytes 0x80..=0x9F; the five unassigned bytes
+/// map to the C1 control character of the same value
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// Encoding used when a buffer is written to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileEncoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Latin1,
+    Windows1252,
+    /// UTF-16 little endian, written with a BOM
+    Utf16Le,
+    /// UTF-16 big endian, written with a BOM
+    Utf16Be,
+}
+
+impl FileEncoding {
+    /// All encodings offered by the conversion and reopen prompts, in prompt order
+    pub(crate) const ALL: [FileEncoding; 6] = [
+        FileEncoding::Utf8,
+        FileEncoding::Utf8Bom,
+        FileEncoding::Latin1,
+        FileEncoding::Windows1252,
+        FileEncoding::Utf16Le,
+        FileEncoding::Utf16Be,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            FileEncoding::Utf8 => "UTF-8",
+            FileEncoding::Utf8Bom => "UTF-8 with BOM",
+            FileEncoding::Latin1 => "Latin-1",
+            FileEncoding::Windows1252 => "Windows-1252",
+            FileEncoding::Utf16Le => "UTF-16 LE",
+            FileEncoding::Utf16Be => "UTF-16 BE",
+        }
+    }
+
+    /// Single byte for `ch` in the 8-bit encodings
+    fn encode_byte(self, ch: char) -> Option<u8> {
+        match self {
+            FileEncoding::Utf8 | FileEncoding::Utf8Bom | FileEncoding::Utf16Le | FileEncoding::Utf16Be => None,
+            FileEncoding::Latin1 => u8::try_from(ch as u32).ok(),
+            FileEncoding::Windows1252 => match ch as u32 {
+                // C1 controls only survive where the byte is unassigned
+                0x80..=0x9F => {
+                    let byte = ch as u32 as u8;
+                    (WINDOWS_1252_HIGH[(byte - 0x80) as usize] == ch).then_some(byte)
+                }
+                0..=0xFF => Some(ch as u32 as u8),
+                _ => WINDOWS_1252_HIGH
+                    .iter()
+                    .position(|&c| c == ch)
+                    .map(|i| 0x80 + i as u8),
+            },
+        }
+    }
+
+    fn can_encode(self, ch: char) -> bool {
+        match self {
+            FileEncoding::Utf8 | FileEncoding::Utf8Bom | FileEncoding::Utf16Le | FileEncoding::Utf16Be => true,
+            FileEncoding::Latin1 | FileEncoding::Windows1252 => self.encode_byte(ch).is_some(),
+        }
+    }
+
+    /// Number of characters in `lines` this encoding cannot represent
+    pub(crate) fn unrepresentable_count(self, lines: &[String]) -> usize {
+        lines
+            .iter()
+            .flat_map(|l| l.chars())
+            .filter(|&c| !self.can_encode(c))
+            .count()
+    }
+
+    /// Encode `text`; characters that cannot be represented become `?`
+    pub(crate) fn encode(self, text: &str) -> Vec<u8> {
+        match self {
+            FileEncoding::Utf8 => text.as_bytes().to_vec(),
+            FileEncoding::Utf8Bom => [UTF8_BOM, text.as_bytes()].concat(),
+            FileEncoding::Latin1 | FileEncoding::Windows1252 => text
+                .chars()
+                .map(|c| self.encode_byte(c).unwrap_or(b'?'))
+                .collect(),
+            FileEncoding::Utf16Le => UTF16LE_BOM
+                .iter()
+                .copied()
+                .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+                .collect(),
+            FileEncoding::Utf16Be => UTF16BE_BOM
+                .iter()
+                .copied()
+                .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+                .collect(),
+        }
+    }
+
+    /// Decode `bytes` as this encoding, whatever detection would have picked.
+    /// Invalid UTF-8 or UTF-16 becomes U+FFFD; a leading BOM of the encoding is dropped.
+    pub(crate) fn decode_as(self, bytes: &[u8]) -> String {
+        match self {
+            FileEncoding::Utf8 | FileEncoding::Utf8Bom => {
+                String::from_utf8_lossy(bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes)).into_owned()
+            }
+            FileEncoding::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+            FileEncoding::Windows1252 => bytes
+                .iter()
+                .map(|&b| match b {
+                    0x80..=0x9F => WINDOWS_1252_HIGH[(b - 0x80) as usize],
+                    _ => b as char,
+                })
+                .collect(),
+            FileEncoding::Utf16Le => decode_utf16(bytes.strip_prefix(UTF16LE_BOM).unwrap_or(bytes), u16::from_le_bytes),
+            FileEncoding::Utf16Be => decode_utf16(bytes.strip_prefix(UTF16BE_BOM).unwrap_or(bytes), u16::from_be_bytes),
+        }
+    }
+}
+
+/// Decode UTF-16 code units read with `unit`; an odd trailing byte becomes U+FFFD
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]])).collect();
+    let mut text = String::from_utf16_lossy(&units);
+    if bytes.len() % 2 == 1 {
+        text.push(char::REPLACEMENT_CHARACTER);
+    }
+    text
+}
+
+/// UTF-16 without a BOM, recognised by the zero bytes of ASCII characters:
+/// zeros in at least half of the high bytes and none in the low bytes
+fn detect_utf16_without_bom(bytes: &[u8]) -> Option<FileEncoding> {
+    let sample = &bytes[..bytes.len().min(1024) & !1];
+    let pairs = sample.len() / 2;
+    if pairs == 0 {
+        return None;
+    }
+    let zeros = |offset: usize| sample.iter().skip(offset).step_by(2).filter(|&&b| b == 0).count();
+    let (even, odd) = (zeros(0), zeros(1));
+    if odd * 2 >= pairs && even == 0 {
+        Some(FileEncoding::Utf16Le)
+    } else if even * 2 >= pairs && odd == 0 {
+        Some(FileEncoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+/// Decode raw file bytes: a BOM selects UTF-8 with BOM or UTF-16, text full of
+/// zero bytes is UTF-16 without BOM, valid UTF-8 means UTF-8, anything else is
+/// read as Latin-1 (every byte maps to one character)
+pub(crate) fn decode(bytes: &[u8]) -> (String, FileEncoding) {
+    if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+        return (String::from_utf8_lossy(rest).into_owned(), FileEncoding::Utf8Bom);
+    }
+    let utf16 = if bytes.starts_with(UTF16LE_BOM) {
+        Some(FileEncoding::Utf16Le)
+    } else if bytes.starts_with(UTF16BE_BOM) {
+        Some(FileEncoding::Utf16Be)
+    } else {
+        detect_utf16_without_bom(bytes)
+    };
+    if let Some(encoding) = utf16 {
+        return (encoding.decode_as(bytes), encoding);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), FileEncoding::Utf8),
+        Err(_) => (bytes.iter().map(|&b| b as char).collect(), FileEncoding::Latin1),
+    }
+}
+
+/// Read a file and decode it, returning the text and the encoding it was stored in
+pub(crate) fn read_file(path: &str) -> io::Result<(String, FileEncoding)> {
+    fs::read(path).map(|bytes| decode(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_detects_bom_utf8_and_latin1() {
+        assert_eq!(decode(b"\xEF\xBB\xBFabc"), ("abc".to_string(), FileEncoding::Utf8Bom));
+        assert_eq!(decode("äbc".as_bytes()), ("äbc".to_string(), FileEncoding::Utf8));
+        assert_eq!(decode(b"\xE4bc"), ("äbc".to_string(), FileEncoding::Latin1));
+    }
+
+    #[test]
+    fn utf16_round_trips_with_and_without_bom() {
+        let le = FileEncoding::Utf16Le.encode("a€😀");
+        assert_eq!(&le[..4], b"\xFF\xFEa\0");
+        assert_eq!(decode(&le), ("a€😀".to_string(), FileEncoding::Utf16Le));
+
+        let be = FileEncoding::Utf16Be.encode("hi\n");
+        assert_eq!(be, b"\xFE\xFF\0h\0i\0\n");
+        assert_eq!(decode(&be), ("hi\n".to_string(), FileEncoding::Utf16Be));
+
+        assert_eq!(decode(b"h\0i\0"), ("hi".to_string(), FileEncoding::Utf16Le));
+        assert_eq!(decode(b"\0h\0i"), ("hi".to_string(), FileEncoding::Utf16Be));
+        assert_eq!(FileEncoding::Utf16Le.decode_as(b"h\0i"), "h\u{FFFD}");
+    }
+
+    #[test]
+    fn encode_round_trips_and_replaces_unrepresentable() {
+        assert_eq!(FileEncoding::Latin1.encode("äbc"), b"\xE4bc");
+        assert_eq!(FileEncoding::Latin1.encode("a€b"), b"a?b");
+        assert_eq!(FileEncoding::Utf8Bom.encode("a"), b"\xEF\xBB\xBFa");
+
+        let lines = vec!["a€b".to_string(), "✓ ä".to_string()];
+        assert_eq!(FileEncoding::Latin1.unrepresentable_count(&lines), 2);
+        assert_eq!(FileEncoding::Windows1252.unrepresentable_count(&lines), 1);
+        assert_eq!(FileEncoding::Utf8.unrepresentable_count(&lines), 0);
+    }
+
+    #[test]
+    fn decode_as_forces_the_chosen_encoding() {
+        let bytes = b"\x80 \xE4 \x81";
+        assert_eq!(FileEncoding::Windows1252.decode_as(bytes), "€ ä \u{81}");
+        assert_eq!(FileEncoding::Latin1.decode_as(bytes), "\u{80} ä \u{81}");
+        assert_eq!(FileEncoding::Utf8.decode_as(bytes), "\u{FFFD} \u{FFFD} \u{FFFD}");
+        assert_eq!(FileEncoding::Utf8.decode_as(b"\xEF\xBB\xBFok"), "ok");
+
+        // Windows-1252 round-trips, including the unassigned bytes
+        let text = FileEncoding::Windows1252.decode_as(bytes);
+        assert_eq!(FileEncoding::Windows1252.encode(&text), bytes);
+        assert_eq!(FileEncoding::Windows1252.encode("\u{80}"), b"?");
+    }
+}
@@ -5,12 +5,16 @@ use std::time::Instant;
 
 use crate::coordinates::line_number_width;
 use crate::editing::{
-    apply_redo, apply_undo, delete_file_history, handle_copy, handle_cut, handle_editing_keys,
-    handle_paste, save_file,
+    apply_redo, apply_undo, delete_file_history, duplicate_selection, handle_copy, handle_cut,
+    handle_editing_keys, handle_paste, move_lines, open_line, parse_time_offset, save_file, undo_to_time,
 };
 use crate::editor_state::FileViewerState;
+use crate::encoding::FileEncoding;
 use crate::settings::Settings;
 
+/// Status shown when trying to save the scratch buffer
+pub(crate) const SCRATCH_NOT_SAVED_MSG: &str = "Scratch buffer is not backed by a file";
+
 /// Normalize key events so keypad Enter (often reported as '\r' or '\n') behaves like Enter
 /// Normalize key events so keypad Enter (often reported as '\r', '\n', or the numpad_enter keybinding) behaves like Enter
 pub(crate) fn normalize_key_event(mut key_event: KeyEvent, settings: &Settings) -> KeyEvent {
@@ -29,6 +33,44 @@ pub(crate) fn normalize_key_event(mut key_event: KeyEvent, settings: &Settings)
     key_event
 }
 
+/// Text the terminal delivered as one bracketed paste. In the editor it goes in
+/// as a whole, one undo step, without the auto-indent and auto-closing typing
+/// would trigger. Footer inputs and the menu get its first line as keystrokes.
+/// Returns (should_quit, should_close_file) like `handle_key_event`.
+pub(crate) fn handle_paste_event(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    text: &str,
+    settings: &Settings,
+    visible_lines: usize,
+    filename: &str,
+) -> Result<(bool, bool), std::io::Error> {
+    if state.find_active || state.replace_active || state.goto_line_active || state.menu_bar.active {
+        let first_line = text.lines().next().unwrap_or("");
+        for ch in first_line.chars() {
+            let key = KeyEvent::new(KeyCode::Char(ch), KeyModifiers::empty());
+            let result = handle_key_event(state, lines, key, settings, visible_lines, filename)?;
+            if result != (false, false) {
+                return Ok(result);
+            }
+        }
+        return Ok((false, false));
+    }
+
+    state.status_message = None;
+    if state.is_editing_blocked() {
+        state.alert_editing_blocked();
+        return Ok((false, false));
+    }
+    // Terminals send line breaks inside a paste as carriage returns
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    if !text.is_empty() && crate::editing::paste_text(state, lines, &text, filename) {
+        state.ensure_cursor_visible(visible_lines, lines);
+        state.needs_redraw = true;
+    }
+    Ok((false, false))
+}
+
 /// Result of handle_key_event: (should_quit, should_close_file)
 pub(crate) fn handle_key_event(
     state: &mut FileViewerState,
@@ -71,11 +113,56 @@ pub(crate) fn handle_key_event(
         }
     }
 
+    // Answer for the highlighted match while confirming replacements
+    if state.replace_confirm.is_some() {
+        crate::find::handle_replace_confirm_key(state, lines, code, visible_lines);
+        if state.modified {
+            let abs = state.absolute_line();
+            state.undo_history.update_state(state.top_line, abs, state.cursor_col, lines.clone());
+            state.undo_history.replace_history = state.replace_history.clone();
+            let _ = state.undo_history.save(filename);
+            state.last_save_time = Some(Instant::now());
+        }
+        return Ok((false, false));
+    }
+
+    // Delimiter key after select_inside / select_around; anything else cancels
+    if let Some(around) = state.pending_delimiter_select.take() {
+        if let KeyCode::Char(c) = code {
+            crate::text_objects::select_delimited(state, lines, c, around, visible_lines);
+        }
+        state.needs_footer_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Keys go to the sidebar while it has the focus; Ctrl and Alt shortcuts it does
+    // not use move the focus back to the text and act there
+    if state.sidebar_focused && crate::sidebar::is_visible(state) && !state.menu_bar.active {
+        if crate::sidebar::handle_key(state, key_event, filename, visible_lines, settings)? {
+            return Ok((false, false));
+        }
+        state.sidebar_focused = false;
+    }
+
+    // Escape hatch: clear selection, multi-cursors and search highlights in one
+    // press. Only consumes the key when there is something to clear
+    if !(state.find_active
+        || state.replace_active
+        || state.goto_line_active
+        || state.help_active
+        || state.menu_bar.active)
+        && settings.keybindings.clear_modes_matches(&code, &modifiers)
+        && state.clear_modes()
+    {
+        return Ok((false, false));
+    }
+
     // Update menu checkable states before rendering
     state.menu_bar.update_checkable(
         crate::menu::MenuAction::ViewLineWrap,
         state.is_line_wrapping_enabled()
     );
+    state.menu_bar.update_checkable(crate::menu::MenuAction::ViewSidebar, state.sidebar_open);
 
     // Handle menu interactions (Alt+letter to open, navigation when active)
     // But not when help is active (help should handle Esc first)
@@ -134,7 +221,8 @@ pub(crate) fn handle_key_event(
                     let is_current_file = current_canonical == removed_canonical;
 
                     // Remove the file from tracking
-                    let _ = delete_file_history(&file_path.to_string_lossy());
+                    let name = file_path.to_string_lossy();
+                    let _ = delete_file_history(&name, crate::ui::is_untitled(&name));
 
                     if is_current_file {
                         // Signal ui.rs to close this file and open a new untitled
@@ -169,6 +257,12 @@ pub(crate) fn handle_key_event(
                 return Ok((false, false));
             }
             crate::menu::MenuAction::FileSave => {
+                if state.is_scratch {
+                    state.status_message = Some(SCRATCH_NOT_SAVED_MSG.to_string());
+                    state.needs_footer_redraw = true;
+                    return Ok((false, false));
+                }
+
                 // If this is an untitled file, we need to show the save-as dialog
                 if state.is_untitled {
                     // Delegate to ui.rs which will show the save dialog
@@ -178,12 +272,18 @@ pub(crate) fn handle_key_event(
 
                 if state.is_read_only {
                     state.status_message = Some("File is read-only".to_string());
+                    state.alert();
                     state.needs_footer_redraw = true;
                     return Ok((false, false));
                 }
 
-                save_file(filename, lines)?;
+                if !prepare_parent_dir(state, filename, settings)? {
+                    return Ok((false, false));
+                }
+                save_file(filename, lines, state.encoding, state.line_ending)?;
+                state.disk_stamp = crate::file_watch::DiskStamp::read(filename);
                 state.modified = false;
+                state.is_new = false;
                 state.undo_history.clear_unsaved_state();
                 let abs = state.absolute_line();
                 state.undo_history.update_cursor(state.top_line, abs, state.cursor_col);
@@ -193,21 +293,26 @@ pub(crate) fn handle_key_event(
                 state.last_save_time = Some(Instant::now());
                 return Ok((false, false));
             }
+            crate::menu::MenuAction::FileSaveAs | crate::menu::MenuAction::FileRename => {
+                // Delegate to ui.rs, which shows the dialog and switches to the new path
+                state.pending_menu_action = Some(action);
+                return Ok((false, false));
+            }
             crate::menu::MenuAction::FileClose => {
                 // Close current file (same as Ctrl+w)
-                if state.modified {
+                if state.modified && !state.is_scratch {
                     // Show confirmation dialog
                     let _ = crossterm::execute!(std::io::stdout(), crossterm::cursor::Show);
                     let confirmed = show_close_confirmation(filename, settings)?;
                     if confirmed {
-                        let _ = delete_file_history(filename);
+                        let _ = delete_file_history(filename, state.is_untitled);
                         return Ok((false, true));
                     } else {
                         state.needs_redraw = true;
                         return Ok((false, false));
                     }
                 } else {
-                    let _ = delete_file_history(filename);
+                    let _ = delete_file_history(filename, state.is_untitled);
                     return Ok((false, true));
                 }
             }
@@ -222,8 +327,7 @@ pub(crate) fn handle_key_event(
             }
             crate::menu::MenuAction::EditUndo => {
                 if state.is_editing_blocked() {
-                    state.status_message = Some(if state.markdown_rendered { "Switch to plain view to edit".to_string() } else { "File is read-only".to_string() });
-                    state.needs_footer_redraw = true;
+                    state.alert_editing_blocked();
                     return Ok((false, false));
                 }
                 if apply_undo(state, lines, filename, visible_lines) {
@@ -233,8 +337,7 @@ pub(crate) fn handle_key_event(
             }
             crate::menu::MenuAction::EditRedo => {
                 if state.is_editing_blocked() {
-                    state.status_message = Some(if state.markdown_rendered { "Switch to plain view to edit".to_string() } else { "File is read-only".to_string() });
-                    state.needs_footer_redraw = true;
+                    state.alert_editing_blocked();
                     return Ok((false, false));
                 }
                 if apply_redo(state, lines, filename, visible_lines) {
@@ -248,8 +351,7 @@ pub(crate) fn handle_key_event(
             }
             crate::menu::MenuAction::EditCut => {
                 if state.is_editing_blocked() {
-                    state.status_message = Some(if state.markdown_rendered { "Switch to plain view to edit".to_string() } else { "File is read-only".to_string() });
-                    state.needs_footer_redraw = true;
+                    state.alert_editing_blocked();
                     return Ok((false, false));
                 }
                 if handle_cut(state, lines, filename) {
@@ -259,8 +361,7 @@ pub(crate) fn handle_key_event(
             }
             crate::menu::MenuAction::EditPaste => {
                 if state.is_editing_blocked() {
-                    state.status_message = Some(if state.markdown_rendered { "Switch to plain view to edit".to_string() } else { "File is read-only".to_string() });
-                    state.needs_footer_redraw = true;
+                    state.alert_editing_blocked();
                     return Ok((false, false));
                 }
                 if handle_paste(state, lines, filename) {
@@ -268,6 +369,10 @@ pub(crate) fn handle_key_event(
                 }
                 return Ok((false, false));
             }
+            crate::menu::MenuAction::EditGitCommit => {
+                commit_current_file(state, filename, settings)?;
+                return Ok((false, false));
+            }
             crate::menu::MenuAction::EditFind => {
                 // Enter find mode (same as Ctrl+F)
                 // Save current search pattern to restore on Esc
@@ -287,6 +392,7 @@ pub(crate) fn handle_key_event(
                 }
 
                 state.find_active = true;
+                state.reset_find_options();
                 state.find_pattern.clear();
                 state.find_cursor_pos = 0;
                 state.needs_redraw = true;
@@ -300,6 +406,10 @@ pub(crate) fn handle_key_event(
                 }
                 return Ok((false, false));
             }
+            crate::menu::MenuAction::ViewSidebar => {
+                crate::sidebar::toggle(state, filename, true);
+                return Ok((false, false));
+            }
             crate::menu::MenuAction::ViewMarkdownRendered => {
                 // Toggle rendered markdown view (only active for .md files)
                 if crate::menu::is_markdown_file(filename) {
@@ -460,6 +570,17 @@ pub(crate) fn handle_key_event(
     }
 
 
+    // Block navigation by indentation; checked before Ctrl+Up/Down paragraph jumps
+    let indent_up = settings.keybindings.indent_block_up_matches(&code, &modifiers);
+    if indent_up || settings.keybindings.indent_block_down_matches(&code, &modifiers) {
+        if let Some(target) = indent_block_target(lines, state.absolute_line(), !indent_up, settings.tab_width) {
+            state.clear_selection();
+            let col = first_non_blank_char(&lines[target]);
+            state.set_cursor_position(target, col, lines, visible_lines);
+        }
+        return Ok((false, false));
+    }
+
     // Ctrl+Arrow custom handling: word-wise (Left/Right) and paragraph-wise (Up/Down)
     if modifiers.contains(KeyModifiers::CONTROL) {
         let extend = modifiers.contains(KeyModifiers::SHIFT);
@@ -515,11 +636,12 @@ pub(crate) fn handle_key_event(
 
     // Handle close file (Ctrl+W)
     if settings.keybindings.close_matches(&code, &modifiers) {
-        if state.modified {
+        // The scratch buffer is throwaway by design - never ask to save it
+        if state.modified && !state.is_scratch {
             // Show confirmation prompt
             if show_close_confirmation(filename, settings)? {
                 // User confirmed - delete file history
-                let _ = delete_file_history(filename);
+                let _ = delete_file_history(filename, state.is_untitled);
                 return Ok((false, true)); // Don't quit editor, but close this file
             } else {
                 // User cancelled
@@ -528,7 +650,7 @@ pub(crate) fn handle_key_event(
             }
         } else {
             // No unsaved changes - just delete
-            let _ = delete_file_history(filename);
+            let _ = delete_file_history(filename, state.is_untitled);
             return Ok((false, true));
         }
     }
@@ -553,6 +675,42 @@ pub(crate) fn handle_key_event(
         return Ok((false, false));
     }
 
+    // Find in files below the working directory (default Ctrl+Shift+F). Checked
+    // before find since terminals may report Ctrl+Shift+F as Ctrl+F with an uppercase F
+    if !state.find_active
+        && !state.replace_active
+        && settings.keybindings.find_in_files_matches(&code, &modifiers)
+    {
+        if let Some(pattern) = show_text_prompt("Find in files (regex)", settings)?.filter(|p| !p.is_empty())
+            && let Some(found) = crate::project_find::run_project_find(state, &pattern, visible_lines, settings)?
+        {
+            let location = (found.line, found.start, found.end);
+            if crate::project_find::same_file(&found.path, std::path::Path::new(filename)) {
+                crate::project_find::select_match(state, lines, location, visible_lines);
+            } else {
+                crate::project_find::set_pending_jump(&found);
+                state.open_file_requested = Some(found.path.to_string_lossy().to_string());
+            }
+        }
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Show the directory tree sidebar or move the focus to it (default Ctrl+B)
+    if settings.keybindings.toggle_sidebar_matches(&code, &modifiers) {
+        crate::sidebar::toggle(state, filename, false);
+        return Ok((false, false));
+    }
+
+    // Open a project file picked with the fuzzy finder (default Ctrl+P)
+    if !state.find_active
+        && !state.replace_active
+        && settings.keybindings.fuzzy_find_matches(&code, &modifiers)
+    {
+        crate::fuzzy_find::run_fuzzy_finder(state, filename, visible_lines, settings)?;
+        return Ok((false, false));
+    }
+
     // Handle find (Ctrl+F)
     if settings.keybindings.find_matches(&code, &modifiers) {
         if state.find_active && !state.find_pattern.is_empty() {
@@ -576,6 +734,7 @@ pub(crate) fn handle_key_event(
             }
 
             state.find_active = true;
+            state.reset_find_options();
             state.find_via_replace = false; // Clear flag - this is normal find mode
             state.find_pattern.clear();
             state.find_cursor_pos = 0;
@@ -584,24 +743,143 @@ pub(crate) fn handle_key_event(
         return Ok((false, false));
     }
 
+    // Handle toggle preserve case while in replace mode (configurable keybinding, default Alt+C)
+    if settings.keybindings.toggle_preserve_case_matches(&code, &modifiers) && state.replace_active {
+        state.replace_preserve_case = !state.replace_preserve_case;
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Save the current find options as the default for new searches (default Ctrl+Alt+S)
+    if settings.keybindings.save_find_options_matches(&code, &modifiers) && state.find_active {
+        let options = state.find_options();
+        state.find_options_default = options;
+        state.undo_history.find_options = Some(options);
+        let _ = state.undo_history.save(filename);
+        state.last_save_time = Some(Instant::now());
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
     // Handle toggle find mode (regex vs wildcard) - Ctrl+Alt+X
     if settings.keybindings.toggle_find_mode_matches(&code, &modifiers)
         && state.find_active {
             // Toggle between regex and wildcard mode while in find mode
             state.find_regex_mode = !state.find_regex_mode;
-            // Update highlights with the new mode - CRITICAL!
-            crate::find::update_live_highlights(state);
-            crate::find::update_search_hit_count(state, lines);
+            // Update highlights and the incremental match with the new mode - CRITICAL!
+            crate::find::incremental_search(state, lines, visible_lines);
             state.needs_redraw = true;
             return Ok((false, false));
         }
 
+    // Handle toggle case-sensitive (Ctrl+Alt+A) and whole-word (Ctrl+Alt+W) matching in find mode
+    if state.find_active
+        && (settings.keybindings.toggle_find_case_matches(&code, &modifiers)
+            || settings.keybindings.toggle_find_whole_word_matches(&code, &modifiers))
+    {
+        if settings.keybindings.toggle_find_case_matches(&code, &modifiers) {
+            state.find_case_sensitive = !state.find_case_sensitive;
+        } else {
+            state.find_whole_word = !state.find_whole_word;
+        }
+        crate::find::incremental_search(state, lines, visible_lines);
+        return Ok((false, false));
+    }
+
+    // Reverse search the find history from the find prompt (default Ctrl+Alt+P)
+    if state.find_active && settings.keybindings.find_history_search_matches(&code, &modifiers) {
+        crate::find::search_find_history(state, lines, visible_lines);
+        return Ok((false, false));
+    }
+
     // Handle open dialog (configurable keybinding, default Ctrl+O)
     if settings.keybindings.open_dialog_matches(&code, &modifiers) {
         state.pending_menu_action = Some(crate::menu::MenuAction::FileOpenDialog);
         return Ok((false, false));
     }
 
+    // Handle scratch buffer toggle (configurable keybinding, default F2)
+    if settings.keybindings.scratch_buffer_matches(&code, &modifiers) {
+        state.scratch_toggle_requested = true;
+        return Ok((false, false));
+    }
+
+    // Switch between open buffers (default Ctrl+Tab / Ctrl+Shift+Tab, Alt+1..9)
+    let buffer_count = state.buffers.len();
+    if buffer_count > 1 {
+        let target = if settings.keybindings.next_buffer_matches(&code, &modifiers) {
+            Some((state.buffer_index + 1) % buffer_count)
+        } else if settings.keybindings.previous_buffer_matches(&code, &modifiers) {
+            Some((state.buffer_index + buffer_count - 1) % buffer_count)
+        } else {
+            settings.keybindings.goto_buffer_matches(&code, &modifiers)
+        };
+        if let Some(target) = target {
+            if target >= buffer_count {
+                state.alert();
+            } else if target != state.buffer_index {
+                state.buffer_switch_requested = Some(target);
+            }
+            return Ok((false, false));
+        }
+    }
+
+    // Show or hide whitespace (configurable keybinding, default Alt+Shift+W)
+    if settings.keybindings.toggle_invisibles_matches(&code, &modifiers) {
+        state.show_invisibles = !state.show_invisibles;
+        crate::ui::set_show_invisibles(state.show_invisibles);
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Show who last changed the cursor line (configurable keybinding, default Alt+B)
+    if settings.keybindings.blame_line_matches(&code, &modifiers) {
+        let line = state.absolute_line();
+        let blame = match &state.blame {
+            Some(blame) => Some(blame.clone()),
+            None => crate::git::blame(filename, lines, state.encoding),
+        };
+        state.status_message = Some(match blame {
+            Some(blame) => blame.get(line).map_or_else(|| "Not committed yet".to_string(), |b| b.describe()),
+            None => "Not tracked by git".to_string(),
+        });
+        state.needs_footer_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Stage and commit the file (configurable keybinding, default Ctrl+Alt+G)
+    if settings.keybindings.git_commit_matches(&code, &modifiers) {
+        commit_current_file(state, filename, settings)?;
+        return Ok((false, false));
+    }
+
+    // Diff the buffer against its file on disk (configurable keybinding, default Alt+Shift+D)
+    if settings.keybindings.diff_with_disk_matches(&code, &modifiers) {
+        crate::diff_view::run_diff_view(state, lines, filename, None, visible_lines, settings)?;
+        return Ok((false, false));
+    }
+
+    // Diff the buffer against another file (configurable keybinding, default Ctrl+Alt+D)
+    if settings.keybindings.diff_with_file_matches(&code, &modifiers) {
+        if let Some(path) = show_text_prompt("Diff with file", settings)? {
+            crate::diff_view::run_diff_view(state, lines, filename, Some(path.trim()), visible_lines, settings)?;
+        }
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Show or hide the blame gutter (configurable keybinding, default Alt+Shift+B)
+    if settings.keybindings.toggle_blame_matches(&code, &modifiers) {
+        state.blame_toggle_requested = true;
+        return Ok((false, false));
+    }
+
+    // Handle zen mode toggle (configurable keybinding, default Alt+Z)
+    if settings.keybindings.toggle_zen_mode_matches(&code, &modifiers) {
+        state.zen_toggle_requested = true;
+        return Ok((false, false));
+    }
+
     // Handle new file (configurable keybinding, default Ctrl+N)
     if settings.keybindings.new_file_matches(&code, &modifiers) {
         state.pending_menu_action = Some(crate::menu::MenuAction::FileNew);
@@ -622,6 +900,174 @@ pub(crate) fn handle_key_event(
         return Ok((false, false));
     }
 
+    // Change the encoding used when saving (configurable keybinding, default Ctrl+Alt+N)
+    if !state.markdown_rendered && settings.keybindings.convert_encoding_matches(&code, &modifiers) {
+        if state.is_read_only {
+            state.status_message = Some("File is read-only".to_string());
+            state.alert();
+        } else if let Some(target) = show_encoding_prompt(state.encoding, lines, settings)? {
+            state.encoding = target;
+            state.status_message = Some(format!("Encoding: {} (applied on save)", target.label()));
+        }
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Change the line ending used when saving (configurable keybinding, default Ctrl+Alt+L)
+    if !state.markdown_rendered && settings.keybindings.convert_line_ending_matches(&code, &modifiers) {
+        if state.is_read_only {
+            state.status_message = Some("File is read-only".to_string());
+            state.alert();
+        } else if let Some(target) = pick_line_ending(state.line_ending, settings)?
+            && target != state.line_ending
+        {
+            state.line_ending = target;
+            state.status_message = Some(format!("Line endings: {} (applied on save)", target.label()));
+        }
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Re-read the file from disk in a chosen encoding (configurable keybinding, default Ctrl+Alt+O)
+    if !state.markdown_rendered && settings.keybindings.reopen_with_encoding_matches(&code, &modifiers) {
+        if state.display_name.is_some() || !std::path::Path::new(filename).exists() {
+            state.status_message = Some("Not saved on disk yet: nothing to reopen".to_string());
+        } else if let Some(target) = pick_encoding("Reopen as", state.encoding, settings)?
+            && (!state.modified
+                || confirm_in_footer(
+                    &format!("Discard unsaved changes and reopen as {}? [Enter=Yes, Esc=No]", target.label()),
+                    settings,
+                )?)
+        {
+            match reload_buffer_as(state, lines, filename, target, visible_lines) {
+                Ok(()) => state.status_message = Some(format!("Reopened as {}", target.label())),
+                Err(e) => state.status_message = Some(format!("Cannot reopen: {}", e)),
+            }
+        }
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Jump between TODO/FIXME markers (configurable keybindings, default Alt+T / Alt+Shift+T)
+    if !state.find_active && !state.replace_active && !state.markdown_rendered {
+        if settings.keybindings.next_todo_marker_matches(&code, &modifiers) {
+            crate::find::goto_todo_marker(state, lines, visible_lines, true);
+            return Ok((false, false));
+        }
+        if settings.keybindings.previous_todo_marker_matches(&code, &modifiers) {
+            crate::find::goto_todo_marker(state, lines, visible_lines, false);
+            return Ok((false, false));
+        }
+        // Jump to the next line over `line_length_limit` (default Alt+Shift+L)
+        if settings.keybindings.next_long_line_matches(&code, &modifiers) {
+            crate::find::goto_long_line(state, lines, visible_lines);
+            return Ok((false, false));
+        }
+    }
+
+    // Show code point, name and width of the character under the cursor (default Alt+U)
+    if !state.find_active
+        && !state.replace_active
+        && !state.markdown_rendered
+        && settings.keybindings.inspect_char_matches(&code, &modifiers)
+    {
+        crate::char_info::inspect_char_at_cursor(state, lines);
+        return Ok((false, false));
+    }
+
+    // Jump to a definition picked from the symbol outline (default Alt+O)
+    if !state.find_active
+        && !state.replace_active
+        && !state.markdown_rendered
+        && settings.keybindings.symbol_outline_matches(&code, &modifiers)
+    {
+        crate::outline::run_symbol_outline(state, lines, visible_lines, settings)?;
+        return Ok((false, false));
+    }
+
+    // Select inside / around the delimiter typed next (default Alt+I / Alt+A)
+    if !state.find_active
+        && !state.replace_active
+        && !state.markdown_rendered
+        && (settings.keybindings.select_inside_matches(&code, &modifiers)
+            || settings.keybindings.select_around_matches(&code, &modifiers))
+    {
+        let around = settings.keybindings.select_around_matches(&code, &modifiers);
+        state.pending_delimiter_select = Some(around);
+        state.status_message = Some(format!(
+            "Select {}: type ( [ {{ < \" ' or `",
+            if around { "around" } else { "inside" }
+        ));
+        state.needs_footer_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Override the file's syntax with one picked from a list (default Alt+Shift+S)
+    if !state.find_active
+        && !state.replace_active
+        && !state.markdown_rendered
+        && settings.keybindings.set_syntax_matches(&code, &modifiers)
+    {
+        crate::outline::run_syntax_picker(state, visible_lines, settings)?;
+        return Ok((false, false));
+    }
+
+    // Insert a character by its code point, entered in the footer (default Alt+Shift+C)
+    if !state.find_active
+        && !state.replace_active
+        && !state.markdown_rendered
+        && settings.keybindings.insert_code_point_matches(&code, &modifiers)
+    {
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+            return Ok((false, false));
+        }
+        if let Some(input) = show_text_prompt("Code point (hex, U+hex or #decimal)", settings)? {
+            match crate::char_info::parse_code_point(&input) {
+                // Goes through the regular typing path: replaces a selection,
+                // respects overwrite mode and multi-cursors, and is one undo step
+                Ok(ch) => {
+                    if handle_editing_keys(
+                        state,
+                        lines,
+                        &KeyCode::Char(ch),
+                        &KeyModifiers::empty(),
+                        visible_lines,
+                        filename,
+                    ) {
+                        state.modified = true;
+                        ensure_cursor_visible_after_edit(state, lines, visible_lines);
+                    }
+                }
+                Err(message) => state.status_message = Some(message),
+            }
+        }
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Remove suspicious characters from the selection or the whole buffer (default Alt+Shift+U)
+    if !state.find_active
+        && !state.replace_active
+        && !state.markdown_rendered
+        && settings.keybindings.strip_suspicious_chars_matches(&code, &modifiers)
+    {
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+            return Ok((false, false));
+        }
+        let removed = crate::char_info::strip_suspicious_chars(state, lines, filename);
+        if removed > 0 {
+            state.modified = true;
+            state.needs_redraw = true;
+            state.status_message = Some(format!("Removed {} suspicious character(s)", removed));
+        } else {
+            state.status_message = Some("No suspicious characters found".to_string());
+        }
+        state.needs_footer_redraw = true;
+        return Ok((false, false));
+    }
+
     // Handle find next (configurable keybinding, default F3)
     // Note: This must be before find mode input handling so it works when find is active
     if settings.keybindings.find_next_matches(&code, &modifiers) {
@@ -641,18 +1087,33 @@ pub(crate) fn handle_key_event(
         return Ok((false, false));
     }
 
+    // Save under another path (default Ctrl+Shift+S) or rename the file on
+    // disk (default Ctrl+Shift+R); ui.rs shows the prompt and switches files.
+    // Checked before save and replace: terminals that send Ctrl+Shift+S as
+    // Ctrl+'S' would otherwise match Ctrl+S
+    if settings.keybindings.save_as_matches(&code, &modifiers) {
+        state.pending_menu_action = Some(crate::menu::MenuAction::FileSaveAs);
+        return Ok((false, false));
+    }
+    if settings.keybindings.rename_file_matches(&code, &modifiers) {
+        state.pending_menu_action = Some(crate::menu::MenuAction::FileRename);
+        return Ok((false, false));
+    }
+
     // Handle replace mode entry (Ctrl+r by default)
     // If no search pattern exists yet, enter find mode first
     // If search pattern exists, enter replace mode
     if settings.keybindings.replace_matches(&code, &modifiers) && !state.replace_active {
         if state.is_read_only {
             state.status_message = Some("File is read-only".to_string());
+            state.alert();
             state.needs_footer_redraw = true;
             return Ok((false, false));
         }
         if state.last_search_pattern.is_none() {
             // No search yet - enter find mode first (will auto-enter replace mode after search)
             state.find_active = true;
+            state.reset_find_options();
             state.find_via_replace = true; // Mark that this was entered via replace
             state.find_pattern.clear();
             state.find_cursor_pos = 0;
@@ -699,11 +1160,20 @@ pub(crate) fn handle_key_event(
             return Ok((false, false));
         }
 
+    // Handle replace with confirmation (Ctrl+Alt+c): step through the matches
+    // and ask y/n/a/q for each one
+    if settings.keybindings.replace_confirm_matches(&code, &modifiers)
+        && !state.is_read_only && state.last_search_pattern.is_some() && (state.replace_active || !state.replace_pattern.is_empty()) {
+            crate::find::start_replace_confirm(state, lines, visible_lines);
+            return Ok((false, false));
+        }
+
     // If in find mode, handle find input
     if state.find_active {
         match crate::find::handle_find_input(state, lines, key_event, visible_lines) {
             Err(error_msg) => {
                 // Invalid pattern: show error on footer, wait for key, then redraw
+                state.alert();
                 show_find_error(&error_msg, state.effective_theme_bg(), state.settings.appearance.line_number_digits as usize)?;
                 state.needs_redraw = true;
             }
@@ -756,13 +1226,51 @@ pub(crate) fn handle_key_event(
         .keybindings
         .save_and_quit_matches(&code, &modifiers)
     {
-        // Untitled files are never saved automatically - just exit like Esc Esc
-        if state.is_untitled {
+        // Untitled files and the scratch buffer are never saved automatically
+        let saves_current = !(state.is_untitled || state.is_scratch);
+        // Other files may still hold unsaved changes in their undo history; ask once for all of them
+        let others = crate::editing::unsaved_files_except(filename);
+        let mut discard_current = false;
+        if !others.is_empty() {
+            let mut listed = others.clone();
+            if saves_current && state.modified && !state.is_read_only {
+                listed.insert(0, filename.to_string());
+            }
+            match show_quit_unsaved_confirmation(&listed, settings)? {
+                QuitChoice::Cancel => {
+                    state.needs_redraw = true;
+                    return Ok((false, false));
+                }
+                QuitChoice::SaveAll => {
+                    for other in &others {
+                        if let Err(e) = crate::editing::save_unsaved_from_history(other) {
+                            show_save_error(other, &e)?;
+                            state.needs_redraw = true;
+                            return Ok((false, false));
+                        }
+                    }
+                }
+                QuitChoice::DiscardAll => {
+                    for other in &others {
+                        let _ = crate::editing::discard_unsaved_from_history(other);
+                    }
+                    discard_current = true;
+                }
+            }
+        }
+        // With the other files handled, untitled and scratch buffers just exit like Esc Esc
+        if !saves_current {
             return Ok((true, false));
         }
-        // For read-only files, skip saving and just quit
-        if !state.is_read_only {
-            save_file(filename, lines)?;
+        if discard_current {
+            state.undo_history.discard_unsaved_state();
+            state.modified = false;
+        } else if !state.is_read_only {
+            // For read-only files, skip saving and just quit
+            if !prepare_parent_dir(state, filename, settings)? {
+                return Ok((false, false));
+            }
+            save_file(filename, lines, state.encoding, state.line_ending)?;
             state.modified = false;
             // Clear the unsaved file content since we just saved
             state.undo_history.clear_unsaved_state();
@@ -791,6 +1299,13 @@ pub(crate) fn handle_key_event(
         // If this is a read-only file, show error and do nothing
         if state.is_read_only {
             state.status_message = Some("File is read-only".to_string());
+            state.alert();
+            state.needs_footer_redraw = true;
+            return Ok((false, false));
+        }
+
+        if state.is_scratch {
+            state.status_message = Some(SCRATCH_NOT_SAVED_MSG.to_string());
             state.needs_footer_redraw = true;
             return Ok((false, false));
         }
@@ -802,8 +1317,13 @@ pub(crate) fn handle_key_event(
             return Ok((false, false));
         }
 
-        save_file(filename, lines)?;
+        if !prepare_parent_dir(state, filename, settings)? {
+            return Ok((false, false));
+        }
+        save_file(filename, lines, state.encoding, state.line_ending)?;
+        state.disk_stamp = crate::file_watch::DiskStamp::read(filename);
         state.modified = false;
+        state.is_new = false;
         state.needs_redraw = true;
         // Clear the unsaved file content since we just saved
         state.undo_history.clear_unsaved_state();
@@ -817,19 +1337,45 @@ pub(crate) fn handle_key_event(
 
     // Handle undo
     if settings.keybindings.undo_matches(&code, &modifiers) {
-        if !state.is_editing_blocked()
-            && apply_undo(state, lines, filename, visible_lines) {
-                state.needs_redraw = true;
-            }
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+        } else if apply_undo(state, lines, filename, visible_lines) {
+            state.needs_redraw = true;
+        }
         return Ok((false, false));
     }
 
     // Handle redo
     if settings.keybindings.redo_matches(&code, &modifiers) {
-        if !state.is_editing_blocked()
-            && apply_redo(state, lines, filename, visible_lines) {
-                state.needs_redraw = true;
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+        } else if apply_redo(state, lines, filename, visible_lines) {
+            state.needs_redraw = true;
+        }
+        return Ok((false, false));
+    }
+
+    // Walk the undo history to the state of a given time (default Alt+Shift+Z)
+    if settings.keybindings.undo_to_time_matches(&code, &modifiers) {
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+        } else if let Some(input) = show_text_prompt("Undo to (5m back, +5m forward)", settings)? {
+            match parse_time_offset(&input) {
+                Some(offset) => {
+                    let steps = undo_to_time(state, lines, filename, visible_lines, offset);
+                    state.status_message = Some(match steps {
+                        0 => "Already at that point in the history".to_string(),
+                        n if n < 0 => format!("Undid {} change(s)", -n),
+                        n => format!("Redid {} change(s)", n),
+                    });
+                }
+                None => {
+                    state.status_message = Some(format!("Invalid time: {}", input.trim()));
+                    state.alert();
+                }
             }
+        }
+        state.needs_redraw = true;
         return Ok((false, false));
     }
 
@@ -841,17 +1387,89 @@ pub(crate) fn handle_key_event(
 
     // Handle paste
     if settings.keybindings.paste_matches(&code, &modifiers) {
-        if !state.is_editing_blocked()
-            && handle_paste(state, lines, filename) {
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+        } else if handle_paste(state, lines, filename) {
+            state.needs_redraw = true;
+        }
+        return Ok((false, false));
+    }
+
+    // Toggle line or block comments for the selection or cursor line (default Alt+/)
+    if !state.markdown_rendered && settings.keybindings.toggle_comment_matches(&code, &modifiers) {
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+            return Ok((false, false));
+        }
+        match crate::editing::toggle_comment(state, lines, filename) {
+            Ok(true) => {
+                state.modified = true;
+                state.needs_redraw = true;
+            }
+            Ok(false) => {}
+            Err(message) => {
+                state.status_message = Some(message);
+                state.needs_footer_redraw = true;
+            }
+        }
+        return Ok((false, false));
+    }
+
+    // Duplicate the selection after itself, or the cursor line (default Ctrl+Shift+D)
+    if settings.keybindings.duplicate_selection_matches(&code, &modifiers) {
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+        } else if duplicate_selection(state, lines, filename) {
+            state.ensure_cursor_visible(visible_lines, lines);
+            state.modified = true;
+            state.needs_redraw = true;
+        }
+        return Ok((false, false));
+    }
+
+    // Move the cursor line or selected lines (default Alt+Up / Alt+Down); the
+    // rendered markdown view keeps scrolling with them
+    let move_up = settings.keybindings.move_line_up_matches(&code, &modifiers);
+    if !state.find_active
+        && !state.replace_active
+        && !state.markdown_rendered
+        && (move_up || settings.keybindings.move_line_down_matches(&code, &modifiers))
+    {
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+        } else if move_lines(state, lines, filename, move_up, visible_lines) {
+            state.modified = true;
+            state.needs_redraw = true;
+        }
+        return Ok((false, false));
+    }
+
+    // Open a blank line below / above without splitting (default Alt+Enter / Alt+Shift+Enter)
+    if !state.find_active
+        && !state.replace_active
+        && (settings.keybindings.open_line_below_matches(&code, &modifiers)
+            || settings.keybindings.open_line_above_matches(&code, &modifiers))
+    {
+        let above = settings.keybindings.open_line_above_matches(&code, &modifiers);
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+        } else {
+            if open_line(state, lines, above, visible_lines, filename) {
+                state.modified = true;
+                ensure_cursor_visible_after_edit(state, lines, visible_lines);
                 state.needs_redraw = true;
             }
+        }
         return Ok((false, false));
     }
 
     // Handle cut
     if settings.keybindings.cut_matches(&code, &modifiers) {
-        if !state.is_editing_blocked()
-            && handle_cut(state, lines, filename) { /* already set redraw */ }
+        if state.is_editing_blocked() {
+            state.alert_editing_blocked();
+        } else {
+            handle_cut(state, lines, filename); // sets redraw itself
+        }
         return Ok((false, false));
     }
 
@@ -963,15 +1581,88 @@ pub(crate) fn handle_key_event(
         return Ok((false, false));
     }
 
+    // Select the word, then add a cursor at its next occurrence on each press (Ctrl+D by default)
+    if settings.keybindings.add_next_occurrence_matches(&code, &modifiers) {
+        if state.is_editing_blocked() || state.markdown_rendered {
+            return Ok((false, false));
+        }
+        if let Err(message) = crate::find::add_next_occurrence(state, lines, visible_lines) {
+            state.status_message = Some(message);
+            state.alert();
+        }
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Highlight every occurrence of the word under the cursor without entering
+    // find mode; pressing again clears it (Alt+Shift+H by default)
+    if settings.keybindings.highlight_word_matches(&code, &modifiers) {
+        if state.markdown_rendered {
+            return Ok((false, false));
+        }
+        if let Err(message) = crate::find::toggle_word_highlight(state, lines) {
+            state.status_message = Some(message);
+            state.alert();
+        }
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Place a cursor at every regex match in the file or selection (Alt+Shift+M by default)
+    if settings.keybindings.cursors_at_regex_matches(&code, &modifiers) {
+        if state.is_editing_blocked() || state.markdown_rendered {
+            return Ok((false, false));
+        }
+        if let Some(pattern) = show_text_prompt("Cursors at regex", settings)? {
+            match crate::find::place_cursors_at_matches(state, lines, &pattern, visible_lines) {
+                Ok((count, true)) => {
+                    state.status_message = Some(format!(
+                        "Too many matches: placed the first {} cursors",
+                        count
+                    ));
+                }
+                Ok((count, false)) => {
+                    state.status_message = Some(format!("{} cursors placed", count));
+                }
+                Err(message) => state.status_message = Some(message),
+            }
+        }
+        state.needs_redraw = true;
+        return Ok((false, false));
+    }
+
+    // Split a multi-line selection into one cursor per line (Alt+Shift+I by default)
+    if settings.keybindings.split_selection_into_cursors_matches(&code, &modifiers) {
+        if !state.is_editing_blocked() {
+            crate::editing::split_selection_into_cursors(state, lines, visible_lines);
+        }
+        return Ok((false, false));
+    }
+
     let is_shift = modifiers.contains(KeyModifiers::SHIFT);
     let is_alt = modifiers.contains(KeyModifiers::ALT);
     let is_navigation = is_navigation_key(&code);
 
     // Clear multi-cursors on any navigation (no longer used for selection)
-    if is_navigation {
+    // Plain arrows/Home/End move every cursor; any other navigation leaves multi-cursor mode
+    let moves_all_cursors = state.has_multi_cursors()
+        && modifiers.is_empty()
+        && !state.markdown_rendered
+        && crate::editing::move_multi_cursors(state, lines, &code);
+    if is_navigation && !moves_all_cursors {
         state.clear_multi_cursors();
     }
 
+    // Scroll the viewport one line without moving the cursor (default Ctrl+Alt+PageUp / Ctrl+Alt+PageDown)
+    let scroll_up = settings.keybindings.scroll_up_matches(&code, &modifiers);
+    if scroll_up || settings.keybindings.scroll_down_matches(&code, &modifiers) {
+        let direction = if scroll_up { KeyCode::Up } else { KeyCode::Down };
+        if handle_viewport_scroll(state, lines, direction, visible_lines) {
+            state.needs_redraw = true;
+        }
+        return Ok((false, false));
+    }
+
     // Handle Alt+Arrow (without Shift) for viewport scrolling without moving cursor
     if is_navigation && is_alt && !is_shift {
         let scrolled = handle_viewport_scroll(state, lines, code, visible_lines);
@@ -1006,6 +1697,14 @@ pub(crate) fn handle_key_event(
     }
 
     let did_edit = if state.is_editing_blocked() {
+        let typing = match code {
+            KeyCode::Char(_) => !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT),
+            KeyCode::Enter | KeyCode::Backspace | KeyCode::Delete | KeyCode::Tab => true,
+            _ => false,
+        };
+        if typing && !state.markdown_rendered {
+            state.alert_editing_blocked();
+        }
         false
     } else {
         handle_editing_keys(state, lines, &code, &modifiers, visible_lines, filename)
@@ -1021,6 +1720,12 @@ pub(crate) fn handle_key_event(
         lines
     };
     let moved = handle_navigation(state, effective_lines, code, visible_lines);
+    if moves_all_cursors {
+        // Cursors that ran into the main cursor merge with it
+        let main = state.current_position();
+        state.multi_cursors.retain(|&p| p != main);
+        state.needs_redraw = true;
+    }
 
     // After editing or navigation, ensure cursor is visible (handles line wrapping auto-scroll)
     if did_edit || moved {
@@ -1444,6 +2149,7 @@ fn handle_up_navigation(state: &mut FileViewerState, lines: &[String], visible_l
                 let prev_line = &lines[state.absolute_line()];
                 state.cursor_col = state.desired_cursor_col.min(prev_line.chars().count());
             }
+        state.apply_scroll_margin(lines, visible_lines);
         return;
     }
 
@@ -1542,6 +2248,7 @@ fn handle_up_navigation(state: &mut FileViewerState, lines: &[String], visible_l
             }
         }
     }
+    state.apply_scroll_margin(lines, visible_lines);
 }
 
 /// Handle moving down through wrapped lines
@@ -1581,6 +2288,7 @@ fn handle_down_navigation(state: &mut FileViewerState, lines: &[String], visible
                 let next_line = &lines[state.absolute_line()];
                 state.cursor_col = state.desired_cursor_col.min(next_line.len());
             }
+        state.apply_scroll_margin(lines, visible_lines);
         return;
     }
 
@@ -1696,6 +2404,7 @@ fn handle_down_navigation(state: &mut FileViewerState, lines: &[String], visible
             }
         }
     }
+    state.apply_scroll_margin(lines, visible_lines);
 }
 
 /// Convert visual column to character index, accounting for tabs
@@ -1787,62 +2496,376 @@ fn handle_navigation(
             if state.top_line + state.cursor_line >= lines.len() {
                 state.cursor_line = lines.len().saturating_sub(state.top_line + 1);
             }
-            true
-        }
-        KeyCode::PageUp => {
-            state.top_line = state.top_line.saturating_sub(visible_lines);
-            true
+            state.apply_scroll_margin(lines, visible_lines);
+            true
+        }
+        KeyCode::PageUp => {
+            state.top_line = state.top_line.saturating_sub(visible_lines);
+            state.apply_scroll_margin(lines, visible_lines);
+            true
+        }
+        _ => false,
+    };
+
+    // Clear wrap warning on any cursor movement
+    if moved {
+        state.wrap_warning_pending = None;
+
+        // Adjust horizontal scroll if wrapping is disabled
+        if !state.is_line_wrapping_enabled() {
+            let text_width = crate::coordinates::calculate_text_width(state, lines, visible_lines) as usize;
+
+            // Get current line and calculate visual position
+            let absolute_line = state.absolute_line();
+            if let Some(line) = lines.get(absolute_line) {
+                use crate::coordinates::visual_width_up_to;
+                let visual_col = visual_width_up_to(line, state.cursor_col, state.settings.tab_width);
+
+                // Adjust horizontal scroll to keep cursor visible
+                if visual_col < state.horizontal_scroll_offset {
+                    // Cursor moved left of visible area
+                    state.horizontal_scroll_offset = visual_col;
+                } else if visual_col >= state.horizontal_scroll_offset + text_width {
+                    // Cursor moved right of visible area
+                    state.horizontal_scroll_offset = visual_col.saturating_sub(text_width - 1);
+                }
+            }
+        }
+    }
+
+    moved
+}
+
+/// Show confirmation prompt when closing a file with unsaved changes
+/// Returns true if user confirms closing (Enter), false if user cancels (Esc)
+pub(crate) fn show_close_confirmation(
+    filename: &str,
+    settings: &Settings,
+) -> Result<bool, std::io::Error> {
+    use crossterm::event;
+    use crossterm::terminal;
+
+    let mut stdout = crate::no_color::stdout();
+    let (_, term_height) = terminal::size()?;
+    let footer_row = term_height - 1;
+
+    // Extract just the filename from the path
+    let path = std::path::Path::new(filename);
+    let display_name = path.file_name().and_then(|n| n.to_str()).unwrap_or(filename);
+
+    // Display warning message in footer
+    execute!(
+        stdout,
+        crossterm::cursor::MoveTo(0, footer_row),
+        crossterm::terminal::Clear(crossterm::terminal::ClearType::CurrentLine),
+        crossterm::style::SetForegroundColor(crossterm::style::Color::Yellow)
+    )?;
+    write!(
+        &mut stdout,
+        "Close '{}' without saving? [Enter=Yes, Esc=No]",
+        display_name
+    )?;
+    execute!(stdout, crossterm::style::ResetColor)?;
+    stdout.flush()?;
+
+    // Wait for user response
+    loop {
+        if let event::Event::Key(key) = event::read()? {
+            let key = normalize_key_event(key, settings);
+            match key.code {
+                KeyCode::Enter => {
+                    return Ok(true); // User confirmed - close file
+                }
+                KeyCode::Esc => {
+                    return Ok(false); // User cancelled - don't close
+                }
+                _ => {
+                    // Ignore other keys, wait for Enter or Esc
+                }
+            }
+        }
+    }
+}
+
+/// Answer to the quit prompt shown when several files have unsaved changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum QuitChoice {
+    SaveAll,
+    DiscardAll,
+    Cancel,
+}
+
+/// Show a single prompt listing every file with unsaved changes before quitting
+pub(crate) fn show_quit_unsaved_confirmation(
+    files: &[String],
+    settings: &Settings,
+) -> Result<QuitChoice, std::io::Error> {
+    use crossterm::event;
+    use crossterm::terminal;
+
+    let mut stdout = crate::no_color::stdout();
+    let (_, term_height) = terminal::size()?;
+    let footer_row = term_height - 1;
+
+    let display_names: Vec<&str> = files
+        .iter()
+        .map(|f| {
+            std::path::Path::new(f)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(f)
+        })
+        .collect();
+
+    // Display warning message in footer
+    execute!(
+        stdout,
+        crossterm::cursor::MoveTo(0, footer_row),
+        crossterm::terminal::Clear(crossterm::terminal::ClearType::CurrentLine),
+        crossterm::style::SetForegroundColor(crossterm::style::Color::Yellow)
+    )?;
+    write!(
+        &mut stdout,
+        "Unsaved: {} [s=Save all, d=Discard all, Esc=Cancel]",
+        display_names.join(", ")
+    )?;
+    execute!(stdout, crossterm::style::ResetColor)?;
+    stdout.flush()?;
+
+    // Wait for user response
+    loop {
+        if let event::Event::Key(key) = event::read()? {
+            let key = normalize_key_event(key, settings);
+            match key.code {
+                KeyCode::Char('s') | KeyCode::Char('S') => return Ok(QuitChoice::SaveAll),
+                KeyCode::Char('d') | KeyCode::Char('D') => return Ok(QuitChoice::DiscardAll),
+                KeyCode::Esc => return Ok(QuitChoice::Cancel),
+                _ => {
+                    // Ignore other keys
+                }
+            }
+        }
+    }
+}
+
+/// Read a line of text in the footer. Returns None when cancelled with Esc
+/// or confirmed empty.
+pub(crate) fn show_text_prompt(label: &str, settings: &Settings) -> Result<Option<String>, std::io::Error> {
+    use crossterm::event;
+    use crossterm::terminal;
+
+    let mut stdout = crate::no_color::stdout();
+    let (_, term_height) = terminal::size()?;
+    let footer_row = term_height - 1;
+    let mut input = String::new();
+
+    loop {
+        execute!(
+            stdout,
+            crossterm::cursor::MoveTo(0, footer_row),
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::CurrentLine),
+            crossterm::style::SetForegroundColor(crossterm::style::Color::Yellow)
+        )?;
+        write!(&mut stdout, "{}: ", label)?;
+        execute!(stdout, crossterm::style::ResetColor)?;
+        write!(&mut stdout, "{}", input)?;
+        stdout.flush()?;
+
+        if let event::Event::Key(key) = event::read()? {
+            let key = normalize_key_event(key, settings);
+            match key.code {
+                KeyCode::Enter => return Ok(Some(input).filter(|s| !s.is_empty())),
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => input.push(c),
+                _ => {}
+            }
         }
-        _ => false,
-    };
+    }
+}
 
-    // Clear wrap warning on any cursor movement
-    if moved {
-        state.wrap_warning_pending = None;
+/// Stage the file and commit it with a message typed in the footer, reporting
+/// the result there. Unsaved changes have to be saved first.
+pub(crate) fn commit_current_file(
+    state: &mut FileViewerState,
+    filename: &str,
+    settings: &Settings,
+) -> Result<(), std::io::Error> {
+    if state.is_untitled || state.is_scratch || state.is_new {
+        state.status_message = Some("Not saved on disk yet: nothing to commit".to_string());
+        state.alert();
+    } else if state.modified {
+        state.status_message = Some("Unsaved changes: save before committing".to_string());
+        state.alert();
+    } else if let Some(message) = show_text_prompt("Commit message", settings)? {
+        state.status_message = Some(match crate::git::commit_file(filename, message.trim()) {
+            Ok(report) => report,
+            Err(e) => {
+                state.alert();
+                format!("Commit failed: {}", e)
+            }
+        });
+        // The gutter and branch are read again from the new HEAD
+        state.saved_snapshot = None;
+    }
+    state.needs_redraw = true;
+    Ok(())
+}
 
-        // Adjust horizontal scroll if wrapping is disabled
-        if !state.is_line_wrapping_enabled() {
-            let text_width = crate::coordinates::calculate_text_width(state, lines, visible_lines) as usize;
+/// Replace the buffer with the file on disk decoded as `encoding`, dropping unsaved
+/// edits and undo steps (they refer to the old decoding). The cursor stays put as
+/// far as the new text allows.
+pub(crate) fn reload_buffer_as(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    filename: &str,
+    encoding: FileEncoding,
+    visible_lines: usize,
+) -> Result<(), std::io::Error> {
+    state.disk_stamp = crate::file_watch::DiskStamp::read(filename);
+    let text = encoding.decode_as(&std::fs::read(filename)?);
+    *lines = crate::line_ending::split_lines(&text);
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    state.encoding = encoding;
+    state.line_ending = crate::line_ending::LineEnding::detect(&text);
+    state.modified = false;
+    state.clear_selection();
+    state.clear_multi_cursors();
+    let (line, col) = state.current_position();
+    state.set_cursor_position(line, col, lines, visible_lines);
+    state.undo_history.discard_unsaved_state();
+    let _ = state.undo_history.save(filename);
+    state.last_save_time = Some(Instant::now());
+    Ok(())
+}
 
-            // Get current line and calculate visual position
-            let absolute_line = state.absolute_line();
-            if let Some(line) = lines.get(absolute_line) {
-                use crate::coordinates::visual_width_up_to;
-                let visual_col = visual_width_up_to(line, state.cursor_col, state.settings.tab_width);
+/// Footer picker listing `labels` by number, e.g. "Encoding (UTF-8): 1=UTF-8, ...".
+/// Returns the index of the picked label, or None when cancelled.
+fn pick_in_footer(
+    title: &str,
+    current: &str,
+    labels: &[&str],
+    settings: &Settings,
+) -> Result<Option<usize>, std::io::Error> {
+    use crossterm::event;
+    use crossterm::terminal;
 
-                // Adjust horizontal scroll to keep cursor visible
-                if visual_col < state.horizontal_scroll_offset {
-                    // Cursor moved left of visible area
-                    state.horizontal_scroll_offset = visual_col;
-                } else if visual_col >= state.horizontal_scroll_offset + text_width {
-                    // Cursor moved right of visible area
-                    state.horizontal_scroll_offset = visual_col.saturating_sub(text_width - 1);
+    let mut stdout = crate::no_color::stdout();
+    let (_, term_height) = terminal::size()?;
+    let footer_row = term_height - 1;
+
+    let choices: Vec<String> = labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| format!("{}={}", i + 1, label))
+        .collect();
+    execute!(
+        stdout,
+        crossterm::cursor::MoveTo(0, footer_row),
+        crossterm::terminal::Clear(crossterm::terminal::ClearType::CurrentLine),
+        crossterm::style::SetForegroundColor(crossterm::style::Color::Yellow)
+    )?;
+    write!(
+        &mut stdout,
+        "{} ({}): {}, Esc=Cancel",
+        title,
+        current,
+        choices.join(", ")
+    )?;
+    execute!(stdout, crossterm::style::ResetColor)?;
+    stdout.flush()?;
+
+    loop {
+        if let event::Event::Key(key) = event::read()? {
+            let key = normalize_key_event(key, settings);
+            match key.code {
+                KeyCode::Char(c) => {
+                    let picked = c
+                        .to_digit(10)
+                        .and_then(|d| (d as usize).checked_sub(1))
+                        .filter(|&i| i < labels.len());
+                    if picked.is_some() {
+                        return Ok(picked);
+                    }
                 }
+                KeyCode::Esc => return Ok(None),
+                _ => {}
             }
         }
     }
+}
 
-    moved
+/// Footer picker listing every encoding by number. Returns None when cancelled.
+fn pick_encoding(
+    title: &str,
+    current: FileEncoding,
+    settings: &Settings,
+) -> Result<Option<FileEncoding>, std::io::Error> {
+    let labels: Vec<&str> = FileEncoding::ALL.iter().map(|e| e.label()).collect();
+    Ok(pick_in_footer(title, current.label(), &labels, settings)?.map(|i| FileEncoding::ALL[i]))
 }
 
-/// Show confirmation prompt when closing a file with unsaved changes
-/// Returns true if user confirms closing (Enter), false if user cancels (Esc)
-pub(crate) fn show_close_confirmation(
-    filename: &str,
+/// Footer picker listing every line ending by number. Returns None when cancelled.
+fn pick_line_ending(
+    current: crate::line_ending::LineEnding,
     settings: &Settings,
-) -> Result<bool, std::io::Error> {
+) -> Result<Option<crate::line_ending::LineEnding>, std::io::Error> {
+    use crate::line_ending::LineEnding;
+    let labels: Vec<&str> = LineEnding::ALL.iter().map(|e| e.label()).collect();
+    Ok(pick_in_footer("Line endings", current.label(), &labels, settings)?.map(|i| LineEnding::ALL[i]))
+}
+
+/// What to do about a file changed on disk while the buffer has unsaved changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExternalChangeAction {
+    Reload,
+    KeepMine,
+    ShowDiff,
+}
+
+/// Footer picker offered when the file changed on disk under unsaved changes.
+/// Esc keeps the buffer as it is.
+pub(crate) fn pick_external_change_action(settings: &Settings) -> Result<ExternalChangeAction, std::io::Error> {
+    const ACTIONS: [ExternalChangeAction; 3] =
+        [ExternalChangeAction::Reload, ExternalChangeAction::KeepMine, ExternalChangeAction::ShowDiff];
+    let picked = pick_in_footer(
+        "File changed on disk",
+        "you have unsaved changes",
+        &["Reload", "Keep mine", "Show diff"],
+        settings,
+    )?;
+    Ok(picked.map_or(ExternalChangeAction::KeepMine, |i| ACTIONS[i]))
+}
+
+/// Ask for the encoding to save the buffer in. If the buffer holds characters the
+/// chosen encoding cannot store, a second prompt warns before committing.
+/// Returns None when cancelled.
+fn show_encoding_prompt(
+    current: FileEncoding,
+    lines: &[String],
+    settings: &Settings,
+) -> Result<Option<FileEncoding>, std::io::Error> {
     use crossterm::event;
     use crossterm::terminal;
 
-    let mut stdout = std::io::stdout();
+    let Some(target) = pick_encoding("Encoding", current, settings)? else {
+        return Ok(None);
+    };
+
+    let lost = target.unrepresentable_count(lines);
+    if lost == 0 {
+        return Ok(Some(target));
+    }
+
+    let mut stdout = crate::no_color::stdout();
     let (_, term_height) = terminal::size()?;
     let footer_row = term_height - 1;
 
-    // Extract just the filename from the path
-    let path = std::path::Path::new(filename);
-    let display_name = path.file_name().and_then(|n| n.to_str()).unwrap_or(filename);
-
-    // Display warning message in footer
     execute!(
         stdout,
         crossterm::cursor::MoveTo(0, footer_row),
@@ -1851,26 +2874,20 @@ pub(crate) fn show_close_confirmation(
     )?;
     write!(
         &mut stdout,
-        "Close '{}' without saving? [Enter=Yes, Esc=No]",
-        display_name
+        "{} character(s) cannot be stored in {} and will be saved as '?' [Enter=Convert, Esc=Cancel]",
+        lost,
+        target.label()
     )?;
     execute!(stdout, crossterm::style::ResetColor)?;
     stdout.flush()?;
 
-    // Wait for user response
     loop {
         if let event::Event::Key(key) = event::read()? {
             let key = normalize_key_event(key, settings);
             match key.code {
-                KeyCode::Enter => {
-                    return Ok(true); // User confirmed - close file
-                }
-                KeyCode::Esc => {
-                    return Ok(false); // User cancelled - don't close
-                }
-                _ => {
-                    // Ignore other keys, wait for Enter or Esc
-                }
+                KeyCode::Enter => return Ok(Some(target)),
+                KeyCode::Esc => return Ok(None),
+                _ => {}
             }
         }
     }
@@ -1886,7 +2903,7 @@ pub(crate) fn show_overwrite_confirmation(
     use crossterm::event;
     use crossterm::terminal;
 
-    let mut stdout = std::io::stdout();
+    let mut stdout = crate::no_color::stdout();
     let (_, term_height) = terminal::size()?;
     let footer_row = term_height - 1;
 
@@ -1928,13 +2945,78 @@ pub(crate) fn show_overwrite_confirmation(
     }
 }
 
+/// Make sure the directory `path` is saved into exists, creating missing directories
+/// as `create_parent_dirs` allows. Returns false, with a status message, when the
+/// save must not go ahead.
+pub(crate) fn prepare_parent_dir(
+    state: &mut FileViewerState,
+    path: &str,
+    settings: &Settings,
+) -> Result<bool, std::io::Error> {
+    let Some(parent) = std::path::Path::new(path).parent() else {
+        return Ok(true);
+    };
+    if parent.as_os_str().is_empty() || parent.exists() {
+        return Ok(true);
+    }
+    let create = match settings.create_parent_dirs.as_str() {
+        "always" => true,
+        "never" => false,
+        _ => confirm_in_footer(
+            &format!("Create directory '{}'? [Enter=Yes, Esc=No]", parent.display()),
+            settings,
+        )?,
+    };
+    state.needs_redraw = true;
+    if !create {
+        state.status_message = Some(format!("Not saved: '{}' does not exist", parent.display()));
+        return Ok(false);
+    }
+    if let Err(e) = std::fs::create_dir_all(parent) {
+        state.status_message = Some(format!("Cannot create '{}': {}", parent.display(), e));
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Show a yes/no question in the footer; true for Enter, false for Esc
+pub(crate) fn confirm_in_footer(question: &str, settings: &Settings) -> Result<bool, std::io::Error> {
+    use crossterm::event;
+    use crossterm::terminal;
+
+    let mut stdout = crate::no_color::stdout();
+    let (_, term_height) = terminal::size()?;
+    let footer_row = term_height - 1;
+
+    execute!(
+        stdout,
+        crossterm::cursor::MoveTo(0, footer_row),
+        crossterm::terminal::Clear(crossterm::terminal::ClearType::CurrentLine),
+        crossterm::style::SetForegroundColor(crossterm::style::Color::Yellow)
+    )?;
+    write!(&mut stdout, "{}", question)?;
+    execute!(stdout, crossterm::style::ResetColor)?;
+    stdout.flush()?;
+
+    loop {
+        if let event::Event::Key(key) = event::read()? {
+            let key = normalize_key_event(key, settings);
+            match key.code {
+                KeyCode::Enter => return Ok(true),
+                KeyCode::Esc => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+}
+
 /// Show confirmation prompt when undo file has unsaved changes but source file was modified externally
 /// Returns true if user confirms opening file anyway (Enter), false if user wants to discard (Esc)
 pub(crate) fn show_undo_conflict_confirmation(settings: &Settings) -> Result<bool, std::io::Error> {
     use crossterm::event;
     use crossterm::terminal;
 
-    let mut stdout = std::io::stdout();
+    let mut stdout = crate::no_color::stdout();
     let (_, term_height) = terminal::size()?;
     let footer_row = term_height - 1;
 
@@ -1981,7 +3063,7 @@ pub(crate) fn show_find_error(
     use crossterm::event;
     use crossterm::terminal;
 
-    let mut stdout = std::io::stdout();
+    let mut stdout = crate::no_color::stdout();
     let (term_width, term_height) = terminal::size()?;
     let footer_row = term_height - 1;
 
@@ -2025,7 +3107,7 @@ pub(crate) fn show_save_error(
     use crossterm::event;
     use crossterm::terminal;
 
-    let mut stdout = std::io::stdout();
+    let mut stdout = crate::no_color::stdout();
     let (_, term_height) = terminal::size()?;
     let footer_row = term_height - 1;
 
@@ -2075,26 +3157,19 @@ fn word_left(state: &mut FileViewerState, lines: &[String]) -> bool {
         }
         let new_abs = state.absolute_line();
         if new_abs < lines.len() {
-            state.cursor_col = lines[new_abs].len();
+            state.cursor_col = lines[new_abs].chars().count();
         }
         return true;
     }
-    let line = &lines[abs];
-    let mut i = state.cursor_col;
+    // Collect once: indexing with chars().nth() per step is quadratic on long lines
+    let chars: Vec<char> = lines[abs].chars().collect();
+    let mut i = state.cursor_col.min(chars.len());
     // First skip any non-word characters (including whitespace & punctuation)
-    while i > 0 {
-        let c = line.chars().nth(i - 1).unwrap_or(' ');
-        if is_word_char(c) {
-            break;
-        }
+    while i > 0 && !is_word_char(chars[i - 1]) {
         i -= 1;
     }
     // Then skip the word characters
-    while i > 0 {
-        let c = line.chars().nth(i - 1).unwrap_or(' ');
-        if !is_word_char(c) {
-            break;
-        }
+    while i > 0 && is_word_char(chars[i - 1]) {
         i -= 1;
     }
     state.cursor_col = i;
@@ -2105,8 +3180,8 @@ fn word_right(state: &mut FileViewerState, lines: &[String]) -> bool {
     if abs >= lines.len() {
         return false;
     }
-    let line = &lines[abs];
-    let len = line.chars().count();
+    let chars: Vec<char> = lines[abs].chars().collect();
+    let len = chars.len();
     if state.cursor_col >= len {
         if abs + 1 >= lines.len() {
             return false;
@@ -2122,19 +3197,11 @@ fn word_right(state: &mut FileViewerState, lines: &[String]) -> bool {
     }
     let mut i = state.cursor_col;
     // Skip any non-word (whitespace / punctuation)
-    while i < len {
-        let c = line.chars().nth(i).unwrap_or(' ');
-        if is_word_char(c) {
-            break;
-        }
+    while i < len && !is_word_char(chars[i]) {
         i += 1;
     }
     // Skip the word
-    while i < len {
-        let c = line.chars().nth(i).unwrap_or(' ');
-        if !is_word_char(c) {
-            break;
-        }
+    while i < len && is_word_char(chars[i]) {
         i += 1;
     }
     state.cursor_col = i;
@@ -2219,6 +3286,22 @@ fn paragraph_down(state: &mut FileViewerState, lines: &[String], visible_lines:
     true
 }
 
+/// Next line (or previous, when `down` is false) from `from` whose indentation is at
+/// most that of `from`, skipping blank lines and deeper-indented children
+fn indent_block_target(lines: &[String], from: usize, down: bool, tab_width: usize) -> Option<usize> {
+    let indent = |line: &str| {
+        let leading: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+        crate::coordinates::visual_width(&leading, tab_width)
+    };
+    let level = indent(lines.get(from)?);
+    let is_sibling = |&i: &usize| !lines[i].trim().is_empty() && indent(&lines[i]) <= level;
+    if down {
+        (from + 1..lines.len()).find(is_sibling)
+    } else {
+        (0..from).rev().find(is_sibling)
+    }
+}
+
 /// Get the character index of the first non-blank character in the line
 fn first_non_blank_char(line: &str) -> usize {
     line.chars().position(|c| !c.is_whitespace()).unwrap_or(0)
@@ -2364,6 +3447,19 @@ fn scroll_without_cursor(
     state.top_line != old_top
 }
 
+/// Resolve goto input to a 0-indexed line: "42" is a line number, "50%" a percentage of the file
+fn goto_target_line(input: &str, line_count: usize) -> Option<usize> {
+    if line_count == 0 {
+        return None;
+    }
+    if let Some(percent) = input.strip_suffix('%') {
+        let percent = percent.parse::<usize>().ok().filter(|p| *p <= 100)?;
+        return Some((percent * line_count / 100).min(line_count - 1));
+    }
+    let line_num = input.parse::<usize>().ok()?;
+    (line_num > 0 && line_num <= line_count).then(|| line_num - 1)
+}
+
 /// Handle input when in go to line mode
 /// Returns (should_quit, should_close) tuple
 fn handle_goto_line_input(
@@ -2380,14 +3476,8 @@ fn handle_goto_line_input(
 
     match code {
         KeyCode::Enter => {
-            // Parse line number and jump to it
-            if let Ok(line_num) = state.goto_line_input.parse::<usize>()
-                && line_num > 0
-                && line_num <= lines.len()
-            {
-                // Convert to 0-indexed
-                let target_line = line_num - 1;
-
+            // Parse line number (or percentage) and jump to it
+            if let Some(target_line) = goto_target_line(&state.goto_line_input, lines.len()) {
                 // Jump to the target line
                 state.top_line = target_line.saturating_sub(visible_lines / 2);
                 state.top_line = state.top_line.min(lines.len().saturating_sub(1));
@@ -2407,9 +3497,9 @@ fn handle_goto_line_input(
             state.needs_redraw = true;
             Ok((false, false))
         }
-        KeyCode::Char(c) if modifiers.is_empty() => {
-            // Only allow digits
-            if c.is_ascii_digit() {
+        KeyCode::Char(c) if modifiers.is_empty() || modifiers == KeyModifiers::SHIFT => {
+            // Only allow digits and a single '%' for jumping to a percentage of the file
+            if c.is_ascii_digit() || (c == '%' && !state.goto_line_input.contains('%')) {
                 if !state.goto_line_typing_started {
                     // First character typed - replace the pre-filled value
                     state.goto_line_input.clear();
@@ -2532,6 +3622,13 @@ mod tests {
         let undo_history = UndoHistory::new();
         FileViewerState::new(80, undo_history, settings)
     }
+    /// A test state whose Alt+Up/Down scroll the viewport instead of moving lines
+    fn create_scroll_test_state() -> FileViewerState<'static> {
+        let mut settings = Settings::load().expect("Failed to load test settings");
+        settings.keybindings.move_line_up = String::new();
+        settings.keybindings.move_line_down = String::new();
+        FileViewerState::new(80, UndoHistory::new(), Box::leak(Box::new(settings)))
+    }
     fn create_test_lines(count: usize) -> Vec<String> {
         (0..count).map(|i| format!("Line {}", i)).collect()
     }
@@ -2688,6 +3785,33 @@ mod tests {
         assert_eq!(state.absolute_line(), 49);
     }
 
+    #[test]
+    fn goto_line_accepts_percent_suffix() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let lines = create_test_lines(100);
+        state.goto_line_active = true;
+        state.goto_line_input = "50".to_string();
+        state.goto_line_cursor_pos = 2;
+        state.goto_line_typing_started = true;
+        let key_event = KeyEvent::new(KeyCode::Char('%'), KeyModifiers::SHIFT);
+        let _ = handle_goto_line_input(&mut state, &lines, key_event, 20);
+        assert_eq!(state.goto_line_input, "50%");
+        let key_event = KeyEvent::new(KeyCode::Enter, KeyModifiers::empty());
+        let _ = handle_goto_line_input(&mut state, &lines, key_event, 20);
+        assert_eq!(state.absolute_line(), 50);
+    }
+
+    #[test]
+    fn goto_target_line_percent_bounds() {
+        assert_eq!(goto_target_line("0%", 10), Some(0));
+        assert_eq!(goto_target_line("100%", 10), Some(9));
+        assert_eq!(goto_target_line("101%", 10), None);
+        assert_eq!(goto_target_line("%", 10), None);
+        assert_eq!(goto_target_line("50%", 0), None);
+        assert_eq!(goto_target_line("3", 10), Some(2));
+    }
+
     #[test]
     fn normalize_key_event_maps_carriage_return_to_enter() {
         let (_tmp, _guard) = set_temp_home();
@@ -2706,6 +3830,25 @@ mod tests {
         assert!(matches!(normalized.code, KeyCode::Enter));
     }
     #[test]
+    fn save_as_and_rename_win_over_save_and_replace_without_shift_flag() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = create_test_lines(10);
+        let settings = state.settings;
+        // Ctrl+Shift+S as many terminals send it: uppercase with CONTROL only
+        let key_event = KeyEvent::new(KeyCode::Char('S'), KeyModifiers::CONTROL);
+        handle_key_event(&mut state, &mut lines, key_event, settings, 20, "test.txt").unwrap();
+        assert!(matches!(state.pending_menu_action, Some(crate::menu::MenuAction::FileSaveAs)));
+        assert!(!std::path::Path::new("test.txt").exists());
+
+        state.pending_menu_action = None;
+        state.last_search_pattern = Some("Line".to_string());
+        let key_event = KeyEvent::new(KeyCode::Char('R'), KeyModifiers::CONTROL);
+        handle_key_event(&mut state, &mut lines, key_event, settings, 20, "test.txt").unwrap();
+        assert!(matches!(state.pending_menu_action, Some(crate::menu::MenuAction::FileRename)));
+        assert!(!state.replace_active && !state.find_active);
+    }
+    #[test]
     fn help_activates_with_f1() {
         let (_tmp, _guard) = set_temp_home();
         let mut state = create_test_state();
@@ -2758,6 +3901,149 @@ mod tests {
             "Should request Find help when in replace mode"
         );
     }
+    #[test]
+    fn find_mode_toggle_is_per_search_until_saved() {
+        let (tmp, _guard) = set_temp_home();
+        let file = tmp.path().join("opts.txt");
+        let file = file.to_string_lossy().to_string();
+        let mut state = create_test_state();
+        let mut lines = create_test_lines(10);
+        let settings = state.settings;
+        let ctrl_alt = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+
+        handle_key_event(&mut state, &mut lines, ctrl_f, settings, 20, &file).unwrap();
+        let toggle = KeyEvent::new(KeyCode::Char('x'), ctrl_alt);
+        handle_key_event(&mut state, &mut lines, toggle, settings, 20, &file).unwrap();
+        assert!(!state.find_regex_mode);
+
+        // A new search starts from the saved default again
+        state.find_active = false;
+        handle_key_event(&mut state, &mut lines, ctrl_f, settings, 20, &file).unwrap();
+        assert!(state.find_regex_mode);
+
+        // Saving makes the current options the default, persisted in the undo file
+        handle_key_event(&mut state, &mut lines, toggle, settings, 20, &file).unwrap();
+        let save = KeyEvent::new(KeyCode::Char('s'), ctrl_alt);
+        handle_key_event(&mut state, &mut lines, save, settings, 20, &file).unwrap();
+        state.find_active = false;
+        handle_key_event(&mut state, &mut lines, ctrl_f, settings, 20, &file).unwrap();
+        assert!(!state.find_regex_mode);
+        let loaded = crate::undo::UndoHistory::load(&file, false).unwrap();
+        assert_eq!(
+            loaded.find_options,
+            Some(crate::find::FindOptions { regex_mode: false, ..Default::default() })
+        );
+    }
+
+    #[test]
+    fn find_case_and_whole_word_toggles_change_matches() {
+        let (tmp, _guard) = set_temp_home();
+        let file = tmp.path().join("flags.txt");
+        let file = file.to_string_lossy().to_string();
+        let mut state = create_test_state();
+        let mut lines = vec!["Cat cat concat".to_string()];
+        let settings = state.settings;
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        handle_key_event(&mut state, &mut lines, ctrl_f, settings, 20, &file).unwrap();
+        for c in "cat".chars() {
+            handle_key_event(&mut state, &mut lines, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), settings, 20, &file).unwrap();
+        }
+        assert_eq!(state.search_hit_count, 3);
+
+        let ctrl_alt = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        let case = KeyEvent::new(KeyCode::Char('a'), ctrl_alt);
+        handle_key_event(&mut state, &mut lines, case, settings, 20, &file).unwrap();
+        assert!(state.find_case_sensitive);
+        assert_eq!(state.search_hit_count, 2);
+
+        let whole_word = KeyEvent::new(KeyCode::Char('w'), ctrl_alt);
+        handle_key_event(&mut state, &mut lines, whole_word, settings, 20, &file).unwrap();
+        assert!(state.find_whole_word);
+        assert_eq!(state.search_hit_count, 1);
+        assert_eq!(crate::rendering::find_flags_label(&state), "[Case][Word]");
+
+        // The flags are remembered with the history entry and restored with it
+        handle_key_event(&mut state, &mut lines, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), settings, 20, &file).unwrap();
+        handle_key_event(&mut state, &mut lines, ctrl_f, settings, 20, &file).unwrap();
+        assert!(!state.find_case_sensitive && !state.find_whole_word);
+        handle_key_event(&mut state, &mut lines, KeyEvent::new(KeyCode::Up, KeyModifiers::NONE), settings, 20, &file).unwrap();
+        assert_eq!(state.find_pattern, "cat");
+        assert!(state.find_case_sensitive && state.find_whole_word);
+    }
+
+    #[test]
+    fn reload_as_encoding_replaces_buffer_and_drops_edits() {
+        let (tmp, _guard) = set_temp_home();
+        let path = tmp.path().join("legacy.txt");
+        std::fs::write(&path, b"caf\xE9\n\x93quoted\x94").unwrap();
+        let path = path.to_str().unwrap();
+        let mut state = create_test_state();
+        let mut lines = vec!["garbled".to_string(), "edited".to_string(), "more".to_string()];
+        state.modified = true;
+        state.undo_history.push(crate::undo::Edit::InsertChar { line: 0, col: 0, ch: 'x' });
+        state.cursor_line = 2;
+        state.cursor_col = 4;
+
+        reload_buffer_as(&mut state, &mut lines, path, FileEncoding::Windows1252, 10).unwrap();
+
+        assert_eq!(lines, vec!["café", "“quoted”"]);
+        assert_eq!(state.encoding, FileEncoding::Windows1252);
+        assert!(!state.modified);
+        assert!(!state.undo_history.can_undo());
+        assert_eq!(state.current_position(), (1, 4), "cursor clamped into the new text");
+    }
+
+    #[test]
+    fn missing_parent_dir_is_created_or_refused_per_setting() {
+        let (tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut settings = state.settings.clone();
+        let target = tmp.path().join("a").join("b").join("new.txt");
+        let target = target.to_str().unwrap();
+
+        settings.create_parent_dirs = "never".to_string();
+        assert!(!prepare_parent_dir(&mut state, target, &settings).unwrap());
+        assert!(!tmp.path().join("a").exists());
+        assert!(state.status_message.as_deref().unwrap().starts_with("Not saved"));
+
+        settings.create_parent_dirs = "always".to_string();
+        assert!(prepare_parent_dir(&mut state, target, &settings).unwrap());
+        assert!(tmp.path().join("a").join("b").is_dir());
+
+        // Existing directories and bare file names need nothing
+        settings.create_parent_dirs = "never".to_string();
+        assert!(prepare_parent_dir(&mut state, target, &settings).unwrap());
+        assert!(prepare_parent_dir(&mut state, "new.txt", &settings).unwrap());
+    }
+
+    #[test]
+    fn clear_modes_key_clears_everything_in_one_press() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let mut lines = create_test_lines(10);
+        let settings = state.settings;
+        state.selection_start = Some((1, 0));
+        state.selection_end = Some((3, 2));
+        state.block_selection = true;
+        state.multi_cursors = vec![(4, 0), (5, 0)];
+        state.last_search_pattern = Some("Line".to_string());
+        state.search_hit_count = 10;
+
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::empty());
+        handle_key_event(&mut state, &mut lines, esc, settings, 20, "test.txt").unwrap();
+        assert!(!state.has_selection());
+        assert!(!state.block_selection);
+        assert!(!state.has_multi_cursors());
+        assert!(state.last_search_pattern.is_none());
+        assert_eq!(state.search_hit_count, 0);
+        assert!(!state.menu_bar.active, "key is consumed when something was cleared");
+
+        // With nothing left to clear, Esc falls through to its usual handling
+        handle_key_event(&mut state, &mut lines, esc, settings, 20, "test.txt").unwrap();
+        assert!(state.menu_bar.active);
+    }
+
     #[test]
     fn help_exits_with_esc_without_clearing_modes() {
         let (_tmp, _guard) = set_temp_home();
@@ -3015,7 +4301,7 @@ mod tests {
     #[test]
     fn alt_up_down_without_shift_does_not_create_multi_cursors() {
         let (_tmp, _guard) = set_temp_home();
-        let mut state = create_test_state();
+        let mut state = create_scroll_test_state();
         let mut lines = vec![
             "line one".to_string(),
             "line two".to_string(),
@@ -3388,7 +4674,7 @@ mod tests {
     #[test]
     fn alt_arrow_up_scrolls_viewport_without_moving_cursor() {
         let (_tmp, _guard) = set_temp_home();
-        let mut state = create_test_state();
+        let mut state = create_scroll_test_state();
         let mut lines = create_test_lines(50);
         state.top_line = 10;
         state.cursor_line = 5;
@@ -3415,7 +4701,7 @@ mod tests {
     #[test]
     fn alt_arrow_down_scrolls_viewport_without_moving_cursor() {
         let (_tmp, _guard) = set_temp_home();
-        let mut state = create_test_state();
+        let mut state = create_scroll_test_state();
         let mut lines = create_test_lines(50);
         state.top_line = 10;
         state.cursor_line = 5;
@@ -3439,9 +4725,33 @@ mod tests {
     }
 
     #[test]
-    fn alt_arrow_up_at_top_does_nothing() {
+    fn scroll_keys_scroll_one_line_and_alt_page_keys_a_full_page() {
         let (_tmp, _guard) = set_temp_home();
         let mut state = create_test_state();
+        let mut lines = create_test_lines(100);
+        state.top_line = 40;
+        state.cursor_line = 5;
+        let settings = state.settings;
+        let mut press = |state: &mut FileViewerState, code, modifiers| {
+            handle_key_event(state, &mut lines, KeyEvent::new(code, modifiers), settings, 20, "test.txt").unwrap();
+        };
+
+        press(&mut state, KeyCode::PageUp, KeyModifiers::CONTROL | KeyModifiers::ALT);
+        assert_eq!(state.top_line, 39, "Ctrl+Alt+PageUp scrolls one line");
+        press(&mut state, KeyCode::PageDown, KeyModifiers::CONTROL | KeyModifiers::ALT);
+        assert_eq!(state.top_line, 40, "Ctrl+Alt+PageDown scrolls one line");
+
+        press(&mut state, KeyCode::PageUp, KeyModifiers::ALT);
+        assert_eq!(state.top_line, 20, "Alt+PageUp scrolls a full page");
+        press(&mut state, KeyCode::PageDown, KeyModifiers::ALT);
+        assert_eq!(state.top_line, 40, "Alt+PageDown scrolls a full page");
+        assert_eq!(state.absolute_line(), 45, "the cursor stays on its line");
+    }
+
+    #[test]
+    fn alt_arrow_up_at_top_does_nothing() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_scroll_test_state();
         let mut lines = create_test_lines(50);
         state.top_line = 0;
         state.cursor_line = 5;
@@ -3562,7 +4872,7 @@ mod tests {
     #[test]
     fn alt_arrow_up_allows_cursor_to_go_offscreen() {
         let (_tmp, _guard) = set_temp_home();
-        let mut state = create_test_state();
+        let mut state = create_scroll_test_state();
         let mut lines = create_test_lines(50);
         state.top_line = 10;
         state.cursor_line = 5;  // Absolute position 15
@@ -3591,7 +4901,7 @@ mod tests {
     #[test]
     fn alt_arrow_down_when_cursor_at_top() {
         let (_tmp, _guard) = set_temp_home();
-        let mut state = create_test_state();
+        let mut state = create_scroll_test_state();
         let mut lines = create_test_lines(50);
         state.top_line = 10;
         state.cursor_line = 0;  // Cursor at top of viewport (absolute position 10)
@@ -3618,7 +4928,7 @@ mod tests {
     #[test]
     fn alt_arrow_up_with_cursor_above_viewport_keeps_it_offscreen() {
         let (_tmp, _guard) = set_temp_home();
-        let mut state = create_test_state();
+        let mut state = create_scroll_test_state();
         let mut lines = create_test_lines(50);
         state.top_line = 15;
         state.cursor_line = 0;  // Cursor at absolute position 15
@@ -3774,6 +5084,37 @@ mod tests {
         assert_eq!(lines[0], "hello world", "should not modify content");
     }
 
+    #[test]
+    fn find_history_search_and_replace_keys_both_work_in_find_mode() {
+        let (tmp, _guard) = set_temp_home();
+        let file = tmp.path().join("history.txt");
+        let file = file.to_string_lossy().to_string();
+        let mut state = create_test_state();
+        let mut lines = vec!["main other".to_string()];
+        let settings = state.settings;
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        for pattern in ["main", "other"] {
+            handle_key_event(&mut state, &mut lines, ctrl_f, settings, 20, &file).unwrap();
+            for c in pattern.chars() {
+                handle_key_event(&mut state, &mut lines, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), settings, 20, &file).unwrap();
+            }
+            handle_key_event(&mut state, &mut lines, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), settings, 20, &file).unwrap();
+        }
+
+        // History search recalls an older pattern from the find prompt
+        handle_key_event(&mut state, &mut lines, ctrl_f, settings, 20, &file).unwrap();
+        handle_key_event(&mut state, &mut lines, KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE), settings, 20, &file).unwrap();
+        let history_search = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL | KeyModifiers::ALT);
+        handle_key_event(&mut state, &mut lines, history_search, settings, 20, &file).unwrap();
+        assert_eq!(state.find_pattern, "main");
+        assert!(!state.replace_active);
+
+        // The replace key still goes from find to replace
+        let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        handle_key_event(&mut state, &mut lines, ctrl_r, settings, 20, &file).unwrap();
+        assert!(state.replace_active, "replace key should enter replace mode from find");
+    }
+
     // -----------------------------------------------------------------------
     // Regression tests: Up navigation in wrapped lines
     //
@@ -4101,9 +5442,60 @@ mod tests {
             "Home and Ctrl+Home in rendered mode must produce the same top_line"
         );
     }
-}
 
+    #[test]
+    fn indent_block_keys_skip_deeper_children() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let settings = state.settings;
+        let mut lines: Vec<String> = [
+            "fn a() {",
+            "    if x {",
+            "        y();",
+            "",
+            "    }",
+            "}",
+            "",
+            "fn b() {}",
+        ]
+        .map(String::from)
+        .to_vec();
+        let down = KeyEvent::new(KeyCode::Down, KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let up = KeyEvent::new(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let mut press = |state: &mut FileViewerState, key| {
+            handle_key_event(state, &mut lines, key, settings, 20, "test.rs").unwrap();
+            (state.absolute_line(), state.cursor_col)
+        };
+
+        assert_eq!(press(&mut state, down), (5, 0));
+        assert_eq!(press(&mut state, down), (7, 0));
+        assert_eq!(press(&mut state, down), (7, 0), "no sibling below: stay");
+        assert_eq!(press(&mut state, up), (5, 0));
 
+        state.cursor_line = 1;
+        assert_eq!(press(&mut state, down), (4, 4), "closing brace is the next sibling");
+        assert_eq!(press(&mut state, up), (1, 4));
+        assert_eq!(press(&mut state, up), (0, 0), "parent is at a lower indentation");
+    }
+
+    #[test]
+    fn bracketed_paste_is_one_undo_step_without_auto_indent() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut state = create_test_state();
+        let settings = state.settings;
+        let mut lines = vec!["fn main() {".to_string(), "}".to_string()];
+        state.cursor_line = 0;
+        state.cursor_col = 11;
 
+        handle_paste_event(&mut state, &mut lines, "\r    let x = (1,\r  2);", settings, 20, "test.rs").unwrap();
+        assert_eq!(lines, vec!["fn main() {", "    let x = (1,", "  2);", "}"]);
 
+        apply_undo(&mut state, &mut lines, "test.rs", 20);
+        assert_eq!(lines, vec!["fn main() {", "}"]);
 
+        // In the find input the first line is typed
+        state.find_active = true;
+        handle_paste_event(&mut state, &mut lines, "main\nrest", settings, 20, "test.rs").unwrap();
+        assert_eq!(state.find_pattern, "main");
+    }
+}
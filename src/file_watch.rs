@@ -0,0 +1,44 @@
+//! Notice when the open file is changed on disk by another program.
+
+use std::fs;
+use std::time::SystemTime;
+
+/// Modification time and size of a file on disk, compared to tell whether
+/// someone else has written it since we last read or saved it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DiskStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl DiskStamp {
+    /// Stamp of `path`, or None when it does not exist (or is not readable)
+    pub(crate) fn read(path: &str) -> Option<DiskStamp> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(DiskStamp {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamp_changes_when_file_is_rewritten() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("watched.txt");
+        let path = path.to_string_lossy().to_string();
+        assert_eq!(DiskStamp::read(&path), None);
+
+        fs::write(&path, "one").unwrap();
+        let first = DiskStamp::read(&path).unwrap();
+        assert_eq!(DiskStamp::read(&path), Some(first));
+
+        // Different size is noticed even within the mtime resolution
+        fs::write(&path, "one two").unwrap();
+        assert_ne!(DiskStamp::read(&path), Some(first));
+    }
+}
@@ -1,9 +1,32 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 use crate::editor_state::{FileViewerState, Position};
 
 const MAX_FIND_HISTORY: usize = 100;
+/// Upper bound on cursors placed by `place_cursors_at_matches`
+pub(crate) const MAX_MATCH_CURSORS: usize = 1000;
+
+/// Find options a new search starts with. Toggling an option during a search only
+/// affects that search; the defaults change when they are saved explicitly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FindOptions {
+    /// true = regex, false = wildcard (* and ? only)
+    pub regex_mode: bool,
+    /// Match letter case exactly instead of ignoring it
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Only match text standing on word boundaries
+    #[serde(default)]
+    pub whole_word: bool,
+}
+
+impl Default for FindOptions {
+    fn default() -> Self {
+        Self { regex_mode: true, case_sensitive: false, whole_word: false }
+    }
+}
 
 /// Convert a character index within `s` to the corresponding byte offset.
 fn char_to_byte(s: &str, char_idx: usize) -> usize {
@@ -69,6 +92,44 @@ fn scope_char_range(
     }
 }
 
+/// Adapt `replacement` to the casing of `matched`: ALL CAPS, Capitalized or lowercase.
+/// Mixed or caseless matches leave the replacement untouched.
+pub(crate) fn match_case(matched: &str, replacement: &str) -> String {
+    let letters: Vec<char> = matched.chars().filter(|c| c.is_alphabetic()).collect();
+    let Some(first) = letters.first() else {
+        return replacement.to_string();
+    };
+    let rest_lower = letters[1..].iter().all(|c| !c.is_uppercase());
+    if letters.len() > 1 && letters.iter().all(|c| !c.is_lowercase()) {
+        replacement.to_uppercase()
+    } else if first.is_uppercase() && rest_lower {
+        let mut chars = replacement.chars();
+        match chars.next() {
+            Some(c) => c.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    } else if letters.iter().all(|c| !c.is_uppercase()) {
+        replacement.to_lowercase()
+    } else {
+        replacement.to_string()
+    }
+}
+
+/// Expand `replacement` (with capture groups) for the first match of `regex` in `text`,
+/// optionally adapting it to the casing of the matched text
+fn replace_match(regex: &Regex, text: &str, replacement: &str, preserve_case: bool) -> String {
+    if !preserve_case {
+        return regex.replace(text, replacement).to_string();
+    }
+    regex
+        .replace(text, |caps: &regex::Captures| {
+            let mut expanded = String::new();
+            caps.expand(replacement, &mut expanded);
+            match_case(&caps[0], &expanded)
+        })
+        .to_string()
+}
+
 /// Replace all occurrences of `regex` (found in the ANSI-stripped text) within
 /// `line[scope_char_from..scope_char_to]` (raw char indices).  ANSI sequences
 /// outside matched regions are preserved; ANSI within a matched region is removed.
@@ -77,6 +138,7 @@ fn replace_in_stripped_line(
     line: &str,
     regex: &Regex,
     replacement: &str,
+    preserve_case: bool,
     scope_char_from: usize,
     scope_char_to: usize,
 ) -> (String, usize) {
@@ -100,7 +162,7 @@ fn replace_in_stripped_line(
             let raw_start = crate::coordinates::stripped_char_to_raw_char(line, sc_start);
             let raw_end   = crate::coordinates::stripped_char_to_raw_char(line, sc_end);
             // Capture-group–aware replacement: apply the regex to the stripped match text
-            let repl = regex.replace(&stripped[m.start()..m.end()], replacement).to_string();
+            let repl = replace_match(regex, &stripped[m.start()..m.end()], replacement, preserve_case);
             (raw_start, raw_end, repl)
         })
         .collect();
@@ -156,18 +218,34 @@ pub(crate) fn wildcard_to_regex(pattern: &str) -> Result<String, Box<dyn std::er
     Ok(regex)
 }
 
-/// Convert a pattern to a regex, applying case-insensitive flag and handling wildcard mode
-pub(crate) fn pattern_to_regex(pattern: &str, regex_mode: bool) -> Result<Regex, Box<dyn std::error::Error>> {
-    let regex_pattern = if regex_mode {
-        // Regex mode: use pattern as-is with case-insensitive flag
-        format!("(?i){}", pattern)
+/// Add the case-insensitive flag and word boundaries the options ask for
+fn apply_find_options(regex: &str, options: FindOptions) -> String {
+    let case_flag = if options.case_sensitive { "" } else { "(?i)" };
+    if options.whole_word {
+        format!(r"{}\b(?:{})\b", case_flag, regex)
+    } else {
+        format!("{}{}", case_flag, regex)
+    }
+}
+
+/// Convert a pattern to a regex, applying the case and whole-word options and handling wildcard mode
+pub(crate) fn pattern_to_regex(pattern: &str, options: FindOptions) -> Result<Regex, Box<dyn std::error::Error>> {
+    let regex_pattern = if options.regex_mode {
+        // Regex mode: use pattern as-is
+        pattern.to_string()
     } else {
-        // Wildcard mode: convert wildcards to regex, then apply case-insensitive flag
-        let wildcard_regex = wildcard_to_regex(pattern)?;
-        format!("(?i){}", wildcard_regex)
+        // Wildcard mode: convert wildcards to regex
+        wildcard_to_regex(pattern)?
     };
 
-    Regex::new(&regex_pattern).map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+    Regex::new(&apply_find_options(&regex_pattern, options)).map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+}
+
+/// Compile a multi-line pattern: `\n` escapes become line breaks and (?m) makes
+/// ^ and $ match at line boundaries. Always a regex, whatever the find mode.
+pub(crate) fn multiline_regex(pattern: &str, options: FindOptions) -> Result<Regex, regex::Error> {
+    let expanded = expand_newline_escapes(pattern);
+    Regex::new(&format!("(?m){}", apply_find_options(&expanded, options)))
 }
 
 /// Returns true if the pattern contains a literal `\n` (i.e. the two-character
@@ -179,7 +257,7 @@ pub(crate) fn pattern_is_multiline(pattern: &str) -> bool {
 /// Extract a single-line summary from a regex error message.
 /// The `regex` crate produces verbose multi-line output; we pull out the
 /// `error: …` line which contains the human-readable description.
-fn summarize_regex_error(e: &str) -> String {
+pub(crate) fn summarize_regex_error(e: &str) -> String {
     e.lines()
         .find(|l| l.trim_start().starts_with("error:"))
         .map(|l| l.trim().to_string())
@@ -192,6 +270,49 @@ pub(crate) fn expand_newline_escapes(pattern: &str) -> String {
     pattern.replace("\\n", "\n")
 }
 
+/// Turn the replacement text typed by the user into a template for
+/// `Captures::expand`: `\1` becomes a group reference like `$1`, `\n` and `\t`
+/// become a line break and a tab, `\\` and `\$` a literal backslash and dollar.
+/// `$1`, `${name}` and `$$` are passed through; other backslashes stay as typed.
+pub(crate) fn replacement_template(replacement: &str) -> String {
+    let mut template = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            template.push(ch);
+            continue;
+        }
+        match chars.peek().copied() {
+            Some(d) if d.is_ascii_digit() => {
+                let mut group = String::new();
+                while let Some(d) = chars.next_if(|c| c.is_ascii_digit()) {
+                    group.push(d);
+                }
+                // Braces keep a following letter out of the group name
+                template.push_str(&format!("${{{}}}", group));
+            }
+            Some('n') => {
+                chars.next();
+                template.push('\n');
+            }
+            Some('t') => {
+                chars.next();
+                template.push('\t');
+            }
+            Some('\\') => {
+                chars.next();
+                template.push('\\');
+            }
+            Some('$') => {
+                chars.next();
+                template.push_str("$$");
+            }
+            _ => template.push('\\'),
+        }
+    }
+    template
+}
+
 /// Build a single string by joining ANSI-stripped versions of `lines[min_line..=max_line]`
 /// with `\n`.  Stripping ANSI ensures that colour-change sequences embedded in lines
 /// do not split search terms.
@@ -341,7 +462,7 @@ pub(crate) fn handle_find_input(
     state: &mut FileViewerState,
     lines: &[String],
     key_event: KeyEvent,
-    _visible_lines: usize,
+    visible_lines: usize,
 ) -> Result<bool, String> {
 
     let KeyEvent { code, modifiers, .. } = key_event;
@@ -358,6 +479,8 @@ pub(crate) fn handle_find_input(
             // Restore the search pattern from before entering find mode
             state.last_search_pattern = state.saved_search_pattern.clone();
             state.saved_search_pattern = None;
+            // Incremental search may have moved the view: go back to where find started
+            restore_find_position(state);
             state.needs_redraw = true;
             Ok(true)
         }
@@ -366,11 +489,11 @@ pub(crate) fn handle_find_input(
             if !state.find_pattern.is_empty() {
                 // Validate pattern (multiline patterns use the expanded form)
                 let pattern_valid = if pattern_is_multiline(&state.find_pattern) {
-                    let expanded = expand_newline_escapes(&state.find_pattern);
-                    let ml_pat = format!("(?i)(?m){}", expanded);
-                    Regex::new(&ml_pat).map(|_| ()).map_err(|e| e.to_string())
+                    multiline_regex(&state.find_pattern, state.find_options())
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
                 } else {
-                    pattern_to_regex(&state.find_pattern, state.find_regex_mode)
+                    pattern_to_regex(&state.find_pattern, state.find_options())
                         .map(|_| ())
                         .map_err(|e| e.to_string())
                 };
@@ -378,7 +501,7 @@ pub(crate) fn handle_find_input(
                     Ok(()) => {
                         // Set last_search_pattern for highlighting
                         state.last_search_pattern = Some(state.find_pattern.clone());
-                        state.last_search_regex_mode = state.find_regex_mode;
+                        state.last_search_options = state.find_options();
                         add_to_history(state, state.find_pattern.clone());
 
                         // Update hit count but don't jump to match
@@ -389,6 +512,8 @@ pub(crate) fn handle_find_input(
                         state.find_active = false;
                         state.find_history_index = None;
                         state.saved_search_pattern = None;
+                        // Stay on the match incremental search moved to
+                        state.saved_find_position = None;
                         // Note: Don't clear selection - keep it visible to show the search scope
                         // Note: Don't clear find_scope - keep it so highlighting remains scoped
 
@@ -416,6 +541,7 @@ pub(crate) fn handle_find_input(
                 state.last_search_pattern = None; // Clear highlights
                 state.saved_search_pattern = None; // Clear saved pattern
                 state.find_scope = None; // Clear search scope for next search
+                restore_find_position(state);
                 state.needs_redraw = true;
             }
             Ok(true)
@@ -429,19 +555,16 @@ pub(crate) fn handle_find_input(
             if let Some(index) = state.find_history_index {
                 if index + 1 < state.find_history.len() {
                     state.find_history_index = Some(index + 1);
-                    state.find_pattern = state.find_history[index + 1].clone();
-                    state.find_cursor_pos = state.find_pattern.chars().count();
+                    recall_history_entry(state, index + 1);
                 }
             } else {
                 // First time pressing Up - save current input so Down can restore it
                 state.find_input_saved = state.find_pattern.clone();
                 state.find_history_index = Some(0);
-                state.find_pattern = state.find_history[0].clone();
-                state.find_cursor_pos = state.find_pattern.chars().count();
+                recall_history_entry(state, 0);
             }
             // Update highlights in real-time
-            update_live_highlights(state);
-            update_search_hit_count(state, lines);
+            incremental_search(state, lines, visible_lines);
             state.needs_redraw = true;
             Ok(false)
         }
@@ -450,8 +573,7 @@ pub(crate) fn handle_find_input(
             if let Some(index) = state.find_history_index {
                 if index > 0 {
                     state.find_history_index = Some(index - 1);
-                    state.find_pattern = state.find_history[index - 1].clone();
-                    state.find_cursor_pos = state.find_pattern.chars().count();
+                    recall_history_entry(state, index - 1);
                 } else {
                     // Back to the text the user had typed before navigating history
                     state.find_history_index = None;
@@ -459,8 +581,7 @@ pub(crate) fn handle_find_input(
                     state.find_cursor_pos = state.find_pattern.chars().count();
                 }
                 // Update highlights in real-time
-                update_live_highlights(state);
-                update_search_hit_count(state, lines);
+                incremental_search(state, lines, visible_lines);
                 state.needs_redraw = true;
             }
             Ok(false)
@@ -480,8 +601,7 @@ pub(crate) fn handle_find_input(
                 state.find_selection = None; // Clear selection
                 state.find_history_index = None;
                 // Update highlights in real-time
-                update_live_highlights(state);
-                update_search_hit_count(state, lines);
+                incremental_search(state, lines, visible_lines);
                 state.needs_redraw = true;
             }
             Ok(false)
@@ -578,8 +698,7 @@ pub(crate) fn handle_find_input(
 
             state.find_history_index = None;
             // Update highlights in real-time
-            update_live_highlights(state);
-            update_search_hit_count(state, lines);
+            incremental_search(state, lines, visible_lines);
             state.needs_redraw = true;
             Ok(false)
         }
@@ -595,10 +714,8 @@ pub(crate) fn find_next_occurrence(
 ) {
     if let Some(ref pattern) = state.last_search_pattern.clone() {
         if pattern_is_multiline(pattern) {
-            // Multi-line search: expand \n and compile with (?s) dot-all + (?m) multiline flags
-            let expanded = expand_newline_escapes(pattern);
-            let ml_pattern = format!("(?i)(?m){}", expanded);
-            if let Ok(regex) = Regex::new(&ml_pattern) {
+            // Multi-line search: expand \n and compile with (?m) multiline flag
+            if let Ok(regex) = multiline_regex(pattern, state.find_options()) {
                 let pos = find_next_multiline(lines, state.current_position(), &regex, false, state.find_scope)
                     .or_else(|| find_next_multiline(lines, state.current_position(), &regex, true, state.find_scope));
                 if let Some(pos) = pos {
@@ -606,11 +723,13 @@ pub(crate) fn find_next_occurrence(
                     state.search_wrapped = false;
                     state.wrap_warning_pending = None;
                     update_search_hit_count(state, lines);
+                } else {
+                    state.alert();
                 }
             }
         } else {
             // Compile pattern with current find mode
-            if let Ok(regex) = pattern_to_regex(pattern, state.find_regex_mode) {
+            if let Ok(regex) = pattern_to_regex(pattern, state.find_options()) {
                 if let Some(pos) = find_next(
                     lines,
                     state.current_position(),
@@ -636,8 +755,10 @@ pub(crate) fn find_next_occurrence(
                         state.search_wrapped = true;
                         state.wrap_warning_pending = None;
                         update_search_hit_count(state, lines);
+                    } else {
+                        // No match anywhere: stay at the current position
+                        state.alert();
                     }
-                    // If still no match, just stay at current position (no error message)
                 }
             }
         }
@@ -653,9 +774,7 @@ pub(crate) fn find_prev_occurrence(
 ) {
     if let Some(ref pattern) = state.last_search_pattern.clone() {
         if pattern_is_multiline(pattern) {
-            let expanded = expand_newline_escapes(pattern);
-            let ml_pattern = format!("(?i)(?m){}", expanded);
-            if let Ok(regex) = Regex::new(&ml_pattern) {
+            if let Ok(regex) = multiline_regex(pattern, state.find_options()) {
                 let pos = find_prev_multiline(lines, state.current_position(), &regex, false, state.find_scope)
                     .or_else(|| find_prev_multiline(lines, state.current_position(), &regex, true, state.find_scope));
                 if let Some(pos) = pos {
@@ -663,11 +782,13 @@ pub(crate) fn find_prev_occurrence(
                     state.search_wrapped = false;
                     state.wrap_warning_pending = None;
                     update_search_hit_count(state, lines);
+                } else {
+                    state.alert();
                 }
             }
         } else {
             // Compile pattern with current find mode
-            if let Ok(regex) = pattern_to_regex(pattern, state.find_regex_mode) {
+            if let Ok(regex) = pattern_to_regex(pattern, state.find_options()) {
                 if let Some(pos) = find_prev(
                     lines,
                     state.current_position(),
@@ -693,8 +814,10 @@ pub(crate) fn find_prev_occurrence(
                         state.search_wrapped = true;
                         state.wrap_warning_pending = None;
                         update_search_hit_count(state, lines);
+                    } else {
+                        // No match anywhere: stay at the current position
+                        state.alert();
                     }
-                    // If still no match, just stay at current position (no error message)
                 }
             }
         }
@@ -702,6 +825,50 @@ pub(crate) fn find_prev_occurrence(
     state.needs_redraw = true;
 }
 
+/// Incremental search: refresh the highlights for the pattern being typed and
+/// move to its first match after where find mode started (wrapping around), or
+/// back to that position when the pattern has no match
+pub(crate) fn incremental_search(state: &mut FileViewerState, lines: &[String], visible_lines: usize) {
+    update_live_highlights(state);
+    let (top_line, cursor_line, cursor_col) =
+        *state.saved_find_position.get_or_insert((state.top_line, state.cursor_line, state.cursor_col));
+    state.top_line = top_line;
+    state.cursor_line = cursor_line;
+    state.cursor_col = cursor_col;
+
+    // Matches are only searched for a valid pattern, and not in huge files
+    if !state.large_file && !state.find_pattern.is_empty() && state.last_search_pattern.as_ref() == Some(&state.find_pattern) {
+        let origin = state.current_position();
+        let options = state.find_options();
+        let scope = state.find_scope;
+        let pos = if pattern_is_multiline(&state.find_pattern) {
+            multiline_regex(&state.find_pattern, options).ok().and_then(|regex| {
+                find_next_multiline(lines, origin, &regex, false, scope)
+                    .or_else(|| find_next_multiline(lines, origin, &regex, true, scope))
+            })
+        } else {
+            pattern_to_regex(&state.find_pattern, options).ok().and_then(|regex| {
+                find_next(lines, origin, &regex, false, scope).or_else(|| find_next(lines, origin, &regex, true, scope))
+            })
+        };
+        if let Some(pos) = pos {
+            move_to_position(state, pos, lines.len(), lines, visible_lines);
+        }
+    }
+    update_search_hit_count(state, lines);
+    state.needs_redraw = true;
+}
+
+/// Put the view and cursor back where find mode started
+pub(crate) fn restore_find_position(state: &mut FileViewerState) {
+    if let Some((top_line, cursor_line, cursor_col)) = state.saved_find_position.take() {
+        state.top_line = top_line;
+        state.cursor_line = cursor_line;
+        state.cursor_col = cursor_col;
+        state.needs_redraw = true;
+    }
+}
+
 /// Update live highlights based on current find pattern
 pub(crate) fn update_live_highlights(state: &mut FileViewerState) {
     if state.find_pattern.is_empty() {
@@ -712,25 +879,27 @@ pub(crate) fn update_live_highlights(state: &mut FileViewerState) {
     } else {
         // Validate pattern: for multiline patterns, check with the expanded form
         let valid = if pattern_is_multiline(&state.find_pattern) {
-            let expanded = expand_newline_escapes(&state.find_pattern);
-            let ml_pat = format!("(?i)(?m){}", expanded);
-            Regex::new(&ml_pat).is_ok()
+            multiline_regex(&state.find_pattern, state.find_options()).is_ok()
         } else {
-            pattern_to_regex(&state.find_pattern, state.find_regex_mode).is_ok()
+            pattern_to_regex(&state.find_pattern, state.find_options()).is_ok()
         };
         if valid {
             state.last_search_pattern = Some(state.find_pattern.clone());
-            state.last_search_regex_mode = state.find_regex_mode;
+            state.last_search_options = state.find_options();
         }
         // Invalid pattern - don't update highlights but don't show error yet (let user finish typing)
     }
 }
 
-/// Add pattern to history, keeping max 100 entries
+/// Add pattern to history, keeping max 100 entries, and remember the find
+/// options it was searched with
 fn add_to_history(state: &mut FileViewerState, pattern: String) {
     // Remove if already exists
     state.find_history.retain(|p| p != &pattern);
 
+    let options = state.find_options();
+    state.undo_history.find_history_options.insert(pattern.clone(), options);
+
     // Add to front
     state.find_history.insert(0, pattern);
 
@@ -738,6 +907,46 @@ fn add_to_history(state: &mut FileViewerState, pattern: String) {
     if state.find_history.len() > MAX_FIND_HISTORY {
         state.find_history.truncate(MAX_FIND_HISTORY);
     }
+    let history = &state.find_history;
+    state.undo_history.find_history_options.retain(|p, _| history.contains(p));
+}
+
+/// Put the find history entry at `index` into the find prompt, together with
+/// the find options it was searched with
+fn recall_history_entry(state: &mut FileViewerState, index: usize) {
+    state.find_pattern = state.find_history[index].clone();
+    state.find_cursor_pos = state.find_pattern.chars().count();
+    if let Some(&options) = state.undo_history.find_history_options.get(&state.find_pattern) {
+        state.set_find_options(options);
+    }
+}
+
+/// Reverse search in the find history (Ctrl+Alt+P in the find prompt): recall the
+/// next older entry containing the typed text. Pressing it again keeps going
+/// back with the same text; Down returns to what was typed.
+pub(crate) fn search_find_history(state: &mut FileViewerState, lines: &[String], visible_lines: usize) {
+    let start = match state.find_history_index {
+        Some(index) => index + 1,
+        None => {
+            state.find_input_saved = state.find_pattern.clone();
+            0
+        }
+    };
+    let query = state.find_input_saved.clone();
+    let found = (start..state.find_history.len()).find(|&i| state.find_history[i].contains(&query));
+    match found {
+        Some(index) => {
+            state.find_history_index = Some(index);
+            recall_history_entry(state, index);
+            state.find_selection = None;
+            incremental_search(state, lines, visible_lines);
+        }
+        None => {
+            state.status_message = Some(format!("No older search contains \"{}\"", query));
+            state.alert();
+        }
+    }
+    state.needs_redraw = true;
 }
 
 /// Add replacement string to replace history, keeping max 100 entries
@@ -877,6 +1086,337 @@ fn find_prev(
     None
 }
 
+thread_local! {
+    /// Compiled TODO marker regex, keyed by the configured marker list
+    static TODO_REGEX_CACHE: std::cell::RefCell<Option<(Vec<String>, Option<Regex>)>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Regex matching any configured TODO marker as a whole word, or `None` if the list is empty
+fn todo_marker_regex(markers: &[String]) -> Option<Regex> {
+    TODO_REGEX_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some((key, regex)) = cache.as_ref()
+            && key.as_slice() == markers
+        {
+            return regex.clone();
+        }
+        let words: Vec<String> = markers
+            .iter()
+            .filter(|m| !m.is_empty())
+            .map(|m| regex::escape(m))
+            .collect();
+        let regex = (!words.is_empty())
+            .then(|| Regex::new(&format!(r"\b(?:{})\b", words.join("|"))).ok())
+            .flatten();
+        *cache = Some((markers.to_vec(), regex.clone()));
+        regex
+    })
+}
+
+/// Char ranges of TODO markers on a line.  In files with a syntax only markers
+/// inside comments count; in plain text every occurrence does.
+pub(crate) fn todo_marker_ranges(line: &str, markers: &[String]) -> Vec<(usize, usize)> {
+    let Some(regex) = todo_marker_regex(markers) else {
+        return Vec::new();
+    };
+    let matches: Vec<regex::Match> = regex.find_iter(line).collect();
+    if matches.is_empty() {
+        return Vec::new();
+    }
+    let comments = crate::syntax::comment_ranges(line);
+    matches
+        .into_iter()
+        .filter(|m| {
+            comments.as_ref().is_none_or(|ranges| {
+                ranges.iter().any(|&(s, e)| m.start() >= s && m.end() <= e)
+            })
+        })
+        .map(|m| (line[..m.start()].chars().count(), line[..m.end()].chars().count()))
+        .collect()
+}
+
+/// Move the cursor to the next (or previous) TODO marker, wrapping around the file
+pub(crate) fn goto_todo_marker(
+    state: &mut FileViewerState,
+    lines: &[String],
+    visible_lines: usize,
+    forward: bool,
+) -> bool {
+    let markers = state.settings.todo_markers.clone();
+    let (cur_line, cur_col) = state.current_position();
+    let count = lines.len();
+    if count == 0 {
+        return false;
+    }
+
+    // Visit the cursor line first (after/before the cursor), then every other
+    // line in search order, and finally the cursor line again for the wrap-around part
+    let found = (0..=count).find_map(|step| {
+        let idx = if forward {
+            (cur_line + step) % count
+        } else {
+            (cur_line + count * 2 - step) % count
+        };
+        let ranges = todo_marker_ranges(&lines[idx], &markers);
+        let mut cols = ranges.iter().map(|&(start, _)| start);
+        let col = match step {
+            0 if forward => cols.find(|&c| c > cur_col),
+            0 => cols.rfind(|&c| c < cur_col),
+            _ if step == count && forward => cols.find(|&c| c <= cur_col),
+            _ if step == count => cols.rfind(|&c| c >= cur_col),
+            _ if forward => cols.next(),
+            _ => cols.next_back(),
+        };
+        col.map(|c| (idx, c))
+    });
+
+    match found {
+        Some(pos) => {
+            move_to_position(state, pos, count, lines, visible_lines);
+            state.needs_redraw = true;
+            true
+        }
+        None => {
+            state.status_message = Some("No TODO markers found".to_string());
+            state.alert();
+            state.needs_footer_redraw = true;
+            false
+        }
+    }
+}
+
+/// Char index where `line` first runs past `limit` display columns (tabs
+/// expanded), or None when the whole line fits
+pub(crate) fn over_limit_column(line: &str, limit: usize, tab_width: usize) -> Option<usize> {
+    let count = line.chars().count();
+    if crate::coordinates::visual_width_up_to(line, count, tab_width) <= limit {
+        return None;
+    }
+    // Widths only grow with the prefix, so binary search for the first char that ends past the limit
+    let (mut lo, mut hi) = (0, count - 1);
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        if crate::coordinates::visual_width_up_to(line, mid + 1, tab_width) > limit {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    Some(lo)
+}
+
+/// Jump to where the next line longer than `line_length_limit` overflows,
+/// wrapping around the file. Returns false when no line is over the limit.
+pub(crate) fn goto_long_line(state: &mut FileViewerState, lines: &[String], visible_lines: usize) -> bool {
+    let limit = state.settings.appearance.line_length_limit;
+    if limit == 0 {
+        state.status_message = Some("No line length limit set (line_length_limit in settings)".to_string());
+        state.alert();
+        state.needs_footer_redraw = true;
+        return false;
+    }
+    let tab_width = state.settings.tab_width;
+    let cur_line = state.current_position().0;
+    let count = lines.len();
+    let found = (1..=count).find_map(|step| {
+        let idx = (cur_line + step) % count;
+        over_limit_column(&lines[idx], limit, tab_width).map(|col| (idx, col))
+    });
+
+    match found {
+        Some(pos) => {
+            move_to_position(state, pos, count, lines, visible_lines);
+            state.needs_redraw = true;
+            true
+        }
+        None => {
+            state.status_message = Some(format!("No lines longer than {} columns", limit));
+            state.alert();
+            state.needs_footer_redraw = true;
+            false
+        }
+    }
+}
+
+/// Place a cursor at the start of every match of the regex `pattern`, within the
+/// selection if one is active, entering multi-cursor mode. The main cursor goes to
+/// the first match. Returns the number of cursors placed and whether the match count
+/// exceeded `MAX_MATCH_CURSORS`, or an error message for the footer.
+pub(crate) fn place_cursors_at_matches(
+    state: &mut FileViewerState,
+    lines: &[String],
+    pattern: &str,
+    visible_lines: usize,
+) -> Result<(usize, bool), String> {
+    let regex = pattern_to_regex(pattern, FindOptions::default()).map_err(|e| summarize_regex_error(&e.to_string()))?;
+    let scope = state.selection_range().filter(|_| !state.block_selection);
+    let (min_line, max_line) = match scope {
+        Some(((start_line, _), (end_line, _))) => (start_line, end_line),
+        None => (0, lines.len().saturating_sub(1)),
+    };
+
+    let mut positions: Vec<Position> = Vec::new();
+    for (line_idx, line) in lines.iter().enumerate().take(max_line + 1).skip(min_line) {
+        let (from, to) = scope_char_range(line_idx, line, scope);
+        positions.extend(stripped_find(line, &regex, from, to).into_iter().map(|col| (line_idx, col)));
+        if positions.len() > MAX_MATCH_CURSORS {
+            break;
+        }
+    }
+    positions.dedup();
+    let capped = positions.len() > MAX_MATCH_CURSORS;
+    positions.truncate(MAX_MATCH_CURSORS);
+    if positions.is_empty() {
+        return Err("No matches".to_string());
+    }
+
+    let (main_line, main_col) = positions.remove(0);
+    state.clear_selection();
+    state.multi_cursors = positions;
+    state.set_cursor_position(main_line, main_col, lines, visible_lines);
+    state.desired_cursor_col = state.cursor_col;
+    state.needs_redraw = true;
+    Ok((state.multi_cursors.len() + 1, capped))
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Char range of the word at or just before `col`
+fn word_at(line: &str, col: usize) -> Option<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let col = col.min(chars.len());
+    let at = if chars.get(col).is_some_and(|&c| is_word_char(c)) {
+        col
+    } else if col > 0 && is_word_char(chars[col - 1]) {
+        col - 1
+    } else {
+        return None;
+    };
+    let start = (0..at).rev().take_while(|&i| is_word_char(chars[i])).last().unwrap_or(at);
+    let end = (at..chars.len()).find(|&i| !is_word_char(chars[i])).unwrap_or(chars.len());
+    Some((start, end))
+}
+
+/// Highlight all occurrences of the word under the cursor with the search
+/// highlight, matching whole words and case exactly, without entering find
+/// mode. The next edit clears it again, as does a second call.
+pub(crate) fn toggle_word_highlight(state: &mut FileViewerState, lines: &[String]) -> Result<(), String> {
+    if state.word_highlight_edit.take().is_some() && state.last_search_pattern.is_some() {
+        state.last_search_pattern = None;
+        state.search_hit_count = 0;
+        state.search_current_hit = 0;
+        return Ok(());
+    }
+    let (line, col) = state.current_position();
+    let text = lines.get(line).ok_or("No word under the cursor")?;
+    let (start, end) = word_at(text, col).ok_or("No word under the cursor")?;
+    let word: String = text.chars().skip(start).take(end - start).collect();
+
+    let options = FindOptions { regex_mode: true, case_sensitive: true, whole_word: true };
+    state.set_find_options(options);
+    state.last_search_options = options;
+    state.last_search_pattern = Some(regex::escape(&word));
+    state.find_scope = None;
+    state.word_highlight_edit = Some(state.undo_history.current);
+    update_search_hit_count(state, lines);
+    Ok(())
+}
+
+/// Ends of the occurrences of `word` after `from`, wrapping around the file
+fn next_occurrence_end(
+    lines: &[String],
+    word: &str,
+    whole_word: bool,
+    from: Position,
+) -> impl Iterator<Item = Position> {
+    let word_len = word.chars().count();
+    let mut found = Vec::new();
+    for (line_idx, line) in lines.iter().enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        for (byte, _) in line.match_indices(word) {
+            let start = line[..byte].chars().count();
+            let end = start + word_len;
+            let bounded = !whole_word
+                || ((start == 0 || !is_word_char(chars[start - 1]))
+                    && chars.get(end).is_none_or(|&c| !is_word_char(c)));
+            if bounded {
+                found.push((line_idx, end));
+            }
+        }
+    }
+    let split = found.partition_point(|&pos| pos <= from);
+    let (before, after) = found.split_at(split);
+    after.iter().chain(before.iter()).copied().collect::<Vec<_>>().into_iter()
+}
+
+/// Ctrl+D: select the word under the cursor, then on each further press add a
+/// cursor at the end of the next occurrence of it (whole words only) or of the
+/// selected text (anywhere) and move the main cursor there. Returns the number of cursors, or a message for
+/// the footer when there is nothing (more) to add.
+pub(crate) fn add_next_occurrence(
+    state: &mut FileViewerState,
+    lines: &[String],
+    visible_lines: usize,
+) -> Result<usize, String> {
+    let (line_idx, col) = state.current_position();
+    let line = lines.get(line_idx).map(String::as_str).unwrap_or("");
+    let continuing = state.has_multi_cursors()
+        && state.occurrence_word.as_ref().is_some_and(|(word, _)| {
+            let chars: Vec<char> = line.chars().collect();
+            let len = word.chars().count();
+            col >= len && chars.get(col - len..col).is_some_and(|s| s.iter().collect::<String>() == *word)
+        });
+    let (word, whole_word) = if continuing {
+        state.occurrence_word.clone().unwrap_or_default()
+    } else {
+        match state.selection_range() {
+            Some(((start_line, start_col), (end_line, end_col)))
+                if start_line == end_line && start_col < end_col && !state.block_selection =>
+            {
+                let text: String = line.chars().skip(start_col).take(end_col - start_col).collect();
+                // Still the word selected by the first press
+                let whole_word = state.occurrence_word.as_ref().is_some_and(|(word, _)| *word == text);
+                (text, whole_word)
+            }
+            _ => {
+                // First press: select the word under the cursor
+                state.clear_multi_cursors();
+                let (start, end) = word_at(line, col).ok_or_else(|| "No word at cursor".to_string())?;
+                state.selection_start = Some((line_idx, start));
+                state.selection_end = Some((line_idx, end));
+                state.set_cursor_position(line_idx, end, lines, visible_lines);
+                state.desired_cursor_col = end;
+                state.occurrence_word = Some((line.chars().skip(start).take(end - start).collect(), true));
+                state.needs_redraw = true;
+                return Ok(1);
+            }
+        }
+    };
+
+    let from = match state.selection_range() {
+        Some((_, end)) if !continuing => end,
+        _ => (line_idx, col),
+    };
+    let taken = state.all_cursor_positions();
+    let Some(next) = next_occurrence_end(lines, &word, whole_word, from).find(|pos| !taken.contains(pos) && *pos != from)
+    else {
+        return Err(format!("No more occurrences of \"{}\"", word));
+    };
+    if state.multi_cursors.len() + 1 >= MAX_MATCH_CURSORS {
+        return Err(format!("At most {} cursors", MAX_MATCH_CURSORS));
+    }
+    state.clear_selection();
+    state.multi_cursors.push(from);
+    state.occurrence_word = Some((word, whole_word));
+    state.set_cursor_position(next.0, next.1, lines, visible_lines);
+    state.desired_cursor_col = state.cursor_col;
+    state.needs_redraw = true;
+    Ok(state.multi_cursors.len() + 1)
+}
+
 /// Move cursor to the specified position, adjusting viewport if needed
 fn move_to_position(
     state: &mut FileViewerState,
@@ -893,6 +1433,7 @@ fn move_to_position(
 
     // Use helper function to set cursor position with proper bounds checking and viewport adjustment
     state.set_cursor_position(target_line, target_col, lines, visible_lines);
+    state.apply_scroll_margin(lines, visible_lines);
 }
 
 /// Calculate the total number of search hits and determine the current hit index
@@ -901,7 +1442,7 @@ pub(crate) fn calculate_search_hits(
     lines: &[String],
     cursor_pos: Position,
     pattern: &str,
-    regex_mode: bool,
+    options: FindOptions,
     scope: Option<(Position, Position)>,
 ) -> (usize, usize) {
     let (cursor_line, cursor_col) = cursor_pos;
@@ -915,9 +1456,7 @@ pub(crate) fn calculate_search_hits(
 
     if pattern_is_multiline(pattern) {
         // Multi-line: join text, find all matches, map back to positions
-        let expanded = expand_newline_escapes(pattern);
-        let ml_pat = format!("(?i)(?m){}", expanded);
-        let Ok(regex) = Regex::new(&ml_pat) else {
+        let Ok(regex) = multiline_regex(pattern, options) else {
             return (0, 0);
         };
         let (joined, line_starts) = build_joined_text(lines, min_line, max_line);
@@ -925,6 +1464,12 @@ pub(crate) fn calculate_search_hits(
         let mut current_hit = 0;
         for m in regex.find_iter(&joined) {
             let (hit_line, hit_col) = byte_offset_to_position(m.start(), &line_starts, lines, min_line);
+            // A selection scope also cuts the first and last line at its columns
+            if let Some((scope_start, scope_end)) = scope
+                && ((hit_line, hit_col) < scope_start || (hit_line, hit_col) >= scope_end)
+            {
+                continue;
+            }
             total_hits += 1;
             if hit_line == cursor_line && hit_col == cursor_col {
                 current_hit = total_hits;
@@ -934,7 +1479,7 @@ pub(crate) fn calculate_search_hits(
     }
 
     // Compile pattern with the specified mode (single-line)
-    let Ok(regex) = pattern_to_regex(pattern, regex_mode) else {
+    let Ok(regex) = pattern_to_regex(pattern, options) else {
         return (0, 0);
     };
 
@@ -962,12 +1507,13 @@ pub(crate) fn calculate_search_hits(
 
 /// Update the search hit count in the state
 pub(crate) fn update_search_hit_count(state: &mut FileViewerState, lines: &[String]) {
-    if let Some(ref pattern) = state.last_search_pattern {
+    // Counting would scan the whole of a huge file on every jump
+    if let Some(pattern) = state.last_search_pattern.as_ref().filter(|_| !state.large_file) {
         let (current, total) = calculate_search_hits(
             lines,
             state.current_position(),
             pattern,
-            state.find_regex_mode,
+            state.find_options(),
             state.find_scope,
         );
         state.search_current_hit = current;
@@ -991,6 +1537,7 @@ pub(crate) fn update_search_hit_count(state: &mut FileViewerState, lines: &[Stri
 pub(crate) fn get_multiline_matches_per_line(
     lines: &[String],
     pattern: &str,
+    options: FindOptions,
     scope: Option<(Position, Position)>,
 ) -> Vec<Vec<(usize, usize)>> {
     let mut result: Vec<Vec<(usize, usize)>> = vec![Vec::new(); lines.len()];
@@ -1005,9 +1552,7 @@ pub(crate) fn get_multiline_matches_per_line(
         (0, lines.len().saturating_sub(1))
     };
 
-    let expanded = expand_newline_escapes(pattern);
-    let ml_pat = format!("(?i)(?m){}", expanded);
-    let Ok(regex) = Regex::new(&ml_pat) else {
+    let Ok(regex) = multiline_regex(pattern, options) else {
         return result;
     };
 
@@ -1254,19 +1799,18 @@ pub(crate) fn handle_replace_input(
     }
 }
 
-/// Replace the current occurrence and jump to next
+/// Replace the current occurrence and jump to next. Returns false when the
+/// cursor was not on a match, so nothing was replaced.
 pub(crate) fn replace_current_occurrence(
     state: &mut FileViewerState,
     lines: &mut Vec<String>,
     visible_lines: usize,
-) {
+) -> bool {
+    let mut replaced = false;
     if let Some(ref pattern) = state.last_search_pattern.clone() {
         if pattern_is_multiline(pattern) {
             // --- Multi-line replace current ---
-            let expanded = expand_newline_escapes(pattern);
-            // (?m) makes ^ and $ match line boundaries; (?s) makes . match newlines
-            let ml_pat = format!("(?i)(?m){}", expanded);
-            if let Ok(regex) = Regex::new(&ml_pat) {
+            if let Ok(regex) = multiline_regex(pattern, state.find_options()) {
                 let (min_line, max_line) = if let Some(((sl, _), (el, _))) = state.find_scope {
                     (sl, el)
                 } else {
@@ -1290,10 +1834,11 @@ pub(crate) fn replace_current_occurrence(
 
                 // Find a match that starts at the cursor byte offset
                 if let Some(m) = regex.find_iter(&joined).find(|m| m.start() == cursor_byte) {
-                    let replace_str = expand_newline_escapes(&state.replace_pattern);
+                    let replace_str = replacement_template(&state.replace_pattern);
                     let before = &joined[..m.start()];
                     let after = &joined[m.end()..];
-                    let replaced_segment = regex.replace(m.as_str(), replace_str.as_str()).to_string();
+                    let replaced_segment =
+                        replace_match(&regex, m.as_str(), &replace_str, state.replace_preserve_case);
                     let new_joined = format!("{}{}{}", before, replaced_segment, after);
 
                     // Snapshot the whole file before/after for single-step undo
@@ -1316,12 +1861,12 @@ pub(crate) fn replace_current_occurrence(
 
                     state.modified = true;
                     state.needs_redraw = true;
+                    replaced = true;
                 }
             }
         } else {
             // --- Single-line replace current ---
-            let pattern_with_flags = format!("(?i){}", pattern);
-            if let Ok(regex) = Regex::new(&pattern_with_flags) {
+            if let Ok(regex) = pattern_to_regex(pattern, state.find_options()) {
                 let (line, col) = state.current_position();
 
                 if line < lines.len() {
@@ -1353,7 +1898,7 @@ pub(crate) fn replace_current_occurrence(
                             .find(|m| m.start() >= b_from && m.start() < b_to && m.start() == cursor_stripped_byte);
 
                         if let Some(m) = match_at_cursor {
-                            let replace_str = expand_newline_escapes(&state.replace_pattern);
+                            let replace_str = replacement_template(&state.replace_pattern);
                             // Map stripped byte positions back to raw char positions
                             let sc_start = stripped[..m.start()].chars().count();
                             let sc_end   = stripped[..m.end()].chars().count();
@@ -1362,9 +1907,12 @@ pub(crate) fn replace_current_occurrence(
                             let raw_byte_start = char_to_byte(&line_text, raw_start);
                             let raw_byte_end   = char_to_byte(&line_text, raw_end);
 
-                            let replaced_segment = regex
-                                .replace(&stripped[m.start()..m.end()], replace_str.as_str())
-                                .to_string();
+                            let replaced_segment = replace_match(
+                                &regex,
+                                &stripped[m.start()..m.end()],
+                                &replace_str,
+                                state.replace_preserve_case,
+                            );
                             let before = &line_text[..raw_byte_start];
                             let after  = &line_text[raw_byte_end..];
 
@@ -1394,6 +1942,7 @@ pub(crate) fn replace_current_occurrence(
                             }
                             state.modified = true;
                             state.needs_redraw = true;
+                            replaced = true;
                         }
                     }
                 }
@@ -1410,6 +1959,68 @@ pub(crate) fn replace_current_occurrence(
             add_to_replace_history(state, p);
         }
     }
+    replaced
+}
+
+/// Start confirming replacements one match at a time, beginning with the
+/// match at or after the cursor. Each match is then answered with y/n/a/q.
+pub(crate) fn start_replace_confirm(
+    state: &mut FileViewerState,
+    lines: &[String],
+    visible_lines: usize,
+) {
+    update_search_hit_count(state, lines);
+    if state.search_hit_count == 0 {
+        state.status_message = Some("No matches to replace".to_string());
+        state.alert();
+        return;
+    }
+    if state.search_current_hit == 0 {
+        find_next_occurrence(state, lines, visible_lines);
+    }
+    state.clear_selection();
+    state.replace_active = false;
+    state.find_active = false;
+    state.replace_confirm = Some((state.search_hit_count, 0));
+    state.needs_redraw = true;
+}
+
+/// Answer for the match under the cursor while confirming replacements:
+/// y replaces it, n skips it, a replaces it and all that follow, q or Esc stops
+pub(crate) fn handle_replace_confirm_key(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    code: KeyCode,
+    visible_lines: usize,
+) {
+    let Some((left, mut replaced)) = state.replace_confirm else {
+        return;
+    };
+    let left = match code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => {
+            replaced += usize::from(replace_current_occurrence(state, lines, visible_lines));
+            left - 1
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') => {
+            find_next_occurrence(state, lines, visible_lines);
+            left - 1
+        }
+        KeyCode::Char('a') | KeyCode::Char('A') => {
+            for _ in 0..left {
+                replaced += usize::from(replace_current_occurrence(state, lines, visible_lines));
+            }
+            0
+        }
+        KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => 0,
+        _ => left,
+    };
+    if left == 0 {
+        state.replace_confirm = None;
+        state.status_message = Some(format!("Replaced {} occurrence(s)", replaced));
+    } else {
+        state.replace_confirm = Some((left, replaced));
+    }
+    state.needs_redraw = true;
 }
 
 /// Replace all occurrences and exit replace mode
@@ -1420,10 +2031,7 @@ pub(crate) fn replace_all_occurrences(
     if let Some(ref pattern) = state.last_search_pattern.clone() {
         if pattern_is_multiline(pattern) {
             // --- Multi-line replace all ---
-            let expanded = expand_newline_escapes(pattern);
-            // (?m) makes ^ and $ match line boundaries; (?s) makes . match newlines
-            let ml_pat = format!("(?i)(?m){}", expanded);
-            if let Ok(regex) = Regex::new(&ml_pat) {
+            if let Ok(regex) = multiline_regex(pattern, state.find_options()) {
                 let (min_line, max_line) = if let Some(((sl, _), (el, _))) = state.find_scope {
                     (sl, el)
                 } else {
@@ -1432,10 +2040,20 @@ pub(crate) fn replace_all_occurrences(
                 let max_line_clamped = max_line.min(lines.len().saturating_sub(1));
                 let (joined, _) = build_joined_text(lines, min_line, max_line_clamped);
 
-                let replace_str = expand_newline_escapes(&state.replace_pattern);
+                let replace_str = replacement_template(&state.replace_pattern);
                 let replaced_count = regex.find_iter(&joined).count();
                 if replaced_count > 0 {
-                    let new_joined = regex.replace_all(&joined, replace_str.as_str()).to_string();
+                    let new_joined = if state.replace_preserve_case {
+                        regex
+                            .replace_all(&joined, |caps: &regex::Captures| {
+                                let mut expanded = String::new();
+                                caps.expand(&replace_str, &mut expanded);
+                                match_case(&caps[0], &expanded)
+                            })
+                            .to_string()
+                    } else {
+                        regex.replace_all(&joined, replace_str.as_str()).to_string()
+                    };
                     let new_region: Vec<String> = new_joined.split('\n').map(|s| s.to_string()).collect();
 
                     // Snapshot the whole file before/after for single-step undo
@@ -1462,8 +2080,7 @@ pub(crate) fn replace_all_occurrences(
             }
         } else {
             // --- Single-line replace all ---
-            let pattern_with_flags = format!("(?i){}", pattern);
-            if let Ok(regex) = Regex::new(&pattern_with_flags) {
+            if let Ok(regex) = pattern_to_regex(pattern, state.find_options()) {
                 let mut replaced_count = 0;
 
                 let (min_line, max_line, scope) = if let Some(((scope_start_line, _), (scope_end_line, _))) = state.find_scope {
@@ -1480,12 +2097,13 @@ pub(crate) fn replace_all_occurrences(
                     let line_text = lines[actual_idx].clone();
 
                     let (scope_char_from, scope_char_to) = scope_char_range(line_idx, &line_text, scope);
-                    let replace_str = expand_newline_escapes(&state.replace_pattern);
+                    let replace_str = replacement_template(&state.replace_pattern);
 
                     let (new_line_text, count) = replace_in_stripped_line(
                         &line_text,
                         &regex,
                         &replace_str,
+                        state.replace_preserve_case,
                         scope_char_from,
                         scope_char_to,
                     );
@@ -1546,6 +2164,26 @@ pub(crate) fn replace_all_occurrences(
 mod tests {
     use super::*;
 
+    fn wildcard() -> FindOptions {
+        FindOptions { regex_mode: false, ..FindOptions::default() }
+    }
+
+    #[test]
+    fn pattern_to_regex_applies_case_and_whole_word() {
+        let options = FindOptions { case_sensitive: true, ..FindOptions::default() };
+        let regex = pattern_to_regex("Cat", options).unwrap();
+        assert_eq!(regex.find_iter("Cat cat Catalog").count(), 2);
+
+        let options = FindOptions { whole_word: true, ..FindOptions::default() };
+        let regex = pattern_to_regex("cat|dog", options).unwrap();
+        let found: Vec<&str> = regex.find_iter("Cat concat dogs dog").map(|m| m.as_str()).collect();
+        assert_eq!(found, vec!["Cat", "dog"]);
+
+        let options = FindOptions { regex_mode: false, whole_word: true, ..FindOptions::default() };
+        let regex = pattern_to_regex("c?t", options).unwrap();
+        assert_eq!(regex.find_iter("cat cut scat").count(), 2);
+    }
+
     #[test]
     fn find_simple_pattern() {
         let lines = vec![
@@ -1638,6 +2276,33 @@ mod tests {
         assert_eq!(state.cursor_col, 0);
     }
 
+    #[test]
+    fn search_without_match_rings_configured_bell() {
+        let lines = vec!["foo bar".to_string()];
+        let settings = crate::settings::Settings {
+            bell: "visual".to_string(),
+            ..Default::default()
+        };
+        let mut state = FileViewerState::new(80, crate::undo::UndoHistory::new(), &settings);
+        state.last_search_pattern = Some("bar".to_string());
+
+        find_next_occurrence(&mut state, &lines, 10);
+        assert!(!state.bell_flash, "a match is no failure");
+
+        state.last_search_pattern = Some("missing".to_string());
+        find_prev_occurrence(&mut state, &lines, 10);
+        assert!(state.bell_flash);
+
+        let quiet = crate::settings::Settings {
+            bell: "none".to_string(),
+            ..Default::default()
+        };
+        let mut state = FileViewerState::new(80, crate::undo::UndoHistory::new(), &quiet);
+        state.last_search_pattern = Some("missing".to_string());
+        find_next_occurrence(&mut state, &lines, 10);
+        assert!(!state.bell_flash);
+    }
+
     #[test]
     fn find_prev_two_press_wrap() {
         let lines = vec![
@@ -1742,7 +2407,7 @@ mod tests {
         let _ = state.undo_history.save(file_path);
 
         // Load in a new state
-        let loaded_history = crate::undo::UndoHistory::load(file_path).expect("load history");
+        let loaded_history = crate::undo::UndoHistory::load(file_path, false).expect("load history");
         assert_eq!(loaded_history.find_history.len(), 3);
         assert_eq!(loaded_history.find_history[0], "search3"); // Most recent first
         assert_eq!(loaded_history.find_history[1], "search2");
@@ -1781,6 +2446,35 @@ mod tests {
         assert_eq!(state.find_history[99], "search50"); // Oldest kept
     }
 
+    #[test]
+    fn find_history_reverse_search_and_down_restores_typed_text() {
+        let settings = crate::settings::Settings::default();
+        let undo_history = crate::undo::UndoHistory::new();
+        let mut state = FileViewerState::new(80, undo_history, &settings);
+        let lines = vec!["fn main() {}".to_string()];
+        for pattern in ["main_loop", "other", "main"] {
+            add_to_history(&mut state, pattern.to_string());
+        }
+        state.find_active = true;
+        state.find_pattern = "ma".to_string();
+        state.find_cursor_pos = 2;
+
+        search_find_history(&mut state, &lines, 10);
+        assert_eq!(state.find_pattern, "main");
+        search_find_history(&mut state, &lines, 10);
+        assert_eq!(state.find_pattern, "main_loop");
+        assert_eq!(state.find_history_index, Some(2));
+        search_find_history(&mut state, &lines, 10);
+        assert_eq!(state.find_pattern, "main_loop", "no older match keeps the entry");
+        assert!(state.status_message.is_some());
+
+        for _ in 0..3 {
+            handle_find_input(&mut state, &lines, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE), 10).unwrap();
+        }
+        assert_eq!(state.find_pattern, "ma");
+        assert_eq!(state.find_history_index, None);
+    }
+
     #[test]
     fn cursor_movement_clears_wrap_warning() {
         let _lines = vec!["hello".to_string(), "world".to_string()];
@@ -1985,31 +2679,42 @@ mod tests {
         ];
 
         // Test 1: Count all hits for "test"
-        let (current, total) = calculate_search_hits(&lines, (0, 0), "test", true, None);
+        let (current, total) = calculate_search_hits(&lines, (0, 0), "test", FindOptions::default(), None);
         assert_eq!(total, 5); // Should find 5 occurrences
         assert_eq!(current, 0); // Cursor not on a match at (0, 0)
 
         // Test 2: Cursor at first occurrence
-        let (current, total) = calculate_search_hits(&lines, (0, 10), "test", true, None);
+        let (current, total) = calculate_search_hits(&lines, (0, 10), "test", FindOptions::default(), None);
         assert_eq!(total, 5);
         assert_eq!(current, 1); // First hit
 
         // Test 3: Cursor at third occurrence
-        let (current, total) = calculate_search_hits(&lines, (2, 16), "test", true, None);
+        let (current, total) = calculate_search_hits(&lines, (2, 16), "test", FindOptions::default(), None);
         assert_eq!(total, 5);
         assert_eq!(current, 3); // Third hit
 
         // Test 4: With scope - only count hits in range
         let scope = Some(((1, 0), (3, 25)));
-        let (current, total) = calculate_search_hits(&lines, (2, 16), "test", true, scope);
+        let (current, total) = calculate_search_hits(&lines, (2, 16), "test", FindOptions::default(), scope);
         assert_eq!(total, 3); // Only 3 hits in lines 1-3
         assert_eq!(current, 2); // Second hit within scope
 
         // Test 5: Case insensitive
-        let (_current, total) = calculate_search_hits(&lines, (0, 0), "TEST", true, None);
+        let (_current, total) = calculate_search_hits(&lines, (0, 0), "TEST", FindOptions::default(), None);
         assert_eq!(total, 5); // Should find all "test" case-insensitively
     }
 
+    #[test]
+    fn calculate_search_hits_multiline_respects_scope_columns() {
+        let lines = vec!["a b".to_string(), "a b".to_string(), "a b".to_string()];
+        let (_, total) = calculate_search_hits(&lines, (0, 0), r"b\na", FindOptions::default(), None);
+        assert_eq!(total, 2);
+        // The scope starts after the first "b", so only the second match counts
+        let scope = Some(((0, 3), (2, 3)));
+        let (current, total) = calculate_search_hits(&lines, (1, 2), r"b\na", FindOptions::default(), scope);
+        assert_eq!((current, total), (1, 1));
+    }
+
     #[test]
     fn test_calculate_search_hits_no_matches() {
         let lines = vec![
@@ -2017,7 +2722,7 @@ mod tests {
             "The word test appears here".to_string(),
         ];
 
-        let (current, total) = calculate_search_hits(&lines, (0, 0), "nomatch", true, None);
+        let (current, total) = calculate_search_hits(&lines, (0, 0), "nomatch", FindOptions::default(), None);
         assert_eq!(total, 0);
         assert_eq!(current, 0);
     }
@@ -2238,7 +2943,7 @@ mod tests {
     #[test]
     fn find_wildcard_to_regex_star() {
         // Test that * matches any number of characters
-        let regex = pattern_to_regex("hello*world", false).unwrap();
+        let regex = pattern_to_regex("hello*world", wildcard()).unwrap();
 
         // All should match
         assert!(regex.is_match("hello world"));
@@ -2249,7 +2954,7 @@ mod tests {
     #[test]
     fn test_wildcard_to_regex_question() {
         // Test that ? matches any single character
-        let regex = pattern_to_regex("ca?", false).unwrap();
+        let regex = pattern_to_regex("ca?", wildcard()).unwrap();
 
         assert!(regex.is_match("cat"));
         assert!(regex.is_match("car"));
@@ -2261,7 +2966,7 @@ mod tests {
     fn test_wildcard_escapes_regex_chars() {
         // Test that regex special characters are properly escaped
         // Pattern with literal dot (not regex "any character")
-        let regex = pattern_to_regex("test.txt", false).unwrap();
+        let regex = pattern_to_regex("test.txt", wildcard()).unwrap();
 
         assert!(regex.is_match("test.txt"));
         assert!(!regex.is_match("testXtxt")); // . is literal, not wildcard
@@ -2272,7 +2977,7 @@ mod tests {
     fn test_wildcard_combined_patterns() {
         // Test combination of * and ?
         // Pattern: foo*?bar means: foo + (zero or more chars) + (exactly one char) + bar
-        let regex = pattern_to_regex("foo*?bar", false).unwrap();
+        let regex = pattern_to_regex("foo*?bar", wildcard()).unwrap();
 
         assert!(!regex.is_match("foobar")); // No character to satisfy the ?
         assert!(regex.is_match("foo123bar")); // 12 matches *, 3 matches ?
@@ -2281,7 +2986,7 @@ mod tests {
         assert!(regex.is_match("foo12bar")); // 1 matches *, 2 matches ?
 
         // Test simpler pattern: f*o matches f + (zero or more) + o
-        let regex2 = pattern_to_regex("f*o", false).unwrap();
+        let regex2 = pattern_to_regex("f*o", wildcard()).unwrap();
         assert!(regex2.is_match("fo"));
         assert!(regex2.is_match("foo"));
         assert!(regex2.is_match("f123o"));
@@ -2290,7 +2995,7 @@ mod tests {
     #[test]
     fn test_wildcard_case_insensitive() {
         // Test that wildcard patterns are case-insensitive
-        let regex = pattern_to_regex("hello", false).unwrap();
+        let regex = pattern_to_regex("hello", wildcard()).unwrap();
 
         assert!(regex.is_match("HELLO"));
         assert!(regex.is_match("hello"));
@@ -2301,7 +3006,7 @@ mod tests {
     fn test_wildcard_with_brackets() {
         // Test that brackets are escaped properly
         // Pattern with literal brackets (not regex character class)
-        let regex = pattern_to_regex("test[abc]", false).unwrap();
+        let regex = pattern_to_regex("test[abc]", wildcard()).unwrap();
 
         assert!(regex.is_match("test[abc]")); // matches literal
         assert!(!regex.is_match("testa")); // doesn't match
@@ -2313,14 +3018,14 @@ mod tests {
         // Test that regex mode and wildcard mode behave differently
 
         // Regex mode: [abc] is a character class
-        let regex_mode = pattern_to_regex("test[abc]", true).unwrap();
+        let regex_mode = pattern_to_regex("test[abc]", FindOptions::default()).unwrap();
         assert!(regex_mode.is_match("testa")); // matches character class
         assert!(regex_mode.is_match("testb")); // matches character class
         assert!(regex_mode.is_match("testc")); // matches character class
         assert!(!regex_mode.is_match("test[abc]")); // doesn't match literal
 
         // Wildcard mode: [abc] is literal
-        let wildcard_mode = pattern_to_regex("test[abc]", false).unwrap();
+        let wildcard_mode = pattern_to_regex("test[abc]", wildcard()).unwrap();
         assert!(!wildcard_mode.is_match("testa")); // doesn't match
         assert!(!wildcard_mode.is_match("testb")); // doesn't match
         assert!(!wildcard_mode.is_match("testc")); // doesn't match
@@ -2328,23 +3033,84 @@ mod tests {
 
         // Test . character
         // Regex mode: . matches any character
-        let regex_dot = pattern_to_regex("test.txt", true).unwrap();
+        let regex_dot = pattern_to_regex("test.txt", FindOptions::default()).unwrap();
         assert!(regex_dot.is_match("test.txt")); // matches
         assert!(regex_dot.is_match("testXtxt")); // . matches X
 
         // Wildcard mode: . is literal
-        let wildcard_dot = pattern_to_regex("test.txt", false).unwrap();
+        let wildcard_dot = pattern_to_regex("test.txt", wildcard()).unwrap();
         assert!(wildcard_dot.is_match("test.txt")); // matches
         assert!(!wildcard_dot.is_match("testXtxt")); // doesn't match
 
         // Test * character
         // Regex mode: * is quantifier (needs something before it)
         // Wildcard mode: * is "any characters"
-        let wildcard_star = pattern_to_regex("test*file", false).unwrap();
+        let wildcard_star = pattern_to_regex("test*file", wildcard()).unwrap();
         assert!(wildcard_star.is_match("testfile")); // zero chars
         assert!(wildcard_star.is_match("test123file")); // multiple chars
     }
 
+    #[test]
+    fn incremental_search_jumps_while_typing_and_esc_goes_back() {
+        let lines: Vec<String> = (0..30).map(|i| if i % 10 == 5 { format!("needle {}", i) } else { format!("hay {}", i) }).collect();
+        let settings = crate::settings::Settings::default();
+        let mut state = FileViewerState::new(80, crate::undo::UndoHistory::new(), &settings);
+        state.cursor_line = 7;
+        state.find_active = true;
+        let type_key = |state: &mut FileViewerState, code| {
+            handle_find_input(state, &lines, KeyEvent::new(code, KeyModifiers::NONE), 10).unwrap();
+        };
+
+        // The first match after the cursor, not the one above it
+        type_key(&mut state, KeyCode::Char('n'));
+        assert_eq!(state.current_position(), (15, 0));
+        // No match: back where find started
+        type_key(&mut state, KeyCode::Char('x'));
+        assert_eq!(state.current_position(), (7, 0));
+        type_key(&mut state, KeyCode::Backspace);
+        type_key(&mut state, KeyCode::Char('e'));
+        assert_eq!(state.current_position(), (15, 0));
+
+        type_key(&mut state, KeyCode::Esc);
+        assert_eq!((state.top_line, state.cursor_line, state.cursor_col), (0, 7, 0));
+
+        // Enter keeps the match
+        state.find_active = true;
+        state.find_cursor_pos = 0;
+        type_key(&mut state, KeyCode::Char('2'));
+        type_key(&mut state, KeyCode::Char('5'));
+        assert_eq!(state.current_position(), (25, 7));
+        type_key(&mut state, KeyCode::Enter);
+        assert_eq!(state.current_position(), (25, 7));
+        assert_eq!(state.saved_find_position, None);
+    }
+
+    #[test]
+    fn word_highlight_toggles_and_clears_after_edit() {
+        let lines = vec!["let val = Val + val_2;".to_string(), "val".to_string()];
+        let settings = crate::settings::Settings::default();
+        let mut state = FileViewerState::new(80, crate::undo::UndoHistory::new(), &settings);
+        state.cursor_col = 5;
+        toggle_word_highlight(&mut state, &lines).unwrap();
+        assert_eq!(state.last_search_pattern.as_deref(), Some("val"));
+        // Whole words with matching case only
+        assert_eq!(state.search_hit_count, 2);
+        assert!(!state.find_active);
+
+        toggle_word_highlight(&mut state, &lines).unwrap();
+        assert_eq!(state.last_search_pattern, None);
+
+        toggle_word_highlight(&mut state, &lines).unwrap();
+        state.clear_word_highlight_if_edited();
+        assert!(state.last_search_pattern.is_some());
+        state.undo_history.push(crate::undo::Edit::InsertChar { line: 1, col: 0, ch: 'x' });
+        state.clear_word_highlight_if_edited();
+        assert_eq!(state.last_search_pattern, None);
+
+        state.cursor_col = 8;
+        assert!(toggle_word_highlight(&mut state, &lines).is_err());
+    }
+
     /// Helper: build a minimal FileViewerState with a given search pattern and replace pattern,
     /// with cursor placed at the given position.
     fn make_state_for_replace(
@@ -2402,6 +3168,180 @@ mod tests {
         assert_eq!(lines[0], "test1 and Hello2");
     }
 
+    #[test]
+    fn replace_confirm_answers_each_match() {
+        let mut lines = vec!["a1 a2".to_string(), "a3 a4".to_string()];
+        let mut state = make_state_for_replace("a", "b", 0, 1);
+        start_replace_confirm(&mut state, &lines, 24);
+        assert_eq!(state.current_position(), (0, 3));
+        assert_eq!(state.replace_confirm, Some((4, 0)));
+
+        handle_replace_confirm_key(&mut state, &mut lines, KeyCode::Char('y'), 24);
+        handle_replace_confirm_key(&mut state, &mut lines, KeyCode::Char('n'), 24);
+        assert_eq!(lines, vec!["a1 b2", "a3 a4"]);
+        assert_eq!(state.current_position(), (1, 3));
+        // Keys other than y/n/a/q are ignored
+        handle_replace_confirm_key(&mut state, &mut lines, KeyCode::Char('x'), 24);
+        assert_eq!(state.replace_confirm, Some((2, 1)));
+
+        handle_replace_confirm_key(&mut state, &mut lines, KeyCode::Char('a'), 24);
+        assert_eq!(lines, vec!["b1 b2", "a3 b4"]);
+        assert_eq!(state.replace_confirm, None);
+        assert_eq!(state.status_message.as_deref(), Some("Replaced 3 occurrence(s)"));
+    }
+
+    #[test]
+    fn replace_confirm_quits_without_replacing() {
+        let mut lines = vec!["a a".to_string()];
+        let mut state = make_state_for_replace("a", "b", 0, 0);
+        start_replace_confirm(&mut state, &lines, 24);
+        assert_eq!(state.current_position(), (0, 0));
+        handle_replace_confirm_key(&mut state, &mut lines, KeyCode::Esc, 24);
+        assert_eq!(lines, vec!["a a"]);
+        assert_eq!(state.replace_confirm, None);
+    }
+
+    #[test]
+    fn match_case_follows_matched_text() {
+        assert_eq!(match_case("FOO", "bar"), "BAR");
+        assert_eq!(match_case("Foo", "bar"), "Bar");
+        assert_eq!(match_case("foo", "Bar"), "bar");
+        assert_eq!(match_case("fOo", "Bar"), "Bar");
+        assert_eq!(match_case("123", "Bar"), "Bar");
+    }
+
+    #[test]
+    fn replace_all_preserves_case_of_each_match() {
+        let mut lines = vec!["foo Foo FOO".to_string()];
+        let mut state = make_state_for_replace("foo", "bar", 0, 0);
+        state.replace_preserve_case = true;
+        replace_all_occurrences(&mut state, &mut lines);
+        assert_eq!(lines[0], "bar Bar BAR");
+    }
+
+    #[test]
+    fn replace_current_preserves_case() {
+        let mut lines = vec!["say Hello".to_string()];
+        let mut state = make_state_for_replace("hello", "goodbye", 0, 4);
+        state.replace_preserve_case = true;
+        replace_current_occurrence(&mut state, &mut lines, 24);
+        assert_eq!(lines[0], "say Goodbye");
+    }
+
+    #[test]
+    fn place_cursors_at_matches_respects_selection_and_cap() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let settings = Box::leak(Box::new(crate::settings::Settings::load().unwrap()));
+        let mut state = FileViewerState::new(80, crate::undo::UndoHistory::new(), settings);
+        let lines: Vec<String> = vec!["let a = 1;".into(), "let b = 2;".into(), "let c = 3;".into()];
+
+        assert_eq!(place_cursors_at_matches(&mut state, &lines, r"\d", 10), Ok((3, false)));
+        assert_eq!(state.current_position(), (0, 8));
+        assert_eq!(state.multi_cursors, vec![(1, 8), (2, 8)]);
+
+        state.clear_multi_cursors();
+        state.selection_start = Some((1, 0));
+        state.selection_end = Some((2, 3));
+        assert_eq!(place_cursors_at_matches(&mut state, &lines, "let", 10), Ok((2, false)));
+        assert_eq!(state.current_position(), (1, 0));
+        assert_eq!(state.multi_cursors, vec![(2, 0)]);
+        assert!(!state.has_selection());
+
+        assert_eq!(place_cursors_at_matches(&mut state, &lines, "xyz", 10), Err("No matches".to_string()));
+        assert!(place_cursors_at_matches(&mut state, &lines, "(", 10).is_err());
+
+        let many = vec!["x".repeat(MAX_MATCH_CURSORS + 5)];
+        assert_eq!(place_cursors_at_matches(&mut state, &many, "x", 10), Ok((MAX_MATCH_CURSORS, true)));
+    }
+
+    #[test]
+    fn add_next_occurrence_selects_word_then_adds_cursors() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let settings = Box::leak(Box::new(crate::settings::Settings::load().unwrap()));
+        let mut state = FileViewerState::new(80, crate::undo::UndoHistory::new(), settings);
+        let lines: Vec<String> = vec!["foo = foobar(foo);".into(), "x".into(), "bar(foo)".into()];
+        state.cursor_col = 1;
+
+        assert_eq!(add_next_occurrence(&mut state, &lines, 10), Ok(1));
+        assert_eq!(state.selection_range(), Some(((0, 0), (0, 3))));
+
+        // Whole words only: `foobar` is skipped
+        assert_eq!(add_next_occurrence(&mut state, &lines, 10), Ok(2));
+        assert!(!state.has_selection());
+        assert_eq!(state.current_position(), (0, 16));
+        assert_eq!(state.multi_cursors, vec![(0, 3)]);
+
+        assert_eq!(add_next_occurrence(&mut state, &lines, 10), Ok(3));
+        assert_eq!(state.current_position(), (2, 7));
+        assert!(add_next_occurrence(&mut state, &lines, 10).is_err());
+
+        // Selected text matches anywhere, wrapping around the file
+        state.clear_multi_cursors();
+        state.selection_start = Some((2, 0));
+        state.selection_end = Some((2, 3));
+        state.set_cursor_position(2, 3, &lines, 10);
+        assert_eq!(add_next_occurrence(&mut state, &lines, 10), Ok(2));
+        assert_eq!(state.current_position(), (0, 12));
+    }
+
+    #[test]
+    fn todo_markers_only_count_inside_comments() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let markers = crate::settings::Settings::default().todo_markers;
+        crate::syntax::set_current_file("test.rs");
+        assert_eq!(todo_marker_ranges("let x = 1; // TODO: fix", &markers), vec![(14, 18)]);
+        assert!(todo_marker_ranges("let TODO = 1;", &markers).is_empty());
+        assert!(todo_marker_ranges("// TODOS are not markers", &markers).is_empty());
+
+        crate::syntax::set_current_file("notes.txt");
+        assert_eq!(todo_marker_ranges("FIXME later", &markers), vec![(0, 5)]);
+        assert!(todo_marker_ranges("FIXME later", &[]).is_empty());
+    }
+
+    #[test]
+    fn goto_todo_marker_wraps_in_both_directions() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        crate::syntax::set_current_file("test.rs");
+        let lines: Vec<String> = ["// TODO one", "fn a() {}", "// XXX two", "fn b() {}"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut state = make_state_for_replace("", "", 1, 0);
+
+        assert!(goto_todo_marker(&mut state, &lines, 10, true));
+        assert_eq!(state.current_position(), (2, 3));
+        assert!(goto_todo_marker(&mut state, &lines, 10, true));
+        assert_eq!(state.current_position(), (0, 3));
+        assert!(goto_todo_marker(&mut state, &lines, 10, false));
+        assert_eq!(state.current_position(), (2, 3));
+    }
+
+    #[test]
+    fn goto_long_line_measures_tabs_and_wraps() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        assert_eq!(over_limit_column("short", 10, 4), None);
+        assert_eq!(over_limit_column("\t\tabc", 10, 4), Some(4));
+        assert_eq!(over_limit_column("0123456789ab", 10, 4), Some(10));
+
+        let lines: Vec<String> = ["x".repeat(12), "fits".into(), "\t\t\tlong".into(), "end".into()]
+            .into_iter()
+            .collect();
+        let mut settings = crate::settings::Settings::default();
+        settings.appearance.line_length_limit = 10;
+        let mut state = FileViewerState::new(80, crate::undo::UndoHistory::new(), &settings);
+        state.cursor_line = 1;
+
+        assert!(goto_long_line(&mut state, &lines, 10));
+        assert_eq!(state.current_position(), (2, 2));
+        assert!(goto_long_line(&mut state, &lines, 10));
+        assert_eq!(state.current_position(), (0, 10));
+
+        settings.appearance.line_length_limit = 20;
+        let mut state = FileViewerState::new(80, crate::undo::UndoHistory::new(), &settings);
+        assert!(!goto_long_line(&mut state, &lines, 10));
+        assert_eq!(state.current_position(), (0, 0));
+    }
+
     // ---- Multi-line (\n) tests ----
 
     #[test]
@@ -2420,6 +3360,24 @@ mod tests {
         assert_eq!(result, "no newline here");
     }
 
+    #[test]
+    fn replacement_template_converts_escapes() {
+        assert_eq!(replacement_template(r"\1-\2"), "${1}-${2}");
+        assert_eq!(replacement_template(r"\1a"), "${1}a");
+        assert_eq!(replacement_template(r"a\nb\tc"), "a\nb\tc");
+        assert_eq!(replacement_template(r"\\n \$1"), "\\n $$1");
+        assert_eq!(replacement_template("$1 ${name} $$"), "$1 ${name} $$");
+        assert_eq!(replacement_template(r"C:\dir\"), r"C:\dir\");
+    }
+
+    #[test]
+    fn replace_all_expands_backslash_groups_and_tabs() {
+        let mut lines = vec!["key=value".to_string()];
+        let mut state = make_state_for_replace(r"(\w+)=(?P<v>\w+)", r"\2\t${v}\1", 0, 0);
+        replace_all_occurrences(&mut state, &mut lines);
+        assert_eq!(lines[0], "value\tvaluekey");
+    }
+
     #[test]
     fn build_joined_text_basic() {
         let lines = vec!["hello".to_string(), "world".to_string(), "foo".to_string()];
@@ -0,0 +1,168 @@
+//! Follow mode (`--follow`): a read-only view that picks up data appended to
+//! the file on disk and keeps the end of the file in view, like `tail -f`.
+
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::encoding::FileEncoding;
+
+/// Read position in a followed file
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FollowState {
+    /// Bytes of the file already shown in the buffer
+    offset: u64,
+    /// Whether the shown text ended with a line break, so the next data starts a new line
+    ends_with_newline: bool,
+    /// Set while the user has scrolled away from the end; no auto-scroll then
+    pub(crate) paused: bool,
+}
+
+/// What a poll found on disk
+#[derive(Debug, PartialEq)]
+pub(crate) enum FollowUpdate {
+    Unchanged,
+    /// New text was appended to the buffer
+    Appended,
+    /// The file shrank (truncated or rotated) and was read again from the start
+    Reloaded,
+}
+
+impl FollowState {
+    /// Start following after the `len` bytes of `content` already loaded
+    pub(crate) fn new(len: u64, content: &str) -> Self {
+        Self {
+            offset: len,
+            ends_with_newline: content.ends_with('\n'),
+            paused: false,
+        }
+    }
+
+    /// Read whatever was appended to `path` since the last poll into `lines`
+    pub(crate) fn poll(
+        &mut self,
+        path: &str,
+        encoding: FileEncoding,
+        lines: &mut Vec<String>,
+    ) -> io::Result<FollowUpdate> {
+        let len = fs::metadata(path)?.len();
+        if len == self.offset {
+            return Ok(FollowUpdate::Unchanged);
+        }
+        if len < self.offset {
+            let text = encoding.decode_as(&fs::read(path)?);
+            *lines = text.lines().map(String::from).collect();
+            if lines.is_empty() {
+                lines.push(String::new());
+            }
+            *self = Self::new(text.len() as u64, &text);
+            self.offset = len;
+            return Ok(FollowUpdate::Reloaded);
+        }
+
+        let mut file = fs::File::open(path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut bytes)?;
+
+        // A multi-byte character cut off by the writer is picked up on the next poll
+        let complete = complete_prefix_len(&bytes, encoding);
+        if complete == 0 {
+            return Ok(FollowUpdate::Unchanged);
+        }
+        self.offset += complete as u64;
+        let text = encoding.decode_as(&bytes[..complete]);
+        self.ends_with_newline = append_text(lines, &text, self.ends_with_newline);
+        Ok(FollowUpdate::Appended)
+    }
+}
+
+/// Length of `bytes` without a trailing incomplete UTF-8 sequence or UTF-16
+/// code unit or surrogate pair
+fn complete_prefix_len(bytes: &[u8], encoding: FileEncoding) -> usize {
+    match encoding {
+        FileEncoding::Utf8 | FileEncoding::Utf8Bom => match std::str::from_utf8(bytes) {
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            _ => bytes.len(),
+        },
+        FileEncoding::Latin1 | FileEncoding::Windows1252 => bytes.len(),
+        FileEncoding::Utf16Le | FileEncoding::Utf16Be => {
+            let even = bytes.len() & !1;
+            let last = match (even, encoding) {
+                (0, _) => return 0,
+                (_, FileEncoding::Utf16Le) => u16::from_le_bytes([bytes[even - 2], bytes[even - 1]]),
+                _ => u16::from_be_bytes([bytes[even - 2], bytes[even - 1]]),
+            };
+            // A high surrogate waits for the low one that completes it
+            if (0xD800..0xDC00).contains(&last) { even - 2 } else { even }
+        }
+    }
+}
+
+/// Append `text` to the buffer, continuing the last line unless it was terminated.
+/// Returns whether the buffer now ends with a line break.
+fn append_text(lines: &mut Vec<String>, text: &str, ends_with_newline: bool) -> bool {
+    let parts: Vec<&str> = text.split('\n').collect();
+    let last = parts.len() - 1;
+    for (i, part) in parts.iter().enumerate() {
+        let part = if i < last { part.strip_suffix('\r').unwrap_or(part) } else { part };
+        if i == last && part.is_empty() {
+            break;
+        }
+        match lines.last_mut() {
+            Some(line) if i == 0 && !ends_with_newline => line.push_str(part),
+            _ => lines.push(part.to_string()),
+        }
+    }
+    text.ends_with('\n') || (text.is_empty() && ends_with_newline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_continues_unterminated_last_line() {
+        let mut lines = vec!["first".to_string(), "sec".to_string()];
+        assert!(append_text(&mut lines, "ond\r\nthird\n", false));
+        assert_eq!(lines, vec!["first", "second", "third"]);
+
+        assert!(!append_text(&mut lines, "fourth", true));
+        assert_eq!(lines, vec!["first", "second", "third", "fourth"]);
+
+        // An empty file loads as one empty line, which the first data fills
+        let mut lines = vec![String::new()];
+        append_text(&mut lines, "a\nb", false);
+        assert_eq!(lines, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn poll_reads_only_appended_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        let path_str = path.to_str().unwrap();
+        fs::write(&path, "one\n").unwrap();
+        let mut lines = vec!["one".to_string()];
+        let mut follow = FollowState::new(4, "one\n");
+        let poll = |follow: &mut FollowState, lines: &mut Vec<String>| {
+            follow.poll(path_str, FileEncoding::Utf8, lines).unwrap()
+        };
+
+        assert_eq!(poll(&mut follow, &mut lines), FollowUpdate::Unchanged);
+
+        // "ä" split across two writes shows up once it is complete
+        fs::write(&path, b"one\ntwo \xC3").unwrap();
+        assert_eq!(poll(&mut follow, &mut lines), FollowUpdate::Appended);
+        assert_eq!(lines, vec!["one", "two "]);
+        fs::write(&path, "one\ntwo ä\n").unwrap();
+        assert_eq!(poll(&mut follow, &mut lines), FollowUpdate::Appended);
+        assert_eq!(lines, vec!["one", "two ä"]);
+
+        // Truncation (log rotation) starts over
+        fs::write(&path, "new\n").unwrap();
+        assert_eq!(poll(&mut follow, &mut lines), FollowUpdate::Reloaded);
+        assert_eq!(lines, vec!["new"]);
+        fs::write(&path, "new\nmore\n").unwrap();
+        assert_eq!(poll(&mut follow, &mut lines), FollowUpdate::Appended);
+        assert_eq!(lines, vec!["new", "more"]);
+    }
+}
@@ -0,0 +1,234 @@
+//! Fuzzy file finder: the files of the project below the working directory
+//! (without those ignored by git), ranked by how well their paths match what
+//! is typed, with a preview of the highlighted file under the list.
+
+use crossterm::{
+    cursor, event, execute,
+    event::{KeyCode, KeyModifiers},
+    style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal,
+};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::editor_state::FileViewerState;
+use crate::settings::Settings;
+
+/// Most files indexed; larger trees are cut off
+const MAX_FILES: usize = 50_000;
+/// Most ranked files listed
+const MAX_LIST_ROWS: usize = 10;
+/// Bytes of the highlighted file read for the preview
+const PREVIEW_BYTES: usize = 64 * 1024;
+
+/// Files of the project at `root`, relative to it and sorted: what git does not
+/// ignore inside a repository, otherwise the files find in files looks at
+pub(crate) fn project_files(root: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match crate::git::project_files(root) {
+        // Deleted files stay listed by git until the deletion is staged
+        Some(files) => files.into_iter().filter(|f| root.join(f).is_file()).collect(),
+        None => {
+            let mut files = Vec::new();
+            crate::project_find::collect_files(root, &mut files);
+            files
+                .into_iter()
+                .filter_map(|f| f.strip_prefix(root).ok().map(Path::to_path_buf))
+                .collect()
+        }
+    };
+    files.sort();
+    files.dedup();
+    files.truncate(MAX_FILES);
+    files
+}
+
+/// Score of `path` for `query`: the path match, raised when the file name
+/// alone matches as well; None when the path does not match
+pub(crate) fn path_score(query: &str, path: &str) -> Option<i64> {
+    let score = crate::outline::fuzzy_score(query, path)?;
+    let name = path.rsplit('/').next().unwrap_or(path);
+    Some(match crate::outline::fuzzy_score(query, name) {
+        Some(name_score) => score.max(name_score) + 10,
+        None => score,
+    })
+}
+
+/// Indices of the `paths` matching `query`, best first (shorter paths, then
+/// path order, for ties)
+pub(crate) fn rank_paths(paths: &[String], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = paths
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| path_score(query, p).map(|score| (score, i)))
+        .collect();
+    scored.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(paths[a.1].len().cmp(&paths[b.1].len()))
+            .then(a.1.cmp(&b.1))
+    });
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// First lines of the file at `path` for the preview; a note for binary or
+/// unreadable files
+fn preview_lines(path: &Path, max_lines: usize) -> Vec<String> {
+    let mut bytes = Vec::new();
+    let read = std::fs::File::open(path)
+        .and_then(|file| std::io::Read::read_to_end(&mut std::io::Read::take(file, PREVIEW_BYTES as u64), &mut bytes));
+    if let Err(e) = read {
+        return vec![format!("({})", e)];
+    }
+    if bytes.iter().take(8192).any(|&b| b == 0) {
+        return vec!["(binary file)".to_string()];
+    }
+    crate::encoding::decode(&bytes).0.lines().take(max_lines).map(String::from).collect()
+}
+
+/// Let the user pick a file of the project below the working directory and
+/// request it to be opened. Returns true if a file was chosen.
+pub(crate) fn run_fuzzy_finder(
+    state: &mut FileViewerState,
+    filename: &str,
+    visible_lines: usize,
+    settings: &Settings,
+) -> Result<bool, std::io::Error> {
+    let root = std::env::current_dir()?;
+    let files = project_files(&root);
+    if files.is_empty() {
+        state.status_message = Some("No files found".to_string());
+        state.alert();
+        state.needs_footer_redraw = true;
+        return Ok(false);
+    }
+    let labels: Vec<String> = files.iter().map(|f| f.to_string_lossy().replace('\\', "/")).collect();
+
+    let mut query = String::new();
+    let mut selected = 0usize;
+    let mut scroll = 0usize;
+    let mut stdout = crate::no_color::stdout();
+    let chosen = loop {
+        let ranked = rank_paths(&labels, &query);
+        selected = selected.min(ranked.len().saturating_sub(1));
+        let rows = MAX_LIST_ROWS.min(visible_lines.saturating_sub(2)).max(1);
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + rows {
+            scroll = selected + 1 - rows;
+        }
+        let items: Vec<String> = ranked.iter().map(|&i| format!(" {}", labels[i])).collect();
+        let title = format!(" Open ({} files): {}", files.len(), query);
+        crate::outline::render_list_overlay(&mut stdout, state, &title, &items, selected, scroll, rows)?;
+        let preview_rows = visible_lines.saturating_sub(rows + 1);
+        if let Some(&i) = ranked.get(selected)
+            && preview_rows > 1
+        {
+            render_preview(&mut stdout, state, &labels[i], &root.join(&files[i]), 2 + rows as u16, preview_rows)?;
+        }
+
+        if let event::Event::Key(key) = event::read()? {
+            let key = crate::event_handlers::normalize_key_event(key, settings);
+            match key.code {
+                KeyCode::Esc => break None,
+                KeyCode::Enter => break ranked.get(selected).map(|&i| root.join(&files[i])),
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down => selected += 1,
+                KeyCode::PageUp => selected = selected.saturating_sub(rows),
+                KeyCode::PageDown => selected += rows,
+                KeyCode::Backspace => {
+                    query.pop();
+                    selected = 0;
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    query.push(c);
+                    selected = 0;
+                }
+                _ => {}
+            }
+        }
+    };
+
+    state.needs_redraw = true;
+    let Some(path) = chosen else {
+        return Ok(false);
+    };
+    if !crate::project_find::same_file(&path, Path::new(filename)) {
+        state.open_file_requested = Some(path.to_string_lossy().to_string());
+    }
+    Ok(true)
+}
+
+/// Draw the preview of `path` below the list: a title row with its `label`,
+/// then up to `rows - 1` of its first lines, starting at screen row `top`
+fn render_preview(
+    stdout: &mut impl Write,
+    state: &FileViewerState,
+    label: &str,
+    path: &Path,
+    top: u16,
+    rows: usize,
+) -> Result<(), std::io::Error> {
+    let (x, width) = crate::outline::overlay_geometry(terminal::size()?.0);
+    let bg = state.effective_theme_bg();
+    let tab_width = state.settings.tab_width;
+    let fit = |text: &str| -> String {
+        let mut text = crate::rendering::truncate_to_width(&crate::rendering::expand_tabs(text, tab_width), width);
+        let pad = width.saturating_sub(crate::coordinates::visual_width(&text, tab_width));
+        text.push_str(&" ".repeat(pad));
+        text
+    };
+
+    let title = format!("\u{2500} {} ", label);
+    let title = format!("{}{}", title, "\u{2500}".repeat(width.saturating_sub(crate::coordinates::visual_width(&title, tab_width))));
+    execute!(stdout, cursor::MoveTo(x, top), SetBackgroundColor(bg), SetForegroundColor(Color::DarkGrey))?;
+    write!(stdout, "{}", fit(&title))?;
+    let content = preview_lines(path, rows - 1);
+    execute!(stdout, SetForegroundColor(Color::Grey))?;
+    for row in 1..rows {
+        execute!(stdout, cursor::MoveTo(x, top + row as u16))?;
+        write!(stdout, "{}", fit(content.get(row - 1).map_or("", |l| l.as_str())))?;
+    }
+    execute!(stdout, ResetColor)?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranking_prefers_file_name_matches() {
+        let paths: Vec<String> = ["src/ui.rs", "src/settings.rs", "defaults/settings.toml", "docs/setup/index.md"]
+            .map(String::from)
+            .to_vec();
+        let names = |q: &str| -> Vec<&str> { rank_paths(&paths, q).iter().map(|&i| paths[i].as_str()).collect() };
+        assert_eq!(names("").len(), 4);
+        assert_eq!(names("settings"), vec!["src/settings.rs", "defaults/settings.toml"]);
+        assert_eq!(names("setrs")[0], "src/settings.rs");
+        assert_eq!(names("ui")[0], "src/ui.rs");
+        assert!(names("xyz").is_empty());
+    }
+
+    #[test]
+    fn project_files_leave_out_ignored_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("build")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(root.join("build/out.o"), "x").unwrap();
+        std::fs::write(root.join(".gitignore"), "build/\n").unwrap();
+
+        // Without a repository the hidden .gitignore is skipped but build/ is not
+        assert_eq!(project_files(root), vec![PathBuf::from("build/out.o"), PathBuf::from("src/main.rs")]);
+
+        let git_ok = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(root)
+            .status()
+            .is_ok_and(|s| s.success());
+        if git_ok {
+            assert_eq!(project_files(root), vec![PathBuf::from(".gitignore"), PathBuf::from("src/main.rs")]);
+        }
+        assert_eq!(preview_lines(&root.join("src/main.rs"), 5), vec!["fn main() {}".to_string()]);
+    }
+}
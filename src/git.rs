@@ -0,0 +1,274 @@
+//! Git integration: the current branch shown in the header (`git_branch`), the
+//! committed version of a file that the gutter markers compare against
+//! (`git_gutter`) and blame. Shells out to `git`; files outside a repository,
+//! untracked files or a missing `git` simply show none of it.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Columns the blame annotation adds in front of the line numbers
+pub(crate) const BLAME_GUTTER_WIDTH: u8 = 20;
+
+/// Last commit that touched a line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BlameLine {
+    /// Abbreviated commit hash; all zeros for lines not committed yet
+    pub(crate) hash: String,
+    pub(crate) author: String,
+    /// Author time, UNIX epoch seconds
+    pub(crate) time: i64,
+    /// First line of the commit message
+    pub(crate) summary: String,
+}
+
+impl BlameLine {
+    /// Whether the line is changed in the buffer or working tree
+    pub(crate) fn uncommitted(&self) -> bool {
+        self.hash.bytes().all(|b| b == b'0')
+    }
+
+    /// One-line description for the footer
+    pub(crate) fn describe(&self) -> String {
+        if self.uncommitted() {
+            return "Not committed yet".to_string();
+        }
+        format!("{}  {}  {}  {}", self.hash, self.author, format_date(self.time), self.summary)
+    }
+
+    /// Annotation shown in the blame gutter, `BLAME_GUTTER_WIDTH` columns wide
+    pub(crate) fn gutter_text(&self) -> String {
+        let width = BLAME_GUTTER_WIDTH as usize;
+        if self.uncommitted() {
+            return format!("{:width$}", "uncommitted", width = width);
+        }
+        let author: String = self.author.chars().take(width - 10).collect();
+        format!("{} {:<w$} ", self.hash, author, w = width - 10)
+    }
+}
+
+/// Directory `git` runs in for `file`
+fn file_dir(file: &str) -> PathBuf {
+    match Path::new(file).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Output of `git args` run in `dir`, or None when it fails
+fn git(dir: &Path, args: &[&str]) -> Option<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output.status.success().then_some(output.stdout)
+}
+
+/// Like `git`, but returns git's error message when it fails
+fn git_reporting(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("cannot run git: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        let error = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let message = error.lines().chain(stdout.lines()).find(|l| !l.trim().is_empty()).unwrap_or("git failed");
+        Err(message.trim().to_string())
+    }
+}
+
+/// Branch checked out in the repository holding `file`; a detached HEAD shows
+/// as its short commit hash in parentheses
+pub(crate) fn current_branch(file: &str) -> Option<String> {
+    let dir = file_dir(file);
+    if let Some(name) = git(&dir, &["symbolic-ref", "--short", "-q", "HEAD"]) {
+        return Some(String::from_utf8_lossy(&name).trim().to_string());
+    }
+    let hash = git(&dir, &["rev-parse", "--short", "HEAD"])?;
+    Some(format!("({})", String::from_utf8_lossy(&hash).trim()))
+}
+
+/// Lines of `file` as committed in HEAD, or None when it is not tracked there
+pub(crate) fn head_lines(file: &str, encoding: crate::encoding::FileEncoding) -> Option<Vec<String>> {
+    let name = Path::new(file).file_name()?.to_str()?;
+    let content = git(&file_dir(file), &["show", &format!("HEAD:./{}", name)])?;
+    Some(crate::line_ending::split_lines(&encoding.decode_as(&content)))
+}
+
+/// Files below `dir` that git does not ignore (tracked and untracked ones),
+/// relative to `dir`, or None when `dir` is not in a repository
+pub(crate) fn project_files(dir: &Path) -> Option<Vec<PathBuf>> {
+    let output = git(dir, &["ls-files", "-z", "--cached", "--others", "--exclude-standard"])?;
+    Some(
+        output
+            .split(|&b| b == 0)
+            .filter(|name| !name.is_empty())
+            .map(|name| PathBuf::from(String::from_utf8_lossy(name).as_ref()))
+            .collect(),
+    )
+}
+
+/// Stage `file` and commit it on its own with `message`. Returns the first line
+/// of git's report (e.g. "[main 1a2b3c4] message"), or git's error message.
+pub(crate) fn commit_file(file: &str, message: &str) -> Result<String, String> {
+    let name = Path::new(file)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| "not a file".to_string())?;
+    let dir = file_dir(file);
+    git_reporting(&dir, &["add", "--", name])?;
+    let report = git_reporting(&dir, &["commit", "-q", "-m", message, "--", name])?;
+    if let Some(line) = report.lines().find(|l| !l.trim().is_empty()) {
+        return Ok(line.trim().to_string());
+    }
+    let hash = git(&dir, &["rev-parse", "--short", "HEAD"]).unwrap_or_default();
+    Ok(format!("Committed {}", String::from_utf8_lossy(&hash).trim()))
+}
+
+/// Blame of the buffer `lines` of `file` (lines changed in the buffer count as
+/// not committed), or None when the file is not tracked by git
+pub(crate) fn blame(file: &str, lines: &[String], encoding: crate::encoding::FileEncoding) -> Option<Vec<BlameLine>> {
+    let name = Path::new(file).file_name()?.to_str()?;
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(file_dir(file))
+        .args(["blame", "--line-porcelain", "--contents", "-", "--", name])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdin = child.stdin.take()?;
+    let content = encoding.encode(&lines.join("\n"));
+    // Written from a thread so a large blame output cannot block the write
+    let writer = std::thread::spawn(move || stdin.write_all(&content));
+    let output = child.wait_with_output().ok()?;
+    let _ = writer.join();
+    output.status.success().then(|| parse_blame(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Lines of `git blame --line-porcelain` output
+fn parse_blame(output: &str) -> Vec<BlameLine> {
+    let mut result = Vec::new();
+    let mut current: Option<BlameLine> = None;
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            result.extend(current.take());
+            continue;
+        }
+        match current.as_mut() {
+            None => {
+                let hash = line.split(' ').next().unwrap_or_default();
+                current = Some(BlameLine {
+                    hash: hash.chars().take(8).collect(),
+                    author: String::new(),
+                    time: 0,
+                    summary: String::new(),
+                });
+            }
+            Some(entry) => {
+                if let Some(author) = line.strip_prefix("author ") {
+                    entry.author = author.to_string();
+                } else if let Some(time) = line.strip_prefix("author-time ") {
+                    entry.time = time.parse().unwrap_or(0);
+                } else if let Some(summary) = line.strip_prefix("summary ") {
+                    entry.summary = summary.to_string();
+                }
+            }
+        }
+    }
+    result
+}
+
+/// `YYYY-MM-DD` (UTC) of UNIX epoch seconds
+pub(crate) fn format_date(secs: i64) -> String {
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let days = secs.div_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    #[test]
+    fn reads_branch_and_committed_content() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let file = dir.join("notes.txt");
+        let file = file.to_string_lossy().to_string();
+        let utf8 = crate::encoding::FileEncoding::Utf8;
+
+        assert_eq!(current_branch(&file), None, "not a repository");
+        run(dir, &["init", "-q", "-b", "work"]);
+        std::fs::write(&file, "one\ntwo\n").unwrap();
+        assert_eq!(head_lines(&file, utf8), None, "no commit yet");
+
+        run(dir, &["add", "notes.txt"]);
+        run(dir, &["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-q", "-m", "init"]);
+        std::fs::write(&file, "one\nchanged\n").unwrap();
+        assert_eq!(current_branch(&file).as_deref(), Some("work"));
+        assert_eq!(head_lines(&file, utf8), Some(vec!["one".to_string(), "two".to_string()]));
+
+        let buffer = vec!["one".to_string(), "edited".to_string()];
+        let blamed = blame(&file, &buffer, utf8).unwrap();
+        assert_eq!(blamed.len(), 2);
+        assert_eq!(blamed[0].author, "t");
+        assert_eq!(blamed[0].summary, "init");
+        assert!(!blamed[0].uncommitted());
+        assert!(blamed[1].uncommitted());
+        assert_eq!(blamed[1].describe(), "Not committed yet");
+
+        // Only the file itself is committed, not other staged changes
+        let other = dir.join("other.txt");
+        std::fs::write(&other, "x").unwrap();
+        run(dir, &["add", "other.txt"]);
+        run(dir, &["config", "user.name", "t"]);
+        run(dir, &["config", "user.email", "t@t"]);
+        assert!(commit_file(&file, "edit notes").is_ok());
+        assert_eq!(head_lines(&file, utf8), Some(vec!["one".to_string(), "changed".to_string()]));
+        assert_eq!(head_lines(&other.to_string_lossy(), utf8), None);
+        assert!(commit_file(&file, "nothing").is_err(), "nothing to commit");
+    }
+
+    #[test]
+    fn formats_epoch_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+    }
+}
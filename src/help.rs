@@ -60,11 +60,65 @@ fn replace_keybindings(content: &str, settings: &crate::settings::Settings) -> S
         .replace("{replace}", &settings.keybindings.replace)
         .replace("{replace_current}", &settings.keybindings.replace_current)
         .replace("{replace_all}", &settings.keybindings.replace_all)
+        .replace("{replace_confirm}", &settings.keybindings.replace_confirm)
+        .replace("{toggle_preserve_case}", &settings.keybindings.toggle_preserve_case)
+        .replace("{toggle_find_mode}", &settings.keybindings.toggle_find_mode)
+        .replace("{toggle_find_case}", &settings.keybindings.toggle_find_case)
+        .replace("{find_history_search}", &settings.keybindings.find_history_search)
+        .replace("{toggle_find_whole_word}", &settings.keybindings.toggle_find_whole_word)
+        .replace("{save_find_options}", &settings.keybindings.save_find_options)
         .replace("{save}", &settings.keybindings.save)
         .replace("{close}", &settings.keybindings.close)
         .replace("{quit}", &settings.keybindings.quit)
+        .replace("{save_and_quit}", &settings.keybindings.save_and_quit)
         .replace("{toggle_line_wrap}", &settings.keybindings.toggle_line_wrap)
         .replace("{render_toggle}", &settings.keybindings.render_toggle)
+        .replace("{toggle_zen_mode}", &settings.keybindings.toggle_zen_mode)
+        .replace("{convert_encoding}", &settings.keybindings.convert_encoding)
+        .replace("{reopen_with_encoding}", &settings.keybindings.reopen_with_encoding)
+        .replace("{convert_line_ending}", &settings.keybindings.convert_line_ending)
+        .replace("{undo_to_time}", &settings.keybindings.undo_to_time)
+        .replace("{toggle_invisibles}", &settings.keybindings.toggle_invisibles)
+        .replace("{blame_line}", &settings.keybindings.blame_line)
+        .replace("{toggle_blame}", &settings.keybindings.toggle_blame)
+        .replace("{git_commit}", &settings.keybindings.git_commit)
+        .replace("{diff_with_disk}", &settings.keybindings.diff_with_disk)
+        .replace("{diff_with_file}", &settings.keybindings.diff_with_file)
+        .replace("{fuzzy_find}", &settings.keybindings.fuzzy_find)
+        .replace("{toggle_sidebar}", &settings.keybindings.toggle_sidebar)
+        .replace("{save_as}", &settings.keybindings.save_as)
+        .replace("{rename_file}", &settings.keybindings.rename_file)
+        .replace("{move_line_up}", &settings.keybindings.move_line_up)
+        .replace("{move_line_down}", &settings.keybindings.move_line_down)
+        .replace("{scroll_up}", &settings.keybindings.scroll_up)
+        .replace("{scroll_down}", &settings.keybindings.scroll_down)
+        .replace("{next_todo_marker}", &settings.keybindings.next_todo_marker)
+        .replace("{next_long_line}", &settings.keybindings.next_long_line)
+        .replace("{recent_dirs}", &settings.keybindings.recent_dirs)
+        .replace("{set_syntax}", &settings.keybindings.set_syntax)
+        .replace("{select_inside}", &settings.keybindings.select_inside)
+        .replace("{select_around}", &settings.keybindings.select_around)
+        .replace("{open_line_below}", &settings.keybindings.open_line_below)
+        .replace("{open_line_above}", &settings.keybindings.open_line_above)
+        .replace("{next_buffer}", &settings.keybindings.next_buffer)
+        .replace("{previous_buffer}", &settings.keybindings.previous_buffer)
+        .replace("{goto_buffer}", &settings.keybindings.goto_buffer)
+        .replace("{add_next_occurrence}", &settings.keybindings.add_next_occurrence)
+        .replace("{find_in_files}", &settings.keybindings.find_in_files)
+        .replace("{previous_todo_marker}", &settings.keybindings.previous_todo_marker)
+        .replace("{inspect_char}", &settings.keybindings.inspect_char)
+        .replace("{strip_suspicious_chars}", &settings.keybindings.strip_suspicious_chars)
+        .replace("{duplicate_selection}", &settings.keybindings.duplicate_selection)
+        .replace("{toggle_comment}", &settings.keybindings.toggle_comment)
+        .replace("{scratch_buffer}", &settings.keybindings.scratch_buffer)
+        .replace("{split_selection_into_cursors}", &settings.keybindings.split_selection_into_cursors)
+        .replace("{cursors_at_regex}", &settings.keybindings.cursors_at_regex)
+        .replace("{highlight_word}", &settings.keybindings.highlight_word)
+        .replace("{clear_modes}", &settings.keybindings.clear_modes)
+        .replace("{insert_code_point}", &settings.keybindings.insert_code_point)
+        .replace("{symbol_outline}", &settings.keybindings.symbol_outline)
+        .replace("{indent_block_up}", &settings.keybindings.indent_block_up)
+        .replace("{indent_block_down}", &settings.keybindings.indent_block_down)
         .replace(
             "{double_tap_speed_ms}",
             &settings.double_tap_speed_ms.to_string(),
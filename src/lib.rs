@@ -6,26 +6,46 @@
 
 // Re-export all modules so integration tests in tests/ can reach them.
 // dead_code warnings are suppressed because some items are only used by the binary.
+pub mod char_info;
 pub mod coordinates;
 pub mod default_syntax;
+pub mod diff;
+pub mod diff_view;
 pub mod double_esc;
 pub mod editing;
 pub mod editor_state;
+pub mod encoding;
 pub mod env;
 pub mod event_handlers;
+pub mod file_watch;
 pub mod find;
+pub mod follow;
+pub mod fuzzy_find;
+pub mod git;
 pub mod help;
+pub mod line_ending;
 pub mod markdown_renderer;
 pub mod menu;
+pub mod minimap;
 pub mod mouse_handlers;
+pub mod no_color;
 pub mod open_dialog;
+pub mod outline;
+pub mod project_find;
 pub mod recent;
 pub mod rendering;
 pub mod session;
 pub mod settings;
+pub mod sidebar;
+pub mod stream_view;
+pub mod swap;
 pub mod syntax;
+pub mod template;
+pub mod text_objects;
+pub mod theme;
 pub mod ui;
 pub mod undo;
+pub mod workspace;
 
 // Re-export commonly used functions for binary
 pub use ui::{generate_untitled_filename, print_keys_mode};
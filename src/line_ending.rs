@@ -0,0 +1,92 @@
+//! Line endings a buffer is read with and written back in.
+
+/// Line ending used between lines when a buffer is written to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    /// All line endings offered by the conversion prompt, in prompt order
+    pub(crate) const ALL: [LineEnding; 3] = [LineEnding::Lf, LineEnding::CrLf, LineEnding::Cr];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+            LineEnding::Cr => "CR",
+        }
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+
+    /// The line ending used most often in `text`; LF when it has none.
+    /// Ties go to LF, then CRLF.
+    pub(crate) fn detect(text: &str) -> LineEnding {
+        let (mut lf, mut crlf, mut cr) = (0usize, 0usize, 0usize);
+        let mut bytes = text.bytes().peekable();
+        while let Some(byte) = bytes.next() {
+            match byte {
+                b'\r' if bytes.peek() == Some(&b'\n') => {
+                    bytes.next();
+                    crlf += 1;
+                }
+                b'\r' => cr += 1,
+                b'\n' => lf += 1,
+                _ => {}
+            }
+        }
+        if crlf > lf && crlf >= cr {
+            LineEnding::CrLf
+        } else if cr > lf && cr > crlf {
+            LineEnding::Cr
+        } else {
+            LineEnding::Lf
+        }
+    }
+}
+
+/// Split `text` into lines at any line ending (LF, CRLF or a lone CR). A final
+/// line ending does not start another line, like `str::lines`.
+pub(crate) fn split_lines(text: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find(['\r', '\n']) {
+        lines.push(rest[..pos].to_string());
+        let skip = if rest[pos..].starts_with("\r\n") { 2 } else { 1 };
+        rest = &rest[pos + skip..];
+    }
+    if !rest.is_empty() {
+        lines.push(rest.to_string());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_picks_the_dominant_line_ending() {
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("a\rb\rc"), LineEnding::Cr);
+        assert_eq!(LineEnding::detect("a\nb\r\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("no line break"), LineEnding::Lf);
+    }
+
+    #[test]
+    fn split_lines_accepts_every_line_ending() {
+        assert_eq!(split_lines("a\r\nb\rc\nd"), vec!["a", "b", "c", "d"]);
+        assert_eq!(split_lines("a\r\n\r\n"), vec!["a", ""]);
+        assert!(split_lines("").is_empty());
+    }
+}
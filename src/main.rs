@@ -1,6 +1,7 @@
 #![deny(warnings)]
 
 use clap::Parser;
+use std::io::{IsTerminal, Read};
 use ue::*;
 
 #[derive(Parser)]
@@ -10,12 +11,31 @@ struct Cli {
     #[clap(long)]
     print_keys: bool,
 
-    /// Files to be processed
+    /// Open files read-only and show data appended to them, like `tail -f`
+    #[clap(long)]
+    follow: bool,
+
+    /// Write the buffer to stdout on quit, e.g. `ue --stdout notes.txt | sort`
+    #[clap(long)]
+    stdout: bool,
+
+    /// Render without colors (reverse video for selections), also set by NO_COLOR
+    #[clap(long)]
+    no_color: bool,
+
+    /// Use the named workspace: its own last session and recent files, in the
+    /// directory it was created in. Without a name, choose one from a list
+    #[clap(long, value_name = "NAME", num_args = 0..=1, default_missing_value = "")]
+    workspace: Option<String>,
+
+    /// Files to be processed (a directory opens the file dialog there); `file:120:5`,
+    /// `+120 file` and `file:10-25` open them at a line, `-` reads stdin
     files: Vec<String>,
 }
 
 fn main() -> std::io::Result<()> {
     let _ = default_syntax::deploy_default_syntax_files();
+    let _ = theme::deploy_default_themes();
 
     // Deploy help files to ~/.local/share/ue/help/ with keybinding substitutions applied.
     // This is done before the terminal takes over so file I/O doesn't race with rendering.
@@ -29,13 +49,85 @@ fn main() -> std::io::Result<()> {
         return print_keys_mode();
     }
 
-    let mut files = cli.files.clone();
+    // `-`, or input piped in without file arguments, opens the piped text as an
+    // untitled buffer; with stdout redirected as well the result goes there on quit
+    let mut inputs = cli.files.clone();
+    let stdin_arg = inputs.iter().position(|f| f == "-");
+    if stdin_arg.is_some() || (inputs.is_empty() && !std::io::stdin().is_terminal()) {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes)?;
+        if stdin_arg.is_some() || !bytes.is_empty() {
+            let name = ui::create_untitled_buffer(&bytes)?;
+            match stdin_arg {
+                Some(pos) => inputs[pos] = name,
+                None => inputs.push(name),
+            }
+            if !std::io::stdout().is_terminal() {
+                ui::enable_stdout_emit();
+            }
+        }
+    }
+
+    // `file:10-25` opens the file with lines 10 to 25 selected, `file:120:5` and
+    // `+120 file` with the cursor on line 120
+    let mut start_positions = Vec::new();
+    let mut plus_line = None;
+    let mut args: Vec<String> = Vec::new();
+    for f in &inputs {
+        if let Some(line) = ui::parse_plus_line(f) {
+            plus_line = Some(line);
+            continue;
+        }
+        let (path, position) = ui::split_start_position(f);
+        let position = position.or(plus_line.take().map(|line| ui::StartPosition::At(line, 1)));
+        if let Some(position) = position {
+            start_positions.push((resolve_path(&path), position));
+        }
+        args.push(path);
+    }
+
+    // A directory argument opens the file dialog there instead of an editor
+    let (dirs, mut files): (Vec<String>, Vec<String>) = args
+        .into_iter()
+        .partition(|f| std::path::Path::new(f).is_dir());
+    let mut browse_dir = dirs.first().map(std::path::PathBuf::from);
+    let mut active = 0;
+
+    // A workspace keeps its own session and recent files and works in its project directory
+    if let Some(name) = cli.workspace.as_deref() {
+        let name = match name {
+            "" => workspace::pick()?,
+            name => Some(name.to_string()),
+        };
+        if let Some(name) = name {
+            // Arguments are relative to where ue was started
+            files = files.iter().map(|f| resolve_path(f)).collect();
+            browse_dir = browse_dir.map(|d| d.canonicalize().unwrap_or(d));
+            let project = workspace::open(&name)?;
+            if let Err(e) = std::env::set_current_dir(&project) {
+                eprintln!("Warning: cannot change to {}: {}", project.display(), e);
+            }
+        }
+    }
 
     if files.is_empty() {
         if let Ok(Some(last)) = session::load_last_session() {
-            // Restore the last file regardless of mode (editor or selector).
-            // For selector mode we still need a file open underneath.
-            if let Some(f) = last.file.as_ref() {
+            // Restore all files open at quit, focusing the previously active one.
+            // Otherwise restore the last file regardless of mode (editor or selector);
+            // for selector mode we still need a file open underneath.
+            let (open_files, open_active) = last.restorable_files();
+            if !open_files.is_empty() {
+                files = open_files
+                    .iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect();
+                active = open_active;
+                // Along with their positions and the sidebar
+                session::remember_restored_views(last.views);
+                if last.sidebar_open {
+                    sidebar::open_on_start();
+                }
+            } else if let Some(f) = last.file.as_ref() {
                 files = vec![f.to_string_lossy().to_string()];
             } else {
                 files = vec![first_recent_or_untitled()];
@@ -45,47 +137,48 @@ fn main() -> std::io::Result<()> {
         }
     }
 
-    // Resolve all paths to absolute form for consistent display.
-    // Untitled buffers (simple names starting with "untitled", no path separators)
-    // are kept as-is since they don't correspond to real filesystem paths yet.
-    let files: Vec<String> = files
-        .into_iter()
-        .map(|f| {
-            let is_untitled = {
-                let lower = std::path::Path::new(&f)
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("")
-                    .to_lowercase();
-                !f.contains('/') && !f.contains('\\') && lower.starts_with("untitled")
-            };
-
-            if is_untitled {
-                f
-            } else {
-                std::fs::canonicalize(&f)
-                    .unwrap_or_else(|_| {
-                        // File doesn't exist yet — build an absolute path manually.
-                        let path = std::path::PathBuf::from(&f);
-                        if path.is_absolute() {
-                            path
-                        } else {
-                            std::env::current_dir()
-                                .unwrap_or_else(|_| std::path::PathBuf::from("."))
-                                .join(path)
-                        }
-                    })
-                    .to_string_lossy()
-                    .to_string()
-            }
-        })
-        .collect();
+    // Resolve all paths to absolute form for consistent display
+    let files: Vec<String> = files.iter().map(|f| resolve_path(f)).collect();
 
-    for f in &files {
+    for f in files.iter().filter(|f| !ui::is_scratch_buffer(f)) {
         let _ = recent::update_recent_file(f);
     }
 
-    ui::show(&files)
+    if cli.follow {
+        ui::enable_follow_mode();
+    }
+    if cli.stdout {
+        ui::enable_stdout_emit();
+    }
+    if cli.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        no_color::enable_no_color();
+    }
+
+    ui::show(&files, active, browse_dir.as_deref(), &start_positions)
+}
+
+/// Absolute form of a file argument for consistent display. Untitled buffers
+/// and the scratch buffer are kept as-is since they don't correspond to real
+/// filesystem paths; a file on disk wins over an untitled buffer of the same name.
+fn resolve_path(f: &str) -> String {
+    if ui::is_scratch_buffer(f) || (!std::path::Path::new(f).exists() && ui::is_untitled(f)) {
+        f.to_string()
+    } else {
+        std::fs::canonicalize(f)
+            .unwrap_or_else(|_| {
+                // File doesn't exist yet — build an absolute path manually.
+                let path = std::path::PathBuf::from(f);
+                if path.is_absolute() {
+                    path
+                } else {
+                    std::env::current_dir()
+                        .unwrap_or_else(|_| std::path::PathBuf::from("."))
+                        .join(path)
+                }
+            })
+            .to_string_lossy()
+            .to_string()
+    }
 }
 
 /// Return the most recently used file, or a fresh untitled buffer if there are none.
@@ -5,7 +5,8 @@ use std::path::Path;
 /// Check if a file has unsaved changes by reading its undo history.
 pub(crate) fn check_file_has_unsaved_changes(file_path: &Path) -> bool {
     let file_str = file_path.to_string_lossy();
-    let Ok(undo_path) = crate::undo::UndoHistory::history_path_for(&file_str) else {
+    let untitled = crate::ui::is_untitled(&file_str);
+    let Ok(undo_path) = crate::undo::UndoHistory::history_path_for(&file_str, untitled) else {
         return false;
     };
     if !undo_path.exists() {
@@ -45,6 +46,8 @@ pub(crate) enum MenuAction {
     #[allow(dead_code)] // Used in ui.rs (binary)
     FileOpenRecent(usize),
     FileSave,
+    FileSaveAs,
+    FileRename,
     FileClose,
     FileCloseAll,
     FileQuit,
@@ -55,9 +58,11 @@ pub(crate) enum MenuAction {
     EditCut,
     EditPaste,
     EditFind,
+    EditGitCommit,
     // View menu
     ViewLineWrap,
     ViewMarkdownRendered,
+    ViewSidebar,
     // Help menu
     HelpEditor,
     HelpFind,
@@ -82,8 +87,8 @@ impl Menu {
 
 // File menu layout constants.
 const FILE_MENU_INDEX: usize = 0;
-// Static items: New, Open, Save, Close, Close all, Separator — files start after these.
-const FILE_SECTION_START_IDX: usize = 6;
+// Static items: New, Open, Save, Save As, Rename, Close, Close all, Separator — files start after these.
+const FILE_SECTION_START_IDX: usize = 8;
 
 /// Helper to create an action menu item.
 fn action(label: &str, action: MenuAction) -> MenuItem {
@@ -143,6 +148,8 @@ impl MenuBar {
                     action("New", MenuAction::FileNew),
                     action("Open...", MenuAction::FileOpenDialog),
                     action("Save", MenuAction::FileSave),
+                    action("Save As...", MenuAction::FileSaveAs),
+                    action("Rename...", MenuAction::FileRename),
                     action("Close", MenuAction::FileClose),
                     action("Close all", MenuAction::FileCloseAll),
                     MenuItem::Separator,
@@ -161,6 +168,8 @@ impl MenuBar {
                     action("Paste", MenuAction::EditPaste),
                     MenuItem::Separator,
                     action("Find", MenuAction::EditFind),
+                    MenuItem::Separator,
+                    action("Commit file...", MenuAction::EditGitCommit),
                 ],
             ),
             Menu::new(
@@ -169,6 +178,7 @@ impl MenuBar {
                 vec![
                     checkable("Line Wrap", MenuAction::ViewLineWrap, false),
                     checkable("Rendered", MenuAction::ViewMarkdownRendered, false),
+                    checkable("Sidebar", MenuAction::ViewSidebar, false),
                 ],
             ),
             Menu::new(
@@ -432,6 +442,8 @@ impl MenuBar {
             action("New", MenuAction::FileNew),
             action("Open...", MenuAction::FileOpenDialog),
             action("Save", MenuAction::FileSave),
+            action("Save As...", MenuAction::FileSaveAs),
+            action("Rename...", MenuAction::FileRename),
             action("Close", MenuAction::FileClose),
             action("Close all", MenuAction::FileCloseAll),
         ];
@@ -530,8 +542,6 @@ pub(crate) fn render_dropdown_menu(
     lines: &[String],
     menu_bg: crossterm::style::Color,
 ) -> Result<(), std::io::Error> {
-    use crossterm::style::Color;
-
     if !menu_bar.active || !menu_bar.dropdown_open {
         return Ok(());
     }
@@ -541,7 +551,7 @@ pub(crate) fn render_dropdown_menu(
     let max_width = menu_display_width(menu);
 
     let menu_bg_color = menu_bg;
-    let selection_color = Color::Rgb { r: 100, g: 149, b: 237 };
+    let selection_color = state.settings.theme.menu_selection_bg;
 
     if menu_bar.selected_menu_index == FILE_MENU_INDEX {
         render_file_menu_dropdown(
@@ -1246,8 +1256,8 @@ mod tests {
         let mut menu_bar = MenuBar::new();
         menu_bar.open_dropdown();
 
-        // File menu: New, Open..., Save, Close, Close all, [Separator], Quit
-        menu_bar.selected_item_index = 4; // "Close all"
+        // File menu: New, Open..., Save, Save As..., Rename..., Close, Close all, [Separator], Quit
+        menu_bar.selected_item_index = 6; // "Close all"
         menu_bar.next_item(); // Should jump over separator to "Quit"
 
         assert!(
@@ -0,0 +1,151 @@
+//! Minimap (`appearance.minimap`): a compressed overview of the buffer in a
+//! column left of the scrollbar, shown when the terminal is wide enough. Each
+//! row stands for a run of lines and each cell for a few text columns; clicking
+//! a row jumps to its lines.
+
+use crate::editor_state::FileViewerState;
+
+/// Text columns summarized by one minimap cell
+const COLUMNS_PER_CELL: usize = 4;
+
+/// Lines looked at per row; rows of very long files sample their run evenly
+const MAX_SAMPLED_LINES: usize = 16;
+
+/// Columns taken by the minimap (a blank separator plus the cells), or 0 when
+/// it is off, the terminal is too narrow, or the view has no source lines
+pub(crate) fn width(state: &FileViewerState) -> u16 {
+    let appearance = &state.settings.appearance;
+    if !appearance.minimap
+        || state.zen_mode
+        || state.markdown_rendered
+        || state.large_file
+        || state.term_width < appearance.minimap_min_term_width
+    {
+        return 0;
+    }
+    appearance.minimap_width
+}
+
+/// First screen column of the minimap
+pub(crate) fn start_column(state: &FileViewerState) -> u16 {
+    state.term_width.saturating_sub(1).saturating_sub(width(state))
+}
+
+/// Lines summarized by each row so the whole buffer fits in `rows` rows
+pub(crate) fn lines_per_row(total_lines: usize, rows: usize) -> usize {
+    if rows == 0 {
+        return 1;
+    }
+    total_lines.div_ceil(rows).max(1)
+}
+
+/// The cells of the row covering `run`: a block wherever one of its lines has
+/// text in the cell's columns
+pub(crate) fn row_cells(run: &[String], cells: usize, tab_width: usize) -> String {
+    let mut filled = vec![false; cells];
+    let step = run.len().div_ceil(MAX_SAMPLED_LINES).max(1);
+    for line in run.iter().step_by(step) {
+        let mut col = 0;
+        for ch in line.chars() {
+            let cell = col / COLUMNS_PER_CELL;
+            if cell >= cells {
+                break;
+            }
+            if !ch.is_whitespace() {
+                filled[cell] = true;
+            }
+            col += crate::coordinates::char_visual_width_pub(ch, col, tab_width);
+        }
+    }
+    filled.iter().map(|&f| if f { '▪' } else { ' ' }).collect()
+}
+
+/// Logical lines `[first, last]` currently on screen, following wrapped lines
+pub(crate) fn viewport_lines(state: &FileViewerState, lines: &[String], visible_lines: usize) -> (usize, usize) {
+    let text_width = crate::coordinates::calculate_text_width(state, lines, visible_lines);
+    let wrapping = state.is_line_wrapping_enabled();
+    let tab_width = state.settings.tab_width;
+    let first = state.top_line.min(lines.len().saturating_sub(1));
+    let mut rows = 0;
+    let mut last = first;
+    for i in first..lines.len() {
+        last = i;
+        rows += crate::coordinates::visual_lines_for(lines, i, text_width, tab_width, wrapping);
+        if rows >= visible_lines + state.top_line_visual_offset {
+            break;
+        }
+    }
+    (first, last)
+}
+
+/// Center the view on the lines of minimap row `row` and put the cursor there
+pub(crate) fn jump_to_row(state: &mut FileViewerState, lines: &[String], row: usize, visible_lines: usize) {
+    if lines.is_empty() {
+        return;
+    }
+    let target = (row * lines_per_row(lines.len(), visible_lines)).min(lines.len() - 1);
+    state.clear_selection();
+    state.top_line = target.saturating_sub(visible_lines / 2);
+    state.cursor_line = target - state.top_line;
+    state.cursor_col = 0;
+    state.desired_cursor_col = 0;
+    state.saved_absolute_cursor = None;
+    state.saved_scroll_state = None;
+    state.top_line_visual_offset = 0;
+    state.needs_redraw = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::set_temp_home;
+    use crate::settings::Settings;
+    use crate::undo::UndoHistory;
+
+    #[test]
+    fn rows_cover_the_whole_buffer() {
+        assert_eq!(lines_per_row(10, 20), 1);
+        assert_eq!(lines_per_row(40, 20), 2);
+        assert_eq!(lines_per_row(41, 20), 3);
+        assert_eq!(lines_per_row(0, 20), 1);
+    }
+
+    #[test]
+    fn row_cells_mark_columns_with_text() {
+        let run = vec!["fn main() {".to_string(), "        x".to_string()];
+        // Columns 0-3 and 4-7 hold "fn m" and "ain(", 8-11 ") {" and the "x"
+        assert_eq!(row_cells(&run, 4, 4), "▪▪▪ ");
+        assert_eq!(row_cells(&["\tx".to_string()], 3, 4), " ▪ ");
+        assert_eq!(row_cells(&[], 2, 4), "  ");
+    }
+
+    #[test]
+    fn minimap_needs_a_wide_terminal() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut settings = Settings::load().unwrap();
+        settings.appearance.minimap = true;
+        settings.appearance.minimap_min_term_width = 120;
+        let settings = Box::leak(Box::new(settings));
+        let mut state = FileViewerState::new(100, UndoHistory::new(), settings);
+        assert_eq!(width(&state), 0);
+        state.term_width = 160;
+        assert_eq!(width(&state), settings.appearance.minimap_width);
+        assert_eq!(start_column(&state), 159 - settings.appearance.minimap_width);
+        state.zen_mode = true;
+        assert_eq!(width(&state), 0);
+    }
+
+    #[test]
+    fn clicking_a_row_centers_its_lines() {
+        let (_tmp, _guard) = set_temp_home();
+        let settings = Box::leak(Box::new(Settings::load().unwrap()));
+        let mut state = FileViewerState::new(160, UndoHistory::new(), settings);
+        let lines = vec!["x".to_string(); 100];
+
+        jump_to_row(&mut state, &lines, 5, 10); // 10 lines per row
+        assert_eq!(state.absolute_line(), 50);
+        assert_eq!(state.top_line, 45);
+        jump_to_row(&mut state, &lines, 20, 10);
+        assert_eq!(state.absolute_line(), 99);
+    }
+}
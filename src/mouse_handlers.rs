@@ -253,6 +253,22 @@ fn handle_scrollbar_click(
         state.scrollbar_drag_start_y = row;
         state.scrollbar_drag_bar_offset = bar_offset;
         state.needs_redraw = true;
+    } else if let Some(&(_, line)) = crate::rendering::search_tick_rows(
+        lines,
+        state,
+        text_width,
+        total_visual_lines,
+        scrollbar_height,
+    )
+    .iter()
+    .find(|&&(tick_row, _)| tick_row == visual_line)
+    {
+        // Click on a search tick - jump to the first match it marks
+        let col = crate::rendering::first_search_match_col(lines, state, line).unwrap_or(0);
+        state.clear_selection();
+        state.set_cursor_position(line, col, lines, visible_lines);
+        crate::find::update_search_hit_count(state, lines);
+        state.needs_redraw = true;
     } else {
         // Click in scrollbar background - jump to that position
         let target_scroll_progress = visual_line as f64 / scrollbar_height as f64;
@@ -284,11 +300,10 @@ fn handle_scrollbar_click(
             }
 
             if new_top_line != state.top_line {
+                let absolute_cursor = state.absolute_line();
                 state.top_line = new_top_line;
-                // Adjust cursor if it goes off screen
-                if state.cursor_line >= visible_lines {
-                    state.cursor_line = visible_lines.saturating_sub(1);
-                }
+                // Update cursor to maintain its absolute position in the text
+                update_cursor_visibility_after_scroll(state, absolute_cursor, visible_lines);
                 state.needs_redraw = true;
             }
         } else {
@@ -297,11 +312,10 @@ fn handle_scrollbar_click(
             let new_top_line = new_top_line.min(max_scroll);
 
             if new_top_line != state.top_line {
+                let absolute_cursor = state.absolute_line();
                 state.top_line = new_top_line;
-                // Adjust cursor if it goes off screen
-                if state.cursor_line >= visible_lines {
-                    state.cursor_line = visible_lines.saturating_sub(1);
-                }
+                // Update cursor to maintain its absolute position in the text
+                update_cursor_visibility_after_scroll(state, absolute_cursor, visible_lines);
                 state.needs_redraw = true;
             }
         }
@@ -448,9 +462,13 @@ fn handle_footer_click(
         if click_col >= toggle_start && click_col < toggle_end {
             // Toggle the mode
             state.find_regex_mode = !state.find_regex_mode;
-            // Update highlights with new mode
-            crate::find::update_live_highlights(state);
-            crate::find::update_search_hit_count(state, lines);
+            // Update highlights (and the incremental match while typing) with new mode
+            if state.find_active {
+                crate::find::incremental_search(state, lines, visible_lines);
+            } else {
+                crate::find::update_live_highlights(state);
+                crate::find::update_search_hit_count(state, lines);
+            }
             state.needs_redraw = true;
             return;
         }
@@ -464,7 +482,7 @@ fn handle_footer_click(
         let line_num = state.absolute_line() + 1;
         let col_num = state.cursor_col + 1;
         let position_info = format!("{}:{}", line_num, col_num);
-        let buttons = "[replace occurrence] [replace all]";
+        let buttons = crate::rendering::replace_footer_buttons(state);
         let right_side = format!("{}  {}", buttons, position_info);
         
         // Calculate where buttons are displayed
@@ -481,6 +499,14 @@ fn handle_footer_click(
         let replace_all_btn = "[replace all]";
         
         let click_col = column as usize;
+
+        // Check if clicked on the preserve-case toggle (always the first button)
+        let case_btn_end = right_start + buttons.find(' ').unwrap_or(0);
+        if click_col >= right_start && click_col < case_btn_end {
+            state.replace_preserve_case = !state.replace_preserve_case;
+            state.needs_redraw = true;
+            return;
+        }
         
         // Check if clicked on "replace occurrence" button
         if let Some(pos) = buttons.find(replace_occurrence_btn) {
@@ -524,15 +550,7 @@ fn handle_footer_click(
     let position_info = format!("{:>width_l$}:{:<width_c$}", line_num, col_num,
         width_l = max_line_w, width_c = max_col_w);
 
-    let hit_display = if state.search_hit_count > 0 {
-        if state.search_current_hit > 0 {
-            format!("({}/{}) ↑↓", state.search_current_hit, state.search_hit_count)
-        } else {
-            format!("(-/{}) ↑↓", state.search_hit_count)
-        }
-    } else {
-        "(0) ↑↓".to_string()
-    };
+    let hit_display = crate::rendering::search_hit_display(state);
 
     // Format: hit_display  position_info (with double space and trailing space) — matches renderer exactly
     let full_info = format!("{}  {} ", hit_display, position_info);
@@ -964,11 +982,33 @@ pub(crate) fn handle_mouse_event(
     }
 
     let visual_line = (row as usize).saturating_sub(1);
+
+    // Clicks and scrolling on the sidebar go to its tree
+    if crate::sidebar::is_visible(state)
+        && column < crate::sidebar::SIDEBAR_WIDTH as u16
+        && visual_line < visible_lines
+        && !matches!(kind, MouseEventKind::Drag(_) | MouseEventKind::Up(_) | MouseEventKind::Moved)
+    {
+        crate::sidebar::handle_mouse(state, mouse_event, visible_lines);
+        return;
+    }
+
+    // Click on the minimap jumps to the lines of that row
+    if kind == MouseEventKind::Down(MouseButton::Left)
+        && crate::minimap::width(state) > 0
+        && column >= crate::minimap::start_column(state)
+        && column < state.term_width - 1
+        && visual_line < visible_lines
+    {
+        crate::minimap::jump_to_row(state, lines, visual_line, visible_lines);
+        return;
+    }
+
     // Ignore clicks beyond visible content, but allow scrollbar events to reach the boundary
     let scrollbar_column = state.term_width - 1;
     
-    // Scrollbar is always visible now, so it's always clickable
-    let is_scrollbar_event = column == scrollbar_column;
+    // Scrollbar is always visible (except in zen mode), so it is clickable
+    let is_scrollbar_event = column == scrollbar_column && !state.zen_mode;
 
     if visual_line >= visible_lines && !is_scrollbar_event {
         return;
@@ -1163,7 +1203,8 @@ fn below_document_position(
         let segment_start_char = wrap_points.last().copied().unwrap_or(0);
         let segment_start_visual =
             crate::coordinates::visual_width_up_to(line, segment_start_char, tab_width);
-        let absolute_visual_col = segment_start_visual + text_col;
+        let indent = crate::coordinates::segment_indent(line, wrap_points.len(), text_width as usize, tab_width);
+        let absolute_visual_col = segment_start_visual + text_col.saturating_sub(indent);
         crate::coordinates::visual_col_to_char_index(line, absolute_visual_col, tab_width)
     } else {
         // In horizontal-scroll mode there is no wrapping; add the scroll offset.
@@ -1226,7 +1267,8 @@ fn handle_mouse_click(
                 // Calculate visual widths
                 let segment_start_visual = crate::coordinates::visual_width_up_to(line, segment_start_char, state.settings.tab_width);
                 let segment_end_visual = crate::coordinates::visual_width_up_to(line, segment_end_char, state.settings.tab_width);
-                let content_width_in_segment = segment_end_visual - segment_start_visual;
+                let content_width_in_segment = segment_end_visual - segment_start_visual
+                    + crate::coordinates::segment_indent(line, segment_idx, text_width as usize, state.settings.tab_width);
 
                 // Calculate where the mouse clicked within this visual line
                 let line_num_width = crate::coordinates::line_number_width(state.settings);
@@ -2112,6 +2154,30 @@ mod tests {
         assert!(state.needs_redraw);
     }
 
+    #[test]
+    fn scrollbar_click_in_background_keeps_cursor_in_text() {
+        let (_tmp, _guard) = set_temp_home();
+        let settings = Box::leak(Box::new(
+            Settings::load().expect("Failed to load test settings"),
+        ));
+        let mut state = create_test_state(settings);
+        let mut lines: Vec<String> = (0..100).map(|i| format!("line {}", i)).collect();
+        state.top_line = 0;
+        state.cursor_line = 5;
+        let visible_lines = 20;
+
+        let mouse_event = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: state.term_width - 1,
+            row: 18,
+            modifiers: KeyModifiers::empty(),
+        };
+        handle_mouse_event(&mut state, &mut lines, mouse_event, visible_lines);
+
+        assert!(state.top_line > 5);
+        assert_eq!(state.saved_absolute_cursor, Some(5));
+    }
+
     #[test]
     fn scrollbar_drag_maintains_cursor_absolute_position() {
         let (_tmp, _guard) = set_temp_home();
@@ -0,0 +1,140 @@
+//! Plain-text output mode (`no_color` setting, `--no-color`, `NO_COLOR`): screen
+//! output passes through a writer that drops every color from the SGR escape
+//! sequences. Backgrounds (selection, search matches, header and footer) turn into
+//! reverse video instead, so they stay distinguishable without color.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static NO_COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Render without colors for the rest of the process
+pub fn enable_no_color() {
+    NO_COLOR.store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn is_enabled() -> bool {
+    NO_COLOR.load(Ordering::Relaxed)
+}
+
+/// Terminal output, stripped of colors when plain-text mode is on
+pub(crate) fn stdout() -> NoColorWriter<io::Stdout> {
+    NoColorWriter {
+        inner: io::stdout(),
+        enabled: is_enabled(),
+        pending: Vec::new(),
+    }
+}
+
+/// Writer that rewrites SGR sequences (`ESC [ ... m`) when plain-text mode was
+/// on at its creation and passes everything else through. A sequence split
+/// across writes is held back until it is complete.
+pub(crate) struct NoColorWriter<W: Write> {
+    inner: W,
+    enabled: bool,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> Write for NoColorWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.enabled {
+            return self.inner.write(buf);
+        }
+        let mut out = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            if self.pending.is_empty() {
+                if byte == 0x1b {
+                    self.pending.push(byte);
+                } else {
+                    out.push(byte);
+                }
+                continue;
+            }
+            self.pending.push(byte);
+            if self.pending.len() == 2 {
+                if byte != b'[' {
+                    out.append(&mut self.pending);
+                }
+            } else if (0x40..=0x7e).contains(&byte) {
+                if byte == b'm' {
+                    let params = &self.pending[2..self.pending.len() - 1];
+                    if let Some(sgr) = strip_colors(&String::from_utf8_lossy(params)) {
+                        out.extend_from_slice(format!("\x1b[{}m", sgr).as_bytes());
+                    }
+                    self.pending.clear();
+                } else {
+                    out.append(&mut self.pending);
+                }
+            } else if !(0x20..0x40).contains(&byte) {
+                // Not a CSI sequence after all
+                out.append(&mut self.pending);
+            }
+        }
+        self.inner.write_all(&out)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// SGR parameters without colors: foregrounds are dropped, any background
+/// becomes reverse video and the default background ends it. None when
+/// nothing is left.
+fn strip_colors(params: &str) -> Option<String> {
+    let mut kept: Vec<&str> = Vec::new();
+    let mut parts = params.split([';', ':']);
+    while let Some(part) = parts.next() {
+        let code: u16 = if part.is_empty() { 0 } else { part.parse().unwrap_or(u16::MAX) };
+        match code {
+            0 => kept.push("0"),
+            30..=37 | 39 | 90..=97 => {}
+            40..=47 | 100..=107 => kept.push("7"),
+            49 => kept.push("27"),
+            38 | 48 => {
+                // Extended color: `5;n` or `2;r;g;b`
+                let skip = match parts.next() {
+                    Some("5") => 1,
+                    Some("2") => 3,
+                    _ => 0,
+                };
+                for _ in 0..skip {
+                    parts.next();
+                }
+                if code == 48 {
+                    kept.push("7");
+                }
+            }
+            u16::MAX => {}
+            _ => kept.push(part),
+        }
+    }
+    kept.dedup();
+    (!kept.is_empty()).then(|| kept.join(";"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_dropped_and_backgrounds_reversed() {
+        assert_eq!(strip_colors("38;2;255;0;0"), None);
+        assert_eq!(strip_colors("1;31"), Some("1".to_string()));
+        assert_eq!(strip_colors("48;5;240"), Some("7".to_string()));
+        assert_eq!(strip_colors("44;38;5;15"), Some("7".to_string()));
+        assert_eq!(strip_colors("49"), Some("27".to_string()));
+        assert_eq!(strip_colors("0"), Some("0".to_string()));
+        assert_eq!(strip_colors(""), Some("0".to_string()));
+    }
+
+    #[test]
+    fn writer_rewrites_sequences_split_across_writes() {
+        let mut writer = NoColorWriter { inner: Vec::new(), enabled: true, pending: Vec::new() };
+        writer.write_all(b"a\x1b[38;2;").unwrap();
+        writer.write_all(b"1;2;3mb\x1b[48;5;8mc\x1b[0m").unwrap();
+        writer.write_all(b"\x1b[2J\x1bxd").unwrap();
+        assert_eq!(writer.inner, b"ab\x1b[7mc\x1b[0m\x1b[2J\x1bxd");
+    }
+}
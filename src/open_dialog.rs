@@ -57,40 +57,19 @@ struct OpenDialogState {
     #[allow(dead_code)] // Used in event loop for help scrolling
     help_scroll_offset: usize,
     mode: DialogMode,
+    /// File open in the editor, which must not be renamed or deleted here
+    current_file: Option<PathBuf>,
+    /// Outcome of the last file operation, shown instead of the key hints
+    message: Option<String>,
 }
 
 impl OpenDialogState {
     fn new(current_file: Option<&Path>, show_hidden: bool, mode: DialogMode) -> io::Result<Self> {
-        // Determine the starting directory
-        let start_dir = if matches!(mode, DialogMode::SaveAs) {
-            // In SaveAs mode, always use current working directory
-            // (the current_file parameter might be "untitled" or a relative path)
-            if let Some(file) = current_file {
-                let file_path = PathBuf::from(file);
-                // If the file has a real parent directory that exists, use it
-                if let Some(parent) = file_path.parent() {
-                    if parent.exists() && parent.is_dir() {
-                        parent.to_path_buf()
-                    } else {
-                        // Parent doesn't exist (e.g., "untitled"), use current_dir
-                        std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-                    }
-                } else {
-                    std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-                }
-            } else {
-                std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-            }
-        } else {
-            // In Open mode, use the file's parent directory or current_dir
-            if let Some(file) = current_file {
-                file.parent()
-                    .map(|p| p.to_path_buf())
-                    .unwrap_or_else(|| PathBuf::from("."))
-            } else {
-                std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-            }
-        };
+        // Start where the last dialog of this session left off, otherwise in the
+        // current file's directory (falling back to the working directory, e.g. for "untitled")
+        let start_dir = last_dialog_dir()
+            .or_else(|| current_file.and_then(Path::parent).filter(|p| p.is_dir()).map(Path::to_path_buf))
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
 
         let mut state = Self {
             nodes: Vec::new(),
@@ -103,6 +82,8 @@ impl OpenDialogState {
             help_active: false,
             help_scroll_offset: 0,
             mode,
+            current_file: current_file.map(Path::to_path_buf),
+            message: None,
         };
 
         state.build_tree(&start_dir, current_file)?;
@@ -125,13 +106,19 @@ impl OpenDialogState {
         }
         ancestors.reverse(); // Now we have [/, /home, /home/user, /home/user/project]
 
-        // In SaveAs mode, we want to expand the target directory and select it
-        // In Open mode, we want to select the current file
-        let expand_target = matches!(self.mode, DialogMode::SaveAs);
-        let select_target = if matches!(self.mode, DialogMode::SaveAs) {
+        // In Open mode, select the current file when it lives in the start directory.
+        // Otherwise (SaveAs mode, or a remembered directory elsewhere) expand the
+        // start directory and select it
+        let current_file = current_file.and_then(|f| f.canonicalize().ok());
+        let file_in_start_dir = current_file
+            .as_deref()
+            .and_then(Path::parent)
+            .is_some_and(|parent| parent == start_dir);
+        let expand_target = matches!(self.mode, DialogMode::SaveAs) || !file_in_start_dir;
+        let select_target = if expand_target {
             Some(start_dir.as_path())
         } else {
-            current_file
+            current_file.as_deref()
         };
 
         // Start from root
@@ -146,10 +133,9 @@ impl OpenDialogState {
         Ok(())
     }
 
-    /// Refresh the tree while preserving expansion states and selection
-    fn refresh_tree(&mut self) -> io::Result<()> {
-        // Save current expansion states
-        let expanded_paths: std::collections::HashSet<PathBuf> = self.nodes.iter()
+    /// Paths of the directories currently expanded in the tree
+    fn expanded_paths(&self) -> std::collections::HashSet<PathBuf> {
+        self.nodes.iter()
             .enumerate()
             .filter(|(idx, node)| {
                 // Check if node has children (is actually expanded)
@@ -158,30 +144,124 @@ impl OpenDialogState {
                     && self.nodes[*idx + 1].depth == node.depth + 1
             })
             .map(|(_, node)| node.path.clone())
-            .collect();
+            .collect()
+    }
 
-        // Save current selection path
-        let selected_path = self.nodes.get(self.selected_index).map(|n| n.path.clone());
+    /// Refresh the tree while preserving expansion states and selection
+    fn refresh_tree(&mut self) -> io::Result<()> {
+        let expanded_paths = self.expanded_paths();
+        let selected_path = self.get_selected_path();
+        self.rebuild_tree(&expanded_paths, selected_path.as_deref())
+    }
 
+    /// Rebuild the tree from root with `expanded_paths` expanded, selecting
+    /// `select` (or keeping the selection in range when it is gone)
+    fn rebuild_tree(
+        &mut self,
+        expanded_paths: &std::collections::HashSet<PathBuf>,
+        select: Option<&Path>,
+    ) -> io::Result<()> {
         // Clear and rebuild from root
         self.nodes.clear();
-        self.refresh_tree_recursive(&PathBuf::from("/"), 0, &expanded_paths)?;
+        self.refresh_tree_recursive(&PathBuf::from("/"), 0, expanded_paths)?;
 
         // Restore selection to same path (or closest match)
-        if let Some(target_path) = selected_path
+        if let Some(target_path) = select
             && let Some(idx) = self.nodes.iter().position(|n| n.path == target_path) {
                 self.selected_index = idx;
-                // Adjust scroll to keep selection visible
-                if self.selected_index < self.scroll_offset {
-                    self.scroll_offset = self.selected_index;
-                } else if self.selected_index >= self.scroll_offset + 20 {
-                    self.scroll_offset = self.selected_index.saturating_sub(10);
-                }
             }
+        self.selected_index = self.selected_index.min(self.nodes.len().saturating_sub(1));
+        // Adjust scroll to keep selection visible
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + 20 {
+            self.scroll_offset = self.selected_index.saturating_sub(10);
+        }
 
         Ok(())
     }
 
+    /// Directory new entries are created in: the selected directory, or the
+    /// one holding the selected file
+    fn target_dir(&self) -> Option<PathBuf> {
+        let node = self.nodes.get(self.selected_index)?;
+        if node.is_directory {
+            Some(node.path.clone())
+        } else {
+            node.path.parent().map(Path::to_path_buf)
+        }
+    }
+
+    /// Whether `path` is the editor's open file or a directory holding it
+    fn holds_current_file(&self, path: &Path) -> bool {
+        self.current_file
+            .as_ref()
+            .is_some_and(|file| crate::sidebar::holds_open_file(path, &file.to_string_lossy()))
+    }
+
+    /// Create `name` (a directory when it ends with '/') in the target
+    /// directory and select it
+    fn create_entry(&mut self, name: &str) -> io::Result<()> {
+        let Some(dir) = self.target_dir() else {
+            return Ok(());
+        };
+        match crate::sidebar::create_entry(&dir, name) {
+            Ok(path) => {
+                // Expand the directories down to the new entry
+                let mut expanded = self.expanded_paths();
+                expanded.extend(path.ancestors().skip(1).take_while(|a| a.starts_with(&dir)).map(Path::to_path_buf));
+                self.message = Some(format!("Created {}", path.display()));
+                self.rebuild_tree(&expanded, Some(&path))
+            }
+            Err(e) => {
+                self.message = Some(e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Rename the selected entry to `name` in its directory
+    fn rename_selected(&mut self, name: &str) -> io::Result<()> {
+        let Some(old) = self.get_selected_path() else {
+            return Ok(());
+        };
+        match crate::sidebar::rename_entry(&old, name) {
+            Ok(path) => {
+                // Directories below a renamed one stay expanded
+                let expanded = self.expanded_paths()
+                    .into_iter()
+                    .map(|p| match p.strip_prefix(&old) {
+                        Ok(rest) => path.join(rest),
+                        Err(_) => p,
+                    })
+                    .collect();
+                self.message = Some(format!("Renamed to {}", path.display()));
+                self.rebuild_tree(&expanded, Some(&path))
+            }
+            Err(e) => {
+                self.message = Some(e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Delete the selected file, or directory when it is empty
+    fn delete_selected(&mut self) -> io::Result<()> {
+        let Some(path) = self.get_selected_path() else {
+            return Ok(());
+        };
+        match crate::sidebar::delete_entry(&path) {
+            Ok(()) => {
+                self.message = Some(format!("Deleted {}", path.display()));
+                self.refresh_tree()
+            }
+            Err(e) => {
+                self.message = Some(e);
+                Ok(())
+            }
+        }
+    }
+
     /// Recursively rebuild tree with preserved expansion states
     fn refresh_tree_recursive(
         &mut self,
@@ -642,7 +722,140 @@ pub(crate) fn run_open_dialog(
 ) -> io::Result<OpenDialogResult> {
     let current_path = current_file.map(PathBuf::from);
     let mut state = OpenDialogState::new(current_path.as_deref(), false, mode)?;
+    let result = dialog_loop(&mut state, settings)?;
+
+    // Remember where the user ended up for the next dialog
+    let dir = match &result {
+        OpenDialogResult::Selected(path) => path.parent().map(Path::to_path_buf),
+        _ => state.get_selected_path().and_then(|path| {
+            if path.is_dir() { Some(path) } else { path.parent().map(Path::to_path_buf) }
+        }),
+    };
+    if let Some(dir) = dir {
+        remember_dialog_dir(&dir);
+        let _ = crate::recent::update_recent_dir(&dir);
+    }
+    Ok(result)
+}
+
+/// Most rows the recent directories overlay takes
+const MAX_RECENT_DIR_ROWS: usize = 15;
+
+/// Recent directories whose path fuzzy-matches `query`, best first
+/// (most recent first for an empty query or ties)
+fn filter_recent_dirs<'a>(dirs: &'a [PathBuf], query: &str) -> Vec<&'a PathBuf> {
+    let mut scored: Vec<(i64, usize, &PathBuf)> = dirs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, d)| crate::outline::fuzzy_score(query, &d.to_string_lossy()).map(|score| (score, i, d)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, _, d)| d).collect()
+}
 
+/// Show the recent directories overlay; returns the chosen directory
+fn pick_recent_dir(settings: &crate::settings::Settings) -> io::Result<Option<PathBuf>> {
+    let dirs = crate::recent::get_recent_dirs().unwrap_or_default();
+    let mut query = String::new();
+    let mut selected = 0usize;
+    let mut scroll = 0usize;
+    loop {
+        let (width, height) = crossterm::terminal::size()?;
+        let matches = filter_recent_dirs(&dirs, &query);
+        selected = selected.min(matches.len().saturating_sub(1));
+        let rows = MAX_RECENT_DIR_ROWS.min((height as usize).saturating_sub(4)).max(1);
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + rows {
+            scroll = selected + 1 - rows;
+        }
+        render_recent_dirs(&query, &matches, selected, scroll, rows, width, &settings.theme)?;
+
+        if let Event::Key(key) = event::read()? {
+            let key = crate::event_handlers::normalize_key_event(key, settings);
+            match key.code {
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Enter => return Ok(matches.get(selected).map(|d| (*d).clone())),
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down => selected += 1,
+                KeyCode::PageUp => selected = selected.saturating_sub(rows),
+                KeyCode::PageDown => selected += rows,
+                KeyCode::Backspace => {
+                    query.pop();
+                    selected = 0;
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    query.push(c);
+                    selected = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn render_recent_dirs(
+    query: &str,
+    matches: &[&PathBuf],
+    selected: usize,
+    scroll: usize,
+    rows: usize,
+    term_width: u16,
+    theme: &crate::theme::Theme,
+) -> io::Result<()> {
+    let mut stdout = crate::no_color::stdout();
+    let width = (term_width as usize).saturating_sub(4).clamp(10, 90);
+    let x = ((term_width as usize).saturating_sub(width) / 2) as u16;
+    let bg = theme.chrome_bg;
+    let fit = |text: String| -> String {
+        let text = crate::rendering::truncate_to_width(&text, width);
+        let pad = width.saturating_sub(crate::coordinates::visual_width(&text, 4));
+        format!("{}{}", text, " ".repeat(pad))
+    };
+
+    queue!(stdout, Hide, MoveTo(x, 1), SetBackgroundColor(bg), SetForegroundColor(Color::Yellow))?;
+    queue!(stdout, Print(fit(format!(" Recent directory: {}", query))), ResetColor)?;
+    for row in 0..rows {
+        let idx = scroll + row;
+        let text = match matches.get(idx) {
+            Some(dir) => format!(" {}", dir.display()),
+            None if idx == 0 => " (no recent directories)".to_string(),
+            None => String::new(),
+        };
+        queue!(stdout, MoveTo(x, 2 + row as u16))?;
+        if idx == selected && idx < matches.len() {
+            queue!(stdout, SetBackgroundColor(theme.menu_selection_bg), SetForegroundColor(Color::White))?;
+        } else {
+            queue!(stdout, SetBackgroundColor(bg), SetForegroundColor(Color::White))?;
+        }
+        queue!(stdout, Print(fit(text)), ResetColor)?;
+    }
+    stdout.flush()
+}
+
+/// Directory the last open/save-as dialog ended in, for this editor session
+static LAST_DIALOG_DIR: std::sync::Mutex<Option<PathBuf>> = std::sync::Mutex::new(None);
+
+fn last_dialog_dir() -> Option<PathBuf> {
+    LAST_DIALOG_DIR
+        .lock()
+        .ok()
+        .and_then(|dir| dir.clone())
+        .filter(|dir| dir.is_dir())
+}
+
+pub(crate) fn remember_dialog_dir(dir: &Path) {
+    if dir.is_dir()
+        && let Ok(mut last) = LAST_DIALOG_DIR.lock()
+    {
+        *last = Some(dir.to_path_buf());
+    }
+}
+
+fn dialog_loop(
+    state: &mut OpenDialogState,
+    settings: &crate::settings::Settings,
+) -> io::Result<OpenDialogResult> {
     loop {
         let (term_width, term_height) = crossterm::terminal::size()?;
         let visible_lines = (term_height as usize).saturating_sub(2); // Header (1) + tree + input/help (1)
@@ -651,17 +864,28 @@ pub(crate) fn run_open_dialog(
             // Render help screen
             let help_content = crate::help::get_open_dialog_help(settings, term_width as usize);
             crate::help::render_help(
-                &mut io::stdout(),
+                &mut crate::no_color::stdout(),
                 &help_content,
                 state.help_scroll_offset,
                 term_width,
                 term_height,
             )?;
         } else {
-            render_dialog(&state, term_width, term_height)?;
+            render_dialog(state, term_width, term_height, &settings.theme)?;
         }
 
-        if let Event::Key(key) = event::read()? {
+        let event = event::read()?;
+        // A path pasted into the terminal goes to the input field
+        if let Event::Paste(text) = &event
+            && !state.help_active
+        {
+            let text = text.lines().next().unwrap_or("");
+            state.focus = FocusMode::Input;
+            state.input_buffer.insert_str(state.input_cursor, text);
+            state.input_cursor += text.len();
+            continue;
+        }
+        if let Event::Key(key) = event {
             // Normalize key event to handle num-pad Enter
             let key = crate::event_handlers::normalize_key_event(key, settings);
             
@@ -698,8 +922,18 @@ pub(crate) fn run_open_dialog(
                 continue;
             }
 
+            // Jump the tree to a recently visited directory
+            if settings.keybindings.recent_dirs_matches(&key.code, &key.modifiers) {
+                if let Some(dir) = pick_recent_dir(settings)? {
+                    state.build_tree(&dir, None)?;
+                    state.focus = FocusMode::Tree;
+                }
+                continue;
+            }
+
             match state.focus {
                 FocusMode::Tree => {
+                    state.message = None;
                     match key.code {
                         KeyCode::Up | KeyCode::Char('k') => {
                             state.move_up(visible_lines);
@@ -743,6 +977,39 @@ pub(crate) fn run_open_dialog(
                         KeyCode::Esc => {
                             return Ok(OpenDialogResult::Cancelled);
                         }
+                        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            if let Some(dir) = state.target_dir() {
+                                let label = format!("New in {}/ (end with / for a directory)", dir.display());
+                                if let Some(name) = crate::event_handlers::show_text_prompt(&label, settings)? {
+                                    state.create_entry(name.trim())?;
+                                }
+                            }
+                        }
+                        KeyCode::F(2) => {
+                            if let Some(path) = state.get_selected_path() {
+                                if state.holds_current_file(&path) {
+                                    state.message = Some("The open file cannot be renamed from here".to_string());
+                                } else {
+                                    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                                    if let Some(new_name) = crate::event_handlers::show_text_prompt(&format!("Rename {} to", name), settings)? {
+                                        state.rename_selected(new_name.trim())?;
+                                    }
+                                }
+                            }
+                        }
+                        KeyCode::Delete => {
+                            if let Some(path) = state.get_selected_path() {
+                                if state.holds_current_file(&path) {
+                                    state.message = Some("The open file cannot be deleted from here".to_string());
+                                } else {
+                                    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                                    let answer = crate::event_handlers::show_text_prompt(&format!("Delete {}? (y/n)", name), settings)?;
+                                    if answer.is_some_and(|a| a.trim().eq_ignore_ascii_case("y")) {
+                                        state.delete_selected()?;
+                                    }
+                                }
+                            }
+                        }
                         KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) && c == 'v' => {
                             // Switch to input on paste
                             if let Ok(mut clipboard) = arboard::Clipboard::new()
@@ -768,8 +1035,8 @@ pub(crate) fn run_open_dialog(
 }
 
 /// Render the complete dialog
-fn render_dialog(state: &OpenDialogState, width: u16, height: u16) -> io::Result<()> {
-    let mut stdout = io::stdout();
+fn render_dialog(state: &OpenDialogState, width: u16, height: u16, theme: &crate::theme::Theme) -> io::Result<()> {
+    let mut stdout = crate::no_color::stdout();
 
     // Hide cursor while we redraw to prevent flickering
     queue!(stdout, Hide)?;
@@ -788,18 +1055,18 @@ fn render_dialog(state: &OpenDialogState, width: u16, height: u16) -> io::Result
     queue!(
         stdout,
         MoveTo(0, 0),
-        SetBackgroundColor(Color::Rgb { r: 0, g: 24, b: 72 }),
+        SetBackgroundColor(theme.chrome_bg),
         SetForegroundColor(Color::White),
     )?;
     let header = format!("{:width$}", title, width = width as usize);
     queue!(stdout, Print(header), ResetColor)?;
 
     // Render tree
-    render_tree(&mut stdout, state, 1, tree_height, width)?;
+    render_tree(&mut stdout, state, 1, tree_height, width, theme)?;
 
     // Render input field at bottom
     let input_y = height - 1;
-    render_input_field(&mut stdout, state, input_y, width)?;
+    render_input_field(&mut stdout, state, input_y, width, theme)?;
 
     // Show cursor and position it when input is focused
     if state.focus == FocusMode::Input {
@@ -811,7 +1078,7 @@ fn render_dialog(state: &OpenDialogState, width: u16, height: u16) -> io::Result
 }
 
 /// Render the tree view
-fn render_tree(stdout: &mut impl Write, state: &OpenDialogState, start_y: u16, visible_lines: usize, width: u16) -> io::Result<()> {
+fn render_tree(stdout: &mut impl Write, state: &OpenDialogState, start_y: u16, visible_lines: usize, width: u16, theme: &crate::theme::Theme) -> io::Result<()> {
     for (i, node) in state.nodes.iter()
         .skip(state.scroll_offset)
         .take(visible_lines)
@@ -824,8 +1091,7 @@ fn render_tree(stdout: &mut impl Write, state: &OpenDialogState, start_y: u16, v
         queue!(stdout, MoveTo(0, y))?;
 
         if is_selected && state.focus == FocusMode::Tree {
-            // Use same color as editor scrollbar
-            queue!(stdout, SetBackgroundColor(Color::Rgb { r: 100, g: 149, b: 237 }), SetForegroundColor(Color::White))?;
+            queue!(stdout, SetBackgroundColor(theme.menu_selection_bg), SetForegroundColor(Color::White))?;
         }
 
         // Build tree prefix with proper lines
@@ -900,18 +1166,21 @@ fn render_tree(stdout: &mut impl Write, state: &OpenDialogState, start_y: u16, v
 }
 
 /// Render the input field
-fn render_input_field(stdout: &mut impl Write, state: &OpenDialogState, y: u16, width: u16) -> io::Result<()> {
+fn render_input_field(stdout: &mut impl Write, state: &OpenDialogState, y: u16, width: u16, theme: &crate::theme::Theme) -> io::Result<()> {
     queue!(
         stdout,
         MoveTo(0, y),
-        SetBackgroundColor(Color::Rgb { r: 0, g: 24, b: 72 }),
+        SetBackgroundColor(theme.chrome_bg),
         SetForegroundColor(Color::White),
     )?;
 
     match state.focus {
         FocusMode::Tree => {
-            // Show help text when tree is focused
-            let help_text = "↑↓:Navigate  ←:Parent  →:Child  Enter:Toggle  Tab:Input  .:Hidden  Esc:Cancel";
+            // Show the last file operation's outcome, otherwise help text when tree is focused
+            let help_text = state.message.as_deref().unwrap_or(
+                "↑↓:Navigate  ←:Parent  →:Child  Enter:Toggle  Tab:Input  .:Hidden  Alt+D:Recent dirs  Ctrl+N:New  F2:Rename  Del:Delete  Esc:Cancel",
+            );
+            let help_text = crate::rendering::truncate_to_width(help_text, width as usize);
             let line = format!("{:width$}", help_text, width = width as usize);
             queue!(stdout, Print(line))?;
         }
@@ -987,5 +1256,74 @@ mod tests {
         assert!(node.is_directory);
         assert!(!node.is_expanded);
     }
+
+    #[test]
+    fn dialog_starts_in_remembered_directory() {
+        // Hidden entries are not listed, so avoid the default ".tmp" prefix
+        let tmp = tempfile::Builder::new().prefix("ue-dialog").tempdir().unwrap();
+        let project = tmp.path().join("project");
+        let elsewhere = tmp.path().join("elsewhere");
+        fs::create_dir_all(&project).unwrap();
+        fs::create_dir_all(&elsewhere).unwrap();
+        let file = project.join("main.rs");
+        fs::write(&file, "").unwrap();
+
+        // Without a remembered directory the current file is selected
+        *LAST_DIALOG_DIR.lock().unwrap() = None;
+        let state = OpenDialogState::new(Some(&file), false, DialogMode::Open).unwrap();
+        assert_eq!(state.get_selected_path(), file.canonicalize().ok());
+
+        remember_dialog_dir(&elsewhere);
+        let state = OpenDialogState::new(Some(&file), false, DialogMode::SaveAs).unwrap();
+        assert_eq!(state.get_selected_path(), elsewhere.canonicalize().ok());
+        *LAST_DIALOG_DIR.lock().unwrap() = None;
+    }
+
+    #[test]
+    fn file_operations_update_the_tree() {
+        let tmp = tempfile::Builder::new().prefix("ue-dialog").tempdir().unwrap();
+        let dir = tmp.path().canonicalize().unwrap();
+        let open = dir.join("open.txt");
+        fs::write(&open, "").unwrap();
+        let mut state = OpenDialogState::new(Some(&open), false, DialogMode::Open).unwrap();
+        // Independent of a directory remembered by another test
+        state.build_tree(&dir, Some(&open)).unwrap();
+        assert_eq!(state.get_selected_path(), Some(open.clone()));
+        assert!(state.holds_current_file(&open));
+        assert!(state.holds_current_file(&dir));
+
+        // New entries land next to the selected file, in expanded directories
+        state.create_entry("docs/notes.md").unwrap();
+        let notes = dir.join("docs/notes.md");
+        assert!(notes.is_file());
+        assert_eq!(state.get_selected_path(), Some(notes.clone()));
+        state.create_entry("notes.md").unwrap();
+        assert_eq!(state.message.as_deref(), Some("notes.md already exists"));
+
+        state.rename_selected("todo.md").unwrap();
+        let todo = dir.join("docs/todo.md");
+        assert!(todo.is_file() && !notes.exists());
+        assert_eq!(state.get_selected_path(), Some(todo.clone()));
+        assert!(!state.holds_current_file(&todo));
+
+        state.delete_selected().unwrap();
+        assert!(!todo.exists());
+        assert!(state.nodes.iter().all(|n| n.path != todo));
+    }
+
+    #[test]
+    fn recent_dirs_filter_fuzzily_and_keep_recency_order() {
+        let dirs = vec![
+            PathBuf::from("/home/me/projects/editor"),
+            PathBuf::from("/home/me/notes"),
+            PathBuf::from("/srv/www/editorial"),
+        ];
+        assert_eq!(filter_recent_dirs(&dirs, "").len(), 3);
+        assert_eq!(filter_recent_dirs(&dirs, "")[0], &dirs[0]);
+        assert_eq!(filter_recent_dirs(&dirs, "notes"), vec![&dirs[1]]);
+        let editor = filter_recent_dirs(&dirs, "edit");
+        assert_eq!(editor.len(), 2);
+        assert!(filter_recent_dirs(&dirs, "xyz").is_empty());
+    }
 }
 
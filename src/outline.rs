@@ -0,0 +1,336 @@
+//! Symbol outline: definitions found by the `symbol|` regexes of the file's
+//! syntax, listed in a filterable overlay for jumping within the file. The
+//! same overlay lists the known languages for overriding the file's syntax.
+
+use crossterm::{
+    cursor, event, execute,
+    event::{KeyCode, KeyModifiers},
+    style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal,
+};
+use regex::Regex;
+use std::io::Write;
+
+use crate::editor_state::FileViewerState;
+use crate::settings::Settings;
+
+/// Most rows the overlay list takes, leaving the surrounding code visible
+const MAX_LIST_ROWS: usize = 15;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Symbol {
+    pub(crate) line: usize,
+    pub(crate) col: usize,
+    pub(crate) label: String,
+}
+
+/// Definitions on `lines`, in file order. Capture group 1 of a pattern is the
+/// label (the whole match when the pattern has no group); the first pattern
+/// that matches a line wins.
+pub(crate) fn extract_symbols(lines: &[String], patterns: &[Regex]) -> Vec<Symbol> {
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            patterns.iter().find_map(|re| {
+                let caps = re.captures(line)?;
+                let m = caps.get(1).or_else(|| caps.get(0))?;
+                let label = m.as_str().trim();
+                (!label.is_empty()).then(|| Symbol {
+                    line: idx,
+                    col: line[..m.start()].chars().count(),
+                    label: label.to_string(),
+                })
+            })
+        })
+        .collect()
+}
+
+/// Case-insensitive subsequence match of `query` in `text`. Higher scores for
+/// consecutive characters and matches at word starts, lower for skipped
+/// characters; None if not all match.
+pub(crate) fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0i64;
+    let mut pos = 0;
+    let mut prev_match: Option<usize> = None;
+    for q in query.chars().flat_map(char::to_lowercase) {
+        let found = (pos..text.len()).find(|&i| text[i].to_lowercase().eq(std::iter::once(q)))?;
+        score += 1;
+        if prev_match.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - pos).min(10) as i64;
+        prev_match = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+/// Symbols matching `query`, best first (file order for an empty query or ties)
+pub(crate) fn filter_symbols<'a>(symbols: &'a [Symbol], query: &str) -> Vec<&'a Symbol> {
+    let mut scored: Vec<(i64, &Symbol)> = symbols
+        .iter()
+        .filter_map(|s| fuzzy_score(query, &s.label).map(|score| (score, s)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.line.cmp(&b.1.line)));
+    scored.into_iter().map(|(_, s)| s).collect()
+}
+
+/// Show the outline overlay and jump to the chosen symbol, centering its line.
+/// Returns true if the cursor moved.
+pub(crate) fn run_symbol_outline(
+    state: &mut FileViewerState,
+    lines: &[String],
+    visible_lines: usize,
+    settings: &Settings,
+) -> Result<bool, std::io::Error> {
+    let symbols = extract_symbols(lines, &crate::syntax::symbol_patterns());
+    if symbols.is_empty() {
+        state.status_message = Some("No symbols found".to_string());
+        state.alert();
+        state.needs_footer_redraw = true;
+        return Ok(false);
+    }
+
+    let mut query = String::new();
+    let mut selected = 0usize;
+    let mut scroll = 0usize;
+    let mut stdout = crate::no_color::stdout();
+    let chosen = loop {
+        let matches = filter_symbols(&symbols, &query);
+        selected = selected.min(matches.len().saturating_sub(1));
+        let rows = MAX_LIST_ROWS.min(visible_lines.saturating_sub(2)).max(1);
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + rows {
+            scroll = selected + 1 - rows;
+        }
+        let number_width = matches.iter().map(|s| (s.line + 1).to_string().len()).max().unwrap_or(1);
+        let items: Vec<String> = matches
+            .iter()
+            .map(|s| format!(" {:>w$}  {}", s.line + 1, s.label, w = number_width))
+            .collect();
+        render_list_overlay(&mut stdout, state, &format!(" Symbol: {}", query), &items, selected, scroll, rows)?;
+
+        if let event::Event::Key(key) = event::read()? {
+            let key = crate::event_handlers::normalize_key_event(key, settings);
+            match key.code {
+                KeyCode::Esc => break None,
+                KeyCode::Enter => break matches.get(selected).map(|s| (*s).clone()),
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down => selected += 1,
+                KeyCode::PageUp => selected = selected.saturating_sub(rows),
+                KeyCode::PageDown => selected += rows,
+                KeyCode::Backspace => {
+                    query.pop();
+                    selected = 0;
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    query.push(c);
+                    selected = 0;
+                }
+                _ => {}
+            }
+        }
+    };
+
+    state.needs_redraw = true;
+    let Some(symbol) = chosen else {
+        return Ok(false);
+    };
+    state.clear_selection();
+    state.clear_multi_cursors();
+    state.top_line = symbol.line.saturating_sub(visible_lines / 2);
+    state.cursor_line = symbol.line - state.top_line;
+    state.cursor_col = symbol.col;
+    state.desired_cursor_col = symbol.col;
+    state.saved_absolute_cursor = None;
+    state.saved_scroll_state = None;
+    state.top_line_visual_offset = 0;
+    Ok(true)
+}
+
+/// Known syntaxes (canonical extensions) whose name or extension matches
+/// `query`, best first (alphabetical by name for an empty query or ties)
+pub(crate) fn filter_languages(query: &str) -> Vec<&'static str> {
+    let mut scored: Vec<(i64, &'static str)> = crate::default_syntax::all_known_extensions()
+        .iter()
+        .filter_map(|&ext| {
+            let name = crate::syntax::language_name(ext);
+            let score = fuzzy_score(query, name).max(fuzzy_score(query, ext))?;
+            Some((score, ext))
+        })
+        .collect();
+    scored.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| crate::syntax::language_name(a.1).cmp(crate::syntax::language_name(b.1)))
+    });
+    scored.into_iter().map(|(_, ext)| ext).collect()
+}
+
+/// Let the user pick the syntax of the current buffer. The choice overrides
+/// extension and content detection and is remembered with the file's undo
+/// history. Returns true if the syntax changed.
+pub(crate) fn run_syntax_picker(
+    state: &mut FileViewerState,
+    visible_lines: usize,
+    settings: &Settings,
+) -> Result<bool, std::io::Error> {
+    let active = crate::syntax::active_syntax();
+    let mut query = String::new();
+    let mut selected = filter_languages("")
+        .iter()
+        .position(|&ext| Some(ext) == active.as_deref())
+        .unwrap_or(0);
+    let mut scroll = 0usize;
+    let mut stdout = crate::no_color::stdout();
+    let chosen = loop {
+        let matches = filter_languages(&query);
+        selected = selected.min(matches.len().saturating_sub(1));
+        let rows = MAX_LIST_ROWS.min(visible_lines.saturating_sub(2)).max(1);
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + rows {
+            scroll = selected + 1 - rows;
+        }
+        let items: Vec<String> = matches
+            .iter()
+            .map(|&ext| {
+                let marker = if Some(ext) == active.as_deref() { '*' } else { ' ' };
+                format!("{}{:<14} {}", marker, crate::syntax::language_name(ext), ext)
+            })
+            .collect();
+        render_list_overlay(&mut stdout, state, &format!(" Syntax: {}", query), &items, selected, scroll, rows)?;
+
+        if let event::Event::Key(key) = event::read()? {
+            let key = crate::event_handlers::normalize_key_event(key, settings);
+            match key.code {
+                KeyCode::Esc => break None,
+                KeyCode::Enter => break matches.get(selected).copied(),
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down => selected += 1,
+                KeyCode::PageUp => selected = selected.saturating_sub(rows),
+                KeyCode::PageDown => selected += rows,
+                KeyCode::Backspace => {
+                    query.pop();
+                    selected = 0;
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    query.push(c);
+                    selected = 0;
+                }
+                _ => {}
+            }
+        }
+    };
+
+    state.needs_redraw = true;
+    let Some(ext) = chosen else {
+        return Ok(false);
+    };
+    crate::syntax::set_base_syntax(ext);
+    state.undo_history.syntax_override = Some(ext.to_string());
+    state.status_message = Some(format!("Syntax: {}", crate::syntax::language_name(ext)));
+    Ok(active.as_deref() != Some(ext))
+}
+
+/// First column and width of the list overlay on a terminal `term_width` wide
+pub(crate) fn overlay_geometry(term_width: u16) -> (u16, usize) {
+    let width = (term_width as usize).saturating_sub(4).clamp(10, 70);
+    (((term_width as usize).saturating_sub(width) / 2) as u16, width)
+}
+
+/// Draw a filterable list overlay below the header: the `title` row with the
+/// query, then `rows` entries of `items` starting at `scroll`
+pub(crate) fn render_list_overlay(
+    stdout: &mut impl Write,
+    state: &FileViewerState,
+    title: &str,
+    items: &[String],
+    selected: usize,
+    scroll: usize,
+    rows: usize,
+) -> Result<(), std::io::Error> {
+    let (x, width) = overlay_geometry(terminal::size()?.0);
+    let bg = state.effective_theme_bg();
+    let selection_color = state.settings.theme.menu_selection_bg;
+
+    let fit = |text: &str| -> String {
+        let mut text = crate::rendering::truncate_to_width(text, width);
+        let pad = width.saturating_sub(crate::coordinates::visual_width(&text, 4));
+        text.push_str(&" ".repeat(pad));
+        text
+    };
+
+    execute!(stdout, cursor::Hide, cursor::MoveTo(x, 1), SetBackgroundColor(bg), SetForegroundColor(Color::Yellow))?;
+    write!(stdout, "{}", fit(title))?;
+    execute!(stdout, ResetColor)?;
+
+    for row in 0..rows {
+        execute!(stdout, cursor::MoveTo(x, 2 + row as u16))?;
+        let idx = scroll + row;
+        let text = match items.get(idx) {
+            Some(item) => item.as_str(),
+            None if idx == 0 => " (no match)",
+            None => "",
+        };
+        if idx == selected && idx < items.len() {
+            execute!(stdout, SetBackgroundColor(selection_color), SetForegroundColor(Color::White))?;
+        } else {
+            execute!(stdout, SetBackgroundColor(bg))?;
+        }
+        write!(stdout, "{}", fit(text))?;
+        execute!(stdout, ResetColor)?;
+    }
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_symbols_uses_capture_group_as_label() {
+        let patterns = vec![
+            Regex::new(r"^\s*(?:pub\s+)?(fn\s+\w+)").unwrap(),
+            Regex::new(r"^(#{1,6}\s+.+)$").unwrap(),
+        ];
+        let lines: Vec<String> = ["# Title", "", "pub fn main() {", "    let x = 1;", "    fn inner() {}"]
+            .map(String::from)
+            .to_vec();
+        let symbols = extract_symbols(&lines, &patterns);
+        let labels: Vec<(usize, usize, &str)> =
+            symbols.iter().map(|s| (s.line, s.col, s.label.as_str())).collect();
+        assert_eq!(labels, vec![(0, 0, "# Title"), (2, 4, "fn main"), (4, 4, "fn inner")]);
+    }
+
+    #[test]
+    fn filter_prefers_consecutive_and_word_start_matches() {
+        let symbols: Vec<Symbol> = ["fn render_line", "fn handle_key_event", "fn read_line"]
+            .iter()
+            .enumerate()
+            .map(|(line, label)| Symbol { line, col: 0, label: label.to_string() })
+            .collect();
+        let labels = |q: &str| -> Vec<String> {
+            filter_symbols(&symbols, q).iter().map(|s| s.label.clone()).collect()
+        };
+        assert_eq!(labels("").len(), 3);
+        assert_eq!(labels("rline"), vec!["fn read_line", "fn render_line"]);
+        assert_eq!(labels("KEY"), vec!["fn handle_key_event"]);
+        assert!(labels("xyz").is_empty());
+    }
+
+    #[test]
+    fn filter_languages_matches_names_and_extensions() {
+        assert_eq!(filter_languages("").len(), crate::default_syntax::all_known_extensions().len());
+        assert_eq!(filter_languages("")[0], "c");
+        assert_eq!(filter_languages("python")[0], "py");
+        assert_eq!(filter_languages("rs")[0], "rs");
+        assert!(filter_languages("yaml").contains(&"yaml"));
+        assert!(filter_languages("qqq").is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+//! Find in files: the find regex run over every text file below the working
+//! directory, with the matches listed in an overlay for opening one of them.
+
+use crossterm::event::{self, KeyCode, KeyModifiers};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::editor_state::FileViewerState;
+use crate::settings::Settings;
+
+/// Most matches collected before the search stops
+pub(crate) const MAX_PROJECT_MATCHES: usize = 1000;
+/// Files larger than this are skipped
+const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+/// Directories that hold build output or dependencies rather than sources
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+/// Most rows the results list takes
+const MAX_LIST_ROWS: usize = 15;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProjectMatch {
+    pub(crate) path: PathBuf,
+    /// Zero-based line and char range of the match on it
+    pub(crate) line: usize,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) preview: String,
+}
+
+/// Match to select in the file opened next: (path, line, start, end)
+static PENDING_JUMP: Mutex<Option<(PathBuf, usize, usize, usize)>> = Mutex::new(None);
+
+/// Matches of `regex` in the text files below `root`, in path order. Hidden
+/// entries, build directories, large and binary files are skipped. The bool is
+/// true when the search stopped at `MAX_PROJECT_MATCHES`.
+pub(crate) fn search_tree(root: &Path, regex: &Regex) -> (Vec<ProjectMatch>, bool) {
+    let mut files = Vec::new();
+    collect_files(root, &mut files);
+    files.sort();
+
+    let mut matches = Vec::new();
+    for path in files {
+        let Ok(bytes) = std::fs::read(&path) else {
+            continue;
+        };
+        if bytes.iter().take(8192).any(|&b| b == 0) {
+            continue;
+        }
+        let text = String::from_utf8_lossy(&bytes);
+        for (line_idx, line) in text.lines().enumerate() {
+            for m in regex.find_iter(line) {
+                if m.start() == m.end() {
+                    continue;
+                }
+                let start = line[..m.start()].chars().count();
+                matches.push(ProjectMatch {
+                    path: path.clone(),
+                    line: line_idx,
+                    start,
+                    end: start + m.as_str().chars().count(),
+                    preview: line.trim().to_string(),
+                });
+                if matches.len() >= MAX_PROJECT_MATCHES {
+                    return (matches, true);
+                }
+            }
+        }
+    }
+    (matches, false)
+}
+
+/// Files below `dir` that find in files looks at, in directory order
+pub(crate) fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            if !SKIPPED_DIRS.contains(&name.as_ref()) {
+                collect_files(&path, files);
+            }
+        } else if file_type.is_file() && entry.metadata().is_ok_and(|m| m.len() <= MAX_FILE_BYTES) {
+            files.push(path);
+        }
+    }
+}
+
+/// Results row: `path:line: preview`, the path relative to `root`
+fn result_label(m: &ProjectMatch, root: &Path) -> String {
+    let path = m.path.strip_prefix(root).unwrap_or(&m.path);
+    format!(" {}:{}: {}", path.display(), m.line + 1, m.preview)
+}
+
+/// Search for `pattern` below the working directory and let the user pick a
+/// match from the results list (typing filters it). Returns the chosen match.
+pub(crate) fn run_project_find(
+    state: &mut FileViewerState,
+    pattern: &str,
+    visible_lines: usize,
+    settings: &Settings,
+) -> Result<Option<ProjectMatch>, std::io::Error> {
+    let regex = match crate::find::pattern_to_regex(pattern, crate::find::FindOptions::default()) {
+        Ok(regex) => regex,
+        Err(e) => {
+            state.status_message = Some(crate::find::summarize_regex_error(&e.to_string()));
+            state.alert();
+            return Ok(None);
+        }
+    };
+    let root = std::env::current_dir()?;
+    let (matches, capped) = search_tree(&root, &regex);
+    if matches.is_empty() {
+        state.status_message = Some(format!("No matches for \"{}\"", pattern));
+        state.alert();
+        return Ok(None);
+    }
+    let labels: Vec<String> = matches.iter().map(|m| result_label(m, &root)).collect();
+    let count = if capped { format!("first {}", matches.len()) } else { matches.len().to_string() };
+
+    let mut query = String::new();
+    let mut selected = 0usize;
+    let mut scroll = 0usize;
+    let mut stdout = crate::no_color::stdout();
+    let chosen = loop {
+        let shown: Vec<usize> = (0..labels.len())
+            .filter(|&i| crate::outline::fuzzy_score(&query, &labels[i]).is_some())
+            .collect();
+        selected = selected.min(shown.len().saturating_sub(1));
+        let rows = MAX_LIST_ROWS.min(visible_lines.saturating_sub(2)).max(1);
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + rows {
+            scroll = selected + 1 - rows;
+        }
+        let items: Vec<String> = shown.iter().map(|&i| labels[i].clone()).collect();
+        let title = format!(" {} ({} matches) Filter: {}", pattern, count, query);
+        crate::outline::render_list_overlay(&mut stdout, state, &title, &items, selected, scroll, rows)?;
+
+        if let event::Event::Key(key) = event::read()? {
+            let key = crate::event_handlers::normalize_key_event(key, settings);
+            match key.code {
+                KeyCode::Esc => break None,
+                KeyCode::Enter => break shown.get(selected).map(|&i| matches[i].clone()),
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down => selected += 1,
+                KeyCode::PageUp => selected = selected.saturating_sub(rows),
+                KeyCode::PageDown => selected += rows,
+                KeyCode::Backspace => {
+                    query.pop();
+                    selected = 0;
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    query.push(c);
+                    selected = 0;
+                }
+                _ => {}
+            }
+        }
+    };
+    state.needs_redraw = true;
+    Ok(chosen)
+}
+
+/// Remember `m` to be selected once its file is opened
+pub(crate) fn set_pending_jump(m: &ProjectMatch) {
+    if let Ok(mut pending) = PENDING_JUMP.lock() {
+        *pending = Some((m.path.clone(), m.line, m.start, m.end));
+    }
+}
+
+/// The match to select in `file` (line, start, end), if one is pending for it
+pub(crate) fn take_pending_jump(file: &str) -> Option<(usize, usize, usize)> {
+    let mut pending = PENDING_JUMP.lock().ok()?;
+    let (path, ..) = pending.as_ref()?;
+    if !same_file(path, Path::new(file)) {
+        return None;
+    }
+    pending.take().map(|(_, line, start, end)| (line, start, end))
+}
+
+/// Whether both paths name the same file (compared as given when one is missing)
+pub(crate) fn same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Select the match at (line, start, end) with the cursor at its end
+pub(crate) fn select_match(
+    state: &mut FileViewerState,
+    lines: &[String],
+    (line, start, end): (usize, usize, usize),
+    visible_lines: usize,
+) {
+    if line >= lines.len() {
+        return;
+    }
+    state.clear_multi_cursors();
+    state.set_cursor_position(line, end, lines, visible_lines);
+    state.desired_cursor_col = state.cursor_col;
+    state.selection_start = Some((line, start));
+    state.selection_end = Some((line, state.cursor_col));
+    state.needs_redraw = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_tree_skips_hidden_build_and_binary_files() {
+        let (tmp, _guard) = crate::env::set_temp_home();
+        let root = tmp.path().join("project");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {\n    let todo = 1; // TODO\n}\n").unwrap();
+        std::fs::write(root.join("README.md"), "Nothing to do\n").unwrap();
+        std::fs::write(root.join(".git/HEAD"), "todo\n").unwrap();
+        std::fs::write(root.join("target/out.txt"), "todo\n").unwrap();
+        std::fs::write(root.join("blob.bin"), b"todo\0\x01").unwrap();
+
+        let regex = crate::find::pattern_to_regex("todo", crate::find::FindOptions::default()).unwrap();
+        let (matches, capped) = search_tree(&root, &regex);
+        assert!(!capped);
+        let found: Vec<(String, usize, usize, usize)> = matches
+            .iter()
+            .map(|m| (result_label(m, &root), m.line, m.start, m.end))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (" src/main.rs:2: let todo = 1; // TODO".to_string(), 1, 8, 12),
+                (" src/main.rs:2: let todo = 1; // TODO".to_string(), 1, 21, 25),
+            ]
+        );
+    }
+
+    #[test]
+    fn pending_jump_applies_only_to_its_file() {
+        let m = ProjectMatch {
+            path: PathBuf::from("/nonexistent/a.rs"),
+            line: 3,
+            start: 1,
+            end: 4,
+            preview: String::new(),
+        };
+        set_pending_jump(&m);
+        assert_eq!(take_pending_jump("/nonexistent/b.rs"), None);
+        assert_eq!(take_pending_jump("/nonexistent/a.rs"), Some((3, 1, 4)));
+        assert_eq!(take_pending_jump("/nonexistent/a.rs"), None);
+    }
+}
@@ -1,12 +1,12 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{fs, io};
 
 const MAX_RECENT: usize = 50;
+const MAX_RECENT_DIRS: usize = 30;
 
 fn recent_list_path() -> io::Result<PathBuf> {
-    let data_dir = crate::env::resolve_data_dir()
-        .map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
-    Ok(data_dir.join("files.ue"))
+    // Each workspace has its own list
+    Ok(crate::workspace::data_dir()?.join("files.ue"))
 }
 
 pub fn get_recent_files() -> io::Result<Vec<PathBuf>> {
@@ -93,6 +93,62 @@ pub fn remove_recent_file(file_path: &str) -> io::Result<()> {
     Ok(())
 }
 
+fn recent_dirs_path() -> io::Result<PathBuf> {
+    let data_dir = crate::env::resolve_data_dir()
+        .map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+    Ok(data_dir.join("dirs.ue"))
+}
+
+/// Recently visited directories with the Unix time of the last visit, most recent
+/// first. Stored one `<seconds>\t<path>` entry per line.
+fn read_recent_dirs() -> io::Result<Vec<(u64, PathBuf)>> {
+    let path = recent_dirs_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| {
+            let (time, dir) = line.split_once('\t')?;
+            Some((time.parse().ok()?, PathBuf::from(dir)))
+        })
+        .collect())
+}
+
+/// Recently visited directories that still exist, most recent first
+pub(crate) fn get_recent_dirs() -> io::Result<Vec<PathBuf>> {
+    Ok(read_recent_dirs()?
+        .into_iter()
+        .map(|(_, dir)| dir)
+        .filter(|dir| dir.is_dir())
+        .collect())
+}
+
+/// Move `dir` to the front of the recent directories list
+pub(crate) fn update_recent_dir(dir: &Path) -> io::Result<()> {
+    let dir = dir.canonicalize()?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut current = read_recent_dirs()?;
+    current.retain(|(_, d)| d != &dir);
+    current.insert(0, (now, dir));
+    current.truncate(MAX_RECENT_DIRS);
+
+    let recent_path = recent_dirs_path()?;
+    if let Some(parent) = recent_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let serialized: Vec<String> = current
+        .iter()
+        .map(|(time, d)| format!("{}\t{}", time, d.to_string_lossy()))
+        .collect();
+    fs::write(&recent_path, serialized.join("\n"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -389,4 +445,30 @@ mod tests {
         let recent_after = get_recent_files().unwrap();
         assert!(recent_after.is_empty(), "All untitled files should be removed");
     }
+
+    #[test]
+    fn recent_dirs_are_deduplicated_and_capped() {
+        let (tmp, _guard) = set_temp_home();
+        let a = tmp.path().join("a");
+        let b = tmp.path().join("b");
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+
+        update_recent_dir(&a).unwrap();
+        update_recent_dir(&b).unwrap();
+        update_recent_dir(&a).unwrap();
+        let recent = get_recent_dirs().unwrap();
+        assert_eq!(recent, vec![a.canonicalize().unwrap(), b.canonicalize().unwrap()]);
+
+        // Directories removed since the visit are not offered
+        fs::remove_dir(&b).unwrap();
+        assert_eq!(get_recent_dirs().unwrap(), vec![a.canonicalize().unwrap()]);
+
+        for i in 0..(MAX_RECENT_DIRS + 5) {
+            let d = tmp.path().join(format!("d{}", i));
+            fs::create_dir_all(&d).unwrap();
+            update_recent_dir(&d).unwrap();
+        }
+        assert_eq!(read_recent_dirs().unwrap().len(), MAX_RECENT_DIRS);
+    }
 }
@@ -11,25 +11,89 @@ use crate::coordinates::{
 };
 use crate::editor_state::{FileViewerState, Position};
 
+/// Header and footer background while the visual bell flashes
+const BELL_FLASH_BG: crossterm::style::Color = crossterm::style::Color::Rgb { r: 200, g: 200, b: 200 };
+
 /// Thin wrapper so rendering.rs can call `effective_theme_bg(state)` without
 /// spelling out `state.effective_theme_bg()` everywhere.  The authoritative
 /// logic lives in `FileViewerState::effective_theme_bg`.
 fn effective_theme_bg(state: &FileViewerState) -> crossterm::style::Color {
+    if state.bell_flash {
+        return BELL_FLASH_BG;
+    }
     state.effective_theme_bg()
 }
 
+/// Buttons shown on the right of the replace prompt; shared with the mouse handler
+pub(crate) fn replace_footer_buttons(state: &FileViewerState) -> String {
+    let case_toggle = if state.replace_preserve_case { "[Aa:on]" } else { "[Aa:off]" };
+    format!("{} [replace occurrence] [replace all]", case_toggle)
+}
+
+/// Flags shown after the find mode button: "[Case]" for case-sensitive and
+/// "[Word]" for whole-word matching, empty when neither is on
+pub(crate) fn find_flags_label(state: &FileViewerState) -> String {
+    let mut label = String::new();
+    if state.find_case_sensitive {
+        label.push_str("[Case]");
+    }
+    if state.find_whole_word {
+        label.push_str("[Word]");
+    }
+    label
+}
+
+/// Search hit count for the footer followed by the ↑↓ navigation arrows:
+/// "match 3 of 27" on a match, "27 matches" elsewhere
+pub(crate) fn search_hit_display(state: &FileViewerState) -> String {
+    match (state.search_current_hit, state.search_hit_count) {
+        (_, 0) => "no matches ↑↓".to_string(),
+        (0, 1) => "1 match ↑↓".to_string(),
+        (0, total) => format!("{} matches ↑↓", total),
+        (current, total) => format!("match {} of {} ↑↓", current, total),
+    }
+}
+
+/// Footer prompt while confirming replacements one match at a time
+pub(crate) fn replace_confirm_prompt(state: &FileViewerState) -> String {
+    format!(
+        "Replace with \"{}\"? ({}/{}) [y]es [n]o [a]ll [q]uit",
+        state.replace_pattern, state.search_current_hit, state.search_hit_count
+    )
+}
+
+/// Write the blank left margin that replaces the line-number gutter in zen mode
+fn write_zen_margin(stdout: &mut impl Write, state: &FileViewerState) -> Result<(), std::io::Error> {
+    let digits = state.settings.appearance.line_number_digits as usize;
+    if digits > 0 {
+        write!(stdout, "{:width$}", "", width = digits + 1)?;
+    }
+    Ok(())
+}
+
 
 /// Expand tabs in a string to spaces, considering tab stops
-fn expand_tabs(s: &str, tab_width: usize) -> String {
+pub(crate) fn expand_tabs(s: &str, tab_width: usize) -> String {
+    expand_whitespace(s, tab_width, false)
+}
+
+/// `expand_tabs`, drawing whitespace when `visible`: a tab as "→" padded to its
+/// tab stop, a space as "·". Every character keeps its width.
+fn expand_whitespace(s: &str, tab_width: usize, visible: bool) -> String {
     let mut result = String::new();
     let mut col = 0;
     for ch in s.chars() {
         if ch == '\t' {
             let spaces_to_next_tab = tab_width - (col % tab_width);
-            result.push_str(&" ".repeat(spaces_to_next_tab));
+            if visible {
+                result.push('→');
+                result.push_str(&" ".repeat(spaces_to_next_tab - 1));
+            } else {
+                result.push_str(&" ".repeat(spaces_to_next_tab));
+            }
             col += spaces_to_next_tab;
         } else {
-            result.push(ch);
+            result.push(if visible && ch == ' ' { '·' } else { ch });
             col += 1;
         }
     }
@@ -107,7 +171,7 @@ fn abbreviate_path(path: &str) -> String {
 }
 
 /// Truncate a string to fit within max_width characters, adding "..." if truncated.
-fn truncate_to_width(s: &str, max_width: usize) -> String {
+pub(crate) fn truncate_to_width(s: &str, max_width: usize) -> String {
     if max_width < 3 {
         return String::new();
     }
@@ -167,6 +231,10 @@ pub(crate) fn render_screen(
     // Render h-scrollbar over the last content line (row visible_lines)
     render_horizontal_scrollbar(stdout, display_lines, state, visible_lines)?;
 
+    if crate::sidebar::is_visible(state) {
+        render_sidebar(stdout, file, state, visible_lines)?;
+    }
+
     // Then render dropdown menu OVER the content if active
     if state.menu_bar.active && state.menu_bar.dropdown_open {
         crate::menu::render_dropdown_menu(stdout, &state.menu_bar, state, lines, effective_theme_bg(state))?;
@@ -190,6 +258,12 @@ fn render_header(
     // Position at top of screen
     execute!(stdout, MoveTo(0, 0))?;
 
+    // Zen mode hides the header unless the menu is open
+    if state.zen_mode && !state.menu_bar.active {
+        execute!(stdout, ResetColor, terminal::Clear(ClearType::CurrentLine))?;
+        return Ok(());
+    }
+
     // Set header background color
     execute!(stdout, SetBackgroundColor(effective_theme_bg(state)))?;
 
@@ -207,7 +281,7 @@ fn render_header(
         // Use the larger of line_number_digits or actual_width
         let display_width = actual_width.max(state.settings.appearance.line_number_digits as usize);
 
-        let modulus = 10usize.pow(state.settings.appearance.line_number_digits as u32);
+        let modulus = 10usize.saturating_pow(state.settings.appearance.line_number_digits as u32);
         let top_number = (state.top_line / modulus) * modulus;
 
         // Determine if cursor is above visible area
@@ -222,8 +296,7 @@ fn render_header(
 
         // Highlight with scrollbar color if cursor is above
         if cursor_above {
-            use crossterm::style::Color;
-            execute!(stdout, SetBackgroundColor(Color::Rgb { r: 100, g: 149, b: 237 }))?;
+            execute!(stdout, SetBackgroundColor(state.settings.theme.scrollbar_fg))?;
         }
 
         // Write digit hint or empty space (always same width based on document length)
@@ -261,7 +334,7 @@ fn render_header(
         for (idx, menu) in state.menu_bar.menus.iter().enumerate() {
             if idx == state.menu_bar.selected_menu_index {
                 // Highlight selected menu with light blue (matching scrollbar style)
-                execute!(stdout, SetBackgroundColor(Color::Rgb { r: 100, g: 149, b: 237 }))?;
+                execute!(stdout, SetBackgroundColor(state.settings.theme.menu_selection_bg))?;
                 execute!(stdout, SetForegroundColor(Color::White))?;
             }
 
@@ -273,6 +346,18 @@ fn render_header(
 
             write!(stdout, "  ")?;
         }
+    } else if state.buffers.len() > 1 {
+        // Several open buffers: a tab per buffer instead of the path
+        let available_width = (state.term_width as usize).saturating_sub(header_prefix_width(state, lines) + 1);
+        for (label, active) in tab_strip(state, available_width) {
+            if active {
+                execute!(stdout, SetBackgroundColor(state.settings.theme.menu_selection_bg))?;
+                execute!(stdout, SetForegroundColor(Color::White))?;
+            }
+            write!(stdout, "{}", label)?;
+            execute!(stdout, ResetColor)?;
+            execute!(stdout, SetBackgroundColor(effective_theme_bg(state)))?;
+        }
     } else {
         // When menu is not active, show filename as usual
         let modified_indicator: &str = if state.is_read_only { "⚿" } else if state.modified { "*" } else { " " };
@@ -300,10 +385,11 @@ fn render_header(
         let burger_width = 2; // "≡ " takes 2 characters
         let available_width = term_width as usize - line_num_width - burger_width - 2; // -2 for safety margin
 
-        // For untitled files, show a special indicator
-        if state.is_untitled {
+        // For untitled files and the scratch buffer, show just the name
+        if state.is_untitled || state.is_scratch {
             let rendered_tag = if state.markdown_rendered { " [Rendered]" } else { "" };
-            let title = format!("{} {} {}", modified_indicator, filename, rendered_tag);
+            let name = state.display_name.as_deref().unwrap_or(filename);
+            let title = format!("{} {} {}", modified_indicator, name, rendered_tag);
             // Truncate if necessary
             let truncated_title = if visual_width(&title, 4) > available_width {
                 truncate_to_width(&title, available_width)
@@ -314,11 +400,18 @@ fn render_header(
         } else {
             // For normal files, try to fit filename and directory
             let rendered_tag = if state.markdown_rendered { " [Rendered]" } else { "" };
+            let new_tag = if state.is_new { " [NEW]" } else { "" };
+            let branch_tag = state.git_branch.as_ref().map(|b| format!("  ⎇ {}", b)).unwrap_or_default();
+            let workspace_tag = crate::workspace::active().map(|w| format!("  [{}]", w)).unwrap_or_default();
             let mut display = format!("{} {} (", modified_indicator, filename);
             let base_width = visual_width(&display, 4);
 
-            // Reserve space for closing parenthesis and optional rendered tag
-            let reserved = 1 + rendered_tag.len(); // for closing ) and rendered tag
+            // Reserve space for closing parenthesis and optional tags
+            let reserved = 1
+                + new_tag.len()
+                + rendered_tag.len()
+                + visual_width(&branch_tag, 4)
+                + visual_width(&workspace_tag, 4);
 
             let available_for_path = available_width.saturating_sub(base_width + reserved);
 
@@ -331,7 +424,10 @@ fn render_header(
 
             display.push_str(&shortened_parent);
             display.push(')');
+            display.push_str(new_tag);
             display.push_str(rendered_tag);
+            display.push_str(&branch_tag);
+            display.push_str(&workspace_tag);
 
             // Final truncation of entire display if still too long
             let final_display = if visual_width(&display, 4) > available_width {
@@ -353,6 +449,57 @@ fn render_header(
     Ok(())
 }
 
+/// Width of the line number area and burger icon that start the header
+fn header_prefix_width(state: &FileViewerState, lines: &[String]) -> usize {
+    let digits = state.settings.appearance.line_number_digits as usize;
+    let line_num_width = if digits > 0 {
+        let actual_width = lines.len().max(1).to_string().len();
+        actual_width.max(digits) + 1
+    } else {
+        0
+    };
+    line_num_width + 2
+}
+
+/// Header tabs that fit in `width`: (" N:name* ", is_current). Tabs before the
+/// current one are dropped first so it always stays visible.
+pub(crate) fn tab_strip(state: &FileViewerState, width: usize) -> Vec<(String, bool)> {
+    let labels: Vec<String> = state
+        .buffers
+        .iter()
+        .enumerate()
+        .map(|(idx, (path, unsaved))| {
+            let name = std::path::Path::new(path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(path);
+            let modified = if idx == state.buffer_index { state.modified } else { *unsaved };
+            format!(" {}:{}{} ", idx + 1, name, if modified { "*" } else { "" })
+        })
+        .collect();
+    let current = state.buffer_index.min(labels.len().saturating_sub(1));
+    let mut first = 0;
+    while first < current
+        && labels[first..=current].iter().map(|l| visual_width(l, 4)).sum::<usize>() > width
+    {
+        first += 1;
+    }
+    let mut used = 0;
+    let mut tabs = Vec::new();
+    for (idx, label) in labels.iter().enumerate().skip(first) {
+        let label_width = visual_width(label, 4);
+        if used + label_width > width {
+            if used < width {
+                tabs.push((truncate_to_width(label, width - used), idx == current));
+            }
+            break;
+        }
+        used += label_width;
+        tabs.push((label.clone(), idx == current));
+    }
+    tabs
+}
+
 /// Render position info (LINE:COL) with the line number portion highlighted
 fn render_goto_position_highlighted(
     stdout: &mut impl Write,
@@ -438,6 +585,30 @@ pub(crate) fn render_footer(
         return Ok(());
     }
 
+    // While confirming replacements, ask about the highlighted match
+    if state.replace_confirm.is_some() {
+        let digits = state.settings.appearance.line_number_digits as usize;
+        let total_width = state.term_width as usize;
+
+        write!(stdout, "\r")?;
+
+        let mut prompt = String::new();
+        if digits > 0 {
+            prompt.push_str(&format!("{:width$} ", "", width = digits));
+        }
+        prompt.push_str(&replace_confirm_prompt(state));
+
+        use crossterm::style::SetForegroundColor;
+        execute!(stdout, SetForegroundColor(crossterm::style::Color::Yellow))?;
+        let shown: String = prompt.chars().take(total_width).collect();
+        write!(stdout, "{}", shown)?;
+        execute!(stdout, ResetColor)?;
+        execute!(stdout, SetBackgroundColor(effective_theme_bg(state)))?;
+        execute!(stdout, terminal::Clear(ClearType::UntilNewLine))?;
+        execute!(stdout, ResetColor)?;
+        return Ok(());
+    }
+
     // If in find mode, show the find prompt on left and hit count/position on right
     if state.find_active {
         let digits = state.settings.appearance.line_number_digits as usize;
@@ -457,6 +628,7 @@ pub(crate) fn render_footer(
         let mode_char = if state.find_regex_mode { 'R' } else { 'W' };
         left_side.push(mode_char);
         left_side.push(']');
+        left_side.push_str(&find_flags_label(state));
         left_side.push(':');
         left_side.push(' ');
 
@@ -477,15 +649,7 @@ pub(crate) fn render_footer(
             width_l = max_line_w, width_c = max_col_w);
 
         // Always show hit count with arrows
-        let hit_display = if state.search_hit_count > 0 {
-            if state.search_current_hit > 0 {
-                format!("({}/{}) ↑↓", state.search_current_hit, state.search_hit_count)
-            } else {
-                format!("(-/{}) ↑↓", state.search_hit_count)
-            }
-        } else {
-            "(0) ↑↓".to_string()
-        };
+        let hit_display = search_hit_display(state);
 
         // Add trailing space for better right margin
         let right_side = format!("{}  {} ", hit_display, position_info);
@@ -499,32 +663,46 @@ pub(crate) fn render_footer(
         write!(stdout, "Find ")?;
 
         // Render mode toggle with U+21C4 (⇄) character
-        // Format: "Find [⇄R]:" or "Find [⇄W]:"
+        // Format: "Find [⇄R]:" or "Find [⇄W]:", plus any "[Case]" / "[Word]" flags
         let mode_char = if state.find_regex_mode { 'R' } else { 'W' };
-        write!(stdout, "[\u{21C4}{}]: ", mode_char)?;
+        write!(stdout, "[\u{21C4}{}]{}: ", mode_char, find_flags_label(state))?;
+
+        // Only the part of a long pattern around the cursor fits between the
+        // prompt and the right side; "…" marks text scrolled out on either end
+        let chars: Vec<char> = state.find_pattern.chars().collect();
+        let pattern_width = total_width
+            .saturating_sub(pattern_start_col + right_side.chars().count() + 1)
+            .max(4);
+        let (win_start, win_end) = find_pattern_window(&chars, state.find_cursor_pos, pattern_width);
+        let mut pattern_cols = 0;
+        if win_start > 0 {
+            write!(stdout, "…")?;
+            pattern_cols += 1;
+        }
 
         // Write find pattern with selection highlighting
-        if let Some((sel_start, sel_end)) = state.find_selection {
-            let chars: Vec<char> = state.find_pattern.chars().collect();
-            for (i, ch) in chars.iter().enumerate() {
-                if i == sel_start {
-                    // Start selection - invert colors
-                    execute!(stdout, crossterm::style::SetAttribute(crossterm::style::Attribute::Reverse))?;
-                }
-                write!(stdout, "{}", ch)?;
-                if i + 1 == sel_end {
-                    // End selection - restore colors
-                    execute!(stdout, crossterm::style::SetAttribute(crossterm::style::Attribute::NoReverse))?;
-                    execute!(stdout, SetBackgroundColor(effective_theme_bg(state)))?;
-                }
+        let (sel_start, sel_end) = state.find_selection.unwrap_or((usize::MAX, usize::MAX));
+        for (i, ch) in chars.iter().enumerate().take(win_end).skip(win_start) {
+            if i == sel_start || (i == win_start && sel_start < i && i < sel_end) {
+                // Start selection - invert colors
+                execute!(stdout, crossterm::style::SetAttribute(crossterm::style::Attribute::Reverse))?;
             }
-        } else {
-            write!(stdout, "{}", state.find_pattern)?;
+            write!(stdout, "{}", ch)?;
+            pattern_cols += crate::coordinates::char_visual_width_pub(*ch, 0, 1);
+            if i + 1 == sel_end || (i + 1 == win_end && sel_start <= i && i < sel_end) {
+                // End selection - restore colors
+                execute!(stdout, crossterm::style::SetAttribute(crossterm::style::Attribute::NoReverse))?;
+                execute!(stdout, SetBackgroundColor(effective_theme_bg(state)))?;
+            }
+        }
+        if win_end < chars.len() {
+            write!(stdout, "…")?;
+            pattern_cols += 1;
         }
 
         // Update left_side length to account for the pattern we just wrote
         // Use chars().count() so multi-byte chars like ⇄ are counted as 1 column each
-        let full_left_len = left_side.chars().count() + state.find_pattern.chars().count();
+        let full_left_len = left_side.chars().count() + pattern_cols;
 
         // Calculate right-aligned position (same method as normal mode)
         // In normal mode: remaining_width = total_width - left_len
@@ -549,9 +727,12 @@ pub(crate) fn render_footer(
         execute!(stdout, terminal::Clear(ClearType::UntilNewLine))?;
         execute!(stdout, ResetColor)?;
 
-        // Position cursor at find_cursor_pos within the search pattern
-        let chars: Vec<char> = state.find_pattern.chars().collect();
-        let cursor_offset = chars.iter().take(state.find_cursor_pos).count();
+        // Position cursor at find_cursor_pos within the visible part of the pattern
+        let cursor_offset = usize::from(win_start > 0)
+            + chars[win_start..state.find_cursor_pos.clamp(win_start, chars.len())]
+                .iter()
+                .map(|&ch| crate::coordinates::char_visual_width_pub(ch, 0, 1))
+                .sum::<usize>();
         let cursor_x = (pattern_start_col + cursor_offset) as u16;
         execute!(stdout, cursor::MoveTo(cursor_x, footer_row))?;
         apply_cursor_shape(stdout, state.settings)?;
@@ -579,7 +760,7 @@ pub(crate) fn render_footer(
         let position_info = format!("{}:{}", line_num, col_num);
 
         // Show buttons for replace operations
-        let buttons = "[replace occurrence] [replace all]";
+        let buttons = replace_footer_buttons(state);
         // Add trailing space for better right margin
         let right_side = format!("{}  {} ", buttons, position_info);
 
@@ -640,6 +821,12 @@ pub(crate) fn render_footer(
         return Ok(());
     }
 
+    // Zen mode hides the position footer; prompts and status messages still show
+    if state.zen_mode && state.status_message.is_none() && !state.goto_line_active {
+        execute!(stdout, ResetColor, terminal::Clear(ClearType::CurrentLine))?;
+        return Ok(());
+    }
+
     // Normal footer with position info (or error message)
     let line_num = state.absolute_line() + 1;
     let col_num = state.cursor_col + 1;
@@ -659,15 +846,7 @@ pub(crate) fn render_footer(
         let max_col = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) + 1;
         let max_col_w = if max_col == 0 { 1 } else { ((max_col as f64).log10().floor() as usize) + 1 };
 
-        let hit_display = if state.search_hit_count > 0 {
-            if state.search_current_hit > 0 {
-                format!("({}/{}) ↑↓", state.search_current_hit, state.search_hit_count)
-            } else {
-                format!("(-/{}) ↑↓", state.search_hit_count)
-            }
-        } else {
-            "(0) ↑↓".to_string()
-        };
+        let hit_display = search_hit_display(state);
 
         // In goto_line mode keep the raw input; otherwise pad both numbers to fixed widths
         let pos_str = if state.goto_line_active {
@@ -691,6 +870,29 @@ pub(crate) fn render_footer(
     // close the outer if/else for markdown_rendered
     };
 
+    // Selection extent goes left of the position so the position stays right-aligned
+    let position_info = match selection_summary(state, lines) {
+        Some(summary) if !state.markdown_rendered && !state.goto_line_active => {
+            format!("{}  {}", summary, position_info)
+        }
+        _ => position_info,
+    };
+
+    // Encoding and line ending the buffer is saved with
+    let position_info = if !state.markdown_rendered && !state.goto_line_active {
+        format!("{}  {}  {}", state.encoding.label(), state.line_ending.label(), position_info)
+    } else {
+        position_info
+    };
+
+    // Syntax language of the buffer, detected or chosen with set_syntax
+    let position_info = match crate::syntax::active_syntax() {
+        Some(ext) if !state.markdown_rendered && !state.goto_line_active => {
+            format!("{}  {}", crate::syntax::language_name(&ext), position_info)
+        }
+        _ => position_info,
+    };
+
     let total_width = state.term_width as usize;
     let digits = state.settings.appearance.line_number_digits as usize;
     let mut bottom_number_str = String::new();
@@ -708,7 +910,7 @@ pub(crate) fn render_footer(
         // Use the larger of line_number_digits or actual_width
         let display_width = actual_width.max(digits);
 
-        let modulus = 10usize.pow(digits as u32);
+        let modulus = 10usize.saturating_pow(digits as u32);
         let mut last_visible_line = state.top_line;
         let mut remaining = visible_lines;
         let text_width = crate::coordinates::calculate_text_width(state, lines, visible_lines);
@@ -751,8 +953,7 @@ pub(crate) fn render_footer(
 
     // Apply scrollbar color highlighting if needed before writing digit hint
     if highlight_digit_hint {
-        use crossterm::style::Color;
-        execute!(stdout, SetBackgroundColor(Color::Rgb { r: 100, g: 149, b: 237 }))?;
+        execute!(stdout, SetBackgroundColor(state.settings.theme.scrollbar_fg))?;
     }
     write!(stdout, "{}", bottom_number_str)?;
     if highlight_digit_hint {
@@ -764,7 +965,20 @@ pub(crate) fn render_footer(
     // Write space separator
     write!(stdout, " ")?;
 
-    let left_len = bottom_number_str.len() + 1; // +1 for the space separator
+    let mut left_len = bottom_number_str.len() + 1; // +1 for the space separator
+
+    // Block selection / multi-cursor mode indicator, left-aligned so the
+    // right-aligned position info (and its click targets) stays put
+    if state.status_message.is_none()
+        && let Some(indicator) = footer_mode_indicator(state)
+    {
+        use crossterm::style::SetForegroundColor;
+        execute!(stdout, SetForegroundColor(crossterm::style::Color::Cyan))?;
+        write!(stdout, "{}", indicator)?;
+        execute!(stdout, ResetColor)?;
+        execute!(stdout, SetBackgroundColor(effective_theme_bg(state)))?;
+        left_len += indicator.chars().count();
+    }
 
     let remaining_width = total_width.saturating_sub(left_len);
 
@@ -804,22 +1018,55 @@ pub(crate) fn render_footer(
     Ok(())
 }
 
-/// Rebuild syntax context by scanning lines from start to target line
-/// This establishes the correct syntax stack state for rendering
-fn rebuild_syntax_context(lines: &[String], target_line: usize) {
-    for (line_idx, line) in lines.iter().enumerate() {
-        if line_idx >= target_line {
-            break;
-        }
-        // Get highlights and check for syntax switches
-        let (_highlights, switch_action) = crate::syntax::highlight_line(line);
-        if let Some((is_switch_back, extension)) = switch_action {
-            if is_switch_back {
-                crate::syntax::pop_syntax();
-            } else {
-                crate::syntax::push_syntax(&extension);
-            }
-        }
+/// Footer summary of a non-empty selection: "Sel: 12 chars, 3 lines", or the
+/// rectangle "Sel: 4 cols x 3 lines" for block selections
+pub(crate) fn selection_summary(state: &FileViewerState, lines: &[String]) -> Option<String> {
+    let ((start_line, start_col), (end_line, end_col)) = state.selection_range()?;
+    if (start_line, start_col) == (end_line, end_col) {
+        return None;
+    }
+    let plural = |n: usize, unit: &str| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+    let line_count = end_line - start_line + 1;
+    if state.block_selection {
+        return Some(format!(
+            "Sel: {} x {}",
+            plural(end_col.abs_diff(start_col), "col"),
+            plural(line_count, "line")
+        ));
+    }
+
+    let len = |idx: usize| lines.get(idx).map_or(0, |l| l.chars().count());
+    let chars = if start_line == end_line {
+        end_col.min(len(end_line)).saturating_sub(start_col)
+    } else {
+        // Each line break inside the selection counts as one character
+        let first = len(start_line).saturating_sub(start_col) + 1;
+        let middle: usize = (start_line + 1..end_line).map(|idx| len(idx) + 1).sum();
+        first + middle + end_col.min(len(end_line))
+    };
+    Some(format!("Sel: {}, {}", plural(chars, "char"), plural(line_count, "line")))
+}
+
+/// Footer label for the active editing mode: "[BLOCK]" for block selection,
+/// "[N cursors]" when multiple cursors are placed, prefixed by "[OVR]" in overwrite mode
+pub(crate) fn footer_mode_indicator(state: &FileViewerState) -> Option<String> {
+    let selection = if state.has_multi_cursors() {
+        Some(format!("[{} cursors]", state.multi_cursors.len() + 1))
+    } else if state.block_selection && state.selection_start.is_some() {
+        Some("[BLOCK]".to_string())
+    } else {
+        None
+    };
+    let mode = match (state.overwrite_mode, selection) {
+        (true, Some(sel)) => Some(format!("[OVR] {}", sel)),
+        (true, None) => Some("[OVR]".to_string()),
+        (false, sel) => sel,
+    };
+    let follow = state.follow.as_ref().map(|f| if f.paused { "[FOLLOW paused]" } else { "[FOLLOW]" });
+    match (follow, mode) {
+        (Some(follow), Some(mode)) => Some(format!("{} {}", follow, mode)),
+        (Some(follow), None) => Some(follow.to_string()),
+        (None, mode) => mode,
     }
 }
 
@@ -849,11 +1096,12 @@ fn render_visible_lines(
         lines,
         state,
         visible_lines,
+        cursor_column: cursor_column_highlight(lines, state, text_width_u16 as usize),
+        color_columns: color_column_positions(state, text_width_u16 as usize),
     };
 
-    // Reset syntax stack and rebuild context from document start to top_line
-    crate::syntax::clear_syntax_stack();
-    rebuild_syntax_context(lines, state.top_line);
+    // Restore the syntax context (embedded languages, open block comments) at top_line
+    crate::syntax::restore_line_state(lines, state.top_line);
 
     let mut visual_lines_rendered = 0;
 
@@ -885,7 +1133,9 @@ fn render_visible_lines(
 
     // Fill remaining content lines with empty lines
     while visual_lines_rendered < content_lines {
-        if state.settings.appearance.line_number_digits > 0 {
+        if state.zen_mode {
+            write_zen_margin(stdout, state)?;
+        } else if state.settings.appearance.line_number_digits > 0 {
             execute!(stdout, SetBackgroundColor(effective_theme_bg(state)))?;
             write!(
                 stdout,
@@ -929,7 +1179,7 @@ fn render_visible_lines_rendered(
     let line_num_digits = state.settings.appearance.line_number_digits as usize;
     let total_lines = rendered_lines.len();
     let modulus = if line_num_digits > 0 {
-        10usize.pow(state.settings.appearance.line_number_digits as u32)
+        10usize.saturating_pow(state.settings.appearance.line_number_digits as u32)
     } else {
         1
     };
@@ -952,7 +1202,10 @@ fn render_visible_lines_rendered(
             let line = &rendered_lines[logical_line_index];
 
             // --- Gutter (no cursor highlight in rendered/read-only view) ---
-            if line_num_digits > 0 {
+            if state.zen_mode {
+                write_zen_margin(stdout, state)?;
+                current_col = gutter_width as u16;
+            } else if line_num_digits > 0 {
                 let line_num = (logical_line_index + 1) % modulus;
                 execute!(stdout, SetBackgroundColor(effective_theme_bg(state)))?;
                 write!(stdout, "{:width$} ", line_num, width = line_num_digits)?;
@@ -1016,7 +1269,10 @@ fn render_visible_lines_rendered(
             }
         } else {
             // Past end of document — just write the gutter and leave the rest for padding.
-            if line_num_digits > 0 {
+            if state.zen_mode {
+                write_zen_margin(stdout, state)?;
+                current_col = gutter_width as u16;
+            } else if line_num_digits > 0 {
                 execute!(stdout, SetBackgroundColor(effective_theme_bg(state)))?;
                 write!(stdout, "{:width$} ", "", width = line_num_digits)?;
                 execute!(stdout, ResetColor)?;
@@ -1077,6 +1333,10 @@ struct RenderContext<'a> {
     lines: &'a [String],
     state: &'a FileViewerState<'a>,
     visible_lines: usize,
+    /// Text-area column of the cursor, tinted on every row (`cursor_column_highlight`)
+    cursor_column: Option<usize>,
+    /// Text-area columns of the `color_columns` rulers
+    color_columns: Vec<usize>,
 }
 
 struct SegmentInfo {
@@ -1090,6 +1350,26 @@ struct SegmentInfo {
     /// Printable (ANSI-stripped) column at the end of this segment.
     end_printable: usize,
     tab_width: usize,
+    /// Screen columns taken by the continuation prefix/indent before the text.
+    indent: usize,
+}
+
+/// Fill the first `indent` columns of a continuation row with the wrap prefix
+fn write_continuation_prefix(stdout: &mut impl Write, indent: usize, tab_width: usize) -> std::io::Result<()> {
+    let prefix = expand_tabs(&crate::coordinates::wrap_prefix(), tab_width);
+    let mut shown = String::new();
+    let mut width = 0;
+    for ch in prefix.chars() {
+        let ch_width = crate::coordinates::char_visual_width_pub(ch, width, tab_width);
+        if width + ch_width > indent {
+            break;
+        }
+        shown.push(ch);
+        width += ch_width;
+    }
+    execute!(stdout, crossterm::style::SetForegroundColor(crossterm::style::Color::DarkGrey))?;
+    write!(stdout, "{}{}", shown, " ".repeat(indent - width))?;
+    execute!(stdout, ResetColor)
 }
 
 fn render_line(
@@ -1109,10 +1389,17 @@ fn render_line(
     let available_width =
         crate::coordinates::calculate_text_width(ctx.state, ctx.lines, ctx.visible_lines) as usize;
     let tab_width = ctx.state.settings.tab_width;
+    let line_bg = current_line_bg(ctx, logical_line_index);
 
     // Expand tabs to spaces for display
-    let expanded_line = expand_tabs(line, tab_width);
-    let chars: Vec<char> = expanded_line.chars().collect();
+    let expanded_line = expand_whitespace(line, tab_width, ctx.state.show_invisibles);
+    let mut chars: Vec<char> = expanded_line.chars().collect();
+    // Leading whitespace is ASCII, so its visual columns index `chars` directly
+    for col in indent_guide_columns(line, ctx.state) {
+        if matches!(chars.get(col), Some(' ' | '·')) {
+            chars[col] = INDENT_GUIDE;
+        }
+    }
 
     // Check if wrapping is enabled
     let wrapping_enabled = ctx.state.is_line_wrapping_enabled();
@@ -1139,12 +1426,28 @@ fn render_line(
         }
 
         // Show line number only if line_number_digits > 0
-        if ctx.state.settings.appearance.line_number_digits > 0 {
+        if ctx.state.zen_mode {
+            write_zen_margin(stdout, ctx.state)?;
+        } else if ctx.state.settings.appearance.line_number_digits > 0 {
             // Show line number on the first segment of the logical line OR on the first
             // visible continuation segment (when viewport is mid-way through a wrapped line).
             if wrap_index == 0 || wrap_index == first_segment {
+                // The sidebar (drawn over it later) and the blame gutter take the front
+                // of the widened line-number gutter
+                let mut digits = ctx.state.settings.appearance.line_number_digits as usize;
+                if crate::sidebar::is_visible(ctx.state) {
+                    digits = digits.saturating_sub(crate::sidebar::SIDEBAR_WIDTH as usize);
+                    write!(stdout, "{:width$}", "", width = crate::sidebar::SIDEBAR_WIDTH as usize)?;
+                }
+                if let Some(blame) = &ctx.state.blame {
+                    digits -= crate::git::BLAME_GUTTER_WIDTH as usize;
+                    execute!(stdout, SetBackgroundColor(effective_theme_bg(ctx.state)))?;
+                    let text = blame.get(logical_line_index).map(|b| b.gutter_text()).unwrap_or_default();
+                    write!(stdout, "{:width$}", text, width = crate::git::BLAME_GUTTER_WIDTH as usize)?;
+                }
+
                 // Calculate line number to display (modulo based on digits)
-                let modulus = 10usize.pow(ctx.state.settings.appearance.line_number_digits as u32);
+                let modulus = 10usize.saturating_pow(digits as u32);
                 let line_num = (logical_line_index + 1) % modulus;
 
                 // Check if this line contains the cursor
@@ -1155,28 +1458,28 @@ fn render_line(
 
                 // Highlight line number with scrollbar color if cursor line
                 if is_cursor_line {
-                    use crossterm::style::Color;
-                    execute!(stdout, SetBackgroundColor(Color::Rgb { r: 100, g: 149, b: 237 }))?;
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.scrollbar_fg))?;
                 }
 
                 // Write line number
-                write!(
-                    stdout,
-                    "{:width$}",
-                    line_num,
-                    width = ctx.state.settings.appearance.line_number_digits as usize
-                )?;
+                if digits > 0 {
+                    write!(stdout, "{:width$}", line_num, width = digits)?;
+                }
 
                 // Reset to line numbers background before writing indicator
                 if is_cursor_line {
                     execute!(stdout, SetBackgroundColor(effective_theme_bg(ctx.state)))?;
                 }
 
-                // Show '>' for cursor line, space for others
+                // Show '>' for cursor line, the diff gutter marker or a space for others
+                let mark = ctx.state.diff_mark(logical_line_index);
+                if let Some(mark) = mark {
+                    execute!(stdout, crossterm::style::SetForegroundColor(diff_mark_color(mark)))?;
+                }
                 if is_cursor_line {
                     write!(stdout, ">")?;
                 } else {
-                    write!(stdout, " ")?;
+                    write!(stdout, "{}", mark.map_or(' ', diff_mark_char))?;
                 }
 
                 execute!(stdout, ResetColor)?;
@@ -1225,6 +1528,19 @@ fn render_line(
             (start, end, false)
         };
 
+        // Continuation rows start with the wrap prefix, padded to the hanging indent
+        let indent = if wrapping_enabled {
+            crate::coordinates::segment_indent(line, wrap_index, available_width, tab_width)
+        } else {
+            0
+        };
+        if indent > 0 {
+            if let Some(bg) = line_bg {
+                execute!(stdout, SetBackgroundColor(bg))?;
+            }
+            write_continuation_prefix(stdout, indent, tab_width)?;
+        }
+
         // Convert character indices to indices in expanded line
         let original_chars: Vec<char> = line.chars().collect();
         let start_visual = if start_char_idx == 0 {
@@ -1254,6 +1570,7 @@ fn render_line(
                 start_printable,
                 end_printable,
                 tab_width,
+                indent,
             };
 
             if let (Some(sel_start), Some(sel_end)) =
@@ -1278,7 +1595,12 @@ fn render_line(
             // Line is shorter than horizontal scroll offset - render as empty
             // (but still takes up a visual row)
             0
-        };
+        } + indent as u16;
+
+        // The rest of the cursor's row keeps the current-line background
+        if let Some(bg) = line_bg {
+            execute!(stdout, SetBackgroundColor(bg))?;
+        }
 
         // Show wrap indicator if needed
         if show_wrap_indicator {
@@ -1286,9 +1608,31 @@ fn render_line(
             execute!(stdout, SetForegroundColor(crossterm::style::Color::DarkGrey))?;
             write!(stdout, "{}", crate::coordinates::WRAP_INDICATOR)?;
             execute!(stdout, crossterm::style::ResetColor)?;
+            if let Some(bg) = line_bg {
+                execute!(stdout, SetBackgroundColor(bg))?;
+            }
             content_width += 1;
         }
 
+        // Continue the cursor column crosshair and the rulers through rows that end before them
+        let mut stripes: Vec<usize> = ctx.color_columns.iter().copied().chain(ctx.cursor_column).collect();
+        stripes.sort_unstable();
+        stripes.dedup();
+        for column in stripes {
+            if column < content_width as usize || column >= available_width {
+                continue;
+            }
+            write!(stdout, "{}", " ".repeat(column - content_width as usize))?;
+            let bg = if ctx.cursor_column == Some(column) { CURSOR_COLUMN_BG } else { COLOR_COLUMN_BG };
+            execute!(stdout, SetBackgroundColor(bg))?;
+            write!(stdout, " ")?;
+            match line_bg {
+                Some(bg) => execute!(stdout, SetBackgroundColor(bg))?,
+                None => execute!(stdout, ResetColor)?,
+            }
+            content_width = column as u16 + 1;
+        }
+
         // Calculate current column position after rendering content
         let current_col = if ctx.state.settings.appearance.line_number_digits > 0 {
             let line_num_width = ctx.state.settings.appearance.line_number_digits as u16 + 1;
@@ -1297,20 +1641,16 @@ fn render_line(
             content_width
         };
         clear_to_scrollbar(stdout, ctx.state, ctx.lines, ctx.visible_lines, current_col)?;
+        if line_bg.is_some() {
+            execute!(stdout, ResetColor)?;
+        }
     }
 
     // Add newline after the last wrapped segment to separate this logical line from the next
     write!(stdout, "\r\n")?;
 
-    // Apply any syntax switches from this line (after rendering so highlighting is correct for this line)
-    let (_highlights, switch_action) = crate::syntax::highlight_line(line);
-    if let Some((is_switch_back, extension)) = switch_action {
-        if is_switch_back {
-            crate::syntax::pop_syntax();
-        } else {
-            crate::syntax::push_syntax(&extension);
-        }
-    }
+    // Apply any syntax switches and open regions from this line (after rendering so highlighting is correct for this line)
+    crate::syntax::advance_line(line);
 
     Ok(lines_to_render)
 }
@@ -1335,6 +1675,8 @@ thread_local! {
     /// Key: (pattern, scope_repr, total_lines).
     /// Value: per-line vec of (char_start, char_end) ranges.
     static MULTILINE_MATCH_CACHE: MultilineMatchCache = const { RefCell::new(None) };
+    /// Lines holding a search match, keyed by a hash of pattern, mode, scope and buffer content
+    static SEARCH_LINES_CACHE: RefCell<Option<(u64, Vec<usize>)>> = const { RefCell::new(None) };
 }
 
 /// Get character ranges for search matches in a line (with caching).
@@ -1345,7 +1687,7 @@ thread_local! {
 /// `visual_width_up_to`.
 /// For single-line patterns this uses the regex cache; multiline patterns always
 /// return empty — use `get_search_matches_for_line` instead when you have `lines`.
-fn get_search_matches(line: &str, pattern: &str, regex_mode: bool) -> Vec<(usize, usize)> {
+fn get_search_matches(line: &str, pattern: &str, options: crate::find::FindOptions) -> Vec<(usize, usize)> {
     if pattern.is_empty() {
         return vec![];
     }
@@ -1362,13 +1704,13 @@ fn get_search_matches(line: &str, pattern: &str, regex_mode: bool) -> Vec<(usize
     SEARCH_REGEX_CACHE.with(|cache| {
         let mut cache = cache.borrow_mut();
 
-        let cache_key = format!("{}:{}", if regex_mode { "R" } else { "W" }, pattern);
+        let cache_key = format!("{:?}:{}", options, pattern);
 
         let regex = if let Some((cached_key, cached_regex)) = cache.as_ref() {
             if cached_key == &cache_key {
                 cached_regex
             } else {
-                match crate::find::pattern_to_regex(pattern, regex_mode) {
+                match crate::find::pattern_to_regex(pattern, options) {
                     Ok(regex) => {
                         *cache = Some((cache_key, regex));
                         &cache.as_ref().unwrap().1
@@ -1377,7 +1719,7 @@ fn get_search_matches(line: &str, pattern: &str, regex_mode: bool) -> Vec<(usize
                 }
             }
         } else {
-            match crate::find::pattern_to_regex(pattern, regex_mode) {
+            match crate::find::pattern_to_regex(pattern, options) {
                 Ok(regex) => {
                     *cache = Some((cache_key, regex));
                     &cache.as_ref().unwrap().1
@@ -1409,7 +1751,7 @@ fn get_search_matches_for_line(
     lines: &[String],
     line_index: usize,
     pattern: &str,
-    regex_mode: bool,
+    options: crate::find::FindOptions,
     scope: Option<((usize, usize), (usize, usize))>,
 ) -> Vec<(usize, usize)> {
     if pattern.is_empty() || line_index >= lines.len() {
@@ -1418,15 +1760,16 @@ fn get_search_matches_for_line(
 
     if !crate::find::pattern_is_multiline(pattern) {
         // Fast path: single-line pattern — no need for all-lines context
-        return get_search_matches(&lines[line_index], pattern, regex_mode);
+        return get_search_matches(&lines[line_index], pattern, options);
     }
 
     // Multiline path: look up (or compute) the per-line result cache.
     // Cache key encodes the pattern, lines count, and scope so that it is
     // invalidated whenever any of those change.
     let cache_key = format!(
-        "ML:{}:{}:{:?}",
+        "ML:{}:{:?}:{}:{:?}",
         pattern,
+        options,
         lines.len(),
         scope
     );
@@ -1441,14 +1784,14 @@ fn get_search_matches_for_line(
                 } else {
                     *cache = Some((
                         cache_key,
-                        crate::find::get_multiline_matches_per_line(lines, pattern, scope),
+                        crate::find::get_multiline_matches_per_line(lines, pattern, options, scope),
                     ));
                     &cache.as_ref().unwrap().1
                 }
             } else {
                 *cache = Some((
                     cache_key,
-                    crate::find::get_multiline_matches_per_line(lines, pattern, scope),
+                    crate::find::get_multiline_matches_per_line(lines, pattern, options, scope),
                 ));
                 &cache.as_ref().unwrap().1
             };
@@ -1460,6 +1803,92 @@ fn get_search_matches_for_line(
     })
 }
 
+/// Lines holding at least one match of the highlighted search, in ascending order.
+/// Empty when search highlighting is off.
+fn search_match_lines(lines: &[String], state: &FileViewerState) -> Vec<usize> {
+    use std::hash::{Hash, Hasher};
+
+    let Some(ref pattern) = state.last_search_pattern else {
+        return Vec::new();
+    };
+    if state.large_file {
+        return Vec::new();
+    }
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    pattern.hash(&mut hasher);
+    state.last_search_options.hash(&mut hasher);
+    state.find_scope.hash(&mut hasher);
+    lines.hash(&mut hasher);
+    let key = hasher.finish();
+
+    SEARCH_LINES_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some((cached_key, ref cached)) = *cache
+            && cached_key == key
+        {
+            return cached.clone();
+        }
+        let found: Vec<usize> = (0..lines.len())
+            .filter(|&idx| {
+                get_search_matches_for_line(lines, idx, pattern, state.last_search_options, state.find_scope)
+                    .into_iter()
+                    .any(|(start, end)| match_overlaps_scope(idx, start, end, state.find_scope))
+            })
+            .collect();
+        *cache = Some((key, found.clone()));
+        found
+    })
+}
+
+/// Column of the first highlighted search match on `line`
+pub(crate) fn first_search_match_col(lines: &[String], state: &FileViewerState, line: usize) -> Option<usize> {
+    let pattern = state.last_search_pattern.as_ref()?;
+    get_search_matches_for_line(lines, line, pattern, state.last_search_options, state.find_scope)
+        .into_iter()
+        .find(|&(start, end)| match_overlaps_scope(line, start, end, state.find_scope))
+        .map(|(start, _)| start)
+}
+
+/// Scrollbar rows marking search matches, as (row, first matching line on that row).
+/// Rows are proportional to the visual line of each match, like the scrollbar thumb.
+pub(crate) fn search_tick_rows(
+    lines: &[String],
+    state: &FileViewerState,
+    text_width: u16,
+    total_visual_lines: usize,
+    scrollbar_height: usize,
+) -> Vec<(usize, usize)> {
+    let match_lines = search_match_lines(lines, state);
+    if match_lines.is_empty() || total_visual_lines == 0 || scrollbar_height == 0 {
+        return Vec::new();
+    }
+
+    let wrapped = total_visual_lines != lines.len();
+    let mut ticks: Vec<(usize, usize)> = Vec::new();
+    let mut visual_start = 0;
+    let mut next_line = 0;
+    for line in match_lines {
+        if wrapped {
+            while next_line < line {
+                visual_start += crate::coordinates::calculate_wrapped_lines_for_line(
+                    lines,
+                    next_line,
+                    text_width,
+                    state.settings.tab_width,
+                ) as usize;
+                next_line += 1;
+            }
+        } else {
+            visual_start = line;
+        }
+        let row = (visual_start * scrollbar_height / total_visual_lines).min(scrollbar_height - 1);
+        if ticks.last().is_none_or(|&(last_row, _)| last_row != row) {
+            ticks.push((row, line));
+        }
+    }
+    ticks
+}
+
 /// Check if a match at (char_start, char_end) on line_idx overlaps with the find_scope
 fn match_overlaps_scope(
     line_idx: usize,
@@ -1490,6 +1919,253 @@ fn match_overlaps_scope(
     }
 }
 
+/// Char range of trailing spaces/tabs on a line, or None if there are none.
+/// When `cursor_col` is inside or after the trailing run (typing at EOL), the run
+/// is skipped so the highlight doesn't flicker on every keystroke.
+fn trailing_whitespace_chars(line: &str, cursor_col: Option<usize>) -> Option<(usize, usize)> {
+    let len = line.chars().count();
+    let start = line.trim_end_matches([' ', '\t']).chars().count();
+    if start == len {
+        return None;
+    }
+    if let Some(col) = cursor_col
+        && col >= start {
+            return None;
+        }
+    Some((start, len))
+}
+
+/// Absolute printable-column range to paint as trailing whitespace for a segment's line
+fn trailing_whitespace_range(
+    original_line: &str,
+    ctx: &RenderContext,
+    segment: &SegmentInfo,
+) -> Option<(usize, usize)> {
+    if !ctx.state.settings.appearance.highlight_trailing_whitespace && !ctx.state.show_invisibles {
+        return None;
+    }
+    let cursor_pos = ctx.state.current_position();
+    let cursor_col = (segment.line_index == cursor_pos.0).then_some(cursor_pos.1);
+    let (start, end) = trailing_whitespace_chars(original_line, cursor_col)?;
+    Some((
+        visual_width_up_to(original_line, start, segment.tab_width),
+        visual_width_up_to(original_line, end, segment.tab_width),
+    ))
+}
+
+/// Diff gutter character of a changed line
+fn diff_mark_char(mark: crate::diff::LineChange) -> char {
+    match mark {
+        crate::diff::LineChange::Added => '+',
+        crate::diff::LineChange::Changed => '~',
+        crate::diff::LineChange::Removed => '-',
+    }
+}
+
+/// Diff gutter color of a changed line, also used for the cursor's '>'
+fn diff_mark_color(mark: crate::diff::LineChange) -> crossterm::style::Color {
+    match mark {
+        crate::diff::LineChange::Added => crossterm::style::Color::DarkGreen,
+        crate::diff::LineChange::Changed => crossterm::style::Color::DarkYellow,
+        crate::diff::LineChange::Removed => crossterm::style::Color::DarkRed,
+    }
+}
+
+/// Character drawn by `indent_guides`
+const INDENT_GUIDE: char = '│';
+
+/// Visual columns of the indent guides in a line's leading whitespace: one at the
+/// start of every indentation level (`indent_size` columns, tabs expanded) that the
+/// whitespace covers. Whitespace-only lines get no guides.
+fn indent_guide_columns(line: &str, state: &FileViewerState) -> Vec<usize> {
+    if !state.settings.appearance.indent_guides || state.markdown_rendered {
+        return Vec::new();
+    }
+    let tab_width = state.settings.tab_width;
+    let leading: String = line.chars().take_while(|&ch| ch == ' ' || ch == '\t').collect();
+    if leading.chars().count() == line.chars().count() {
+        return Vec::new();
+    }
+    let width = crate::coordinates::visual_width(&leading, tab_width);
+    (0..width).step_by(state.settings.indent_size().max(1)).collect()
+}
+
+/// Color the tabs and spaces drawn by `show_invisibles` (indexed like
+/// `visual_to_color`: printable column relative to the segment start)
+fn color_invisibles(
+    visual_to_color: &mut [Option<crossterm::style::Color>],
+    original_line: &str,
+    ctx: &RenderContext,
+    segment: &SegmentInfo,
+) {
+    let color = ctx.state.settings.theme.invisibles_fg;
+    let mut col = 0;
+    let mut in_escape = false;
+    for ch in original_line.chars() {
+        if ch == '\x1b' {
+            in_escape = true;
+            continue;
+        }
+        if in_escape {
+            in_escape = !ch.is_ascii_alphabetic();
+            continue;
+        }
+        let width = crate::coordinates::char_visual_width_pub(ch, col, segment.tab_width);
+        if ch == ' ' || ch == '\t' {
+            for abs in col..col + width {
+                if let Some(slot) = abs
+                    .checked_sub(segment.start_printable)
+                    .and_then(|rel| visual_to_color.get_mut(rel))
+                {
+                    *slot = Some(color);
+                }
+            }
+        }
+        col += width;
+    }
+}
+
+/// Absolute printable-column ranges of TODO markers (see `todo_markers` setting) on a segment's line
+fn todo_marker_ranges(original_line: &str, segment: &SegmentInfo, markers: &[String]) -> Vec<(usize, usize)> {
+    crate::find::todo_marker_ranges(original_line, markers)
+        .into_iter()
+        .map(|(start, end)| {
+            (
+                visual_width_up_to(original_line, start, segment.tab_width),
+                visual_width_up_to(original_line, end, segment.tab_width),
+            )
+        })
+        .collect()
+}
+
+/// Absolute printable-column range to tint on a line wider than `line_length_limit`:
+/// the overflowing part, or the whole line when `line_length_highlight` is "line"
+fn over_limit_range(original_line: &str, ctx: &RenderContext, segment: &SegmentInfo) -> Option<(usize, usize)> {
+    let appearance = &ctx.state.settings.appearance;
+    if appearance.line_length_limit == 0 {
+        return None;
+    }
+    let width = visual_width_up_to(original_line, original_line.chars().count(), segment.tab_width);
+    if width <= appearance.line_length_limit {
+        return None;
+    }
+    let start = if appearance.line_length_highlight == "line" { 0 } else { appearance.line_length_limit };
+    Some((start, width))
+}
+
+/// Background of text past the line length limit
+const OVER_LIMIT_BG: crossterm::style::Color = crossterm::style::Color::Rgb { r: 90, g: 40, b: 40 };
+
+/// Background behind TODO markers
+const TODO_MARKER_BG: crossterm::style::Color = crossterm::style::Color::DarkYellow;
+
+/// Absolute printable-column ranges of `suspicious_chars` on a segment's line.
+/// Zero-width characters take no column, so they mark the cell that follows them.
+fn suspicious_char_ranges(original_line: &str, ctx: &RenderContext, segment: &SegmentInfo) -> Vec<(usize, usize)> {
+    if !ctx.state.settings.appearance.highlight_suspicious_chars {
+        return Vec::new();
+    }
+    let set = crate::char_info::parse_char_set(&ctx.state.settings.suspicious_chars);
+    crate::char_info::suspicious_char_indices(original_line, &set)
+        .into_iter()
+        .map(|idx| {
+            let start = visual_width_up_to(original_line, idx, segment.tab_width);
+            let end = visual_width_up_to(original_line, idx + 1, segment.tab_width);
+            (start, end.max(start + 1))
+        })
+        .collect()
+}
+
+/// Background behind suspicious characters
+const SUSPICIOUS_CHAR_BG: crossterm::style::Color = crossterm::style::Color::Magenta;
+
+/// Text-area column of the cursor for the crosshair highlight, relative to the
+/// start of the cursor's wrap segment (or the horizontal scroll offset)
+fn cursor_column_highlight(lines: &[String], state: &FileViewerState, text_width: usize) -> Option<usize> {
+    if !state.settings.appearance.cursor_column_highlight || state.markdown_rendered {
+        return None;
+    }
+    let (line_idx, col) = state.current_position();
+    let line = lines.get(line_idx)?;
+    let col = col.min(line.chars().count());
+    let tab_width = state.settings.tab_width;
+    let cursor_visual = visual_width_up_to(line, col, tab_width);
+    if state.is_line_wrapping_enabled() {
+        let wrap_points = crate::coordinates::calculate_word_wrap_points(line, text_width, tab_width);
+        let segment = wrap_points.iter().take_while(|&&point| point <= col).count();
+        let segment_start = if segment == 0 { 0 } else { wrap_points[segment - 1] };
+        let indent = crate::coordinates::segment_indent(line, segment, text_width, tab_width);
+        Some(indent + cursor_visual - visual_width_up_to(line, segment_start, tab_width))
+    } else {
+        cursor_visual.checked_sub(state.horizontal_scroll_offset)
+    }
+}
+
+/// Absolute printable column of the cursor crosshair on a segment's row
+fn cursor_column_on_segment(ctx: &RenderContext, segment: &SegmentInfo) -> Option<usize> {
+    let origin = if ctx.state.is_line_wrapping_enabled() {
+        segment.start_printable
+    } else {
+        ctx.state.horizontal_scroll_offset
+    };
+    ctx.cursor_column.and_then(|col| col.checked_sub(segment.indent)).map(|col| origin + col)
+}
+
+/// Faint background of the cursor column crosshair
+const CURSOR_COLUMN_BG: crossterm::style::Color = crossterm::style::Color::Rgb { r: 48, g: 48, b: 48 };
+
+/// Text-area columns of the `color_columns` rulers (1-based settings values),
+/// shifted by the horizontal scroll offset and limited to the text width
+fn color_column_positions(state: &FileViewerState, text_width: usize) -> Vec<usize> {
+    if state.markdown_rendered {
+        return Vec::new();
+    }
+    let offset = if state.is_line_wrapping_enabled() { 0 } else { state.horizontal_scroll_offset };
+    state
+        .settings
+        .appearance
+        .color_columns
+        .iter()
+        .filter_map(|&column| column.checked_sub(1)?.checked_sub(offset))
+        .filter(|&column| column < text_width)
+        .collect()
+}
+
+/// Absolute printable columns of the rulers on a segment's row
+fn color_columns_on_segment(ctx: &RenderContext, segment: &SegmentInfo) -> Vec<usize> {
+    let origin = if ctx.state.is_line_wrapping_enabled() {
+        segment.start_printable
+    } else {
+        ctx.state.horizontal_scroll_offset
+    };
+    ctx.color_columns
+        .iter()
+        .filter_map(|col| col.checked_sub(segment.indent))
+        .map(|col| origin + col)
+        .collect()
+}
+
+/// Background of every row of the cursor's line when `current_line_highlight` is on
+fn current_line_bg(ctx: &RenderContext, line_index: usize) -> Option<crossterm::style::Color> {
+    let state = ctx.state;
+    (state.settings.appearance.current_line_highlight
+        && !state.markdown_rendered
+        && line_index == state.absolute_line())
+    .then_some(state.settings.theme.current_line_bg)
+}
+
+/// Subtle background of the `color_columns` rulers
+const COLOR_COLUMN_BG: crossterm::style::Color = crossterm::style::Color::Rgb { r: 38, g: 38, b: 38 };
+
+/// Cursor shape for the text area: overwrite mode always shows a block
+fn apply_text_cursor_shape(stdout: &mut impl Write, state: &FileViewerState) -> std::io::Result<()> {
+    if state.overwrite_mode {
+        write!(stdout, "\x1b[1 q")
+    } else {
+        apply_cursor_shape(stdout, state.settings)
+    }
+}
+
 fn apply_cursor_shape(
     stdout: &mut impl Write,
     settings: &crate::settings::Settings,
@@ -1674,7 +2350,8 @@ fn position_cursor(
 
                         // Calculate visual position within segment
                         let segment_start_visual = visual_width_up_to(line, segment_start_char, tab_width);
-                        let cursor_x_in_segment = visual_col - segment_start_visual;
+                        let cursor_x_in_segment = visual_col - segment_start_visual
+                            + crate::coordinates::segment_indent(line, segment_idx, text_width_usize, tab_width);
                         let cursor_x = cursor_x_in_segment as u16 + line_num_width;
 
                         (cursor_x, segment_idx as u16)
@@ -1687,13 +2364,14 @@ fn position_cursor(
 
                 cursor_y += wrapped_offset;
                 execute!(stdout, cursor::MoveTo(cursor_x, cursor_y))?;
-                apply_cursor_shape(stdout, state.settings)?;
+                apply_text_cursor_shape(stdout, state)?;
                 execute!(stdout, cursor::Show)?;
                 return Ok(());
             }
         }
     }
-    if !state.is_cursor_visible(lines, visible_lines, text_width) {
+    // Secondary cursors are drawn even when the primary one is scrolled out of view
+    if !state.has_multi_cursors() && !state.is_cursor_visible(lines, visible_lines, text_width) {
         return Ok(());
     }
 
@@ -1777,7 +2455,8 @@ fn position_cursor(
 
                     // Calculate visual position within segment
                     let segment_start_visual = visual_width_up_to(line, segment_start_char, tab_width);
-                    let cursor_x_in_segment = visual_col - segment_start_visual;
+                    let cursor_x_in_segment = visual_col - segment_start_visual
+                        + crate::coordinates::segment_indent(line, segment_idx, text_width_usize, tab_width);
                     let cursor_x = cursor_x_in_segment as u16 + line_num_width;
 
                     (cursor_x, segment_idx as u16)
@@ -1876,7 +2555,8 @@ fn position_cursor(
                     // Calculate position at end of previous segment (where wrap indicator is shown)
                     let segment_start_char = if segment_idx == 0 { 0 } else { wrap_points[segment_idx - 1] };
                     let segment_start_visual = visual_width_up_to(line, segment_start_char, tab_width);
-                    let cursor_x_in_segment = visual_col - segment_start_visual;
+                    let cursor_x_in_segment = visual_col - segment_start_visual
+                        + crate::coordinates::segment_indent(line, segment_idx, text_width_usize, tab_width);
                     let cursor_x = cursor_x_in_segment as u16 + line_num_width;
 
                     (cursor_x, segment_idx as u16)
@@ -1901,7 +2581,8 @@ fn position_cursor(
 
                     // Calculate visual position within segment
                     let segment_start_visual = visual_width_up_to(line, segment_start_char, tab_width);
-                    let cursor_x_in_segment = visual_col - segment_start_visual;
+                    let cursor_x_in_segment = visual_col - segment_start_visual
+                        + crate::coordinates::segment_indent(line, segment_idx, text_width_usize, tab_width);
                     let cursor_x = cursor_x_in_segment as u16 + line_num_width;
 
                     (cursor_x, segment_idx as u16)
@@ -1926,7 +2607,7 @@ fn position_cursor(
         return Ok(());
     }
     execute!(stdout, cursor::MoveTo(cursor_x, cursor_y))?;
-    apply_cursor_shape(stdout, state.settings)?;
+    apply_text_cursor_shape(stdout, state)?;
     execute!(stdout, cursor::Show)?;
     Ok(())
 }
@@ -1963,10 +2644,19 @@ fn render_line_segment_expanded(
         let visual_end = crate::coordinates::visual_width_up_to(original_line, char_end, segment.tab_width)
             .saturating_sub(segment.start_printable);
 
+        let color = ctx.state.settings.theme.syntax_color(color);
         for i in visual_start..visual_end.min(visual_to_color.len()) {
             visual_to_color[i] = Some(color);
         }
     }
+    if ctx.state.show_invisibles {
+        color_invisibles(&mut visual_to_color, original_line, ctx, segment);
+    }
+    for col in indent_guide_columns(original_line, ctx.state) {
+        if let Some(slot) = col.checked_sub(segment.start_printable).and_then(|rel| visual_to_color.get_mut(rel)) {
+            *slot = Some(ctx.state.settings.theme.indent_guide_fg);
+        }
+    }
 
     // Apply search match highlighting; cache current-match printable-col range
     let mut current_match_range: Option<(usize, usize)> = None;
@@ -1975,7 +2665,7 @@ fn render_line_segment_expanded(
             ctx.lines,
             segment.line_index,
             pattern,
-            ctx.state.last_search_regex_mode,
+            ctx.state.last_search_options,
             ctx.state.find_scope,
         );
         let cursor_pos = ctx.state.current_position();
@@ -2006,6 +2696,14 @@ fn render_line_segment_expanded(
         }
     }
 
+    let trailing_ws_range = trailing_whitespace_range(original_line, ctx, segment);
+    let todo_ranges = todo_marker_ranges(original_line, segment, &ctx.state.settings.todo_markers);
+    let suspicious_ranges = suspicious_char_ranges(original_line, ctx, segment);
+    let over_limit = over_limit_range(original_line, ctx, segment);
+    let cursor_column = cursor_column_on_segment(ctx, segment);
+    let color_columns = color_columns_on_segment(ctx, segment);
+    let line_bg = current_line_bg(ctx, segment.line_index);
+
     // Render the segment
     let mut current_color: Option<crossterm::style::Color> = None;
     let mut current_bg: bool = false;
@@ -2043,10 +2741,30 @@ fn render_line_segment_expanded(
                 let array_idx_next = printable_col.saturating_sub(segment.start_printable);
                 let is_sm = visual_to_search_match.get(array_idx_next).copied().unwrap_or(false);
                 let is_cm = current_match_range.map_or(false, |(s, e)| printable_col >= s && printable_col < e);
+                let is_tw = trailing_ws_range.is_some_and(|(s, e)| printable_col >= s && printable_col < e);
+                let is_todo = todo_ranges.iter().any(|&(s, e)| printable_col >= s && printable_col < e);
+                let is_sus = suspicious_ranges.iter().any(|&(s, e)| printable_col >= s && printable_col < e);
+                let is_ol = over_limit.is_some_and(|(s, e)| printable_col >= s && printable_col < e);
+                let is_cc = cursor_column == Some(printable_col);
+                let is_rc = color_columns.contains(&printable_col);
                 if is_cm {
-                    execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 50, g: 100, b: 200 }))?;
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_current_bg))?;
                 } else if is_sm {
-                    execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 100, g: 150, b: 200 }))?;
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_match_bg))?;
+                } else if is_tw {
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.trailing_whitespace_bg))?;
+                } else if is_sus {
+                    execute!(stdout, SetBackgroundColor(SUSPICIOUS_CHAR_BG))?;
+                } else if is_todo {
+                    execute!(stdout, SetBackgroundColor(TODO_MARKER_BG))?;
+                } else if is_ol {
+                    execute!(stdout, SetBackgroundColor(OVER_LIMIT_BG))?;
+                } else if is_cc {
+                    execute!(stdout, SetBackgroundColor(CURSOR_COLUMN_BG))?;
+                } else if is_rc {
+                    execute!(stdout, SetBackgroundColor(COLOR_COLUMN_BG))?;
+                } else if let Some(bg) = line_bg {
+                    execute!(stdout, SetBackgroundColor(bg))?;
                 }
                 if let Some(color) = current_color {
                     execute!(stdout, SetForegroundColor(color))?;
@@ -2066,13 +2784,36 @@ fn render_line_segment_expanded(
             false
         };
 
-        let new_bg_state = is_search_match || is_current_match;
+        let is_trailing_ws = trailing_ws_range.is_some_and(|(s, e)| printable_col >= s && printable_col < e);
+        let is_todo = todo_ranges.iter().any(|&(s, e)| printable_col >= s && printable_col < e);
+        let is_suspicious = suspicious_ranges.iter().any(|&(s, e)| printable_col >= s && printable_col < e);
+        let is_over_limit = over_limit.is_some_and(|(s, e)| printable_col >= s && printable_col < e);
+        let char_width = crate::coordinates::char_visual_width_pub(ch, printable_col, segment.tab_width);
+        let is_cursor_column = cursor_column.is_some_and(|c| c >= printable_col && c < printable_col + char_width.max(1));
+        let is_color_column = color_columns.iter().any(|&c| c >= printable_col && c < printable_col + char_width.max(1));
+
+        let is_current_line = line_bg.is_some();
+        let new_bg_state = is_search_match || is_current_match || is_trailing_ws || is_suspicious || is_todo || is_over_limit || is_cursor_column || is_color_column || is_current_line;
         if new_bg_state != current_bg {
             if new_bg_state {
                 if is_current_match {
-                    execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 50, g: 100, b: 200 }))?;
-                } else {
-                    execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 100, g: 150, b: 200 }))?;
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_current_bg))?;
+                } else if is_search_match {
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_match_bg))?;
+                } else if is_trailing_ws {
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.trailing_whitespace_bg))?;
+                } else if is_suspicious {
+                    execute!(stdout, SetBackgroundColor(SUSPICIOUS_CHAR_BG))?;
+                } else if is_todo {
+                    execute!(stdout, SetBackgroundColor(TODO_MARKER_BG))?;
+                } else if is_over_limit {
+                    execute!(stdout, SetBackgroundColor(OVER_LIMIT_BG))?;
+                } else if is_cursor_column {
+                    execute!(stdout, SetBackgroundColor(CURSOR_COLUMN_BG))?;
+                } else if is_color_column {
+                    execute!(stdout, SetBackgroundColor(COLOR_COLUMN_BG))?;
+                } else if let Some(bg) = line_bg {
+                    execute!(stdout, SetBackgroundColor(bg))?;
                 }
             } else {
                 execute!(stdout, ResetColor)?;
@@ -2083,23 +2824,37 @@ fn render_line_segment_expanded(
             current_bg = new_bg_state;
         } else if new_bg_state {
             if is_current_match {
-                execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 50, g: 100, b: 200 }))?;
+                execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_current_bg))?;
             } else if is_search_match {
-                execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 100, g: 150, b: 200 }))?;
+                execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_match_bg))?;
+            } else if is_trailing_ws {
+                execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.trailing_whitespace_bg))?;
+            } else if is_suspicious {
+                execute!(stdout, SetBackgroundColor(SUSPICIOUS_CHAR_BG))?;
+            } else if is_todo {
+                execute!(stdout, SetBackgroundColor(TODO_MARKER_BG))?;
+            } else if is_over_limit {
+                execute!(stdout, SetBackgroundColor(OVER_LIMIT_BG))?;
+            } else if is_cursor_column {
+                execute!(stdout, SetBackgroundColor(CURSOR_COLUMN_BG))?;
+            } else if is_color_column {
+                execute!(stdout, SetBackgroundColor(COLOR_COLUMN_BG))?;
+            } else if let Some(bg) = line_bg {
+                execute!(stdout, SetBackgroundColor(bg))?;
             }
         }
 
         if desired_color != current_color {
             if let Some(color) = desired_color {
                 execute!(stdout, SetForegroundColor(color))?;
-            } else if !(is_search_match || is_current_match) {
+            } else if !(is_search_match || is_current_match || is_trailing_ws || is_suspicious || is_todo || is_over_limit || is_cursor_column || is_color_column || is_current_line) {
                 execute!(stdout, ResetColor)?;
             }
             current_color = desired_color;
         }
 
         write!(stdout, "{}", ch)?;
-        printable_col += crate::coordinates::char_visual_width_pub(ch, printable_col, segment.tab_width);
+        printable_col += char_width;
     }
 
     if current_color.is_some() || current_bg {
@@ -2148,10 +2903,19 @@ fn render_line_segment_with_selection_expanded(
         let rel_start = abs_start.saturating_sub(segment.start_printable);
         let rel_end   = abs_end.saturating_sub(segment.start_printable);
 
+        let color = ctx.state.settings.theme.syntax_color(color);
         for i in rel_start..rel_end.min(visual_to_color.len()) {
             visual_to_color[i] = Some(color);
         }
     }
+    if ctx.state.show_invisibles {
+        color_invisibles(&mut visual_to_color, original_line, ctx, segment);
+    }
+    for col in indent_guide_columns(original_line, ctx.state) {
+        if let Some(slot) = col.checked_sub(segment.start_printable).and_then(|rel| visual_to_color.get_mut(rel)) {
+            *slot = Some(ctx.state.settings.theme.indent_guide_fg);
+        }
+    }
 
     // Populate search-match highlights
     if let Some(ref pattern) = ctx.state.last_search_pattern {
@@ -2160,7 +2924,7 @@ fn render_line_segment_with_selection_expanded(
             ctx.lines,
             segment.line_index,
             pattern,
-            ctx.state.last_search_regex_mode,
+            ctx.state.last_search_options,
             ctx.state.find_scope,
         );
         for (char_start, char_end) in &matches {
@@ -2209,7 +2973,7 @@ fn render_line_segment_with_selection_expanded(
                 ctx.lines,
                 segment.line_index,
                 pattern,
-                ctx.state.last_search_regex_mode,
+                ctx.state.last_search_options,
                 ctx.state.find_scope,
             );
             for (char_start, char_end) in matches {
@@ -2226,6 +2990,14 @@ fn render_line_segment_with_selection_expanded(
         }
     }
 
+    let trailing_ws_range = trailing_whitespace_range(original_line, ctx, segment);
+    let todo_ranges = todo_marker_ranges(original_line, segment, &ctx.state.settings.todo_markers);
+    let suspicious_ranges = suspicious_char_ranges(original_line, ctx, segment);
+    let over_limit = over_limit_range(original_line, ctx, segment);
+    let cursor_column = cursor_column_on_segment(ctx, segment);
+    let color_columns = color_columns_on_segment(ctx, segment);
+    let line_bg = current_line_bg(ctx, segment.line_index);
+
     let mut current_color: Option<crossterm::style::Color> = None;
     let mut current_bg: Option<&str> = None;
     let mut in_file_ansi = false;
@@ -2256,9 +3028,16 @@ fn render_line_segment_with_selection_expanded(
             if was_in_ansi && !in_file_ansi {
                 if let Some(bg) = current_bg {
                     match bg {
-                        "selection" => execute!(stdout, SetBackgroundColor(crossterm::style::Color::DarkGrey))?,
-                        "current"   => execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 50, g: 100, b: 200 }))?,
-                        "search"    => execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 100, g: 150, b: 200 }))?,
+                        "selection" => execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.selection_bg))?,
+                        "current"   => execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_current_bg))?,
+                        "search"    => execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_match_bg))?,
+                        "trailing"  => execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.trailing_whitespace_bg))?,
+                        "suspicious" => execute!(stdout, SetBackgroundColor(SUSPICIOUS_CHAR_BG))?,
+                        "todo"      => execute!(stdout, SetBackgroundColor(TODO_MARKER_BG))?,
+                        "over_limit" => execute!(stdout, SetBackgroundColor(OVER_LIMIT_BG))?,
+                        "column"    => execute!(stdout, SetBackgroundColor(CURSOR_COLUMN_BG))?,
+                        "ruler"     => execute!(stdout, SetBackgroundColor(COLOR_COLUMN_BG))?,
+                        "line"      => execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.current_line_bg))?,
                         _ => {}
                     }
                     if let Some(color) = current_color {
@@ -2280,12 +3059,34 @@ fn render_line_segment_with_selection_expanded(
             false
         };
 
+        let is_trailing_ws = trailing_ws_range.is_some_and(|(s, e)| printable_col >= s && printable_col < e);
+        let is_todo = todo_ranges.iter().any(|&(s, e)| printable_col >= s && printable_col < e);
+        let is_suspicious = suspicious_ranges.iter().any(|&(s, e)| printable_col >= s && printable_col < e);
+        let is_over_limit = over_limit.is_some_and(|(s, e)| printable_col >= s && printable_col < e);
+        let char_width = crate::coordinates::char_visual_width_pub(ch, printable_col, segment.tab_width);
+        let is_cursor_column = cursor_column.is_some_and(|c| c >= printable_col && c < printable_col + char_width.max(1));
+        let is_color_column = color_columns.iter().any(|&c| c >= printable_col && c < printable_col + char_width.max(1));
+
         let desired_bg = if is_current_match {
             Some("current")
         } else if is_search_match {
             Some("search")
         } else if is_selected {
             Some("selection")
+        } else if is_trailing_ws {
+            Some("trailing")
+        } else if is_suspicious {
+            Some("suspicious")
+        } else if is_todo {
+            Some("todo")
+        } else if is_over_limit {
+            Some("over_limit")
+        } else if is_cursor_column {
+            Some("column")
+        } else if is_color_column {
+            Some("ruler")
+        } else if line_bg.is_some() {
+            Some("line")
         } else {
             None
         };
@@ -2293,13 +3094,34 @@ fn render_line_segment_with_selection_expanded(
         if desired_bg != current_bg {
             match desired_bg {
                 Some("selection") => {
-                    execute!(stdout, SetBackgroundColor(crossterm::style::Color::DarkGrey))?;
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.selection_bg))?;
                 }
                 Some("current") => {
-                    execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 50, g: 100, b: 200 }))?;
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_current_bg))?;
                 }
                 Some("search") => {
-                    execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 100, g: 150, b: 200 }))?;
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_match_bg))?;
+                }
+                Some("trailing") => {
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.trailing_whitespace_bg))?;
+                }
+                Some("suspicious") => {
+                    execute!(stdout, SetBackgroundColor(SUSPICIOUS_CHAR_BG))?;
+                }
+                Some("todo") => {
+                    execute!(stdout, SetBackgroundColor(TODO_MARKER_BG))?;
+                }
+                Some("over_limit") => {
+                    execute!(stdout, SetBackgroundColor(OVER_LIMIT_BG))?;
+                }
+                Some("column") => {
+                    execute!(stdout, SetBackgroundColor(CURSOR_COLUMN_BG))?;
+                }
+                Some("ruler") => {
+                    execute!(stdout, SetBackgroundColor(COLOR_COLUMN_BG))?;
+                }
+                Some("line") => {
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.current_line_bg))?;
                 }
                 _ => {
                     execute!(stdout, ResetColor)?;
@@ -2312,21 +3134,21 @@ fn render_line_segment_with_selection_expanded(
         if desired_color != current_color {
             if let Some(color) = desired_color {
                 execute!(stdout, SetForegroundColor(color))?;
-            } else if !(is_search_match || is_current_match || is_selected) {
+            } else if !(is_search_match || is_current_match || is_selected || is_trailing_ws || is_suspicious || is_todo || is_over_limit || is_cursor_column || is_color_column || line_bg.is_some()) {
                 execute!(stdout, ResetColor)?;
                 if is_search_match {
-                    execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 100, g: 150, b: 200 }))?;
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_match_bg))?;
                 } else if is_current_match {
-                    execute!(stdout, SetBackgroundColor(crossterm::style::Color::Rgb { r: 50, g: 100, b: 200 }))?;
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.search_current_bg))?;
                 } else if is_selected {
-                    execute!(stdout, SetBackgroundColor(crossterm::style::Color::DarkGrey))?;
+                    execute!(stdout, SetBackgroundColor(ctx.state.settings.theme.selection_bg))?;
                 }
             }
             current_color = desired_color;
         }
 
         write!(stdout, "{}", ch)?;
-        printable_col += crate::coordinates::char_visual_width_pub(ch, printable_col, segment.tab_width);
+        printable_col += char_width;
     }
 
     if current_color.is_some() || current_bg.is_some() {
@@ -2345,7 +3167,7 @@ fn clear_to_scrollbar(
     current_column: u16,
 ) -> Result<(), std::io::Error> {
     // Always reserve space for scrollbar to prevent text jumping
-    let end_column = state.term_width.saturating_sub(1); // Stop before scrollbar
+    let end_column = crate::minimap::start_column(state); // Stop before minimap and scrollbar
 
     // Fill with spaces from current position to end_column
     let spaces_needed = end_column.saturating_sub(current_column);
@@ -2355,6 +3177,51 @@ fn clear_to_scrollbar(
     Ok(())
 }
 
+/// Char range `[start, end)` of the find pattern shown in `width` columns.
+/// A pattern that does not fit scrolls so the cursor stays visible, reserving
+/// one column for each "…" marker and one for the cursor past the last char.
+fn find_pattern_window(chars: &[char], cursor: usize, width: usize) -> (usize, usize) {
+    let w = |ch: &char| crate::coordinates::char_visual_width_pub(*ch, 0, 1);
+    let cursor = cursor.min(chars.len());
+    let total: usize = chars.iter().map(w).sum();
+    if total + usize::from(cursor == chars.len()) <= width {
+        return (0, chars.len());
+    }
+
+    // Walk back from the cursor while the text before it, the cursor cell and
+    // the markers still fit
+    let cursor_cell = chars.get(cursor).map(w).unwrap_or(1);
+    let right_marker = usize::from(cursor + 1 < chars.len());
+    let mut start = cursor;
+    let mut used = cursor_cell + right_marker;
+    while start > 0 {
+        let left_marker = usize::from(start > 1);
+        if used + w(&chars[start - 1]) + left_marker > width {
+            break;
+        }
+        used += w(&chars[start - 1]);
+        start -= 1;
+    }
+
+    // Fill the rest of the line after the cursor
+    let mut budget = width - usize::from(start > 0);
+    let rest: usize = chars[start..].iter().map(w).sum::<usize>() + usize::from(cursor == chars.len());
+    if rest <= budget {
+        return (start, chars.len());
+    }
+    budget -= 1;
+    let mut end = start;
+    let mut used = 0;
+    while end < chars.len() && used + w(&chars[end]) <= budget {
+        used += w(&chars[end]);
+        end += 1;
+    }
+    (start, end.max(cursor.min(chars.len())))
+}
+
+/// Scrollbar tick color for lines with search matches
+const SEARCH_TICK_FG: crossterm::style::Color = crossterm::style::Color::Yellow;
+
 fn render_scrollbar(
     stdout: &mut impl Write,
     lines: &[String],
@@ -2362,7 +3229,7 @@ fn render_scrollbar(
     visible_lines: usize,
 ) -> Result<(), std::io::Error> {
     use crossterm::cursor::{RestorePosition, SavePosition};
-    use crossterm::style::{ResetColor, SetBackgroundColor};
+    use crossterm::style::{ResetColor, SetBackgroundColor, SetForegroundColor};
 
     // Calculate text width and total visual lines (accounting for wrapping)
     let text_width = crate::coordinates::calculate_text_width(state, lines, visible_lines);
@@ -2371,19 +3238,24 @@ fn render_scrollbar(
     // Save current cursor position to restore later
     execute!(stdout, SavePosition)?;
 
-    // Get colors - use same blue as header/footer for background, light blue for bar
-    let bg_color = effective_theme_bg(state);
-    let bar_color = crossterm::style::Color::Rgb {
-        r: 100,
-        g: 149,
-        b: 237,
-    }; // Light blue
+    if crate::minimap::width(state) > 0 {
+        render_minimap(stdout, lines, state, visible_lines)?;
+    }
+
+    // Get colors - header/footer background for the track, the theme's scrollbar color for the bar
+    // Zen mode keeps the column reserved but leaves it blank
+    let bg_color = if state.zen_mode {
+        crossterm::style::Color::Reset
+    } else {
+        effective_theme_bg(state)
+    };
+    let bar_color = state.settings.theme.scrollbar_fg;
 
     let scrollbar_column = state.term_width - 1;
 
     // Always render scrollbar background to prevent text jumping
     // Only show the actual bar if there are more visual lines than visible
-    if total_visual_lines > visible_lines {
+    if total_visual_lines > visible_lines && !state.zen_mode {
         // Calculate scrollbar dimensions using visual line counts
         let scrollbar_height = visible_lines;
         let bar_height = (visible_lines * visible_lines / total_visual_lines).max(1);
@@ -2431,6 +3303,21 @@ fn render_scrollbar(
                 write!(stdout, " ")?;
             }
         }
+
+        // Search match ticks on top of the track and the bar
+        let ticks = search_tick_rows(lines, state, text_width, total_visual_lines, scrollbar_height);
+        if !ticks.is_empty() {
+            execute!(stdout, SetForegroundColor(SEARCH_TICK_FG))?;
+            for (row, _) in ticks {
+                let on_bar = row >= bar_position && row < bottom_start;
+                execute!(
+                    stdout,
+                    SetBackgroundColor(if on_bar { bar_color } else { bg_color }),
+                    cursor::MoveTo(scrollbar_column, (row + 1) as u16)
+                )?;
+                write!(stdout, "━")?;
+            }
+        }
     } else {
         // No scrolling needed, but render background to reserve space
         execute!(stdout, SetBackgroundColor(bg_color))?;
@@ -2448,6 +3335,91 @@ fn render_scrollbar(
     Ok(())
 }
 
+/// Draw the minimap left of the scrollbar: text shown as blocks, the rows in
+/// view on the header background, rows with search matches in the tick color
+fn render_minimap(
+    stdout: &mut impl Write,
+    lines: &[String],
+    state: &FileViewerState,
+    visible_lines: usize,
+) -> Result<(), std::io::Error> {
+    use crossterm::style::{ResetColor, SetBackgroundColor, SetForegroundColor};
+
+    let start_column = crate::minimap::start_column(state);
+    let cells = crate::minimap::width(state).saturating_sub(1) as usize;
+    let per_row = crate::minimap::lines_per_row(lines.len(), visible_lines);
+    let (first_visible, last_visible) = crate::minimap::viewport_lines(state, lines, visible_lines);
+    let match_lines = search_match_lines(lines, state);
+
+    for row in 0..visible_lines {
+        let first = row * per_row;
+        let last = (first + per_row).min(lines.len());
+        let run = lines.get(first..last).unwrap_or(&[]);
+        let in_view = !run.is_empty() && first <= last_visible && last > first_visible;
+        let has_match = match_lines.iter().any(|&line| line >= first && line < last);
+
+        execute!(stdout, cursor::MoveTo(start_column, (row + 1) as u16), ResetColor)?;
+        write!(stdout, " ")?;
+        if in_view {
+            execute!(stdout, SetBackgroundColor(state.settings.theme.chrome_bg))?;
+        }
+        execute!(
+            stdout,
+            SetForegroundColor(if has_match { SEARCH_TICK_FG } else { crossterm::style::Color::DarkGrey })
+        )?;
+        write!(stdout, "{}", crate::minimap::row_cells(run, cells, state.settings.tab_width))?;
+        execute!(stdout, ResetColor)?;
+    }
+    Ok(())
+}
+
+/// Draw the sidebar over the front of the gutter: a title row with the tree's
+/// root, then the tree with the open file in yellow and the selected entry
+/// highlighted while the sidebar has the focus
+fn render_sidebar(
+    stdout: &mut impl Write,
+    file: &str,
+    state: &FileViewerState,
+    visible_lines: usize,
+) -> Result<(), std::io::Error> {
+    use crossterm::style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor};
+
+    let width = crate::sidebar::SIDEBAR_WIDTH as usize - 1;
+    let bg = effective_theme_bg(state);
+    let fit = |text: &str| -> String {
+        let mut text = truncate_to_width(text, width);
+        let pad = width.saturating_sub(crate::coordinates::visual_width(&text, state.settings.tab_width));
+        text.push_str(&" ".repeat(pad));
+        text
+    };
+    let open_file = std::fs::canonicalize(file).ok();
+
+    crate::sidebar::with_tree(|tree| -> Result<(), std::io::Error> {
+        let rows = crate::sidebar::tree_rows(visible_lines);
+        let root = tree.root.file_name().map_or_else(|| tree.root.display().to_string(), |n| n.to_string_lossy().to_string());
+        execute!(stdout, cursor::MoveTo(0, 1), SetBackgroundColor(bg), SetForegroundColor(Color::Yellow))?;
+        write!(stdout, "{}", fit(&format!(" {}", root)))?;
+        execute!(stdout, SetForegroundColor(Color::DarkGrey))?;
+        write!(stdout, "\u{2502}")?;
+
+        for row in 0..rows {
+            let idx = tree.scroll + row;
+            let entry = tree.entries.get(idx);
+            let is_open = entry.is_some_and(|e| open_file.as_ref() == Some(&e.path));
+            execute!(stdout, cursor::MoveTo(0, (row + 2) as u16), SetBackgroundColor(bg))?;
+            if idx == tree.selected && entry.is_some() && state.sidebar_focused {
+                execute!(stdout, SetBackgroundColor(state.settings.theme.menu_selection_bg))?;
+            }
+            execute!(stdout, SetForegroundColor(if is_open { Color::Yellow } else { Color::Reset }))?;
+            write!(stdout, "{}", fit(&tree.label(idx)))?;
+            execute!(stdout, SetBackgroundColor(bg), SetForegroundColor(Color::DarkGrey))?;
+            write!(stdout, "\u{2502}")?;
+        }
+        execute!(stdout, ResetColor)?;
+        Ok(())
+    })
+}
+
 /// Check if horizontal scrollbar should be shown
 fn should_show_horizontal_scrollbar(
     state: &FileViewerState,
@@ -2512,11 +3484,7 @@ fn render_horizontal_scrollbar(
 
     // Get colors - same as vertical scrollbar
     let bg_color = effective_theme_bg(state);
-    let bar_color = crossterm::style::Color::Rgb {
-        r: 100,
-        g: 149,
-        b: 237,
-    }; // Light blue
+    let bar_color = state.settings.theme.scrollbar_fg;
 
     // Position at last content line (visible_lines), overlaying it
     let h_scrollbar_row = visible_lines as u16;
@@ -2609,6 +3577,12 @@ mod tests {
         assert_eq!(result, "abc de  f");
     }
 
+    #[test]
+    fn expand_whitespace_draws_tabs_and_spaces() {
+        assert_eq!(expand_whitespace("a\tb c", 4, true), "a→  b·c");
+        assert_eq!(expand_whitespace("a\tb c", 4, false), "a   b c");
+    }
+
     #[test]
     fn normalize_selection_ordered_returns_same() {
         let start = (5, 10);
@@ -2645,22 +3619,36 @@ mod tests {
         assert_eq!(e, start);
     }
 
+    #[test]
+    fn search_hit_display_reads_as_match_n_of_total() {
+        let settings = crate::settings::Settings::default();
+        let mut state = FileViewerState::new(80, crate::undo::UndoHistory::new(), &settings);
+        assert_eq!(search_hit_display(&state), "no matches ↑↓");
+        state.search_hit_count = 27;
+        assert_eq!(search_hit_display(&state), "27 matches ↑↓");
+        state.search_current_hit = 3;
+        assert_eq!(search_hit_display(&state), "match 3 of 27 ↑↓");
+        state.search_hit_count = 1;
+        state.search_current_hit = 0;
+        assert_eq!(search_hit_display(&state), "1 match ↑↓");
+    }
+
     #[test]
     fn get_search_matches_empty_pattern_returns_empty() {
-        let matches = get_search_matches("hello world", "", true);
+        let matches = get_search_matches("hello world", "", crate::find::FindOptions::default());
         assert!(matches.is_empty());
     }
 
     #[test]
     fn get_search_matches_simple_literal() {
-        let matches = get_search_matches("hello world", "world", true);
+        let matches = get_search_matches("hello world", "world", crate::find::FindOptions::default());
         assert_eq!(matches.len(), 1);
         assert_eq!(matches[0], (6, 11)); // "world" starts at char 6, ends at 11
     }
 
     #[test]
     fn get_search_matches_multiple_occurrences() {
-        let matches = get_search_matches("hello hello", "hello", true);
+        let matches = get_search_matches("hello hello", "hello", crate::find::FindOptions::default());
         assert_eq!(matches.len(), 2);
         assert_eq!(matches[0], (0, 5));
         assert_eq!(matches[1], (6, 11));
@@ -2668,7 +3656,7 @@ mod tests {
 
     #[test]
     fn get_search_matches_regex_pattern() {
-        let matches = get_search_matches("test123 test456", r"\d+", true);
+        let matches = get_search_matches("test123 test456", r"\d+", crate::find::FindOptions::default());
         assert_eq!(matches.len(), 2);
         assert_eq!(matches[0], (4, 7)); // "123"
         assert_eq!(matches[1], (12, 15)); // "456"
@@ -2676,19 +3664,19 @@ mod tests {
 
     #[test]
     fn get_search_matches_no_match_returns_empty() {
-        let matches = get_search_matches("hello world", "xyz", true);
+        let matches = get_search_matches("hello world", "xyz", crate::find::FindOptions::default());
         assert!(matches.is_empty());
     }
 
     #[test]
     fn get_search_matches_invalid_regex_returns_empty() {
-        let matches = get_search_matches("hello world", "[invalid", true);
+        let matches = get_search_matches("hello world", "[invalid", crate::find::FindOptions::default());
         assert!(matches.is_empty());
     }
 
     #[test]
     fn get_search_matches_handles_multibyte_chars() {
-        let matches = get_search_matches("hello 世界 world", "世界", true);
+        let matches = get_search_matches("hello 世界 world", "世界", crate::find::FindOptions::default());
         assert_eq!(matches.len(), 1);
         assert_eq!(matches[0], (6, 8)); // Character positions, not bytes
     }
@@ -2696,18 +3684,18 @@ mod tests {
     #[test]
     fn get_search_matches_case_insensitive() {
         // Lowercase pattern should match all case variations
-        let matches = get_search_matches("Hello WORLD hello", "hello", true);
+        let matches = get_search_matches("Hello WORLD hello", "hello", crate::find::FindOptions::default());
         assert_eq!(matches.len(), 2);
         assert_eq!(matches[0], (0, 5)); // "Hello"
         assert_eq!(matches[1], (12, 17)); // "hello"
 
         // Search for "world" should match "WORLD" case-insensitively
-        let matches = get_search_matches("Hello WORLD hello", "world", true);
+        let matches = get_search_matches("Hello WORLD hello", "world", crate::find::FindOptions::default());
         assert_eq!(matches.len(), 1);
         assert_eq!(matches[0], (6, 11)); // "WORLD"
 
         // Verify all case variations are found
-        let matches = get_search_matches("Hello hello HELLO HeLLo", "hello", true);
+        let matches = get_search_matches("Hello hello HELLO HeLLo", "hello", crate::find::FindOptions::default());
         assert_eq!(matches.len(), 4);
     }
 
@@ -2808,26 +3796,42 @@ mod tests {
         assert!(!match_overlaps_scope(3, 25, 30, scope));
     }
 
+    #[test]
+    fn trailing_whitespace_chars_finds_spaces_and_tabs() {
+        assert_eq!(trailing_whitespace_chars("foo  \t", None), Some((3, 6)));
+        assert_eq!(trailing_whitespace_chars("foo", None), None);
+        assert_eq!(trailing_whitespace_chars("   ", None), Some((0, 3)));
+    }
+
+    #[test]
+    fn trailing_whitespace_chars_skips_run_at_cursor() {
+        // Cursor at EOL or inside the run: no highlight while typing
+        assert_eq!(trailing_whitespace_chars("foo  ", Some(5)), None);
+        assert_eq!(trailing_whitespace_chars("foo  ", Some(4)), None);
+        // Cursor earlier on the line: the run is still highlighted
+        assert_eq!(trailing_whitespace_chars("foo  ", Some(1)), Some((3, 5)));
+    }
+
     // Performance optimization tests
     #[test]
     fn regex_cache_reuses_same_pattern() {
         // First call should compile and cache
-        let matches1 = get_search_matches("hello world", "world", true);
+        let matches1 = get_search_matches("hello world", "world", crate::find::FindOptions::default());
         assert_eq!(matches1.len(), 1);
         assert_eq!(matches1[0], (6, 11));
 
         // Second call with same pattern should use cache (no recompilation)
-        let matches2 = get_search_matches("goodbye world", "world", true);
+        let matches2 = get_search_matches("goodbye world", "world", crate::find::FindOptions::default());
         assert_eq!(matches2.len(), 1);
         assert_eq!(matches2[0], (8, 13));
 
         // Third call with different pattern should recompile and re-cache
-        let matches3 = get_search_matches("hello world", "hello", true);
+        let matches3 = get_search_matches("hello world", "hello", crate::find::FindOptions::default());
         assert_eq!(matches3.len(), 1);
         assert_eq!(matches3[0], (0, 5));
 
         // Fourth call with original pattern should recompile again (cache was replaced)
-        let matches4 = get_search_matches("world hello world", "world", true);
+        let matches4 = get_search_matches("world hello world", "world", crate::find::FindOptions::default());
         assert_eq!(matches4.len(), 2);
         assert_eq!(matches4[0], (0, 5));
         assert_eq!(matches4[1], (12, 17));
@@ -2835,21 +3839,21 @@ mod tests {
 
     #[test]
     fn regex_cache_handles_empty_pattern() {
-        let matches = get_search_matches("hello world", "", true);
+        let matches = get_search_matches("hello world", "", crate::find::FindOptions::default());
         assert!(matches.is_empty());
 
         // Should still work after empty pattern
-        let matches2 = get_search_matches("hello world", "hello", true);
+        let matches2 = get_search_matches("hello world", "hello", crate::find::FindOptions::default());
         assert_eq!(matches2.len(), 1);
     }
 
     #[test]
     fn regex_cache_handles_invalid_regex() {
-        let matches = get_search_matches("hello world", "[invalid", true);
+        let matches = get_search_matches("hello world", "[invalid", crate::find::FindOptions::default());
         assert!(matches.is_empty());
 
         // Should recover and work with valid pattern
-        let matches2 = get_search_matches("hello world", "hello", true);
+        let matches2 = get_search_matches("hello world", "hello", crate::find::FindOptions::default());
         assert_eq!(matches2.len(), 1);
     }
 
@@ -2875,6 +3879,56 @@ mod tests {
         assert!(!output_str.contains("test.txt (.)"));
     }
 
+    #[test]
+    fn tab_strip_keeps_current_buffer_visible() {
+        use crate::editor_state::FileViewerState;
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let settings = Settings::default();
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        state.buffers = vec![
+            ("/src/main.rs".to_string(), false),
+            ("/src/lib.rs".to_string(), true),
+            ("/README.md".to_string(), false),
+        ];
+        state.buffer_index = 2;
+        state.modified = true;
+        let tabs = tab_strip(&state, 80);
+        assert_eq!(
+            tabs,
+            vec![
+                (" 1:main.rs ".to_string(), false),
+                (" 2:lib.rs* ".to_string(), false),
+                (" 3:README.md* ".to_string(), true),
+            ]
+        );
+
+        // Too narrow: leading tabs go first
+        let tabs = tab_strip(&state, 26);
+        assert_eq!(tabs.first().map(|t| t.0.as_str()), Some(" 2:lib.rs* "));
+        assert!(tabs.last().unwrap().1);
+    }
+
+    #[test]
+    fn render_header_marks_files_not_yet_on_disk() {
+        use crate::editor_state::FileViewerState;
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let settings = Settings::default();
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        let lines = vec!["test".to_string()];
+        let mut output = Vec::new();
+        render_header(&mut output, "test.txt", &state, &lines, 10).unwrap();
+        assert!(!String::from_utf8(output).unwrap().contains("[NEW]"));
+
+        state.is_new = true;
+        let mut output = Vec::new();
+        render_header(&mut output, "test.txt", &state, &lines, 10).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("test.txt () [NEW]"));
+    }
+
     #[test]
     fn render_header_handles_path_with_directory() {
         use crate::editor_state::FileViewerState;
@@ -3087,4 +4141,171 @@ mod tests {
             assert!(scroll_progress <= 1.0);
         }
     }
+
+    #[test]
+    fn footer_mode_indicator_reports_block_and_cursor_count() {
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let settings = Settings::default();
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        assert_eq!(footer_mode_indicator(&state), None);
+
+        state.block_selection = true;
+        state.selection_start = Some((0, 2));
+        assert_eq!(footer_mode_indicator(&state).as_deref(), Some("[BLOCK]"));
+
+        state.block_selection = false;
+        state.selection_start = None;
+        state.multi_cursors = vec![(1, 0), (2, 0)];
+        assert_eq!(footer_mode_indicator(&state).as_deref(), Some("[3 cursors]"));
+
+        state.overwrite_mode = true;
+        assert_eq!(footer_mode_indicator(&state).as_deref(), Some("[OVR] [3 cursors]"));
+        state.multi_cursors.clear();
+        assert_eq!(footer_mode_indicator(&state).as_deref(), Some("[OVR]"));
+    }
+
+    #[test]
+    fn cursor_column_highlight_uses_visual_columns() {
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let mut settings = Settings {
+            line_wrapping: false,
+            ..Default::default()
+        };
+        let lines = vec!["a\tb".to_string(), "日本x".to_string()];
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        state.cursor_line = 1;
+        state.cursor_col = 2;
+        assert_eq!(cursor_column_highlight(&lines, &state, 80), None, "off by default");
+
+        settings.appearance.cursor_column_highlight = true;
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        state.cursor_line = 1;
+        state.cursor_col = 2;
+        assert_eq!(cursor_column_highlight(&lines, &state, 80), Some(4), "wide chars take two cells");
+        state.cursor_line = 0;
+        assert_eq!(cursor_column_highlight(&lines, &state, 80), Some(settings.tab_width), "tab expands");
+        state.horizontal_scroll_offset = 1;
+        assert_eq!(cursor_column_highlight(&lines, &state, 80), Some(settings.tab_width - 1));
+    }
+
+    #[test]
+    fn current_line_highlight_tints_only_the_cursor_line() {
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let mut settings = Settings::default();
+        let lines = vec!["a".to_string(), "b".to_string()];
+        let state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        let ctx = RenderContext { lines: &lines, state: &state, visible_lines: 10, cursor_column: None, color_columns: Vec::new() };
+        assert_eq!(current_line_bg(&ctx, 0), None, "off by default");
+
+        settings.appearance.current_line_highlight = true;
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        state.cursor_line = 1;
+        let ctx = RenderContext { lines: &lines, state: &state, visible_lines: 10, cursor_column: None, color_columns: Vec::new() };
+        assert_eq!(current_line_bg(&ctx, 0), None);
+        assert_eq!(current_line_bg(&ctx, 1), Some(settings.theme.current_line_bg));
+    }
+
+    #[test]
+    fn indent_guides_mark_each_level_of_leading_whitespace() {
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let mut settings = Settings::default();
+        settings.tab_width = 4;
+        settings.indent_size = Some(4);
+        let state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        assert!(indent_guide_columns("        x", &state).is_empty(), "off by default");
+
+        settings.appearance.indent_guides = true;
+        let state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        assert_eq!(indent_guide_columns("        x", &state), vec![0, 4]);
+        assert_eq!(indent_guide_columns("\t  x", &state), vec![0, 4], "tabs expand");
+        assert_eq!(indent_guide_columns("x = 1", &state), Vec::<usize>::new());
+        assert!(indent_guide_columns("        ", &state).is_empty(), "blank lines get none");
+    }
+
+    #[test]
+    fn color_columns_follow_horizontal_scroll() {
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let mut settings = Settings::default();
+        settings.line_wrapping = false;
+        settings.appearance.color_columns = vec![80, 120, 0];
+        let mut state = FileViewerState::new(200, UndoHistory::new(), &settings);
+        assert_eq!(color_column_positions(&state, 100), vec![79], "120 is past the text width");
+        state.horizontal_scroll_offset = 30;
+        assert_eq!(color_column_positions(&state, 100), vec![49, 89]);
+        state.horizontal_scroll_offset = 90;
+        assert_eq!(color_column_positions(&state, 100), vec![29], "scrolled past 80");
+    }
+
+    #[test]
+    fn selection_summary_counts_chars_lines_and_block_size() {
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let settings = Settings::default();
+        let lines: Vec<String> = ["hello world", "äöü", "end"].map(String::from).to_vec();
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        assert_eq!(selection_summary(&state, &lines), None);
+
+        state.selection_start = Some((0, 6));
+        state.selection_end = Some((0, 6));
+        assert_eq!(selection_summary(&state, &lines), None, "empty selection");
+
+        state.selection_end = Some((0, 11));
+        assert_eq!(selection_summary(&state, &lines).as_deref(), Some("Sel: 5 chars, 1 line"));
+
+        // "world\n" + "äöü\n" + "e"
+        state.selection_end = Some((2, 1));
+        assert_eq!(selection_summary(&state, &lines).as_deref(), Some("Sel: 11 chars, 3 lines"));
+
+        state.block_selection = true;
+        state.selection_start = Some((0, 1));
+        state.selection_end = Some((1, 2));
+        assert_eq!(selection_summary(&state, &lines).as_deref(), Some("Sel: 1 col x 2 lines"));
+    }
+
+    #[test]
+    fn find_pattern_window_keeps_cursor_visible() {
+        let chars: Vec<char> = "abcdefghijklmnopqrst".chars().collect();
+        assert_eq!(find_pattern_window(&chars[..9], 9, 10), (0, 9), "short patterns are shown whole");
+        assert_eq!(find_pattern_window(&chars, 0, 10), (0, 9), "\"abcdefghi…\"");
+        assert_eq!(find_pattern_window(&chars, 20, 10), (12, 20), "\"…mnopqrst\" plus the cursor cell");
+        assert_eq!(find_pattern_window(&chars, 10, 10), (3, 11), "\"…defghijk…\"");
+
+        // Wide characters take two columns each
+        let wide: Vec<char> = "日本語テキスト".chars().collect();
+        assert_eq!(find_pattern_window(&wide, 7, 8), (4, 7));
+    }
+
+    #[test]
+    fn search_ticks_map_match_lines_to_scrollbar_rows() {
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let mut settings = Settings::default();
+        settings.line_wrapping = false;
+        let mut lines: Vec<String> = (0..100).map(|i| format!("line {}", i)).collect();
+        lines[5] = "needle".to_string();
+        lines[7] = "needle".to_string();
+        lines[95] = "a needle".to_string();
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        assert!(search_tick_rows(&lines, &state, 80, 100, 10).is_empty(), "no ticks without a search");
+
+        state.last_search_pattern = Some("needle".to_string());
+        // Lines 5 and 7 share the first row; the earlier one is the jump target
+        assert_eq!(search_tick_rows(&lines, &state, 80, 100, 10), vec![(0, 5), (9, 95)]);
+        assert_eq!(first_search_match_col(&lines, &state, 95), Some(2));
+
+        lines[95] = "gone".to_string();
+        assert_eq!(search_tick_rows(&lines, &state, 80, 100, 10), vec![(0, 5)], "edits refresh the cache");
+    }
 }
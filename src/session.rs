@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
 use std::{fs, io, path::PathBuf};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -9,13 +11,55 @@ pub enum SessionMode {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct LastSession {
     pub mode: SessionMode,
+    /// The file that was active when the session ended
     pub file: Option<PathBuf>,
+    /// All files open at quit, in order
+    pub open_files: Vec<PathBuf>,
+    /// Scroll and cursor position of the open files at quit
+    pub views: HashMap<PathBuf, BufferView>,
+    /// Whether the directory tree sidebar was shown
+    pub sidebar_open: bool,
+}
+
+/// Where a buffer was scrolled to and where its cursor was
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct BufferView {
+    /// First visible logical line
+    pub scroll_top: usize,
+    /// Absolute cursor line
+    pub cursor_line: usize,
+    pub cursor_col: usize,
+}
+
+impl LastSession {
+    /// Open files worth restoring, with the index of the previously active one.
+    /// A file qualifies if it still exists on disk or has persisted undo state
+    /// (e.g. an untitled buffer with unsaved content).
+    pub fn restorable_files(&self) -> (Vec<PathBuf>, usize) {
+        let files: Vec<PathBuf> = self
+            .open_files
+            .iter()
+            .filter(|p| {
+                let name = p.to_string_lossy();
+                p.exists()
+                    || crate::undo::UndoHistory::history_path_for(&name, crate::ui::is_untitled(&name))
+                        .map(|h| h.exists())
+                        .unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+        let active = self
+            .file
+            .as_ref()
+            .and_then(|f| files.iter().position(|p| p == f))
+            .unwrap_or(0);
+        (files, active)
+    }
 }
 
 fn session_file_path() -> io::Result<PathBuf> {
-    let data_dir = crate::env::resolve_data_dir()
-        .map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
-    Ok(data_dir.join("last_session"))
+    // Each workspace has its own session
+    Ok(crate::workspace::data_dir()?.join("last_session"))
 }
 
 pub fn load_last_session() -> io::Result<Option<LastSession>> {
@@ -26,6 +70,9 @@ pub fn load_last_session() -> io::Result<Option<LastSession>> {
     let content = fs::read_to_string(&path)?;
     let mut mode: Option<SessionMode> = None;
     let mut file: Option<PathBuf> = None;
+    let mut open_files: Vec<PathBuf> = Vec::new();
+    let mut views = HashMap::new();
+    let mut sidebar_open = false;
     for line in content.lines() {
         let line = line.trim();
         if line.is_empty() {
@@ -40,16 +87,33 @@ pub fn load_last_session() -> io::Result<Option<LastSession>> {
         } else if let Some(rest) = line.strip_prefix("file=") {
             let p = PathBuf::from(rest.trim());
             file = Some(p);
+        } else if let Some(rest) = line.strip_prefix("open=") {
+            open_files.push(PathBuf::from(rest.trim()));
+        } else if let Some(rest) = line.strip_prefix("view=") {
+            // `<scroll top> <cursor line> <cursor col> <path>`
+            let mut parts = rest.splitn(4, ' ');
+            let mut number = || parts.next().and_then(|n| n.parse().ok());
+            if let (Some(scroll_top), Some(cursor_line), Some(cursor_col)) = (number(), number(), number())
+                && let Some(path) = parts.next()
+            {
+                views.insert(PathBuf::from(path.trim()), BufferView { scroll_top, cursor_line, cursor_col });
+            }
+        } else if let Some(rest) = line.strip_prefix("sidebar=") {
+            sidebar_open = rest.trim() == "open";
         }
     }
     if let Some(m) = mode {
-        Ok(Some(LastSession { mode: m, file }))
+        Ok(Some(LastSession { mode: m, file, open_files, views, sidebar_open }))
     } else {
         Ok(None)
     }
 }
 
 pub fn save_editor_session(file: &str) -> io::Result<()> {
+    // Never restore into the scratch buffer; keep the previous session file instead
+    if crate::ui::is_scratch_buffer(file) {
+        return Ok(());
+    }
     let path = session_file_path()?;
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
@@ -59,6 +123,62 @@ pub fn save_editor_session(file: &str) -> io::Result<()> {
     Ok(())
 }
 
+/// Record the files open at quit with their scroll and cursor positions (as
+/// persisted in their undo histories) and whether the sidebar is shown, keeping
+/// the mode and active file of the last session. The scratch buffer is never restored.
+pub fn save_open_files(files: &[String], sidebar_open: bool) -> io::Result<()> {
+    let Some(last) = load_last_session()? else {
+        return Ok(());
+    };
+    let mut data = match last.mode {
+        SessionMode::Editor => "mode=editor\n".to_string(),
+        SessionMode::Selector => "mode=selector\n".to_string(),
+    };
+    if let Some(file) = last.file {
+        data.push_str(&format!("file={}\n", file.to_string_lossy()));
+    }
+    for f in files.iter().filter(|f| !crate::ui::is_scratch_buffer(f)) {
+        data.push_str(&format!("open={}\n", f));
+        if let Some(view) = saved_view(f) {
+            data.push_str(&format!("view={} {} {} {}\n", view.scroll_top, view.cursor_line, view.cursor_col, f));
+        }
+    }
+    if sidebar_open {
+        data.push_str("sidebar=open\n");
+    }
+    fs::write(session_file_path()?, data)
+}
+
+/// Position of `file` as persisted in its undo history, if it has one
+fn saved_view(file: &str) -> Option<BufferView> {
+    let untitled = crate::ui::is_untitled(file);
+    if !crate::undo::UndoHistory::history_path_for(file, untitled).ok()?.exists() {
+        return None;
+    }
+    let history = crate::undo::UndoHistory::load(file, untitled).ok()?;
+    Some(BufferView {
+        scroll_top: history.scroll_top,
+        cursor_line: history.cursor_line,
+        cursor_col: history.cursor_col,
+    })
+}
+
+/// Positions of the restored buffers, handed out once each as they are opened
+static RESTORED_VIEWS: Mutex<Option<HashMap<PathBuf, BufferView>>> = Mutex::new(None);
+
+/// Keep the positions of a restored session for buffers that have no undo
+/// history left to take them from (see `delete_history_on_clean_close`)
+pub fn remember_restored_views(views: HashMap<PathBuf, BufferView>) {
+    if let Ok(mut restored) = RESTORED_VIEWS.lock() {
+        *restored = Some(views);
+    }
+}
+
+/// Position of `file` in the restored session, if not taken before
+pub(crate) fn take_restored_view(file: &str) -> Option<BufferView> {
+    RESTORED_VIEWS.lock().ok()?.as_mut()?.remove(&PathBuf::from(file))
+}
+
 pub fn save_selector_session() -> io::Result<()> {
     let path = session_file_path()?;
     if let Some(parent) = path.parent() {
@@ -85,6 +205,90 @@ mod tests {
         assert_eq!(ls.file.unwrap(), PathBuf::from("/tmp/test.txt"));
     }
 
+    #[test]
+    fn scratch_buffer_does_not_replace_session() {
+        let (_tmp, _guard) = set_temp_home();
+        save_editor_session("/tmp/test.txt").unwrap();
+        save_editor_session(crate::ui::SCRATCH_BUFFER_NAME).unwrap();
+        let ls = load_last_session().unwrap().unwrap();
+        assert_eq!(ls.file.unwrap(), PathBuf::from("/tmp/test.txt"));
+    }
+
+    #[test]
+    fn open_files_are_restored_with_active_focused() {
+        let (tmp, _guard) = set_temp_home();
+        let a = tmp.path().join("a.txt");
+        let b = tmp.path().join("b.txt");
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+        let gone = tmp.path().join("gone.txt");
+        let (a, b, gone) = (
+            a.to_string_lossy().to_string(),
+            b.to_string_lossy().to_string(),
+            gone.to_string_lossy().to_string(),
+        );
+
+        save_editor_session(&b).unwrap();
+        save_open_files(
+            &[
+                a.clone(),
+                gone,
+                crate::ui::SCRATCH_BUFFER_NAME.to_string(),
+                b.clone(),
+            ],
+            false,
+        )
+        .unwrap();
+
+        let ls = load_last_session().unwrap().unwrap();
+        assert_eq!(ls.mode, SessionMode::Editor);
+        assert_eq!(ls.open_files.len(), 3);
+        assert!(!ls.sidebar_open);
+        let (files, active) = ls.restorable_files();
+        assert_eq!(files, vec![PathBuf::from(&a), PathBuf::from(&b)]);
+        assert_eq!(active, 1);
+    }
+
+    #[test]
+    fn buffer_views_and_sidebar_are_restored() {
+        let (tmp, _guard) = set_temp_home();
+        let a = tmp.path().join("a b.txt");
+        fs::write(&a, "a\n".repeat(100)).unwrap();
+        let a = a.to_string_lossy().to_string();
+        let mut history = crate::undo::UndoHistory::new();
+        history.update_cursor(40, 52, 1);
+        history.save(&a).unwrap();
+
+        save_editor_session(&a).unwrap();
+        save_open_files(&[a.clone(), "untitled-1".to_string()], true).unwrap();
+
+        let ls = load_last_session().unwrap().unwrap();
+        assert!(ls.sidebar_open);
+        assert_eq!(ls.views.len(), 1, "buffers without history have no position");
+        let view = BufferView { scroll_top: 40, cursor_line: 52, cursor_col: 1 };
+        assert_eq!(ls.views.get(&PathBuf::from(&a)), Some(&view));
+
+        remember_restored_views(ls.views);
+        assert_eq!(take_restored_view(&a), Some(view));
+        assert_eq!(take_restored_view(&a), None, "handed out once");
+    }
+
+    #[test]
+    fn untitled_with_history_is_restorable() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut history = crate::undo::UndoHistory::new();
+        history.untitled = true;
+        history.update_state(0, 0, 0, vec!["draft".to_string()]);
+        history.save("untitled-1").unwrap();
+
+        save_editor_session("untitled-1").unwrap();
+        save_open_files(&["untitled-1".to_string(), "untitled-2".to_string()], false).unwrap();
+
+        let (files, active) = load_last_session().unwrap().unwrap().restorable_files();
+        assert_eq!(files, vec![PathBuf::from("untitled-1")]);
+        assert_eq!(active, 0);
+    }
+
     #[test]
     fn save_and_load_selector_session() {
         let (_tmp, _guard) = set_temp_home();
@@ -46,6 +46,110 @@ pub(crate) struct KeyBindings {
     pub(crate) cursor_right: String,
     #[serde(default = "default_numpad_enter")]
     pub(crate) numpad_enter: String,
+    #[serde(default = "default_scratch_buffer")]
+    pub(crate) scratch_buffer: String,
+    #[serde(default = "default_split_selection_into_cursors")]
+    pub(crate) split_selection_into_cursors: String,
+    #[serde(default = "default_toggle_zen_mode")]
+    pub(crate) toggle_zen_mode: String,
+    #[serde(default = "default_toggle_preserve_case")]
+    pub(crate) toggle_preserve_case: String,
+    #[serde(default = "default_next_todo_marker")]
+    pub(crate) next_todo_marker: String,
+    #[serde(default = "default_previous_todo_marker")]
+    pub(crate) previous_todo_marker: String,
+    #[serde(default = "default_convert_encoding")]
+    pub(crate) convert_encoding: String,
+    #[serde(default = "default_save_find_options")]
+    pub(crate) save_find_options: String,
+    #[serde(default = "default_inspect_char")]
+    pub(crate) inspect_char: String,
+    #[serde(default = "default_strip_suspicious_chars")]
+    pub(crate) strip_suspicious_chars: String,
+    #[serde(default = "default_duplicate_selection")]
+    pub(crate) duplicate_selection: String,
+    #[serde(default = "default_cursors_at_regex")]
+    pub(crate) cursors_at_regex: String,
+    #[serde(default = "default_toggle_comment")]
+    pub(crate) toggle_comment: String,
+    #[serde(default = "default_clear_modes")]
+    pub(crate) clear_modes: String,
+    #[serde(default = "default_insert_code_point")]
+    pub(crate) insert_code_point: String,
+    #[serde(default = "default_symbol_outline")]
+    pub(crate) symbol_outline: String,
+    #[serde(default = "default_reopen_with_encoding")]
+    pub(crate) reopen_with_encoding: String,
+    #[serde(default = "default_indent_block_up")]
+    pub(crate) indent_block_up: String,
+    #[serde(default = "default_indent_block_down")]
+    pub(crate) indent_block_down: String,
+    #[serde(default = "default_next_long_line")]
+    pub(crate) next_long_line: String,
+    #[serde(default = "default_recent_dirs")]
+    pub(crate) recent_dirs: String,
+    #[serde(default = "default_set_syntax")]
+    pub(crate) set_syntax: String,
+    #[serde(default = "default_select_inside")]
+    pub(crate) select_inside: String,
+    #[serde(default = "default_select_around")]
+    pub(crate) select_around: String,
+    #[serde(default = "default_open_line_below")]
+    pub(crate) open_line_below: String,
+    #[serde(default = "default_open_line_above")]
+    pub(crate) open_line_above: String,
+    #[serde(default = "default_next_buffer")]
+    pub(crate) next_buffer: String,
+    #[serde(default = "default_previous_buffer")]
+    pub(crate) previous_buffer: String,
+    #[serde(default = "default_goto_buffer")]
+    pub(crate) goto_buffer: String,
+    #[serde(default = "default_add_next_occurrence")]
+    pub(crate) add_next_occurrence: String,
+    #[serde(default = "default_find_in_files")]
+    pub(crate) find_in_files: String,
+    #[serde(default = "default_replace_confirm")]
+    pub(crate) replace_confirm: String,
+    #[serde(default = "default_toggle_find_case")]
+    pub(crate) toggle_find_case: String,
+    #[serde(default = "default_toggle_find_whole_word")]
+    pub(crate) toggle_find_whole_word: String,
+    #[serde(default = "default_highlight_word")]
+    pub(crate) highlight_word: String,
+    #[serde(default = "default_find_history_search")]
+    pub(crate) find_history_search: String,
+    #[serde(default = "default_convert_line_ending")]
+    pub(crate) convert_line_ending: String,
+    #[serde(default = "default_undo_to_time")]
+    pub(crate) undo_to_time: String,
+    #[serde(default = "default_toggle_invisibles")]
+    pub(crate) toggle_invisibles: String,
+    #[serde(default = "default_blame_line")]
+    pub(crate) blame_line: String,
+    #[serde(default = "default_toggle_blame")]
+    pub(crate) toggle_blame: String,
+    #[serde(default = "default_git_commit")]
+    pub(crate) git_commit: String,
+    #[serde(default = "default_diff_with_disk")]
+    pub(crate) diff_with_disk: String,
+    #[serde(default = "default_diff_with_file")]
+    pub(crate) diff_with_file: String,
+    #[serde(default = "default_fuzzy_find")]
+    pub(crate) fuzzy_find: String,
+    #[serde(default = "default_toggle_sidebar")]
+    pub(crate) toggle_sidebar: String,
+    #[serde(default = "default_save_as")]
+    pub(crate) save_as: String,
+    #[serde(default = "default_rename_file")]
+    pub(crate) rename_file: String,
+    #[serde(default = "default_move_line_up")]
+    pub(crate) move_line_up: String,
+    #[serde(default = "default_move_line_down")]
+    pub(crate) move_line_down: String,
+    #[serde(default = "default_scroll_up")]
+    pub(crate) scroll_up: String,
+    #[serde(default = "default_scroll_down")]
+    pub(crate) scroll_down: String,
 }
 
 fn default_new_file() -> String {
@@ -105,6 +209,214 @@ fn default_toggle_line_wrap() -> String {
 }
 
 
+fn default_scratch_buffer() -> String {
+    "F2".into()
+}
+
+fn default_split_selection_into_cursors() -> String {
+    "Alt+Shift+i".into()
+}
+
+fn default_toggle_zen_mode() -> String {
+    "Alt+z".into()
+}
+
+fn default_toggle_preserve_case() -> String {
+    "Alt+c".into()
+}
+
+fn default_next_todo_marker() -> String {
+    "Alt+t".into()
+}
+
+fn default_previous_todo_marker() -> String {
+    "Alt+Shift+t".into()
+}
+
+fn default_convert_encoding() -> String {
+    "Ctrl+Alt+n".into()
+}
+
+fn default_save_find_options() -> String {
+    "Ctrl+Alt+s".into()
+}
+
+fn default_inspect_char() -> String {
+    "Alt+u".into()
+}
+
+fn default_strip_suspicious_chars() -> String {
+    "Alt+Shift+u".into()
+}
+
+fn default_duplicate_selection() -> String {
+    "Ctrl+Shift+d".into()
+}
+
+fn default_cursors_at_regex() -> String {
+    "Alt+Shift+m".into()
+}
+
+fn default_toggle_comment() -> String {
+    "Alt+/".into()
+}
+
+fn default_clear_modes() -> String {
+    "Esc".into()
+}
+
+fn default_insert_code_point() -> String {
+    "Alt+Shift+c".into()
+}
+
+fn default_symbol_outline() -> String {
+    "Alt+o".into()
+}
+
+fn default_reopen_with_encoding() -> String {
+    "Ctrl+Alt+o".into()
+}
+
+fn default_indent_block_up() -> String {
+    "Ctrl+Alt+Up".into()
+}
+
+fn default_indent_block_down() -> String {
+    "Ctrl+Alt+Down".into()
+}
+
+fn default_next_long_line() -> String {
+    "Alt+Shift+l".into()
+}
+
+fn default_recent_dirs() -> String {
+    "Alt+d".into()
+}
+
+fn default_set_syntax() -> String {
+    "Alt+Shift+s".into()
+}
+
+fn default_select_inside() -> String {
+    "Alt+i".into()
+}
+
+fn default_select_around() -> String {
+    "Alt+a".into()
+}
+
+fn default_open_line_below() -> String {
+    "Alt+Enter".into()
+}
+
+fn default_open_line_above() -> String {
+    "Alt+Shift+Enter".into()
+}
+
+fn default_next_buffer() -> String {
+    "Ctrl+Tab".into()
+}
+
+fn default_previous_buffer() -> String {
+    "Ctrl+Shift+Tab".into()
+}
+
+fn default_goto_buffer() -> String {
+    "Alt".into()
+}
+
+fn default_add_next_occurrence() -> String {
+    "Ctrl+d".into()
+}
+
+fn default_find_in_files() -> String {
+    "Ctrl+Shift+f".into()
+}
+
+fn default_replace_confirm() -> String {
+    "Ctrl+Alt+c".into()
+}
+
+fn default_toggle_find_case() -> String {
+    "Ctrl+Alt+a".into()
+}
+
+fn default_toggle_find_whole_word() -> String {
+    "Ctrl+Alt+w".into()
+}
+
+fn default_highlight_word() -> String {
+    "Alt+Shift+h".into()
+}
+
+fn default_find_history_search() -> String {
+    "Ctrl+Alt+p".into()
+}
+
+fn default_convert_line_ending() -> String {
+    "Ctrl+Alt+l".into()
+}
+
+fn default_undo_to_time() -> String {
+    "Alt+Shift+z".into()
+}
+
+fn default_toggle_invisibles() -> String {
+    "Alt+Shift+w".into()
+}
+
+fn default_blame_line() -> String {
+    "Alt+b".into()
+}
+
+fn default_toggle_blame() -> String {
+    "Alt+Shift+b".into()
+}
+
+fn default_git_commit() -> String {
+    "Ctrl+Alt+g".into()
+}
+
+fn default_diff_with_disk() -> String {
+    "Alt+Shift+d".into()
+}
+
+fn default_diff_with_file() -> String {
+    "Ctrl+Alt+d".into()
+}
+
+fn default_fuzzy_find() -> String {
+    "Ctrl+p".into()
+}
+
+fn default_toggle_sidebar() -> String {
+    "Ctrl+b".into()
+}
+
+fn default_save_as() -> String {
+    "Ctrl+Shift+s".into()
+}
+
+fn default_rename_file() -> String {
+    "Ctrl+Shift+r".into()
+}
+
+fn default_move_line_up() -> String {
+    "Alt+Up".into()
+}
+
+fn default_move_line_down() -> String {
+    "Alt+Down".into()
+}
+
+fn default_scroll_up() -> String {
+    "Ctrl+Alt+PageUp".into()
+}
+
+fn default_scroll_down() -> String {
+    "Ctrl+Alt+PageDown".into()
+}
+
 fn default_open_dialog() -> String {
     "Ctrl+o".into()
 }
@@ -113,14 +425,62 @@ fn default_open_dialog() -> String {
 pub(crate) struct AppearanceSettings {
     #[serde(default = "default_line_number_digits")]
     pub(crate) line_number_digits: u8,
-    #[serde(default = "default_header_bg")]
-    pub(crate) header_bg: String,
-    #[serde(default = "default_footer_bg")]
-    pub(crate) footer_bg: String,
-    #[serde(default = "default_line_numbers_bg")]
-    pub(crate) line_numbers_bg: String,
+    /// Color theme: a file name in the themes directory without `.toml`
+    #[serde(default = "default_theme")]
+    pub(crate) theme: String,
+    /// Overrides the theme's header, footer and line number background
+    #[serde(default)]
+    pub(crate) header_bg: Option<String>,
+    #[serde(default)]
+    pub(crate) footer_bg: Option<String>,
+    #[serde(default)]
+    pub(crate) line_numbers_bg: Option<String>,
     #[serde(default = "default_cursor_shape")]
     pub(crate) cursor_shape: String,
+    #[serde(default = "default_highlight_trailing_whitespace")]
+    pub(crate) highlight_trailing_whitespace: bool,
+    #[serde(default = "default_highlight_suspicious_chars")]
+    pub(crate) highlight_suspicious_chars: bool,
+    #[serde(default = "default_cursor_column_highlight")]
+    pub(crate) cursor_column_highlight: bool,
+    /// Tint every row of the cursor's line
+    #[serde(default)]
+    pub(crate) current_line_highlight: bool,
+    /// Vertical guides at each indentation level of leading whitespace
+    #[serde(default)]
+    pub(crate) indent_guides: bool,
+    /// Mark lines added, changed or removed since the last save next to the line numbers
+    #[serde(default = "default_diff_gutter")]
+    pub(crate) diff_gutter: bool,
+    /// For files tracked by git, mark lines against the committed version instead
+    #[serde(default = "default_git_gutter")]
+    pub(crate) git_gutter: bool,
+    /// Show the git branch of the file's repository in the header
+    #[serde(default = "default_git_branch")]
+    pub(crate) git_branch: bool,
+    /// Show tabs as arrows, spaces as dots and mark trailing whitespace
+    #[serde(default)]
+    pub(crate) show_invisibles: bool,
+    #[serde(default = "default_zen_margin")]
+    pub(crate) zen_margin: u16,
+    /// Lines wider than this many columns are marked; 0 turns the marking off
+    #[serde(default)]
+    pub(crate) line_length_limit: usize,
+    /// What an over-limit line marks: "overflow" (the part past the limit) or "line"
+    #[serde(default = "default_line_length_highlight")]
+    pub(crate) line_length_highlight: String,
+    /// Columns (1-based, tabs expanded) marked with a faint vertical stripe
+    #[serde(default)]
+    pub(crate) color_columns: Vec<usize>,
+    /// Overview of the whole buffer left of the scrollbar
+    #[serde(default)]
+    pub(crate) minimap: bool,
+    /// The minimap is only shown in terminals at least this many columns wide
+    #[serde(default = "default_minimap_min_term_width")]
+    pub(crate) minimap_min_term_width: u16,
+    /// Columns taken by the minimap, including a blank separator
+    #[serde(default = "default_minimap_width")]
+    pub(crate) minimap_width: u16,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -128,27 +488,103 @@ pub struct Settings {
     pub(crate) keybindings: KeyBindings,
     #[serde(default = "default_tab_width")]
     pub(crate) tab_width: usize,
+    /// Spaces inserted per indentation level; falls back to `tab_width` when unset
+    #[serde(default)]
+    pub(crate) indent_size: Option<usize>,
+    /// What Tab inserts within a line's leading whitespace: "indent" (up to the
+    /// next indentation stop), "spaces" (`indent_size` spaces) or "tab"
+    #[serde(default = "default_tab_leading")]
+    pub(crate) tab_leading: String,
+    /// What Tab inserts after the first non-blank character, same choices
+    #[serde(default = "default_tab_inline")]
+    pub(crate) tab_inline: String,
     #[serde(default = "default_double_tap_speed_ms")]
     pub(crate) double_tap_speed_ms: u64,
     #[serde(default = "default_keyboard_scroll_lines")]
     pub(crate) keyboard_scroll_lines: usize,
     #[serde(default = "default_mouse_scroll_lines")]
     pub(crate) mouse_scroll_lines: usize,
+    /// Lines of context kept above and below the cursor before the view scrolls
+    #[serde(default)]
+    pub(crate) scroll_margin: usize,
     #[serde(default = "default_line_wrapping")]
     pub(crate) line_wrapping: bool,
+    /// Wrap at this column even when the window is wider; 0 uses the full width
+    #[serde(default)]
+    pub(crate) wrap_column: u16,
+    /// Where wrapped lines break: "word" (after whitespace where possible) or
+    /// "char" (exactly at the text width)
+    #[serde(default = "default_wrap_mode")]
+    pub(crate) wrap_mode: String,
+    /// Text shown at the start of each continuation row of a wrapped line
+    #[serde(default)]
+    pub(crate) wrap_prefix: String,
+    /// Indent continuation rows as deep as the wrapped line's leading whitespace
+    #[serde(default)]
+    pub(crate) wrap_hanging_indent: bool,
+    /// Files with a line longer than this many characters open without line
+    /// wrapping; 0 disables the check
+    #[serde(default = "default_long_line_threshold")]
+    pub(crate) long_line_threshold: usize,
+    /// Files larger than this many MB are only opened after confirmation, and
+    /// then read-only; 0 disables the check
+    #[serde(default = "default_large_file_threshold_mb")]
+    pub(crate) large_file_threshold_mb: u64,
+    /// Files larger than this many MB open in the streamed view, which reads
+    /// only the lines on screen; 0 disables it
+    #[serde(default = "default_stream_file_threshold_mb")]
+    pub(crate) stream_file_threshold_mb: u64,
     #[serde(default = "default_horizontal_auto_scroll_speed")]
     pub(crate) horizontal_auto_scroll_speed: usize,
     #[serde(default = "default_horizontal_scroll_speed")]
     pub(crate) horizontal_scroll_speed: usize,
     #[serde(default = "default_appearance")]
     pub(crate) appearance: AppearanceSettings,
+    /// Colors of the theme selected by `appearance.theme`
+    #[serde(skip)]
+    pub(crate) theme: crate::theme::Theme,
     #[serde(default = "default_max_menu_files")]
     pub(crate) max_menu_files: usize,
+    #[serde(default = "default_electric_indent")]
+    pub(crate) electric_indent: bool,
+    /// Let block-selection cursors sit past the end of short lines; typing pads
+    /// those lines with spaces. Off: such cursors snap to the line end
+    #[serde(default)]
+    pub(crate) virtual_space: bool,
+    /// Delete a file's undo history when the editor exits while the file has
+    /// no unsaved changes, instead of keeping it for undo across sessions
+    #[serde(default)]
+    pub(crate) delete_history_on_clean_close: bool,
+    /// Write modified buffers to disk every this many seconds; 0 disables autosave
+    #[serde(default)]
+    pub(crate) autosave_seconds: u64,
+    /// Saving into a directory that does not exist: "ask" first, "always" create
+    /// it, or "never" create it (the file is not saved)
+    #[serde(default = "default_create_parent_dirs")]
+    pub(crate) create_parent_dirs: String,
+    /// How failed actions alert the user: "audible" (terminal bell), "visual"
+    /// (flash the header and footer) or "none"
+    #[serde(default = "default_bell")]
+    pub(crate) bell: String,
+    /// Render without any colors; highlights use reverse video instead
+    #[serde(default)]
+    pub(crate) no_color: bool,
+    #[serde(default = "default_todo_markers")]
+    pub(crate) todo_markers: Vec<String>,
+    /// Characters highlighted and removed by `strip_suspicious_chars` (`U+XXXX` or literal)
+    #[serde(default = "default_suspicious_chars")]
+    pub(crate) suspicious_chars: Vec<String>,
 }
 
 fn default_tab_width() -> usize {
     4
 }
+fn default_tab_leading() -> String {
+    "indent".into()
+}
+fn default_tab_inline() -> String {
+    "spaces".into()
+}
 fn default_double_tap_speed_ms() -> u64 {
     300
 }
@@ -164,6 +600,18 @@ fn default_mouse_scroll_lines() -> usize {
 fn default_line_wrapping() -> bool {
     true
 }
+fn default_wrap_mode() -> String {
+    "word".into()
+}
+fn default_large_file_threshold_mb() -> u64 {
+    100
+}
+fn default_stream_file_threshold_mb() -> u64 {
+    500
+}
+fn default_long_line_threshold() -> usize {
+    10_000
+}
 fn default_horizontal_auto_scroll_speed() -> usize {
     3
 }
@@ -174,26 +622,84 @@ fn default_horizontal_scroll_speed() -> usize {
 fn default_max_menu_files() -> usize {
     5
 }
+fn default_electric_indent() -> bool {
+    true
+}
+fn default_create_parent_dirs() -> String {
+    "ask".into()
+}
+fn default_bell() -> String {
+    "none".into()
+}
+fn default_todo_markers() -> Vec<String> {
+    ["TODO", "FIXME", "HACK", "XXX"].map(String::from).to_vec()
+}
+fn default_suspicious_chars() -> Vec<String> {
+    ["U+200B", "U+200C", "U+200D", "U+2060", "U+00A0", "U+FEFF"]
+        .map(String::from)
+        .to_vec()
+}
 
 fn default_line_number_digits() -> u8 {
     2
 }
-fn default_header_bg() -> String {
-    "#001848".into()
+fn default_theme() -> String {
+    "dark".into()
+}
+fn default_highlight_trailing_whitespace() -> bool {
+    false
+}
+fn default_highlight_suspicious_chars() -> bool {
+    true
+}
+fn default_cursor_column_highlight() -> bool {
+    false
+}
+fn default_diff_gutter() -> bool {
+    true
+}
+fn default_git_gutter() -> bool {
+    true
+}
+fn default_git_branch() -> bool {
+    true
+}
+fn default_zen_margin() -> u16 {
+    8
 }
-fn default_footer_bg() -> String {
-    "#001848".into()
+fn default_minimap_min_term_width() -> u16 {
+    140
 }
-fn default_line_numbers_bg() -> String {
-    "#001848".into()
+fn default_minimap_width() -> u16 {
+    12
+}
+fn default_line_length_highlight() -> String {
+    "overflow".into()
 }
 fn default_appearance() -> AppearanceSettings {
     AppearanceSettings {
         line_number_digits: default_line_number_digits(),
-        header_bg: default_header_bg(),
-        footer_bg: default_footer_bg(),
-        line_numbers_bg: default_line_numbers_bg(),
+        theme: default_theme(),
+        header_bg: None,
+        footer_bg: None,
+        line_numbers_bg: None,
         cursor_shape: default_cursor_shape(),
+        highlight_trailing_whitespace: default_highlight_trailing_whitespace(),
+        highlight_suspicious_chars: default_highlight_suspicious_chars(),
+        cursor_column_highlight: default_cursor_column_highlight(),
+        current_line_highlight: false,
+        indent_guides: false,
+        diff_gutter: default_diff_gutter(),
+        git_gutter: default_git_gutter(),
+        git_branch: default_git_branch(),
+        show_invisibles: false,
+        zen_margin: default_zen_margin(),
+        line_length_limit: 0,
+        line_length_highlight: default_line_length_highlight(),
+        color_columns: Vec::new(),
+        minimap: false,
+        minimap_min_term_width: default_minimap_min_term_width(),
+        minimap_width: default_minimap_width(),
     }
 }
 
@@ -213,7 +719,8 @@ impl Settings {
 
         // Read config (either existing or just created)
         let content = fs::read_to_string(&config_path)?;
-        let settings: Settings = toml::from_str(&content)?;
+        let mut settings: Settings = toml::from_str(&content)?;
+        settings.theme = crate::theme::Theme::load(&settings.appearance.theme);
 
         Ok(settings)
     }
@@ -239,6 +746,39 @@ impl Default for Settings {
 }
 
 impl Settings {
+    /// Settings used while zen mode is active: the line-number gutter becomes a blank
+    /// left margin of `zen_margin` columns
+    pub(crate) fn zen_variant(&self) -> Settings {
+        let mut zen = self.clone();
+        zen.appearance.line_number_digits =
+            self.appearance.zen_margin.saturating_sub(1).min(u8::MAX as u16) as u8;
+        zen
+    }
+
+    /// Settings used while the blame gutter is shown: the line-number gutter is
+    /// widened by the blame annotation
+    pub(crate) fn blame_variant(&self) -> Settings {
+        let mut blame = self.clone();
+        blame.appearance.line_number_digits =
+            self.appearance.line_number_digits.saturating_add(crate::git::BLAME_GUTTER_WIDTH);
+        blame
+    }
+
+    /// Settings used while the sidebar is shown: the line-number gutter is widened
+    /// by the sidebar columns
+    pub(crate) fn sidebar_variant(&self) -> Settings {
+        let mut sidebar = self.clone();
+        sidebar.appearance.line_number_digits =
+            self.appearance.line_number_digits.saturating_add(crate::sidebar::SIDEBAR_WIDTH);
+        sidebar
+    }
+
+    /// Number of spaces inserted by Tab and used for one indentation level.
+    /// Independent of `tab_width`, which only controls how tab characters are displayed.
+    pub(crate) fn indent_size(&self) -> usize {
+        self.indent_size.filter(|&n| n > 0).unwrap_or(self.tab_width)
+    }
+
     /// Get tab width (for testing)
     #[allow(dead_code)]
     pub fn get_tab_width(&self) -> usize {
@@ -334,6 +874,219 @@ impl KeyBindings {
         parse_keybinding(&self.open_dialog, code, modifiers)
     }
 
+    pub fn scratch_buffer_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.scratch_buffer, code, modifiers)
+    }
+
+    pub fn split_selection_into_cursors_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.split_selection_into_cursors, code, modifiers)
+    }
+
+    pub fn toggle_zen_mode_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.toggle_zen_mode, code, modifiers)
+    }
+
+    pub fn toggle_preserve_case_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.toggle_preserve_case, code, modifiers)
+    }
+
+    pub fn next_todo_marker_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.next_todo_marker, code, modifiers)
+    }
+
+    pub fn previous_todo_marker_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.previous_todo_marker, code, modifiers)
+    }
+
+    pub fn convert_encoding_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.convert_encoding, code, modifiers)
+    }
+
+    pub fn save_find_options_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.save_find_options, code, modifiers)
+    }
+
+    pub fn inspect_char_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.inspect_char, code, modifiers)
+    }
+
+    pub fn strip_suspicious_chars_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.strip_suspicious_chars, code, modifiers)
+    }
+
+    pub fn duplicate_selection_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.duplicate_selection, code, modifiers)
+    }
+
+    pub fn cursors_at_regex_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.cursors_at_regex, code, modifiers)
+    }
+
+    pub fn toggle_comment_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.toggle_comment, code, modifiers)
+    }
+
+    pub fn clear_modes_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.clear_modes, code, modifiers)
+    }
+
+    pub fn insert_code_point_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.insert_code_point, code, modifiers)
+    }
+
+    pub fn symbol_outline_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.symbol_outline, code, modifiers)
+    }
+
+    pub fn reopen_with_encoding_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.reopen_with_encoding, code, modifiers)
+    }
+
+    pub fn indent_block_up_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.indent_block_up, code, modifiers)
+    }
+
+    pub fn indent_block_down_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.indent_block_down, code, modifiers)
+    }
+
+    pub fn next_long_line_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.next_long_line, code, modifiers)
+    }
+
+    pub fn recent_dirs_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.recent_dirs, code, modifiers)
+    }
+
+    pub fn set_syntax_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.set_syntax, code, modifiers)
+    }
+
+    pub fn select_inside_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.select_inside, code, modifiers)
+    }
+
+    pub fn select_around_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.select_around, code, modifiers)
+    }
+
+    pub fn open_line_below_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.open_line_below, code, modifiers)
+    }
+
+    pub fn open_line_above_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.open_line_above, code, modifiers)
+    }
+
+    pub fn next_buffer_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.next_buffer, code, modifiers)
+    }
+
+    pub fn previous_buffer_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.previous_buffer, code, modifiers)
+    }
+
+    /// Buffer index (0-based) for the `goto_buffer` modifiers plus a digit 1-9
+    pub fn goto_buffer_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> Option<usize> {
+        let KeyCode::Char(c @ '1'..='9') = code else {
+            return None;
+        };
+        parse_keybinding(&format!("{}+{}", self.goto_buffer, c), code, modifiers)
+            .then(|| *c as usize - '1' as usize)
+    }
+
+    pub fn add_next_occurrence_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.add_next_occurrence, code, modifiers)
+    }
+
+    pub fn find_in_files_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.find_in_files, code, modifiers)
+    }
+
+    pub fn replace_confirm_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.replace_confirm, code, modifiers)
+    }
+
+    pub fn toggle_find_case_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.toggle_find_case, code, modifiers)
+    }
+
+    pub fn toggle_find_whole_word_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.toggle_find_whole_word, code, modifiers)
+    }
+
+    pub fn highlight_word_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.highlight_word, code, modifiers)
+    }
+
+    pub fn find_history_search_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.find_history_search, code, modifiers)
+    }
+
+    pub fn convert_line_ending_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.convert_line_ending, code, modifiers)
+    }
+
+    pub fn undo_to_time_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.undo_to_time, code, modifiers)
+    }
+
+    pub fn toggle_invisibles_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.toggle_invisibles, code, modifiers)
+    }
+
+    pub fn blame_line_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.blame_line, code, modifiers)
+    }
+
+    pub fn toggle_blame_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.toggle_blame, code, modifiers)
+    }
+
+    pub fn git_commit_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.git_commit, code, modifiers)
+    }
+
+    pub fn diff_with_disk_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.diff_with_disk, code, modifiers)
+    }
+
+    pub fn diff_with_file_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.diff_with_file, code, modifiers)
+    }
+
+    pub fn fuzzy_find_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.fuzzy_find, code, modifiers)
+    }
+
+    pub fn toggle_sidebar_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.toggle_sidebar, code, modifiers)
+    }
+
+    pub fn save_as_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.save_as, code, modifiers)
+    }
+
+    pub fn rename_file_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.rename_file, code, modifiers)
+    }
+
+    pub fn move_line_up_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.move_line_up, code, modifiers)
+    }
+
+    pub fn move_line_down_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.move_line_down, code, modifiers)
+    }
+
+    pub fn scroll_up_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.scroll_up, code, modifiers)
+    }
+
+    pub fn scroll_down_matches(&self, code: &KeyCode, modifiers: &KeyModifiers) -> bool {
+        parse_keybinding(&self.scroll_down, code, modifiers)
+    }
+
     pub fn help_matches(&self, key: &crossterm::event::KeyEvent) -> bool {
         parse_keybinding(&self.help, &key.code, &key.modifiers)
     }
@@ -357,8 +1110,16 @@ fn parse_keybinding(binding: &str, code: &KeyCode, modifiers: &KeyModifiers) ->
         KeyCode::Esc => key == "esc" || key == "escape",
         KeyCode::Enter => key == "enter" || key == "return" || key == "numpadenter",
         KeyCode::Tab => key == "tab",
+        // Shift+Tab arrives as BackTab
+        KeyCode::BackTab => key == "tab",
         KeyCode::Backspace => key == "backspace",
         KeyCode::Delete => key == "delete" || key == "del",
+        KeyCode::Up => key == "up",
+        KeyCode::Down => key == "down",
+        KeyCode::Left => key == "left",
+        KeyCode::Right => key == "right",
+        KeyCode::PageUp => key == "pageup" || key == "pgup",
+        KeyCode::PageDown => key == "pagedown" || key == "pgdn",
         KeyCode::F(n) => {
             // Match F1-F12 keys
             if let Some(num_str) = key.strip_prefix('f') {
@@ -381,7 +1142,7 @@ fn parse_keybinding(binding: &str, code: &KeyCode, modifiers: &KeyModifiers) ->
     // Check if modifiers match
     let has_ctrl = modifiers.contains(KeyModifiers::CONTROL);
     let has_alt = modifiers.contains(KeyModifiers::ALT);
-    let mut has_shift = modifiers.contains(KeyModifiers::SHIFT);
+    let mut has_shift = modifiers.contains(KeyModifiers::SHIFT) || *code == KeyCode::BackTab;
 
     let needs_ctrl = modifier_parts.iter().any(|m| {
         let m_lower = m.to_lowercase();
@@ -465,6 +1226,58 @@ mod tests {
             cursor_left: "Alt+h".into(),
             cursor_right: "Alt+l".into(),
             numpad_enter: "Ctrl+j".into(),
+            scratch_buffer: "F2".into(),
+            split_selection_into_cursors: "Alt+Shift+i".into(),
+            toggle_zen_mode: "Alt+z".into(),
+            toggle_preserve_case: "Alt+c".into(),
+            next_todo_marker: "Alt+t".into(),
+            previous_todo_marker: "Alt+Shift+t".into(),
+            convert_encoding: "Ctrl+Alt+n".into(),
+            save_find_options: "Ctrl+Alt+s".into(),
+            inspect_char: "Alt+u".into(),
+            strip_suspicious_chars: "Alt+Shift+u".into(),
+            duplicate_selection: "Ctrl+Shift+d".into(),
+            cursors_at_regex: "Alt+Shift+m".into(),
+            toggle_comment: "Alt+/".into(),
+            clear_modes: "Esc".into(),
+            insert_code_point: "Alt+Shift+c".into(),
+            symbol_outline: "Alt+o".into(),
+            reopen_with_encoding: "Ctrl+Alt+o".into(),
+            indent_block_up: "Ctrl+Alt+Up".into(),
+            indent_block_down: "Ctrl+Alt+Down".into(),
+            next_long_line: "Alt+Shift+l".into(),
+            recent_dirs: "Alt+d".into(),
+            set_syntax: "Alt+Shift+s".into(),
+            select_inside: "Alt+i".into(),
+            select_around: "Alt+a".into(),
+            open_line_below: "Alt+Enter".into(),
+            open_line_above: "Alt+Shift+Enter".into(),
+            next_buffer: "Ctrl+Tab".into(),
+            previous_buffer: "Ctrl+Shift+Tab".into(),
+            goto_buffer: "Alt".into(),
+            add_next_occurrence: "Ctrl+d".into(),
+            find_in_files: "Ctrl+Shift+f".into(),
+            replace_confirm: "Ctrl+Alt+c".into(),
+            toggle_find_case: "Ctrl+Alt+a".into(),
+            toggle_find_whole_word: "Ctrl+Alt+w".into(),
+            highlight_word: "Alt+Shift+h".into(),
+            find_history_search: "Ctrl+Alt+p".into(),
+            convert_line_ending: "Ctrl+Alt+l".into(),
+            undo_to_time: "Alt+Shift+z".into(),
+            toggle_invisibles: "Alt+Shift+w".into(),
+            blame_line: "Alt+b".into(),
+            toggle_blame: "Alt+Shift+b".into(),
+            git_commit: "Ctrl+Alt+g".into(),
+            diff_with_disk: "Alt+Shift+d".into(),
+            diff_with_file: "Ctrl+Alt+d".into(),
+            fuzzy_find: "Ctrl+p".into(),
+            toggle_sidebar: "Ctrl+b".into(),
+            save_as: "Ctrl+Shift+s".into(),
+            rename_file: "Ctrl+Shift+r".into(),
+            move_line_up: "Alt+Up".into(),
+            move_line_down: "Alt+Down".into(),
+            scroll_up: "Ctrl+Alt+PageUp".into(),
+            scroll_down: "Ctrl+Alt+PageDown".into(),
         }
     }
 
@@ -551,10 +1364,9 @@ mod tests {
     fn default_color_values_present() {
         let (_tmp, _guard) = crate::env::set_temp_home();
         let s = Settings::load().expect("load settings");
-        assert_eq!(s.appearance.header_bg, "#001848");
-        assert_eq!(s.appearance.footer_bg, "#001848");
-        assert_eq!(s.appearance.line_numbers_bg, "#001848");
-        assert!(Settings::parse_color(&s.appearance.header_bg).is_some());
+        assert_eq!(s.appearance.theme, "dark");
+        assert_eq!(s.appearance.header_bg, None);
+        assert_eq!(Settings::parse_color("#001848"), Some(s.theme.chrome_bg));
     }
 
     #[test]
@@ -616,6 +1428,17 @@ mod tests {
         assert!(settings.tab_width <= 16); // Reasonable max
     }
 
+    #[test]
+    fn indent_size_defaults_to_tab_width() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.indent_size, None);
+        settings.tab_width = 8;
+        assert_eq!(settings.indent_size(), 8);
+        settings.indent_size = Some(2);
+        assert_eq!(settings.indent_size(), 2);
+        assert_eq!(settings.tab_width, 8);
+    }
+
     #[test]
     fn settings_line_number_digits_validation() {
         let settings = Settings::default();
@@ -663,6 +1486,22 @@ mod tests {
         assert!(!kb.goto_line_matches(&KeyCode::Char('g'), &KeyModifiers::SHIFT));
     }
 
+    #[test]
+    fn buffer_switch_keybindings_match() {
+        let (_tmp, _guard) = set_temp_home();
+        let kb = create_test_keybindings();
+        let ctrl_shift = KeyModifiers::CONTROL | KeyModifiers::SHIFT;
+
+        assert!(kb.next_buffer_matches(&KeyCode::Tab, &KeyModifiers::CONTROL));
+        assert!(kb.previous_buffer_matches(&KeyCode::BackTab, &ctrl_shift));
+        assert!(kb.previous_buffer_matches(&KeyCode::BackTab, &KeyModifiers::CONTROL));
+        assert!(!kb.previous_buffer_matches(&KeyCode::BackTab, &KeyModifiers::SHIFT));
+        assert_eq!(kb.goto_buffer_matches(&KeyCode::Char('1'), &KeyModifiers::ALT), Some(0));
+        assert_eq!(kb.goto_buffer_matches(&KeyCode::Char('9'), &KeyModifiers::ALT), Some(8));
+        assert_eq!(kb.goto_buffer_matches(&KeyCode::Char('0'), &KeyModifiers::ALT), None);
+        assert_eq!(kb.goto_buffer_matches(&KeyCode::Char('3'), &KeyModifiers::empty()), None);
+    }
+
     #[test]
     fn alt_modifier_parsing() {
         let (_tmp, _guard) = set_temp_home();
@@ -826,4 +1665,42 @@ goto_line = "Ctrl+b"
         let kb: KeyBindings = toml::from_str(toml_without_help).expect("should parse with default");
         assert_eq!(kb.help, "F1", "help field should default to F1");
     }
+
+    #[test]
+    fn default_keybindings_do_not_collide() {
+        // Pairs that share a key on purpose: only one of them is active at a time
+        let shared = [("replace", "replace_current")];
+        let kb = Settings::default().keybindings;
+        let serde_json::Value::Object(map) = serde_json::to_value(&kb).unwrap() else {
+            panic!("keybindings should serialize to a map");
+        };
+        let mut seen: std::collections::HashMap<String, &str> = std::collections::HashMap::new();
+        for (action, binding) in &map {
+            let binding = binding.as_str().unwrap();
+            let mut parts: Vec<String> = binding.split('+').map(|p| p.trim().to_lowercase()).collect();
+            let key = parts.pop().unwrap();
+            parts.sort();
+            parts.push(key);
+            let normalized = parts.join("+");
+            if let Some(other) = seen.insert(normalized.clone(), action) {
+                assert!(
+                    shared.contains(&(other, action)) || shared.contains(&(action, other)),
+                    "{} and {} both default to {}",
+                    other,
+                    action,
+                    binding
+                );
+            }
+            // Any Alt+<hotkey> opens a menu before other bindings are checked
+            for menu in crate::menu::MenuBar::new().menus {
+                assert!(
+                    !(parts.contains(&"alt".to_string()) && normalized.ends_with(&format!("+{}", menu.hotkey))),
+                    "{} defaults to {}, which opens the {} menu",
+                    action,
+                    binding,
+                    menu.label
+                );
+            }
+        }
+    }
 }
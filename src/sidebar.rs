@@ -0,0 +1,485 @@
+//! Directory tree sidebar: the working directory as an expandable tree left of
+//! the editor. Shown with the line-number gutter widened by `SIDEBAR_WIDTH`
+//! columns, the same way as the blame gutter, and drawn over the front of it.
+//! It stays open, with its expanded directories, across files.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::editor_state::FileViewerState;
+use crate::settings::Settings;
+
+/// Columns taken by the sidebar, its right border included
+pub(crate) const SIDEBAR_WIDTH: u8 = 30;
+
+/// Rows scrolled per mouse wheel step
+const WHEEL_ROWS: usize = 3;
+
+/// Whether the sidebar is shown, kept across files for the lifetime of the process
+static OPEN: AtomicBool = AtomicBool::new(false);
+
+/// The tree, created when the sidebar is first shown
+static TREE: Mutex<Option<Tree>> = Mutex::new(None);
+
+/// A file or directory listed in the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Entry {
+    pub(crate) path: PathBuf,
+    pub(crate) depth: usize,
+    pub(crate) is_dir: bool,
+}
+
+/// Expanded directories, selection and scroll position of the tree below `root`
+#[derive(Debug)]
+pub(crate) struct Tree {
+    pub(crate) root: PathBuf,
+    expanded: HashSet<PathBuf>,
+    pub(crate) entries: Vec<Entry>,
+    pub(crate) selected: usize,
+    pub(crate) scroll: usize,
+}
+
+impl Tree {
+    pub(crate) fn new(root: PathBuf) -> Self {
+        let mut tree = Tree { root, expanded: HashSet::new(), entries: Vec::new(), selected: 0, scroll: 0 };
+        tree.refresh();
+        tree
+    }
+
+    /// List the entries again from disk, keeping the selected path selected
+    pub(crate) fn refresh(&mut self) {
+        let selected = self.selected_entry().map(|e| e.path.clone());
+        self.entries.clear();
+        list_dir(&self.root, 0, &self.expanded, &mut self.entries);
+        self.selected = selected
+            .and_then(|path| self.entries.iter().position(|e| e.path == path))
+            .unwrap_or(self.selected)
+            .min(self.entries.len().saturating_sub(1));
+    }
+
+    pub(crate) fn selected_entry(&self) -> Option<&Entry> {
+        self.entries.get(self.selected)
+    }
+
+    /// Select `path` when it is listed
+    pub(crate) fn select_path(&mut self, path: &Path) {
+        if let Some(idx) = self.entries.iter().position(|e| e.path == path) {
+            self.selected = idx;
+        }
+    }
+
+    /// Expand or collapse the directory at `idx`
+    pub(crate) fn toggle(&mut self, idx: usize) {
+        let Some(entry) = self.entries.get(idx).filter(|e| e.is_dir) else {
+            return;
+        };
+        let path = entry.path.clone();
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+        self.refresh();
+    }
+
+    pub(crate) fn is_expanded(&self, path: &Path) -> bool {
+        self.expanded.contains(path)
+    }
+
+    /// Move the selection by `delta` rows and keep it within the `rows` shown
+    pub(crate) fn move_selection(&mut self, delta: isize, rows: usize) {
+        let last = self.entries.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, last.max(0)) as usize;
+        self.scroll_to_selection(rows);
+    }
+
+    pub(crate) fn scroll_to_selection(&mut self, rows: usize) {
+        let rows = rows.max(1);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + rows {
+            self.scroll = self.selected + 1 - rows;
+        }
+    }
+
+    /// Label of the entry at `idx`: indentation, an arrow for directories, the name
+    pub(crate) fn label(&self, idx: usize) -> String {
+        let Some(entry) = self.entries.get(idx) else {
+            return String::new();
+        };
+        let name = entry.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let marker = match (entry.is_dir, self.is_expanded(&entry.path)) {
+            (true, true) => "\u{25be} ",
+            (true, false) => "\u{25b8} ",
+            (false, _) => "  ",
+        };
+        let slash = if entry.is_dir { "/" } else { "" };
+        format!(" {}{}{}{}", "  ".repeat(entry.depth), marker, name, slash)
+    }
+
+    /// Directory new entries go into: the selected directory, or the directory
+    /// of the selected file
+    pub(crate) fn target_dir(&self) -> PathBuf {
+        match self.selected_entry() {
+            Some(entry) if entry.is_dir => entry.path.clone(),
+            Some(entry) => entry.path.parent().map_or_else(|| self.root.clone(), Path::to_path_buf),
+            None => self.root.clone(),
+        }
+    }
+}
+
+/// Entries of `dir` (directories first, then files, each sorted by name) and of
+/// its expanded subdirectories. Hidden entries are left out.
+fn list_dir(dir: &Path, depth: usize, expanded: &HashSet<PathBuf>, out: &mut Vec<Entry>) {
+    let Ok(read) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut children: Vec<Entry> = read
+        .flatten()
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .map(|e| Entry { is_dir: e.path().is_dir(), path: e.path(), depth })
+        .collect();
+    children.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.path.cmp(&b.path)));
+    for child in children {
+        let recurse = child.is_dir && expanded.contains(&child.path);
+        let path = child.path.clone();
+        out.push(child);
+        if recurse {
+            list_dir(&path, depth + 1, expanded, out);
+        }
+    }
+}
+
+/// Create the file `name` in `dir`, or the directory when `name` ends with '/'
+pub(crate) fn create_entry(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let is_dir = name.ends_with('/');
+    let name = name.trim_end_matches('/');
+    if name.is_empty() {
+        return Err("No name given".to_string());
+    }
+    let path = dir.join(name);
+    if path.exists() {
+        return Err(format!("{} already exists", name));
+    }
+    let created = if is_dir {
+        std::fs::create_dir_all(&path)
+    } else {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::File::create_new(&path).map(|_| ())
+    };
+    created.map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Rename `path` to `name` in the same directory
+pub(crate) fn rename_entry(path: &Path, name: &str) -> Result<PathBuf, String> {
+    let name = name.trim_end_matches('/');
+    if name.is_empty() {
+        return Err("No name given".to_string());
+    }
+    let target = path.parent().unwrap_or(Path::new(".")).join(name);
+    if target.exists() {
+        return Err(format!("{} already exists", name));
+    }
+    std::fs::rename(path, &target).map_err(|e| e.to_string())?;
+    Ok(target)
+}
+
+/// Delete the file at `path`, or the directory when it is empty
+pub(crate) fn delete_entry(path: &Path) -> Result<(), String> {
+    if path.is_dir() {
+        std::fs::remove_dir(path).map_err(|_| "Directory is not empty".to_string())
+    } else {
+        std::fs::remove_file(path).map_err(|e| e.to_string())?;
+        // The undo history of a deleted file is of no further use
+        let _ = crate::editing::delete_history_file(&path.to_string_lossy(), false);
+        Ok(())
+    }
+}
+
+pub(crate) fn is_open() -> bool {
+    OPEN.load(Ordering::Relaxed)
+}
+
+/// Show the sidebar from the first file on, as in the restored session
+pub fn open_on_start() {
+    OPEN.store(true, Ordering::Relaxed);
+}
+
+/// Whether the sidebar takes the front of the gutter (not in zen mode)
+pub(crate) fn is_visible(state: &FileViewerState) -> bool {
+    state.sidebar_open && !state.zen_mode
+}
+
+/// Run `f` on the tree, creating it for the working directory first
+pub(crate) fn with_tree<T>(f: impl FnOnce(&mut Tree) -> T) -> T {
+    let mut guard = TREE.lock().unwrap_or_else(|e| e.into_inner());
+    let tree = guard.get_or_insert_with(|| Tree::new(std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))));
+    f(tree)
+}
+
+/// Toggle key and View menu: show the sidebar with the focus on it, move the
+/// focus to a shown sidebar, or hide it when it has the focus
+pub(crate) fn toggle(state: &mut FileViewerState, filename: &str, from_menu: bool) {
+    if state.sidebar_open && (state.sidebar_focused || from_menu) {
+        state.sidebar_open = false;
+        state.sidebar_focused = false;
+    } else {
+        state.sidebar_open = true;
+        state.sidebar_focused = true;
+        with_tree(|tree| {
+            tree.refresh();
+            if tree.selected_entry().is_none_or(|e| e.depth == 0 && !e.is_dir) {
+                tree.select_path(Path::new(filename));
+            }
+        });
+    }
+    OPEN.store(state.sidebar_open, Ordering::Relaxed);
+    state.needs_redraw = true;
+}
+
+/// Rows of the tree shown below the sidebar's title row
+pub(crate) fn tree_rows(visible_lines: usize) -> usize {
+    visible_lines.saturating_sub(1).max(1)
+}
+
+/// Open the file at `path`, or just return the focus when it is the open one
+fn open_path(state: &mut FileViewerState, path: &Path, filename: &str) {
+    if !crate::project_find::same_file(path, Path::new(filename)) {
+        state.open_file_requested = Some(path.to_string_lossy().to_string());
+    }
+    state.sidebar_focused = false;
+}
+
+/// Whether `path` is the open file or a directory holding it
+pub(crate) fn holds_open_file(path: &Path, filename: &str) -> bool {
+    let (Ok(path), Ok(open)) = (std::fs::canonicalize(path), std::fs::canonicalize(filename)) else {
+        return false;
+    };
+    open.starts_with(path)
+}
+
+/// Handle a key while the sidebar has the focus. Returns false for Ctrl and
+/// Alt shortcuts the sidebar does not use; all other keys are consumed.
+pub(crate) fn handle_key(
+    state: &mut FileViewerState,
+    key: KeyEvent,
+    filename: &str,
+    visible_lines: usize,
+    settings: &Settings,
+) -> Result<bool, std::io::Error> {
+    let rows = tree_rows(visible_lines);
+    state.needs_redraw = true;
+    if settings.keybindings.toggle_sidebar_matches(&key.code, &key.modifiers) {
+        toggle(state, filename, false);
+        return Ok(true);
+    }
+    if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+        return Ok(false);
+    }
+    let selected = with_tree(|tree| tree.selected_entry().cloned());
+    match key.code {
+        KeyCode::Esc | KeyCode::Tab => state.sidebar_focused = false,
+        KeyCode::Up => with_tree(|tree| tree.move_selection(-1, rows)),
+        KeyCode::Down => with_tree(|tree| tree.move_selection(1, rows)),
+        KeyCode::PageUp => with_tree(|tree| tree.move_selection(-(rows as isize), rows)),
+        KeyCode::PageDown => with_tree(|tree| tree.move_selection(rows as isize, rows)),
+        KeyCode::Home => with_tree(|tree| tree.move_selection(isize::MIN / 2, rows)),
+        KeyCode::End => with_tree(|tree| tree.move_selection(isize::MAX / 2, rows)),
+        KeyCode::Enter | KeyCode::Right => match selected {
+            Some(entry) if !entry.is_dir => open_path(state, &entry.path, filename),
+            Some(entry) => with_tree(|tree| {
+                if key.code == KeyCode::Enter || !tree.is_expanded(&entry.path) {
+                    tree.toggle(tree.selected);
+                }
+            }),
+            None => {}
+        },
+        KeyCode::Left => with_tree(|tree| {
+            let Some(entry) = tree.selected_entry().cloned() else {
+                return;
+            };
+            if entry.is_dir && tree.is_expanded(&entry.path) {
+                tree.toggle(tree.selected);
+            } else if let Some(parent) = entry.path.parent() {
+                tree.select_path(parent);
+            }
+            tree.scroll_to_selection(rows);
+        }),
+        KeyCode::Char('n') => {
+            let dir = with_tree(|tree| tree.target_dir());
+            let label = format!("New in {}/ (end with / for a directory)", display_dir(&dir));
+            if let Some(name) = crate::event_handlers::show_text_prompt(&label, settings)? {
+                match create_entry(&dir, name.trim()) {
+                    Ok(path) => {
+                        with_tree(|tree| {
+                            tree.expanded.insert(dir.clone());
+                            tree.refresh();
+                            tree.select_path(&path);
+                            tree.scroll_to_selection(rows);
+                        });
+                        if path.is_file() {
+                            open_path(state, &path, filename);
+                        }
+                    }
+                    Err(e) => fail(state, e),
+                }
+            }
+        }
+        KeyCode::Char('r') | KeyCode::F(2) => {
+            let Some(entry) = selected else {
+                return Ok(true);
+            };
+            if holds_open_file(&entry.path, filename) {
+                fail(state, "The open file cannot be renamed from here".to_string());
+                return Ok(true);
+            }
+            let name = entry.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            if let Some(new_name) = crate::event_handlers::show_text_prompt(&format!("Rename {} to", name), settings)? {
+                match rename_entry(&entry.path, new_name.trim()) {
+                    Ok(path) => with_tree(|tree| {
+                        if tree.expanded.remove(&entry.path) {
+                            tree.expanded.insert(path.clone());
+                        }
+                        tree.refresh();
+                        tree.select_path(&path);
+                        tree.scroll_to_selection(rows);
+                    }),
+                    Err(e) => fail(state, e),
+                }
+            }
+        }
+        KeyCode::Char('d') | KeyCode::Delete => {
+            let Some(entry) = selected else {
+                return Ok(true);
+            };
+            if holds_open_file(&entry.path, filename) {
+                fail(state, "The open file cannot be deleted from here".to_string());
+                return Ok(true);
+            }
+            let name = entry.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let answer = crate::event_handlers::show_text_prompt(&format!("Delete {}? (y/n)", name), settings)?;
+            if answer.is_some_and(|a| a.trim().eq_ignore_ascii_case("y")) {
+                match delete_entry(&entry.path) {
+                    Ok(()) => {
+                        state.status_message = Some(format!("Deleted {}", name));
+                        with_tree(|tree| tree.refresh());
+                    }
+                    Err(e) => fail(state, e),
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(true)
+}
+
+/// Directory shown in prompts, relative to the tree root where possible
+fn display_dir(dir: &Path) -> String {
+    with_tree(|tree| match dir.strip_prefix(&tree.root) {
+        Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+        Ok(rel) => rel.display().to_string(),
+        Err(_) => dir.display().to_string(),
+    })
+}
+
+fn fail(state: &mut FileViewerState, message: String) {
+    state.status_message = Some(message);
+    state.alert();
+}
+
+/// Handle a mouse event on the sidebar columns. A click selects the row under
+/// the pointer and focuses the sidebar; a directory is expanded or collapsed
+/// and a file requested to be opened (the editing loop ignores the open one).
+pub(crate) fn handle_mouse(state: &mut FileViewerState, mouse_event: MouseEvent, visible_lines: usize) {
+    let rows = tree_rows(visible_lines);
+    state.needs_redraw = true;
+    match mouse_event.kind {
+        MouseEventKind::ScrollDown => with_tree(|tree| {
+            tree.scroll = (tree.scroll + WHEEL_ROWS).min(tree.entries.len().saturating_sub(rows));
+        }),
+        MouseEventKind::ScrollUp => with_tree(|tree| tree.scroll = tree.scroll.saturating_sub(WHEEL_ROWS)),
+        MouseEventKind::Down(MouseButton::Left) => {
+            state.sidebar_focused = true;
+            // Screen row 1 is the title, the tree starts below it
+            let Some(row) = (mouse_event.row as usize).checked_sub(2) else {
+                return;
+            };
+            let clicked = with_tree(|tree| {
+                let idx = tree.scroll + row;
+                let entry = tree.entries.get(idx).cloned()?;
+                tree.selected = idx;
+                if entry.is_dir {
+                    tree.toggle(idx);
+                }
+                Some(entry)
+            });
+            if let Some(entry) = clicked.filter(|e| !e.is_dir) {
+                state.open_file_requested = Some(entry.path.to_string_lossy().to_string());
+                state.sidebar_focused = false;
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_lists_directories_first_and_expands_them() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_path_buf();
+        std::fs::create_dir_all(root.join("src/bin")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        std::fs::write(root.join("README.md"), "").unwrap();
+        std::fs::write(root.join(".hidden"), "").unwrap();
+
+        let mut tree = Tree::new(root.clone());
+        let labels = |tree: &Tree| -> Vec<String> { (0..tree.entries.len()).map(|i| tree.label(i)).collect() };
+        assert_eq!(labels(&tree), vec![" \u{25b8} src/", "   README.md"]);
+        assert_eq!(tree.target_dir(), root.join("src"));
+
+        tree.toggle(0);
+        assert_eq!(labels(&tree), vec![" \u{25be} src/", "   \u{25b8} bin/", "     main.rs", "   README.md"]);
+        tree.move_selection(2, 10);
+        assert_eq!(tree.selected_entry().unwrap().path, root.join("src/main.rs"));
+        assert_eq!(tree.target_dir(), root.join("src"));
+
+        // Collapsing keeps the selection on a listed entry
+        tree.toggle(0);
+        assert_eq!(tree.entries.len(), 2);
+        assert!(tree.selected < 2);
+    }
+
+    #[test]
+    fn file_operations_create_rename_and_delete() {
+        let (tmp, _guard) = crate::env::set_temp_home();
+        let dir = tmp.path().join("project");
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.as_path();
+
+        let file = create_entry(dir, "notes.txt").unwrap();
+        assert!(file.is_file());
+        assert!(create_entry(dir, "notes.txt").is_err());
+        let sub = create_entry(dir, "docs/").unwrap();
+        assert!(sub.is_dir());
+        assert!(create_entry(dir, "/").is_err());
+
+        let renamed = rename_entry(&file, "todo.txt").unwrap();
+        assert_eq!(renamed, dir.join("todo.txt"));
+        assert!(!file.exists());
+        assert!(rename_entry(&renamed, "docs").is_err(), "target exists");
+
+        std::fs::write(sub.join("a.txt"), "").unwrap();
+        assert!(delete_entry(&sub).is_err(), "not empty");
+        delete_entry(&sub.join("a.txt")).unwrap();
+        delete_entry(&sub).unwrap();
+        delete_entry(&renamed).unwrap();
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 0);
+    }
+}
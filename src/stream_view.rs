@@ -0,0 +1,326 @@
+//! Streamed view of files too large to load (`stream_file_threshold_mb`): a
+//! read-only pager that reads only the lines on screen. Line starts are indexed
+//! on demand, as far as the view has been scrolled, and only every
+//! `CHECKPOINT_LINES`th of them is kept, so memory stays small however large the
+//! file is. Text is shown as UTF-8 and there is no undo history.
+
+use crossterm::{
+    cursor, event, execute,
+    event::{Event, KeyCode, KeyModifiers, MouseEventKind},
+    style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal,
+};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+
+use crate::settings::Settings;
+
+/// Lines between two remembered line starts
+const CHECKPOINT_LINES: usize = 1024;
+/// Bytes read at a time while indexing
+const CHUNK_BYTES: usize = 1024 * 1024;
+/// Bytes of a line shown; the rest of longer lines is skipped
+const MAX_LINE_BYTES: usize = 16 * 1024;
+/// Lines one mouse wheel step scrolls
+const WHEEL_LINES: usize = 3;
+
+/// Line starts of a file found so far
+pub(crate) struct LineIndex {
+    file: File,
+    len: u64,
+    /// Start of every `CHECKPOINT_LINES`th line, beginning with line 0
+    checkpoints: Vec<u64>,
+    /// Lines whose start is known
+    known_lines: usize,
+    /// Bytes scanned for line breaks
+    scanned: u64,
+    /// Whether the whole file has been scanned
+    complete: bool,
+}
+
+impl LineIndex {
+    pub(crate) fn open(path: &str) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            file,
+            len,
+            checkpoints: vec![0],
+            known_lines: 1,
+            scanned: 0,
+            complete: len == 0,
+        })
+    }
+
+    /// Number of lines once the whole file has been indexed
+    pub(crate) fn total_lines(&self) -> Option<usize> {
+        self.complete.then_some(self.known_lines)
+    }
+
+    /// Scan for line breaks until the start of `line` is known or the file ends
+    pub(crate) fn index_to(&mut self, line: usize) -> io::Result<()> {
+        let mut chunk = vec![0u8; CHUNK_BYTES];
+        while !self.complete && self.known_lines <= line {
+            self.file.seek(SeekFrom::Start(self.scanned))?;
+            let want = CHUNK_BYTES.min((self.len - self.scanned) as usize);
+            let read = self.file.read(&mut chunk[..want])?;
+            for pos in chunk[..read].iter().enumerate().filter(|(_, b)| **b == b'\n').map(|(i, _)| i) {
+                let start = self.scanned + pos as u64 + 1;
+                // A final line break does not start another line
+                if start < self.len {
+                    if self.known_lines.is_multiple_of(CHECKPOINT_LINES) {
+                        self.checkpoints.push(start);
+                    }
+                    self.known_lines += 1;
+                }
+            }
+            self.scanned += read as u64;
+            // A file that shrank while open ends where reading stops
+            self.complete = read == 0 || self.scanned >= self.len;
+        }
+        Ok(())
+    }
+
+    /// Up to `count` lines starting at `first`, each cut to `MAX_LINE_BYTES`
+    pub(crate) fn lines(&mut self, first: usize, count: usize) -> io::Result<Vec<String>> {
+        self.index_to(first.saturating_add(count))?;
+        if first >= self.known_lines {
+            return Ok(Vec::new());
+        }
+        let checkpoint = first / CHECKPOINT_LINES;
+        let start = self.checkpoints[checkpoint];
+        self.file.seek(SeekFrom::Start(start))?;
+        let mut reader = BufReader::new((&self.file).take(self.len - start));
+        for _ in checkpoint * CHECKPOINT_LINES..first {
+            next_line(&mut reader, 0)?;
+        }
+        let mut lines = Vec::with_capacity(count);
+        while lines.len() < count {
+            match next_line(&mut reader, MAX_LINE_BYTES)? {
+                Some(mut bytes) => {
+                    if bytes.last() == Some(&b'\r') {
+                        bytes.pop();
+                    }
+                    lines.push(String::from_utf8_lossy(&bytes).into_owned());
+                }
+                None => break,
+            }
+        }
+        if self.len == 0 && first == 0 {
+            lines.push(String::new());
+        }
+        Ok(lines)
+    }
+}
+
+/// Next line of `reader` without its line break, keeping at most `keep` bytes of
+/// it; None at the end of the input
+fn next_line(reader: &mut impl BufRead, keep: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut line = Vec::new();
+    let mut found = false;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(found.then_some(line));
+        }
+        found = true;
+        let (content, used, done) = match buf.iter().position(|&b| b == b'\n') {
+            Some(pos) => (pos, pos + 1, true),
+            None => (buf.len(), buf.len(), false),
+        };
+        let room = keep.saturating_sub(line.len()).min(content);
+        line.extend_from_slice(&buf[..room]);
+        reader.consume(used);
+        if done {
+            return Ok(Some(line));
+        }
+    }
+}
+
+/// Show `path` in the streamed view until it is closed with Esc or q
+pub(crate) fn run_stream_view(path: &str, settings: &Settings) -> io::Result<()> {
+    let mut index = LineIndex::open(path)?;
+    let name = std::path::Path::new(path)
+        .file_name()
+        .map_or_else(|| path.to_string(), |n| n.to_string_lossy().to_string());
+    let size_mb = index.len / (1024 * 1024);
+    let mut stdout = crate::no_color::stdout();
+    let mut top = 0usize;
+    let mut left = 0usize;
+    let mut message: Option<String> = None;
+    execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+    loop {
+        let (term_width, term_height) = terminal::size()?;
+        let page = (term_height as usize).saturating_sub(2).max(1);
+        index.index_to(top.saturating_add(page))?;
+        top = top.min(index.known_lines.saturating_sub(page));
+        let lines = index.lines(top, page)?;
+        let status = message.take().unwrap_or_else(|| {
+            let total = match index.total_lines() {
+                Some(total) => total.to_string(),
+                None => format!("{}+", index.known_lines),
+            };
+            format!(
+                " Lines {}-{} of {}  {} MB  g: go to line  End: index to end  q: close",
+                top + 1,
+                top + lines.len(),
+                total,
+                size_mb
+            )
+        });
+        let title = format!(" {}  (streamed, read-only)", name);
+        render(&mut stdout, settings, &title, &status, &lines, top, left, term_width as usize, page)?;
+
+        let key = match event::read()? {
+            Event::Key(key) => crate::event_handlers::normalize_key_event(key, settings),
+            Event::Mouse(mouse) => {
+                match mouse.kind {
+                    MouseEventKind::ScrollUp => top = top.saturating_sub(WHEEL_LINES),
+                    MouseEventKind::ScrollDown => top += WHEEL_LINES,
+                    _ => {}
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => break,
+            KeyCode::Up => top = top.saturating_sub(1),
+            KeyCode::Down => top += 1,
+            KeyCode::PageUp => top = top.saturating_sub(page),
+            KeyCode::PageDown => top += page,
+            KeyCode::Left => left = left.saturating_sub(8),
+            KeyCode::Right => left += 8,
+            KeyCode::Home => {
+                top = 0;
+                left = 0;
+            }
+            KeyCode::End => {
+                render_status(&mut stdout, settings, " Indexing to the end of the file...", term_width as usize, term_height)?;
+                index.index_to(usize::MAX)?;
+                top = usize::MAX;
+            }
+            KeyCode::Char('g') | KeyCode::Char('G') if !key.modifiers.contains(KeyModifiers::ALT) => {
+                let Some(input) = crate::event_handlers::show_text_prompt("Go to line", settings)? else {
+                    continue;
+                };
+                match input.trim().parse::<usize>() {
+                    Ok(line) => {
+                        index.index_to(line)?;
+                        let line = line.clamp(1, index.known_lines);
+                        top = line - 1;
+                    }
+                    Err(_) => message = Some(format!(" Not a line number: {}", input.trim())),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+    Ok(())
+}
+
+/// Draw the whole screen: the `title` row, `page` rows of `lines` (the first of
+/// them being line `top`) scrolled `left` columns, and the `status` row
+#[allow(clippy::too_many_arguments)]
+fn render(
+    stdout: &mut impl Write,
+    settings: &Settings,
+    title: &str,
+    status: &str,
+    lines: &[String],
+    top: usize,
+    left: usize,
+    width: usize,
+    page: usize,
+) -> io::Result<()> {
+    let tab_width = settings.tab_width;
+    let bg = settings.theme.chrome_bg;
+    let fit = |text: &str, width: usize| -> String {
+        let mut text = crate::rendering::truncate_to_width(text, width);
+        let pad = width.saturating_sub(crate::coordinates::visual_width(&text, tab_width));
+        text.push_str(&" ".repeat(pad));
+        text
+    };
+    let gutter = (top + page).to_string().len() + 1;
+
+    execute!(stdout, cursor::Hide, cursor::MoveTo(0, 0), SetBackgroundColor(bg), SetForegroundColor(Color::White))?;
+    write!(stdout, "{}", fit(title, width))?;
+    for row in 0..page {
+        execute!(stdout, cursor::MoveTo(0, 1 + row as u16), SetBackgroundColor(bg), SetForegroundColor(Color::DarkGrey))?;
+        let Some(line) = lines.get(row) else {
+            write!(stdout, "{}", " ".repeat(gutter + 1))?;
+            execute!(stdout, ResetColor)?;
+            write!(stdout, "{}", fit("", width.saturating_sub(gutter + 1)))?;
+            continue;
+        };
+        write!(stdout, "{:>w$} ", top + row + 1, w = gutter)?;
+        execute!(stdout, ResetColor)?;
+        let text: String = crate::rendering::expand_tabs(line, tab_width).chars().skip(left).collect();
+        write!(stdout, "{}", fit(&text, width.saturating_sub(gutter + 1)))?;
+    }
+    let (_, term_height) = terminal::size()?;
+    render_status(stdout, settings, status, width, term_height)
+}
+
+/// Draw `status` on the bottom row
+fn render_status(stdout: &mut impl Write, settings: &Settings, status: &str, width: usize, term_height: u16) -> io::Result<()> {
+    let text = crate::rendering::truncate_to_width(status, width);
+    let pad = width.saturating_sub(crate::coordinates::visual_width(&text, settings.tab_width));
+    execute!(
+        stdout,
+        cursor::MoveTo(0, term_height.saturating_sub(1)),
+        SetBackgroundColor(settings.theme.chrome_bg),
+        SetForegroundColor(Color::White)
+    )?;
+    write!(stdout, "{}{}", text, " ".repeat(pad))?;
+    execute!(stdout, ResetColor)?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_of(content: &[u8]) -> (tempfile::TempDir, LineIndex) {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("big.log");
+        std::fs::write(&path, content).unwrap();
+        let index = LineIndex::open(&path.to_string_lossy()).unwrap();
+        (tmp, index)
+    }
+
+    #[test]
+    fn lines_are_read_from_the_nearest_checkpoint() {
+        // More than one chunk, so indexing stops early
+        let content: String = (0..150_000).map(|n| format!("line {}\n", n)).collect();
+        let (_tmp, mut index) = index_of(content.as_bytes());
+        assert_eq!(index.lines(0, 2).unwrap(), vec!["line 0", "line 1"]);
+        assert_eq!(index.total_lines(), None, "only the first chunk is indexed");
+
+        assert_eq!(index.lines(2047, 3).unwrap(), vec!["line 2047", "line 2048", "line 2049"]);
+        assert_eq!(index.lines(149_998, 5).unwrap(), vec!["line 149998", "line 149999"]);
+        assert_eq!(index.total_lines(), Some(150_000), "the final line break starts no line");
+        assert_eq!(index.checkpoints.len(), 150_000 / CHECKPOINT_LINES + 1);
+        assert!(index.lines(150_000, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn line_endings_and_long_lines() {
+        let mut content = b"a\r\nb\n".to_vec();
+        content.extend(std::iter::repeat_n(b'x', MAX_LINE_BYTES + 10));
+        content.extend(b"\nlast");
+        let (_tmp, mut index) = index_of(&content);
+        let lines = index.lines(0, 10).unwrap();
+        assert_eq!(&lines[..2], &["a", "b"]);
+        assert_eq!(lines[2].len(), MAX_LINE_BYTES);
+        assert_eq!(lines[3], "last");
+        assert_eq!(index.total_lines(), Some(4));
+
+        let (_tmp, mut empty) = index_of(b"");
+        assert_eq!(empty.lines(0, 5).unwrap(), vec![String::new()]);
+        assert_eq!(empty.total_lines(), Some(1));
+    }
+}
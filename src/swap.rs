@@ -0,0 +1,100 @@
+//! Crash recovery: while a buffer has unsaved changes, a snapshot of it is
+//! flushed to a swap file in `~/.local/share/ue/swap/` every few seconds. A
+//! session that ends normally removes its swap file, so one that is left over
+//! from a process that no longer runs holds edits the next session can recover.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// How often a buffer with unsaved changes is written to its swap file
+pub(crate) const SWAP_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Without `/proc` to look up the writing process, a swap file that has not
+/// been flushed for this long counts as left behind
+const STALE_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SwapFile {
+    file: String,
+    pid: u32,
+    lines: Vec<String>,
+}
+
+/// Swap file for `file`: its path with `/` escaped, so each buffer has its own
+fn swap_path(file: &str) -> Option<PathBuf> {
+    let name = file.replace('%', "%%").replace('/', "%");
+    Some(crate::env::resolve_data_dir().ok()?.join("swap").join(format!("{}.swp", name)))
+}
+
+/// Write a snapshot of `lines` as the swap file of `file`
+pub(crate) fn write(file: &str, lines: &[String]) -> std::io::Result<()> {
+    let path = swap_path(file).ok_or_else(|| std::io::Error::other("no data directory"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let swap = SwapFile {
+        file: file.to_string(),
+        pid: std::process::id(),
+        lines: lines.to_vec(),
+    };
+    fs::write(path, serde_json::to_string(&swap)?)
+}
+
+/// Delete the swap file of `file`, if any
+pub(crate) fn remove(file: &str) {
+    if let Some(path) = swap_path(file) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Lines in a swap file of `file` that was left behind by a session that did
+/// not end normally. Swap files of running sessions are not reported.
+pub(crate) fn orphaned(file: &str) -> Option<Vec<String>> {
+    let path = swap_path(file)?;
+    let swap: SwapFile = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+    if swap.file != file || swap.pid == std::process::id() {
+        return None;
+    }
+    let proc_dir = std::path::Path::new("/proc");
+    let running = if proc_dir.is_dir() {
+        proc_dir.join(swap.pid.to_string()).exists()
+    } else {
+        fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age < STALE_AFTER)
+    };
+    (!running).then_some(swap.lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swap_of_another_process_that_ended_is_orphaned() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let file = "/tmp/project/notes.txt";
+        let lines = vec!["unsaved".to_string(), "edit".to_string()];
+
+        // Our own swap file belongs to a running session
+        write(file, &lines).unwrap();
+        assert_eq!(orphaned(file), None);
+
+        // Rewrite it as if a process that no longer exists had written it
+        let path = swap_path(file).unwrap();
+        let stale = SwapFile { file: file.to_string(), pid: u32::MAX, lines: lines.clone() };
+        fs::write(&path, serde_json::to_string(&stale).unwrap()).unwrap();
+        if std::path::Path::new("/proc").is_dir() {
+            assert_eq!(orphaned(file), Some(lines));
+        }
+        assert_eq!(orphaned("/tmp/project/other.txt"), None);
+
+        remove(file);
+        assert!(!path.exists());
+        assert_eq!(orphaned(file), None);
+    }
+}
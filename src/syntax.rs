@@ -1,6 +1,7 @@
 use crossterm::style::Color;
 use regex::Regex;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::path::Path;
 
 /// Byte ranges with colors for one highlighted line, plus an optional syntax switch action.
@@ -28,12 +29,48 @@ struct Pattern {
     switch_action: Option<SwitchAction>,
 }
 
+/// A construct that can span lines, like a block comment or a docstring
+/// (`region|<color>|<start>|<end>` directive, delimiters taken literally)
+#[derive(Debug, Clone)]
+struct Region {
+    start: String,
+    end: String,
+    color: Color,
+}
+
+/// Highlighting state at the start of a line, carried over from the lines above
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct LineState {
+    /// Embedded syntaxes entered by switch patterns (e.g. markdown code fences)
+    syntax_stack: Vec<String>,
+    /// Index of the region left open by the previous line
+    region: Option<usize>,
+}
+
 #[derive(Debug)]
 struct SyntaxDefinition {
     patterns: Vec<Pattern>,
     /// Regexes matched against the first few lines of a file to detect the syntax
     /// when the file has an unrecognized extension (e.g. `NuGet.config`, `00-init`).
     detect_patterns: Vec<Regex>,
+    /// Trailing tokens (e.g. `{`, `:`) after which Enter adds one indentation level
+    indent_after: Vec<String>,
+    /// Regexes matching comments, used to find TODO markers
+    comment_patterns: Vec<Regex>,
+    /// Delimiters inserted and removed by the comment toggle
+    comment_style: CommentStyle,
+    /// Regexes finding definitions for the symbol outline (group 1 is the label)
+    symbol_patterns: Vec<Regex>,
+    /// Multi-line constructs, highlighted across lines
+    regions: Vec<Region>,
+}
+
+/// Comment delimiters of a syntax (`line_comment|`, `block_comment_start|`,
+/// `block_comment_end|` directives)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct CommentStyle {
+    pub(crate) line: Option<String>,
+    pub(crate) block: Option<(String, String)>,
 }
 
 impl SyntaxDefinition {
@@ -41,6 +78,11 @@ impl SyntaxDefinition {
         Self {
             patterns: Vec::new(),
             detect_patterns: Vec::new(),
+            indent_after: Vec::new(),
+            comment_patterns: Vec::new(),
+            comment_style: CommentStyle::default(),
+            symbol_patterns: Vec::new(),
+            regions: Vec::new(),
         }
     }
 
@@ -67,9 +109,69 @@ impl SyntaxDefinition {
         Ok(())
     }
 
+    /// Byte ranges covered by the `comment|` patterns
+    fn comment_ranges(&self, line: &str) -> Vec<(usize, usize)> {
+        self.comment_patterns
+            .iter()
+            .flat_map(|re| re.find_iter(line).map(|m| (m.start(), m.end())))
+            .collect()
+    }
+
+    /// Byte ranges of the regions on a line and the region still open at its end.
+    /// `open` is the region left open by the previous line; delimiters inside
+    /// other matches (`segments`), such as `/*` in a string, are ignored
+    fn region_ranges(
+        &self,
+        line: &str,
+        open: Option<usize>,
+        segments: &[(usize, usize, Color, i32)],
+    ) -> (Vec<(usize, usize, Color)>, Option<usize>) {
+        let mut ranges = Vec::new();
+        let mut pos = 0;
+        if let Some(region) = open.and_then(|index| self.regions.get(index)) {
+            match line.find(&region.end) {
+                Some(end) => {
+                    pos = end + region.end.len();
+                    ranges.push((0, pos, region.color));
+                }
+                None => {
+                    ranges.push((0, line.len(), region.color));
+                    return (ranges, open);
+                }
+            }
+        }
+        loop {
+            let next = self
+                .regions
+                .iter()
+                .enumerate()
+                .flat_map(|(index, region)| {
+                    line[pos..].match_indices(region.start.as_str()).map(move |(at, _)| (pos + at, index))
+                })
+                .filter(|&(at, _)| !segments.iter().any(|&(start, end, _, _)| start >= pos && start < at && at < end))
+                .min();
+            let Some((start, index)) = next else {
+                return (ranges, None);
+            };
+            let region = &self.regions[index];
+            let body = start + region.start.len();
+            match line[body..].find(&region.end) {
+                Some(end) => {
+                    pos = body + end + region.end.len();
+                    ranges.push((start, pos, region.color));
+                }
+                None => {
+                    ranges.push((start, line.len(), region.color));
+                    return (ranges, Some(index));
+                }
+            }
+        }
+    }
+
     /// Apply syntax highlighting to a line, returning styled segments and optional switch action
     /// Returns (Vec of (start_byte, end_byte, color), Option<(SwitchAction, captured_extension)>)
-    fn highlight_line(&self, line: &str) -> HighlightResult {
+    /// and the region still open at the end of the line
+    fn highlight_line(&self, line: &str, open_region: Option<usize>) -> (HighlightResult, Option<usize>) {
         let mut segments = Vec::new();
         let mut switch_result: Option<(SwitchAction, String)> = None;
 
@@ -106,6 +208,10 @@ impl SyntaxDefinition {
             }
         }
 
+        // Regions win over every pattern
+        let (regions, open_region) = self.region_ranges(line, open_region, &segments);
+        segments.extend(regions.into_iter().map(|(start, end, color)| (start, end, color, i32::MAX)));
+
         // Sort by priority (higher first), then by start position
         segments.sort_by(|a, b| b.3.cmp(&a.3).then_with(|| a.0.cmp(&b.0)));
 
@@ -131,7 +237,7 @@ impl SyntaxDefinition {
 
         // Sort by start position for rendering
         result.sort_by_key(|s| s.0);
-        (result, switch_result)
+        ((result, switch_result), open_region)
     }
 }
 
@@ -244,11 +350,37 @@ impl SyntaxCache {
     fn load_syntax_file(extension: &str) -> Option<SyntaxDefinition> {
         // Use the default_syntax module which handles both user files and embedded defaults
         let content = crate::default_syntax::get_syntax_content(extension)?;
-        Self::parse_syntax_file(&content)
+        let mut def = Self::parse_syntax_file(&content)?;
+        // A deployed syntax file written by an older version lacks the newer
+        // directives; take each missing one from the compiled-in defaults so TODO
+        // markers, comment toggling, the symbol outline, multi-line regions and
+        // indentation still work
+        if let Some(embedded) = crate::default_syntax::get_embedded_syntax(extension)
+            .and_then(Self::parse_syntax_file)
+        {
+            if def.comment_patterns.is_empty() {
+                def.comment_patterns = embedded.comment_patterns;
+            }
+            if def.comment_style == CommentStyle::default() {
+                def.comment_style = embedded.comment_style;
+            }
+            if def.symbol_patterns.is_empty() {
+                def.symbol_patterns = embedded.symbol_patterns;
+            }
+            if def.regions.is_empty() {
+                def.regions = embedded.regions;
+            }
+            if def.indent_after.is_empty() {
+                def.indent_after = embedded.indent_after;
+            }
+        }
+        Some(def)
     }
 
     fn parse_syntax_file(content: &str) -> Option<SyntaxDefinition> {
         let mut def = SyntaxDefinition::new();
+        let mut block_start = None;
+        let mut block_end = None;
 
         for line in content.lines() {
             let line = line.trim();
@@ -271,6 +403,69 @@ impl SyntaxCache {
                 continue;
             }
 
+            // Handle auto-indent directives: indent_after|<token>
+            if parts.len() >= 2 && parts[0].trim() == "indent_after" {
+                let token = parts[1..].join("|");
+                if !token.trim().is_empty() {
+                    def.indent_after.push(token.trim().to_string());
+                }
+                continue;
+            }
+
+            // Handle comment directives: comment|<regex>
+            if parts.len() >= 2 && parts[0].trim() == "comment" {
+                let pattern = parts[1..].join("|");
+                if let Ok(regex) = Regex::new(pattern.trim()) {
+                    def.comment_patterns.push(regex);
+                }
+                continue;
+            }
+
+            // Handle symbol outline directives: symbol|<regex>
+            if parts.len() >= 2 && parts[0].trim() == "symbol" {
+                let pattern = parts[1..].join("|");
+                if let Ok(regex) = Regex::new(pattern.trim()) {
+                    def.symbol_patterns.push(regex);
+                }
+                continue;
+            }
+
+            // Handle comment toggle delimiters: line_comment|//, block_comment_start|/* ...
+            if parts.len() >= 2 {
+                let delimiter = parts[1..].join("|").trim().to_string();
+                match parts[0].trim() {
+                    "line_comment" if !delimiter.is_empty() => {
+                        def.comment_style.line = Some(delimiter);
+                        continue;
+                    }
+                    "block_comment_start" if !delimiter.is_empty() => {
+                        block_start = Some(delimiter);
+                        continue;
+                    }
+                    "block_comment_end" if !delimiter.is_empty() => {
+                        block_end = Some(delimiter);
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            // Handle multi-line regions: region|<color>|<start>|<end>
+            if parts[0].trim() == "region" {
+                if let [_, color, start, end] = parts[..]
+                    && let Some(color) = Self::parse_color(color.trim())
+                    && !start.trim().is_empty()
+                    && !end.trim().is_empty()
+                {
+                    def.regions.push(Region {
+                        start: start.trim().to_string(),
+                        end: end.trim().to_string(),
+                        color,
+                    });
+                }
+                continue;
+            }
+
             if parts.len() < 3 {
                 continue;
             }
@@ -295,6 +490,7 @@ impl SyntaxCache {
             }
         }
 
+        def.comment_style.block = block_start.zip(block_end);
         Some(def)
     }
 
@@ -334,6 +530,13 @@ struct SyntaxHighlighter {
     base_extension: Option<String>,
     /// Stack of syntax contexts - last is current
     syntax_stack: Vec<String>,
+    /// Region left open by the previous line
+    region: Option<usize>,
+    /// State at the start of each line of the document, as far as it was computed
+    line_states: Vec<LineState>,
+    /// Hash of each line the states after it were computed from; an edited line
+    /// no longer matches, which invalidates the states below it
+    line_hashes: Vec<u64>,
 }
 
 impl SyntaxHighlighter {
@@ -342,16 +545,40 @@ impl SyntaxHighlighter {
             cache: SyntaxCache::new(),
             base_extension: None,
             syntax_stack: Vec::new(),
+            region: None,
+            line_states: Vec::new(),
+            line_hashes: Vec::new(),
         }
     }
 
+    /// Forget the line states and start over at the top of the document
+    fn reset_line_states(&mut self) {
+        self.syntax_stack.clear();
+        self.region = None;
+        self.line_states.clear();
+        self.line_hashes.clear();
+    }
+
     fn set_file(&mut self, filepath: &str) {
         self.base_extension = Path::new(filepath)
             .extension()
             .and_then(|e| e.to_str())
             .map(|s| s.to_string());
         // Reset stack when changing files
-        self.syntax_stack.clear();
+        self.reset_line_states();
+    }
+
+    /// Use `extension`'s syntax for the current file regardless of its name
+    fn set_base(&mut self, extension: &str) {
+        self.base_extension = Some(SyntaxCache::resolve_alias(extension).to_string());
+        self.reset_line_states();
+    }
+
+    /// Canonical extension of the current file's syntax, if it is a known one
+    fn active_syntax(&mut self) -> Option<String> {
+        let ext = self.base_extension.clone()?;
+        self.cache.get_or_load(&ext)?;
+        Some(SyntaxCache::resolve_alias(&ext).to_string())
     }
 
     fn current_extension(&self) -> Option<&str> {
@@ -371,6 +598,56 @@ impl SyntaxHighlighter {
 
     fn clear_syntax_stack(&mut self) {
         self.syntax_stack.clear();
+        self.region = None;
+    }
+
+    /// Move past `line`: apply its syntax switch and carry an unclosed region
+    /// over to the next line
+    fn advance_line(&mut self, line: &str) {
+        let ((_, switch), region) = self.highlight_line(line);
+        self.region = region;
+        match switch {
+            Some((SwitchAction::SwitchBack, _)) => {
+                self.syntax_stack.pop();
+                self.region = None;
+            }
+            Some((SwitchAction::SwitchTo(_), extension)) => {
+                self.syntax_stack.push(extension);
+                self.region = None;
+            }
+            None => {}
+        }
+    }
+
+    /// Set up the state at the start of `lines[line_index]`, reusing the states
+    /// computed earlier for lines that have not changed since
+    fn restore_line_state(&mut self, lines: &[String], line_index: usize) {
+        let target = line_index.min(lines.len());
+        let mut known = 0;
+        while known < target
+            && known < self.line_hashes.len()
+            && self.line_hashes[known] == line_hash(&lines[known])
+        {
+            known += 1;
+        }
+        if known < target {
+            self.line_hashes.truncate(known);
+            self.line_states.truncate(known + 1);
+        }
+        let state = self.line_states.get(known).cloned().unwrap_or_default();
+        if self.line_states.is_empty() {
+            self.line_states.push(state.clone());
+        }
+        self.syntax_stack = state.syntax_stack;
+        self.region = state.region;
+        for line in &lines[known..target] {
+            self.advance_line(line);
+            self.line_hashes.push(line_hash(line));
+            self.line_states.push(LineState {
+                syntax_stack: self.syntax_stack.clone(),
+                region: self.region,
+            });
+        }
     }
 
     /// Returns `true` if the current file's extension maps to a known syntax definition.
@@ -391,44 +668,98 @@ impl SyntaxHighlighter {
         let preview: Vec<&str> = lines.iter().take(5).map(|s| s.as_str()).collect();
         if let Some(detected) = self.cache.detect_extension_from_content(&preview) {
             self.base_extension = Some(detected);
+            self.reset_line_states();
             true
         } else {
             false
         }
     }
 
-    fn highlight_line(&mut self, line: &str) -> HighlightResult {
+    /// Indent-increasing tokens for the current file's syntax
+    fn indent_after_tokens(&mut self) -> Vec<String> {
+        let Some(ext) = self.base_extension.clone() else {
+            return Vec::new();
+        };
+        self.cache
+            .get_or_load(&ext)
+            .map(|def| def.indent_after.clone())
+            .unwrap_or_default()
+    }
+
+    /// Comment byte ranges for a line in the current syntax, or `None` when the syntax
+    /// declares no `comment|` patterns (plain text, markdown, ...)
+    fn comment_ranges(&mut self, line: &str) -> Option<Vec<(usize, usize)>> {
+        let ext = self.current_extension()?.to_string();
+        let def = self.cache.get_or_load(&ext)?;
+        if def.comment_patterns.is_empty() {
+            return None;
+        }
+        Some(def.comment_ranges(line))
+    }
+
+    /// Comment delimiters of the file's own syntax (embedded languages are ignored)
+    fn comment_style(&mut self) -> CommentStyle {
+        let Some(ext) = self.base_extension.clone() else {
+            return CommentStyle::default();
+        };
+        self.cache
+            .get_or_load(&ext)
+            .map(|def| def.comment_style.clone())
+            .unwrap_or_default()
+    }
+
+    /// Symbol outline patterns of the file's own syntax
+    fn symbol_patterns(&mut self) -> Vec<Regex> {
+        let Some(ext) = self.base_extension.clone() else {
+            return Vec::new();
+        };
+        self.cache
+            .get_or_load(&ext)
+            .map(|def| def.symbol_patterns.clone())
+            .unwrap_or_default()
+    }
+
+    /// Highlight a line in the current state, also returning the region still
+    /// open at its end
+    fn highlight_line(&mut self, line: &str) -> (HighlightResult, Option<usize>) {
         let ext = self.current_extension().map(|s| s.to_string());
         let base_ext = self.base_extension.clone();
         let is_embedded = !self.syntax_stack.is_empty();
+        let open_region = self.region;
 
         if let Some(ext_str) = ext
             && let Some(def) = self.cache.get_or_load(&ext_str)
         {
-            let (highlights, switch) = def.highlight_line(line);
+            let ((highlights, switch), region) = def.highlight_line(line, open_region);
 
             // If we're in an embedded language and didn't find a switch action,
             // also check the base syntax for switch_back patterns
             if is_embedded && switch.is_none()
                 && let Some(ref base) = base_ext
                     && let Some(base_def) = self.cache.get_or_load(base) {
-                        let (base_highlights, base_switch) = base_def.highlight_line(line);
+                        let ((base_highlights, base_switch), _) = base_def.highlight_line(line, None);
                         // Only use base_switch if it's a switch_back action.
                         // Use the base syntax's highlights so the closing fence
                         // (e.g. ```) is coloured by the markdown rules, not the
                         // embedded language (which wouldn't match it at all).
                         if let Some((ref action, ref ext)) = base_switch
                             && matches!(action, SwitchAction::SwitchBack) {
-                                return (base_highlights, Some((action.clone(), ext.clone())));
+                                return ((base_highlights, Some((action.clone(), ext.clone()))), None);
                             }
                     }
 
-            return (highlights, switch);
+            return ((highlights, switch), region);
         }
-        (Vec::new(), None)
+        ((Vec::new(), None), None)
     }
 }
 
+fn line_hash(line: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    line.hash(&mut hasher);
+    hasher.finish()
+}
+
 // Thread-local singleton for syntax highlighter
 use std::cell::RefCell;
 thread_local! {
@@ -440,6 +771,48 @@ pub(crate) fn set_current_file(filepath: &str) {
     HIGHLIGHTER.with(|h| h.borrow_mut().set_file(filepath));
 }
 
+/// Override the current file's syntax (chosen by the user instead of detected)
+pub(crate) fn set_base_syntax(extension: &str) {
+    HIGHLIGHTER.with(|h| h.borrow_mut().set_base(extension));
+}
+
+/// Canonical extension of the current file's syntax, or `None` for plain text
+pub(crate) fn active_syntax() -> Option<String> {
+    HIGHLIGHTER.with(|h| h.borrow_mut().active_syntax())
+}
+
+/// Display name of a syntax, falling back to the extension itself
+pub(crate) fn language_name(extension: &str) -> &str {
+    match SyntaxCache::resolve_alias(extension) {
+        "rs" => "Rust",
+        "py" => "Python",
+        "js" => "JavaScript",
+        "ts" => "TypeScript",
+        "c" => "C",
+        "cpp" => "C++",
+        "go" => "Go",
+        "java" => "Java",
+        "sh" => "Shell",
+        "html" => "HTML",
+        "css" => "CSS",
+        "md" => "Markdown",
+        "json" => "JSON",
+        "xml" => "XML",
+        "toml" => "TOML",
+        "yaml" => "YAML",
+        "sql" => "SQL",
+        "txt" => "Plain text",
+        "ue-syntax" => "ue syntax",
+        "cs" => "C#",
+        _ => extension,
+    }
+}
+
+/// Color from a name used in syntax files (`dark_green`, `grey`, ...) or `#RRGGBB`
+pub(crate) fn parse_color(s: &str) -> Option<Color> {
+    SyntaxCache::parse_color(s)
+}
+
 /// Push a syntax override onto the stack (for embedded languages)
 pub(crate) fn push_syntax(extension: &str) {
     HIGHLIGHTER.with(|h| h.borrow_mut().push_syntax(extension.to_string()));
@@ -455,6 +828,18 @@ pub(crate) fn clear_syntax_stack() {
     HIGHLIGHTER.with(|h| h.borrow_mut().clear_syntax_stack());
 }
 
+/// Set up the highlighting state (embedded syntaxes, open block comments and
+/// strings) at the start of `lines[line_index]`. States of unchanged lines are
+/// cached, so only lines below an edit are highlighted again.
+pub(crate) fn restore_line_state(lines: &[String], line_index: usize) {
+    HIGHLIGHTER.with(|h| h.borrow_mut().restore_line_state(lines, line_index));
+}
+
+/// Carry the highlighting state past `line` to the start of the next line
+pub(crate) fn advance_line(line: &str) {
+    HIGHLIGHTER.with(|h| h.borrow_mut().advance_line(line));
+}
+
 /// For files with an unrecognized extension (or no extension at all), examine
 /// the first few lines of content and select the best matching syntax.
 /// Must be called *after* `set_current_file` and *after* the lines are loaded.
@@ -465,11 +850,31 @@ pub(crate) fn maybe_detect_syntax_from_content(lines: &[String]) {
     });
 }
 
+/// Trailing tokens after which Enter adds an indentation level for the current file
+pub(crate) fn indent_after_tokens() -> Vec<String> {
+    HIGHLIGHTER.with(|h| h.borrow_mut().indent_after_tokens())
+}
+
+/// Byte ranges of comments on a line, or `None` if the current file has no syntax
+pub(crate) fn comment_ranges(line: &str) -> Option<Vec<(usize, usize)>> {
+    HIGHLIGHTER.with(|h| h.borrow_mut().comment_ranges(line))
+}
+
+/// Comment delimiters for the current file
+pub(crate) fn comment_style() -> CommentStyle {
+    HIGHLIGHTER.with(|h| h.borrow_mut().comment_style())
+}
+
+/// Symbol outline patterns for the current file
+pub(crate) fn symbol_patterns() -> Vec<Regex> {
+    HIGHLIGHTER.with(|h| h.borrow_mut().symbol_patterns())
+}
+
 /// Get syntax highlighting for a line, with optional switch action
 /// Returns (Vec of (start_byte, end_byte, color), Option<(is_switch_back, extension)>)
 /// where is_switch_back is true for switch_back, false for switch_to with the extension name
 pub(crate) fn highlight_line(line: &str) -> HighlightOutput {
-    let (highlights, switch) = HIGHLIGHTER.with(|h| h.borrow_mut().highlight_line(line));
+    let ((highlights, switch), _) = HIGHLIGHTER.with(|h| h.borrow_mut().highlight_line(line));
 
     // Convert SwitchAction to simpler bool + string tuple
     let switch_result = switch.map(|(action, ext)| {
@@ -515,7 +920,7 @@ mod tests {
         def.add_pattern(r#""[^"]*""#, Color::Green, 2, None).unwrap();
 
         let line = r#"let x = "hello";"#;
-        let (highlights, _) = def.highlight_line(line);
+        let ((highlights, _), _) = def.highlight_line(line, None);
 
         assert!(!highlights.is_empty());
     }
@@ -533,7 +938,7 @@ mod tests {
         .unwrap();
 
         let line = "```rs";
-        let (_highlights, switch) = def.highlight_line(line);
+        let ((_highlights, switch), _) = def.highlight_line(line, None);
 
         assert!(switch.is_some());
         let (action, ext) = switch.unwrap();
@@ -549,13 +954,75 @@ mod tests {
             .unwrap();
 
         let line = "```";
-        let (_highlights, switch) = def.highlight_line(line);
+        let ((_highlights, switch), _) = def.highlight_line(line, None);
 
         assert!(switch.is_some());
         let (action, _) = switch.unwrap();
         assert!(matches!(action, SwitchAction::SwitchBack));
     }
 
+    #[test]
+    fn block_comment_region_spans_lines() {
+        let def = SyntaxCache::parse_syntax_file(
+            "10|dark_green|//.*$\n9|green|\"[^\"]*\"\n7|blue|\\bfn\\b\nregion|dark_green|/*|*/\n",
+        )
+        .unwrap();
+
+        let ((highlights, _), open) = def.highlight_line("let s = \"/*\"; // /* not a comment", None);
+        assert_eq!(open, None);
+        assert!(highlights.iter().all(|&(_, _, color)| color != Color::Blue));
+
+        let ((highlights, _), open) = def.highlight_line("fn a() {} /* start", None);
+        assert_eq!(open, Some(0));
+        assert_eq!(highlights.last(), Some(&(10, 18, Color::DarkGreen)));
+
+        let ((highlights, _), open) = def.highlight_line("fn inside", open);
+        assert_eq!(open, Some(0));
+        assert_eq!(highlights, vec![(0, 9, Color::DarkGreen)]);
+
+        let ((highlights, _), open) = def.highlight_line("end */ fn", open);
+        assert_eq!(open, None);
+        assert_eq!(highlights, vec![(0, 6, Color::DarkGreen), (7, 9, Color::Blue)]);
+    }
+
+    #[test]
+    fn line_states_are_cached_and_invalidated_by_edits() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let mut highlighter = SyntaxHighlighter::new();
+        highlighter.set_file("test.rs");
+        let mut lines: Vec<String> = ["/* one", "two", "*/ three", "four"].iter().map(|s| s.to_string()).collect();
+
+        highlighter.restore_line_state(&lines, 2);
+        assert_eq!(highlighter.region, Some(0));
+        highlighter.restore_line_state(&lines, 4);
+        assert_eq!(highlighter.region, None);
+        assert_eq!(highlighter.line_states.len(), 5);
+
+        // Closing the comment on the first line changes the state of every line below
+        lines[0] = "/* one */".to_string();
+        highlighter.restore_line_state(&lines, 2);
+        assert_eq!(highlighter.region, None);
+        assert_eq!(highlighter.line_states.len(), 3);
+        highlighter.restore_line_state(&lines, 4);
+        assert_eq!(highlighter.region, None);
+    }
+
+    #[test]
+    fn old_deployed_syntax_takes_missing_directives_from_defaults() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let dir = crate::env::resolve_config_dir().unwrap().join("syntax");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("rs.ue-syntax"), "7|blue|\\bfn\\b\n").unwrap();
+
+        let def = SyntaxCache::load_syntax_file("rs").unwrap();
+        assert!(def.indent_after.contains(&"{".to_string()));
+        // Every other directive missing from the old file comes from the same defaults
+        assert!(!def.comment_patterns.is_empty());
+        assert_ne!(def.comment_style, CommentStyle::default());
+        assert!(!def.symbol_patterns.is_empty());
+        assert!(!def.regions.is_empty());
+    }
+
     #[test]
     fn test_resolve_alias() {
         assert_eq!(SyntaxCache::resolve_alias("bash"), "sh");
@@ -565,6 +1032,23 @@ mod tests {
         assert_eq!(SyntaxCache::resolve_alias("unknown"), "unknown"); // No alias
     }
 
+    #[test]
+    fn set_base_overrides_file_extension() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let mut highlighter = SyntaxHighlighter::new();
+        highlighter.set_file("build.log");
+        assert_eq!(highlighter.active_syntax(), None);
+
+        highlighter.push_syntax("rs".to_string());
+        highlighter.set_base("python");
+        assert_eq!(highlighter.current_extension(), Some("py"));
+        assert_eq!(highlighter.active_syntax().as_deref(), Some("py"));
+        assert!(!highlighter.highlight_line("x = 1  # note").0.0.is_empty());
+        assert_eq!(language_name("py"), "Python");
+        assert_eq!(language_name("bash"), "Shell");
+        assert_eq!(language_name("log"), "log");
+    }
+
     #[test]
     fn test_syntax_highlighter_stack() {
         let mut highlighter = SyntaxHighlighter::new();
@@ -0,0 +1,102 @@
+//! Per-extension templates that pre-populate newly created files.
+//!
+//! Templates live in `~/.config/ue/templates/` and are named after the
+//! extension they apply to, e.g. `template.sh` or `template.rs`.
+
+use std::path::{Path, PathBuf};
+
+/// Marker inside a template where the cursor is placed; removed on insertion
+const CURSOR_MARKER: &str = "$CURSOR";
+
+fn templates_dir() -> Option<PathBuf> {
+    crate::env::resolve_config_dir().ok().map(|dir| dir.join("templates"))
+}
+
+/// Template text for the extension of `file`, if one is configured
+pub(crate) fn template_for(file: &str) -> Option<String> {
+    let ext = Path::new(file).extension()?.to_str()?;
+    let path = templates_dir()?.join(format!("template.{}", ext));
+    std::fs::read_to_string(path).ok()
+}
+
+/// Expanded template for the first save of a blank buffer under `target`,
+/// a path that does not exist yet. A buffer with any text keeps it.
+pub(crate) fn for_first_save(target: &str, lines: &[String]) -> Option<(Vec<String>, (usize, usize))> {
+    let blank = lines.len() <= 1 && lines.iter().all(|line| line.is_empty());
+    if !blank || Path::new(target).exists() {
+        return None;
+    }
+    template_for(target).map(|t| expand(&t))
+}
+
+/// Split template text into buffer lines, removing the first `$CURSOR` marker
+/// and returning its (line, column) position (start of the buffer if absent)
+pub(crate) fn expand(template: &str) -> (Vec<String>, (usize, usize)) {
+    let mut cursor = None;
+    let mut lines: Vec<String> = template
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            if cursor.is_none()
+                && let Some(byte) = line.find(CURSOR_MARKER)
+            {
+                cursor = Some((idx, line[..byte].chars().count()));
+                return line.replacen(CURSOR_MARKER, "", 1);
+            }
+            line.to_string()
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    (lines, cursor.unwrap_or((0, 0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::set_temp_home;
+
+    #[test]
+    fn expand_places_cursor_at_marker() {
+        let (lines, cursor) = expand("#!/bin/sh\n\nmain() {\n    $CURSOR\n}\n");
+        assert_eq!(lines, vec!["#!/bin/sh", "", "main() {", "    ", "}"]);
+        assert_eq!(cursor, (3, 4));
+
+        let (lines, cursor) = expand("fn main() {}\n");
+        assert_eq!(lines, vec!["fn main() {}"]);
+        assert_eq!(cursor, (0, 0));
+    }
+
+    #[test]
+    fn template_is_resolved_by_extension() {
+        let (tmp, _guard) = set_temp_home();
+        let dir = tmp.path().join("config").join("templates");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("template.sh"), "#!/bin/sh\n").unwrap();
+
+        assert_eq!(template_for("/tmp/new.sh").as_deref(), Some("#!/bin/sh\n"));
+        assert_eq!(template_for("/tmp/new.rs"), None);
+        assert_eq!(template_for("/tmp/Makefile"), None);
+    }
+
+    #[test]
+    fn blank_buffer_takes_the_template_of_its_first_name() {
+        let (tmp, _guard) = set_temp_home();
+        let dir = tmp.path().join("config").join("templates");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("template.sh"), "#!/bin/sh
+$CURSOR
+").unwrap();
+        let target = tmp.path().join("new.sh").to_string_lossy().to_string();
+        let blank = vec![String::new()];
+
+        let (lines, cursor) = for_first_save(&target, &blank).unwrap();
+        assert_eq!(lines, vec!["#!/bin/sh", ""]);
+        assert_eq!(cursor, (1, 0));
+        assert_eq!(for_first_save(&target, &["echo".to_string()]), None, "text is kept");
+
+        std::fs::write(&target, "").unwrap();
+        assert_eq!(for_first_save(&target, &blank), None, "existing files are not replaced");
+    }
+}
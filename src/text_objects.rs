@@ -0,0 +1,205 @@
+//! Select inside / around delimiters: the contents of the nearest enclosing
+//! bracket pair or quoted string around the cursor, with or without the
+//! delimiters themselves.
+
+use crate::editor_state::{FileViewerState, Position};
+
+/// Opening and closing character for a delimiter key (either half of a pair)
+pub(crate) fn delimiter_pair(key: char) -> Option<(char, char)> {
+    match key {
+        '(' | ')' => Some(('(', ')')),
+        '[' | ']' => Some(('[', ']')),
+        '{' | '}' => Some(('{', '}')),
+        '<' | '>' => Some(('<', '>')),
+        '"' | '\'' | '`' => Some((key, key)),
+        _ => None,
+    }
+}
+
+/// Range (start, end) of the delimited region around `pos`, end exclusive.
+/// `around` includes the delimiters.
+pub(crate) fn delimited_range(
+    lines: &[String],
+    pos: Position,
+    key: char,
+    around: bool,
+) -> Option<(Position, Position)> {
+    let (open, close) = delimiter_pair(key)?;
+    let (start, end) = if open == close {
+        quote_pair(lines.get(pos.0)?, pos, open)?
+    } else {
+        bracket_pair(lines, pos, open, close)?
+    };
+    if around {
+        Some((start, (end.0, end.1 + 1)))
+    } else {
+        Some(((start.0, start.1 + 1), end))
+    }
+}
+
+/// Positions of the enclosing `open` and its matching `close`. A bracket under
+/// the cursor counts as enclosing it.
+fn bracket_pair(lines: &[String], pos: Position, open: char, close: char) -> Option<(Position, Position)> {
+    let chars: Vec<Vec<char>> = lines.iter().map(|l| l.chars().collect()).collect();
+    let at = |(line, col): Position| chars.get(line).and_then(|l| l.get(col)).copied();
+
+    let start = if at(pos) == Some(open) {
+        pos
+    } else {
+        // Scan backwards for an unmatched opener; a closer under the cursor
+        // belongs to the pair being searched for
+        let mut depth = 0usize;
+        let mut found = None;
+        let mut line = pos.0.min(chars.len().checked_sub(1)?);
+        let mut col = if line == pos.0 { pos.1.min(chars[line].len()) } else { chars[line].len() };
+        loop {
+            while col > 0 {
+                col -= 1;
+                match chars[line][col] {
+                    c if c == close => depth += 1,
+                    c if c == open && depth == 0 => {
+                        found = Some((line, col));
+                        break;
+                    }
+                    c if c == open => depth -= 1,
+                    _ => {}
+                }
+            }
+            if found.is_some() || line == 0 {
+                break;
+            }
+            line -= 1;
+            col = chars[line].len();
+        }
+        found?
+    };
+
+    let mut depth = 0usize;
+    let (mut line, mut col) = (start.0, start.1 + 1);
+    while line < chars.len() {
+        while col < chars[line].len() {
+            match chars[line][col] {
+                c if c == open => depth += 1,
+                c if c == close && depth == 0 => return Some((start, (line, col))),
+                c if c == close => depth -= 1,
+                _ => {}
+            }
+            col += 1;
+        }
+        line += 1;
+        col = 0;
+    }
+    None
+}
+
+/// Positions of the quotes around `pos` on its line, skipping escaped quotes.
+/// Quotes pair up from the start of the line; when the cursor is in no pair,
+/// the next pair after it is used.
+fn quote_pair(line: &str, pos: Position, quote: char) -> Option<(Position, Position)> {
+    let mut quotes = Vec::new();
+    let mut escaped = false;
+    for (col, ch) in line.chars().enumerate() {
+        if escaped {
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == quote {
+            quotes.push(col);
+        }
+    }
+    let pairs: Vec<(usize, usize)> = quotes.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+    let (open, close) = pairs
+        .iter()
+        .find(|&&(open, close)| open <= pos.1 && pos.1 <= close)
+        .or_else(|| pairs.iter().find(|&&(open, _)| open > pos.1))
+        .copied()?;
+    Some(((pos.0, open), (pos.0, close)))
+}
+
+/// Select the region delimited by `key` around the cursor, leaving the cursor
+/// at its end. An empty inside region just moves the cursor there. Returns
+/// false (with an alert) when there is no such region.
+pub(crate) fn select_delimited(
+    state: &mut FileViewerState,
+    lines: &[String],
+    key: char,
+    around: bool,
+    visible_lines: usize,
+) -> bool {
+    let Some((start, end)) = delimited_range(lines, state.current_position(), key, around) else {
+        state.status_message = Some(match delimiter_pair(key) {
+            Some((open, close)) => format!("No enclosing {}{}", open, close),
+            None => format!("Not a delimiter: {}", key),
+        });
+        state.alert();
+        return false;
+    };
+    state.clear_multi_cursors();
+    state.block_selection = false;
+    state.set_cursor_position(end.0, end.1, lines, visible_lines);
+    state.desired_cursor_col = end.1;
+    if start == end {
+        state.clear_selection();
+    } else {
+        state.selection_start = Some(start);
+        state.selection_end = Some(end);
+    }
+    state.needs_redraw = true;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn brackets_match_nesting_across_lines() {
+        let text = lines(&["call(a, (b), {", "    x: [1],", "}) + 1"]);
+        assert_eq!(delimited_range(&text, (0, 6), '(', false), Some(((0, 5), (2, 1))));
+        assert_eq!(delimited_range(&text, (0, 9), ')', false), Some(((0, 9), (0, 10))));
+        assert_eq!(delimited_range(&text, (0, 9), '(', true), Some(((0, 8), (0, 11))));
+        assert_eq!(delimited_range(&text, (1, 4), '{', true), Some(((0, 13), (2, 1))));
+        // On the closing bracket itself
+        assert_eq!(delimited_range(&text, (2, 1), '(', true), Some(((0, 4), (2, 2))));
+        assert_eq!(delimited_range(&text, (2, 4), '(', false), None);
+        assert_eq!(delimited_range(&text, (0, 0), 'x', false), None);
+    }
+
+    #[test]
+    fn quotes_skip_escapes_and_fall_forward() {
+        let text = lines(&[r#"say("a \"b\" c", 'd')"#]);
+        assert_eq!(delimited_range(&text, (0, 7), '"', false), Some(((0, 5), (0, 14))));
+        assert_eq!(delimited_range(&text, (0, 7), '"', true), Some(((0, 4), (0, 15))));
+        // Before the first pair: the next pair is used
+        assert_eq!(delimited_range(&text, (0, 0), '\'', false), Some(((0, 18), (0, 19))));
+        assert_eq!(delimited_range(&text, (0, 20), '\'', false), None);
+    }
+
+    #[test]
+    fn select_delimited_selects_and_moves_cursor_to_end() {
+        use crate::settings::Settings;
+        use crate::undo::UndoHistory;
+
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let settings = Settings::default();
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        let text = lines(&["f(x, y)", "g()"]);
+        state.cursor_col = 3;
+        assert!(select_delimited(&mut state, &text, '(', false, 10));
+        assert_eq!(state.selection_start, Some((0, 2)));
+        assert_eq!(state.selection_end, Some((0, 6)));
+        assert_eq!(state.current_position(), (0, 6));
+
+        state.clear_selection();
+        state.cursor_line = 1;
+        state.cursor_col = 2;
+        assert!(select_delimited(&mut state, &text, ')', false, 10));
+        assert_eq!(state.selection_start, None);
+        assert_eq!(state.current_position(), (1, 2));
+        assert!(!select_delimited(&mut state, &text, '[', false, 10));
+    }
+}
@@ -0,0 +1,178 @@
+//! Color themes (`appearance.theme` setting): the colors of the UI chrome,
+//! selections, search matches and scrollbars, and a mapping for the colors
+//! named in syntax files. Themes are TOML files in `~/.config/ue/themes/`;
+//! the built-in `dark` and `light` themes are deployed there on startup.
+
+use crossterm::style::Color;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+
+const THEME_DARK: &str = include_str!("../defaults/themes/dark.toml");
+const THEME_LIGHT: &str = include_str!("../defaults/themes/light.toml");
+
+/// Built-in themes, by name
+fn builtin_theme(name: &str) -> Option<&'static str> {
+    match name {
+        "dark" => Some(THEME_DARK),
+        "light" => Some(THEME_LIGHT),
+        _ => None,
+    }
+}
+
+/// A theme file as written: color names or `#RRGGBB` per key
+#[derive(Debug, Default, Deserialize)]
+struct ThemeFile {
+    #[serde(default)]
+    ui: HashMap<String, String>,
+    #[serde(default)]
+    syntax: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Theme {
+    /// Header, footer, line numbers, menus and dialogs
+    pub(crate) chrome_bg: Color,
+    pub(crate) selection_bg: Color,
+    pub(crate) search_match_bg: Color,
+    /// The search match the cursor is on
+    pub(crate) search_current_bg: Color,
+    /// Scrollbar thumb, also used to mark the cursor's line number
+    pub(crate) scrollbar_fg: Color,
+    /// Selected entry in menus, tabs and dialog lists
+    pub(crate) menu_selection_bg: Color,
+    /// Tab arrows and space dots shown by `show_invisibles`
+    pub(crate) invisibles_fg: Color,
+    /// Trailing spaces and tabs
+    pub(crate) trailing_whitespace_bg: Color,
+    /// The cursor's line when `current_line_highlight` is on
+    pub(crate) current_line_bg: Color,
+    /// Indent guides (`indent_guides`)
+    pub(crate) indent_guide_fg: Color,
+    /// Colors named in syntax files, mapped to the color shown
+    syntax: HashMap<Color, Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let mut theme = Theme {
+            chrome_bg: Color::Rgb { r: 0, g: 24, b: 72 },
+            selection_bg: Color::DarkGrey,
+            search_match_bg: Color::Rgb { r: 100, g: 150, b: 200 },
+            search_current_bg: Color::Rgb { r: 50, g: 100, b: 200 },
+            scrollbar_fg: Color::Rgb { r: 100, g: 149, b: 237 },
+            menu_selection_bg: Color::Rgb { r: 100, g: 149, b: 237 },
+            invisibles_fg: Color::DarkGrey,
+            trailing_whitespace_bg: Color::Red,
+            current_line_bg: Color::Rgb { r: 38, g: 38, b: 46 },
+            indent_guide_fg: Color::Rgb { r: 70, g: 70, b: 70 },
+            syntax: HashMap::new(),
+        };
+        theme.apply(THEME_DARK);
+        theme
+    }
+}
+
+impl Theme {
+    /// Theme `name` from the themes directory, falling back to the built-in
+    /// theme of that name and then to the dark theme
+    pub(crate) fn load(name: &str) -> Theme {
+        let user_file = crate::env::resolve_config_dir()
+            .ok()
+            .and_then(|dir| fs::read_to_string(dir.join("themes").join(format!("{}.toml", name))).ok());
+        let mut theme = Theme::default();
+        if let Some(content) = user_file.as_deref().or_else(|| builtin_theme(name)) {
+            theme.apply(content);
+        }
+        theme
+    }
+
+    /// Override the colors given in a theme file; unknown keys and colors
+    /// that do not parse are ignored
+    fn apply(&mut self, content: &str) {
+        let Ok(file) = toml::from_str::<ThemeFile>(content) else {
+            return;
+        };
+        for (key, value) in &file.ui {
+            let Some(color) = crate::syntax::parse_color(value.trim()) else {
+                continue;
+            };
+            match key.as_str() {
+                "chrome_bg" => self.chrome_bg = color,
+                "selection_bg" => self.selection_bg = color,
+                "search_match_bg" => self.search_match_bg = color,
+                "search_current_bg" => self.search_current_bg = color,
+                "scrollbar_fg" => self.scrollbar_fg = color,
+                "menu_selection_bg" => self.menu_selection_bg = color,
+                "invisibles_fg" => self.invisibles_fg = color,
+                "trailing_whitespace_bg" => self.trailing_whitespace_bg = color,
+                "current_line_bg" => self.current_line_bg = color,
+                "indent_guide_fg" => self.indent_guide_fg = color,
+                _ => {}
+            }
+        }
+        for (name, value) in &file.syntax {
+            if let (Some(from), Some(to)) =
+                (crate::syntax::parse_color(name.trim()), crate::syntax::parse_color(value.trim()))
+            {
+                self.syntax.insert(from, to);
+            }
+        }
+    }
+
+    /// Color shown for a color used by the syntax highlighting
+    pub(crate) fn syntax_color(&self, color: Color) -> Color {
+        self.syntax.get(&color).copied().unwrap_or(color)
+    }
+}
+
+/// Deploy the built-in themes to ~/.config/ue/themes/, skipping existing files
+/// so edited copies are kept
+pub fn deploy_default_themes() -> Result<(), Box<dyn std::error::Error>> {
+    let theme_dir = crate::env::resolve_config_dir()?.join("themes");
+    fs::create_dir_all(&theme_dir)?;
+    for (name, content) in [("dark", THEME_DARK), ("light", THEME_LIGHT)] {
+        let path = theme_dir.join(format!("{}.toml", name));
+        if !path.exists() {
+            fs::write(&path, content)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_theme_overrides_ui_and_maps_syntax_colors() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let dark = Theme::load("dark");
+        assert_eq!(dark, Theme::default());
+        assert_eq!(dark.syntax_color(Color::Yellow), Color::Yellow);
+
+        let light = Theme::load("light");
+        assert_ne!(light.chrome_bg, dark.chrome_bg);
+        assert_eq!(light.syntax_color(Color::White), Color::Black);
+        assert_eq!(light.syntax_color(Color::Red), Color::Red);
+
+        assert_eq!(Theme::load("no-such-theme"), dark);
+    }
+
+    #[test]
+    fn user_theme_file_overrides_only_given_colors() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let dir = crate::env::resolve_config_dir().unwrap().join("themes");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("mine.toml"),
+            "[ui]\nselection_bg = \"#102030\"\nbogus = \"red\"\n[syntax]\ndark_green = \"grey\"\n",
+        )
+        .unwrap();
+
+        let theme = Theme::load("mine");
+        assert_eq!(theme.selection_bg, Color::Rgb { r: 16, g: 32, b: 48 });
+        assert_eq!(theme.chrome_bg, Theme::default().chrome_bg);
+        assert_eq!(theme.syntax_color(Color::DarkGreen), Color::Grey);
+    }
+}
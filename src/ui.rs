@@ -3,8 +3,8 @@ use std::io::{self, Write};
 use std::time::{Duration, Instant};
 
 use crossterm::{
-    cursor::{Hide, SetCursorStyle, Show},
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    cursor::{SetCursorStyle, Show},
+    event::{self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event, KeyCode, KeyEvent},
     execute,
     terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen, size},
 };
@@ -12,6 +12,7 @@ use crossterm::{
 use crate::coordinates::adjust_view_for_resize;
 use crate::double_esc::{DoubleEscDetector, EscResult};
 use crate::editor_state::FileViewerState;
+use crate::encoding::FileEncoding;
 use crate::event_handlers::{
     handle_key_event, handle_mouse_event, show_undo_conflict_confirmation,
 };
@@ -40,7 +41,7 @@ const CURSOR_CONTEXT_LINES: usize = 5;
 const UNDO_FILE_CHECK_INTERVAL_MS: u64 = 150;
 const SAVE_GRACE_PERIOD_MS: u64 = 200;
 
-/// Generate a unique untitled filename (untitled, untitled-2, untitled-3, etc.)
+/// Create a new untitled buffer and return its unique name (untitled-1, untitled-2, etc.)
 pub fn generate_untitled_filename() -> String {
     // Collect the set of untitled names already in use (from recent files).
     let recent = crate::recent::get_recent_files().unwrap_or_default();
@@ -55,13 +56,133 @@ pub fn generate_untitled_filename() -> String {
     let mut n: usize = 1;
     loop {
         let candidate = format!("untitled-{}", n);
-        if !used.contains(&candidate) {
+        if !used.contains(&candidate) && !is_untitled(&candidate) {
+            // The buffer's history records that it is untitled until it is saved or closed
+            let mut history = UndoHistory::new();
+            history.untitled = true;
+            let _ = history.save(&candidate);
             return candidate;
         }
         n += 1;
     }
 }
 
+/// Whether `name` is an untitled buffer, as recorded in its history when it was created.
+/// Only bare names can be one; files are always opened by their path.
+pub fn is_untitled(name: &str) -> bool {
+    std::path::Path::new(name).file_name().is_some_and(|n| n == name)
+        && UndoHistory::records_untitled(name)
+}
+
+/// Read the content behind a buffer name. Untitled buffers have no file on disk,
+/// even when the working directory happens to hold a file of the same name.
+fn read_buffer(file: &str, untitled: bool) -> io::Result<(String, FileEncoding)> {
+    if untitled {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "untitled buffer"));
+    }
+    crate::encoding::read_file(file)
+}
+
+/// Put `bytes` (e.g. piped into `ue -`) into a new untitled buffer and return its
+/// name. The text is kept as the buffer's unsaved content until it is saved.
+pub fn create_untitled_buffer(bytes: &[u8]) -> io::Result<String> {
+    let name = generate_untitled_filename();
+    let mut lines = crate::line_ending::split_lines(&crate::encoding::decode(bytes).0);
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    let mut history = UndoHistory::new();
+    history.untitled = true;
+    history.file_content = Some(lines);
+    history.modified = true;
+    history.save(&name).map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(name)
+}
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Name of the scratch buffer. It is never backed by a file and never gets an undo file.
+pub const SCRATCH_BUFFER_NAME: &str = "*scratch*";
+
+/// Scratch buffer content, kept in memory for the lifetime of the process
+static SCRATCH_LINES: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+/// Zen mode stays active across files for the lifetime of the process
+static ZEN_MODE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Settings for each gutter layout: the blank margin of zen mode, or the
+/// line-number gutter widened by the blame annotation and the sidebar
+struct GutterSettings<'a> {
+    plain: &'a Settings,
+    zen: Settings,
+    blame: Settings,
+    sidebar: Settings,
+    blame_sidebar: Settings,
+}
+
+impl<'a> GutterSettings<'a> {
+    fn new(settings: &'a Settings) -> Self {
+        let blame = settings.blame_variant();
+        GutterSettings {
+            plain: settings,
+            zen: settings.zen_variant(),
+            sidebar: settings.sidebar_variant(),
+            blame_sidebar: blame.sidebar_variant(),
+            blame,
+        }
+    }
+
+    /// The settings matching the gutter `state` shows
+    fn for_state(&self, state: &FileViewerState) -> &Settings {
+        match (state.zen_mode, state.blame.is_some(), state.sidebar_open) {
+            (true, _, _) => &self.zen,
+            (false, false, false) => self.plain,
+            (false, true, false) => &self.blame,
+            (false, false, true) => &self.sidebar,
+            (false, true, true) => &self.blame_sidebar,
+        }
+    }
+}
+
+/// Whitespace display (`show_invisibles`) stays as toggled across files
+static SHOW_INVISIBLES: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Remember the whitespace display for files opened later
+pub(crate) fn set_show_invisibles(show: bool) {
+    SHOW_INVISIBLES.store(show, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Set by `--follow`: files open read-only and pick up data appended on disk
+static FOLLOW_MODE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Open files in follow mode (`tail -f` style) for the rest of the process
+pub fn enable_follow_mode() {
+    FOLLOW_MODE.store(true, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Set by `--stdout`: the buffer open at quit is written to stdout for use in pipelines
+static EMIT_TO_STDOUT: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Write the final buffer to stdout when the editor quits
+pub fn enable_stdout_emit() {
+    EMIT_TO_STDOUT.store(true, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Check whether a filename refers to the scratch buffer
+pub fn is_scratch_buffer(file: &str) -> bool {
+    file == SCRATCH_BUFFER_NAME
+}
+
+fn load_scratch_lines() -> Vec<String> {
+    SCRATCH_LINES.lock().map(|l| l.clone()).unwrap_or_default()
+}
+
+fn store_scratch_lines(lines: &[String]) {
+    if let Ok(mut stored) = SCRATCH_LINES.lock() {
+        *stored = lines.to_vec();
+    }
+}
+
 
 /// Helper to fully restore terminal state on exit or when switching out of the editor
 fn restore_terminal(stdout: &mut impl Write) -> io::Result<()> {
@@ -71,6 +192,7 @@ fn restore_terminal(stdout: &mut impl Write) -> io::Result<()> {
         SetCursorStyle::DefaultUserShape,
         Show,
         DisableMouseCapture,
+        DisableBracketedPaste,
         LeaveAlternateScreen
     )?;
     // Best-effort: raw mode might already be disabled in some flows
@@ -78,34 +200,177 @@ fn restore_terminal(stdout: &mut impl Write) -> io::Result<()> {
     Ok(())
 }
 
-pub fn show(files: &[String]) -> std::io::Result<()> {
+/// When stdout is not a terminal (`ue --stdout file | sort`), point fd 1 at the
+/// terminal so the screen does not end up in the pipe. Returns the original stdout.
+#[cfg(unix)]
+fn redirect_stdout_to_tty() -> io::Result<Option<fs::File>> {
+    use std::io::IsTerminal;
+    use std::os::fd::{AsRawFd, FromRawFd};
+
+    unsafe extern "C" {
+        fn dup(fd: i32) -> i32;
+        fn dup2(old_fd: i32, new_fd: i32) -> i32;
+    }
+
+    if io::stdout().is_terminal() {
+        return Ok(None);
+    }
+    let tty = fs::OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    // SAFETY: plain fd duplication; `saved` is a fresh descriptor owned by the returned File
+    unsafe {
+        let saved = dup(1);
+        if saved < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let saved = fs::File::from_raw_fd(saved);
+        if dup2(tty.as_raw_fd(), 1) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Some(saved))
+    }
+}
+
+#[cfg(not(unix))]
+fn redirect_stdout_to_tty() -> io::Result<Option<fs::File>> {
+    Ok(None)
+}
+
+/// Text of `file` as the user left it: unsaved content from its undo history,
+/// the scratch buffer's memory, or the file on disk
+fn final_buffer_text(file: &str) -> String {
+    let untitled = is_untitled(file);
+    let lines = if is_scratch_buffer(file) {
+        load_scratch_lines()
+    } else if let Some(content) = UndoHistory::load(file, untitled).ok().and_then(|h| h.file_content) {
+        content
+    } else {
+        return read_buffer(file, untitled).map(|(content, _)| content).unwrap_or_default();
+    };
+    if lines.len() == 1 && lines[0].is_empty() {
+        return String::new();
+    }
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+/// Run the editor over `files`, starting with the file at index `active`
+/// `start_positions` holds where files open (see `split_start_position`) the first time
+pub fn show(
+    files: &[String],
+    active: usize,
+    browse_dir: Option<&std::path::Path>,
+    start_positions: &[(String, StartPosition)],
+) -> std::io::Result<()> {
     let settings = Settings::load().expect("Failed to load settings");
-    let mut stdout = io::stdout();
+    if settings.no_color {
+        crate::no_color::enable_no_color();
+    }
+    crate::coordinates::set_wrap_mode(&settings.wrap_mode);
+    set_show_invisibles(settings.appearance.show_invisibles);
+    crate::coordinates::set_wrap_continuation(&settings.wrap_prefix, settings.wrap_hanging_indent);
+    let emit = EMIT_TO_STDOUT.load(std::sync::atomic::Ordering::Relaxed);
+    let pipe = if emit { redirect_stdout_to_tty()? } else { None };
+    let mut stdout = crate::no_color::stdout();
     terminal::enable_raw_mode()?;
     execute!(
         stdout,
         EnterAlternateScreen,
         EnableMouseCapture,
+        EnableBracketedPaste,
         SetCursorStyle::BlinkingBar,
         terminal::Clear(ClearType::All)
     )?;
 
     let mut current_files: Vec<String> = files.to_vec();
     let mut unsaved: Vec<String> = Vec::new();
-    let mut idx: usize = 0;
+    let mut idx: usize = active.min(files.len().saturating_sub(1));
+    let mut start_positions = start_positions.to_vec();
+
+    // Started with a directory argument: browse it before opening anything
+    if let Some(dir) = browse_dir {
+        crate::open_dialog::remember_dialog_dir(dir);
+        match crate::open_dialog::run_open_dialog(None, &settings, crate::open_dialog::DialogMode::Open)? {
+            crate::open_dialog::OpenDialogResult::Selected(path) => {
+                let path = path.to_string_lossy().to_string();
+                idx = match current_files.iter().position(|f| f == &path) {
+                    Some(pos) => pos,
+                    None => {
+                        current_files.push(path);
+                        current_files.len() - 1
+                    }
+                };
+            }
+            crate::open_dialog::OpenDialogResult::Quit => {
+                restore_terminal(&mut stdout)?;
+                return Ok(());
+            }
+            crate::open_dialog::OpenDialogResult::Cancelled => {}
+        }
+    }
 
     loop {
         if idx >= current_files.len() {
             break;
         }
         let file = current_files[idx].clone();
-        // Update recent list so selector orders most recent first
-        let _ = crate::recent::update_recent_file(&file);
-        match fs::read_to_string(&file) {
-            Ok(content) => {
-                let (modified, next, quit, close_file) =
-                    editing_session(&file, content, &settings)?;
-                if modified {
+        // Update recent list so selector orders most recent first.
+        // The scratch buffer has no file behind it and never shows up there.
+        if !is_scratch_buffer(&file) {
+            let _ = crate::recent::update_recent_file(&file);
+            if let Some(dir) = std::path::Path::new(&file).parent().filter(|d| d.is_dir()) {
+                let _ = crate::recent::update_recent_dir(dir);
+            }
+        }
+
+        // Files too large to load at all are shown in the streamed view instead
+        if large_file_size(&file, settings.stream_file_threshold_mb).is_some() {
+            if let Err(e) = crate::stream_view::run_stream_view(&file, &settings) {
+                crate::event_handlers::confirm_in_footer(&format!("Cannot read {}: {} [Enter]", file, e), &settings)?;
+            }
+            current_files.remove(idx);
+            idx = idx.min(current_files.len().saturating_sub(1));
+            continue;
+        }
+
+        // Reading and splitting a huge file can take long: ask first, then open it restricted
+        let large_size = large_file_size(&file, settings.large_file_threshold_mb);
+        let restricted = large_size.is_some();
+        if let Some(size) = large_size {
+            let question = format!(
+                "File is {} MB. Open anyway (read-only)? [Enter]=Yes [Esc]=No",
+                size / BYTES_PER_MB
+            );
+            if !crate::event_handlers::confirm_in_footer(&question, &settings)? {
+                current_files.remove(idx);
+                idx = idx.min(current_files.len().saturating_sub(1));
+                continue;
+            }
+        }
+
+        let start_position = start_positions
+            .iter()
+            .position(|(f, _)| f == &file)
+            .map(|pos| start_positions.remove(pos).1);
+        let buffers: Vec<(String, bool)> = current_files
+            .iter()
+            .map(|f| (f.clone(), unsaved.contains(f)))
+            .collect();
+
+        let untitled = is_untitled(&file);
+        match read_buffer(&file, untitled) {
+            Ok((content, encoding)) => {
+                let (modified, next, quit, close_file) = editing_session(
+                    (&file, untitled),
+                    content,
+                    encoding,
+                    &settings,
+                    restricted,
+                    start_position,
+                    (&buffers, idx),
+                )?;
+                crate::swap::remove(&file);
+                if modified && !is_scratch_buffer(&file) {
                     if !unsaved.contains(&file) {
                         unsaved.push(file.clone());
                     }
@@ -118,10 +383,17 @@ pub fn show(files: &[String]) -> std::io::Result<()> {
                     // Remove from current files list and unsaved tracking
                     current_files.remove(idx);
                     unsaved.retain(|f| f != &file);
+                    // Closing the scratch buffer discards its content
+                    if is_scratch_buffer(&file) {
+                        store_scratch_lines(&[]);
+                    }
 
-                    // Get first recent file or create new one
+                    // Continue with the requested file (e.g. after Save As or Rename),
+                    // otherwise the first recent file or a new one
                     let recent_files = crate::recent::get_recent_files().unwrap_or_default();
-                    let next_file = if let Some(first) = recent_files.first() {
+                    let next_file = if let Some(next) = next {
+                        next
+                    } else if let Some(first) = recent_files.first() {
                         first.to_string_lossy().to_string()
                     } else {
                         // No recent files - create new untitled file
@@ -161,8 +433,17 @@ pub fn show(files: &[String]) -> std::io::Result<()> {
             Err(_e) => {
                 // Treat missing/unreadable file as a new buffer with empty content
                 let (modified, next, quit, close_file) =
-                    editing_session(&file, String::new(), &settings)?;
-                if modified {
+                    editing_session(
+                    (&file, untitled),
+                    String::new(),
+                    FileEncoding::default(),
+                    &settings,
+                    restricted,
+                    start_position,
+                    (&buffers, idx),
+                )?;
+                crate::swap::remove(&file);
+                if modified && !is_scratch_buffer(&file) {
                     if !unsaved.contains(&file) {
                         unsaved.push(file.clone());
                     }
@@ -173,10 +454,17 @@ pub fn show(files: &[String]) -> std::io::Result<()> {
                 if close_file {
                     current_files.remove(idx);
                     unsaved.retain(|f| f != &file);
+                    // Closing the scratch buffer discards its content
+                    if is_scratch_buffer(&file) {
+                        store_scratch_lines(&[]);
+                    }
 
-                    // Get first recent file or create new one
+                    // Continue with the requested file (e.g. after Save As or Rename),
+                    // otherwise the first recent file or a new one
                     let recent_files = crate::recent::get_recent_files().unwrap_or_default();
-                    let next_file = if let Some(first) = recent_files.first() {
+                    let next_file = if let Some(next) = next {
+                        next
+                    } else if let Some(first) = recent_files.first() {
                         first.to_string_lossy().to_string()
                     } else {
                         // No recent files - create new untitled file
@@ -214,6 +502,21 @@ pub fn show(files: &[String]) -> std::io::Result<()> {
         }
     }
 
+    // Read before histories of clean files may be deleted below
+    let emitted = current_files
+        .get(idx)
+        .or(current_files.last())
+        .filter(|_| emit)
+        .map(|file| final_buffer_text(file));
+
+    // Remember every open file so the next start can restore them all, before
+    // the histories holding their positions may be deleted
+    let _ = crate::session::save_open_files(&current_files, crate::sidebar::is_open());
+
+    if settings.delete_history_on_clean_close {
+        delete_clean_file_histories(&current_files);
+    }
+
     restore_terminal(&mut stdout)?;
     if !unsaved.is_empty() {
         println!(
@@ -222,6 +525,14 @@ pub fn show(files: &[String]) -> std::io::Result<()> {
         );
     }
 
+    // Only now that raw mode and the alternate screen are gone
+    if let Some(text) = emitted {
+        match pipe {
+            Some(mut pipe) => pipe.write_all(text.as_bytes())?,
+            None => io::stdout().write_all(text.as_bytes())?,
+        }
+    }
+
     // Note: Session is already saved in event_handlers.rs when quitting from editor
     // (via is_exit_command or save_and_quit handlers, or double-Esc in persist_editor_state).
     // Only save selector session if we explicitly switch to the selector or have no files.
@@ -231,16 +542,30 @@ pub fn show(files: &[String]) -> std::io::Result<()> {
 }
 
 
-/// Helper function to update undo history timestamp to current file time
-fn update_undo_timestamp(undo_history: &mut UndoHistory, file: &str) {
-    use std::time::SystemTime;
-    if let Ok(metadata) = std::fs::metadata(file)
-        && let Ok(modified) = metadata.modified()
-        && let Ok(duration) = modified.duration_since(SystemTime::UNIX_EPOCH)
-    {
-        undo_history.file_timestamp = Some(duration.as_secs());
-        // find_history is already in undo_history, no need to update it here
-        let _ = undo_history.save(file);
+/// First line longer than `threshold` characters, as (index, length); 0 disables the check
+fn first_long_line(lines: &[String], threshold: usize) -> Option<(usize, usize)> {
+    if threshold == 0 {
+        return None;
+    }
+    lines
+        .iter()
+        .enumerate()
+        // Byte length bounds the char count, so most lines skip the count
+        .filter(|(_, line)| line.len() > threshold)
+        .map(|(idx, line)| (idx, line.chars().count()))
+        .find(|&(_, len)| len > threshold)
+}
+
+/// Delete the undo history of every file that has no unsaved changes.
+/// Files stay in the recent list; only their history is dropped.
+fn delete_clean_file_histories(files: &[String]) {
+    for file in files {
+        if is_scratch_buffer(file)
+            || crate::menu::check_file_has_unsaved_changes(std::path::Path::new(file))
+        {
+            continue;
+        }
+        let _ = crate::editing::delete_history_file(file, is_untitled(file));
     }
 }
 
@@ -253,7 +578,7 @@ fn try_reload_undo_from_external_change(
     last_known_mtime: Option<u128>,
     visible_lines: usize,
 ) -> (bool, Option<u128>) {
-    let current_mtime = match UndoHistory::get_undo_file_mtime(file) {
+    let current_mtime = match UndoHistory::get_undo_file_mtime(file, state.is_untitled) {
         Some(mtime) => mtime,
         None => return (false, last_known_mtime),
     };
@@ -282,7 +607,7 @@ fn try_reload_undo_from_external_change(
     }
 
     // Undo file was modified externally - reload it
-    let new_history = match UndoHistory::load(file) {
+    let new_history = match UndoHistory::load(file, state.is_untitled) {
         Ok(h) => h,
         Err(_) => return (false, Some(current_mtime)),
     };
@@ -392,7 +717,9 @@ fn handle_open_dialog_in_loop(
     }
     state.last_save_time = Some(Instant::now());
 
-    match crate::open_dialog::run_open_dialog(Some(file), settings, crate::open_dialog::DialogMode::Open)? {
+    // Buffers without a file on disk have no directory to start from
+    let current_file = state.display_name.is_none().then_some(file);
+    match crate::open_dialog::run_open_dialog(current_file, settings, crate::open_dialog::DialogMode::Open)? {
         crate::open_dialog::OpenDialogResult::Selected(path) => {
             let path_str = path.to_string_lossy().to_string();
             Ok(Some((state.modified, Some(path_str), false, false)))
@@ -410,9 +737,138 @@ fn handle_open_dialog_in_loop(
     }
 }
 
+/// Save the buffer under a path chosen in the save-as dialog and continue with
+/// that file in place of this buffer. An untitled buffer's history is deleted and a
+/// blank one is saved from the template for the new name; a named file keeps its
+/// last saved content while its undo history moves along.
+/// Returns Some((modified, next_file, quit, close)) to exit loop, or None to continue
+fn save_as_in_loop(
+    file: &str,
+    state: &mut FileViewerState,
+    lines: &[String],
+    settings: &Settings,
+) -> std::io::Result<FileSelectorResult> {
+    use crate::editing::{delete_file_history, save_file};
+
+    if state.is_scratch {
+        state.status_message = Some(crate::event_handlers::SCRATCH_NOT_SAVED_MSG.to_string());
+        state.needs_footer_redraw = true;
+        return Ok(None);
+    }
+    state.needs_redraw = true;
+
+    // Buffers without a file on disk have no directory to start from
+    let current_file = state.display_name.is_none().then_some(file);
+    let path = match crate::open_dialog::run_open_dialog(current_file, settings, crate::open_dialog::DialogMode::SaveAs)? {
+        crate::open_dialog::OpenDialogResult::Selected(path) => path,
+        crate::open_dialog::OpenDialogResult::Cancelled => return Ok(None),
+        crate::open_dialog::OpenDialogResult::Quit => return Ok(Some((state.modified, None, true, false))),
+    };
+    let target_path = path.to_string_lossy().to_string();
+    let same_file = crate::project_find::same_file(&path, std::path::Path::new(file));
+
+    // Check if target file already exists and ask for confirmation
+    if path.exists()
+        && !same_file
+        && !crate::event_handlers::show_overwrite_confirmation(&target_path, settings)?
+    {
+        return Ok(None);
+    }
+    if !crate::event_handlers::prepare_parent_dir(state, &target_path, settings)? {
+        return Ok(None);
+    }
+    // A blank untitled buffer gets the template for the extension of its first name
+    let template = state
+        .is_untitled
+        .then(|| crate::template::for_first_save(&target_path, lines))
+        .flatten();
+    let lines = template.as_ref().map_or(lines, |(template_lines, _)| template_lines.as_slice());
+    if let Err(e) = save_file(&target_path, lines, state.encoding, state.line_ending) {
+        // Show error (e.g. permission denied) and continue editing
+        let _ = crate::event_handlers::show_save_error(&target_path, &e);
+        return Ok(None);
+    }
+
+    if state.is_untitled {
+        // Delete the old untitled undo file and remove from recent files
+        let _ = delete_file_history(file, true);
+        state.undo_history.untitled = false;
+        if let Some((_, (line, col))) = template {
+            // The template starts a new history, with the cursor at its marker
+            state.undo_history = UndoHistory::new();
+            state.top_line = 0;
+            state.saved_absolute_cursor = None;
+            state.cursor_line = line;
+            state.cursor_col = col;
+        }
+    } else if !same_file {
+        // The history is saved under the new path below
+        let _ = crate::editing::delete_history_file(file, false);
+    }
+    state.modified = false;
+    state.undo_history.clear_unsaved_state();
+    let abs = state.absolute_line();
+    state.undo_history.update_cursor(state.top_line, abs, state.cursor_col);
+    state.undo_history.find_history = state.find_history.clone();
+    state.undo_history.replace_history = state.replace_history.clone();
+    let _ = state.undo_history.save(&target_path);
+    state.last_save_time = Some(Instant::now());
+
+    // Switch to the new file, closing this buffer unless it is the same file
+    Ok(Some((false, Some(target_path), false, !same_file)))
+}
+
+/// Rename the file on disk to a path typed in the footer (relative to its
+/// directory) and continue editing it there; unsaved changes, the undo history
+/// and the recent files entry move along.
+/// Returns Some((modified, next_file, quit, close)) to exit loop, or None to continue
+fn rename_in_loop(
+    file: &str,
+    state: &mut FileViewerState,
+    settings: &Settings,
+) -> std::io::Result<FileSelectorResult> {
+    let path = std::path::Path::new(file);
+    if state.display_name.is_some() || !path.is_file() {
+        state.status_message = Some("Not saved on disk yet: use Save As".to_string());
+        state.alert();
+        state.needs_footer_redraw = true;
+        return Ok(None);
+    }
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let input = crate::event_handlers::show_text_prompt(&format!("Rename {} to", name), settings)?;
+    state.needs_redraw = true;
+    let Some(input) = input.map(|i| i.trim().to_string()).filter(|i| !i.is_empty()) else {
+        return Ok(None);
+    };
+
+    // Relative names stay in the file's directory
+    let target = path.parent().unwrap_or(std::path::Path::new("")).join(&input);
+    let target_path = target.to_string_lossy().to_string();
+    if crate::project_find::same_file(&target, path) {
+        return Ok(None);
+    }
+    if target.exists() {
+        state.status_message = Some(format!("{} already exists", target.display()));
+        state.alert();
+        return Ok(None);
+    }
+    if !crate::event_handlers::prepare_parent_dir(state, &target_path, settings)? {
+        return Ok(None);
+    }
+    if let Err(e) = crate::editing::rename_file(file, &target_path) {
+        state.status_message = Some(format!("Cannot rename {}: {}", name, e));
+        state.alert();
+        return Ok(None);
+    }
+
+    // Unsaved changes are restored from the history when the new path opens
+    persist_editor_state(state, &target_path);
+    Ok(Some((state.modified, Some(target_path), false, true)))
+}
+
 /// Handle first Esc press in various modes
 /// Returns true if handled (should continue waiting), false if in normal mode (should process Esc)
-fn handle_first_esc(state: &mut FileViewerState) -> bool {
+fn handle_first_esc(state: &mut FileViewerState, key_event: &KeyEvent) -> bool {
 
     // In help mode, ESC exits help
     if state.help_active {
@@ -428,6 +884,13 @@ fn handle_first_esc(state: &mut FileViewerState) -> bool {
         return true;
     }
 
+    // With the focus on the sidebar, ESC returns it to the text
+    if state.sidebar_focused {
+        state.sidebar_focused = false;
+        state.needs_redraw = true;
+        return true;
+    }
+
     // In find mode, exit find
     if state.find_active {
         state.find_active = false;
@@ -435,6 +898,7 @@ fn handle_first_esc(state: &mut FileViewerState) -> bool {
         state.find_history_index = None;
         state.last_search_pattern = state.saved_search_pattern.clone();
         state.saved_search_pattern = None;
+        crate::find::restore_find_position(state);
         state.needs_redraw = true;
         return true;
     }
@@ -457,16 +921,6 @@ fn handle_first_esc(state: &mut FileViewerState) -> bool {
         return true;
     }
 
-    // Clear search highlights (after exiting find mode with Enter)
-    if state.last_search_pattern.is_some() {
-        state.last_search_pattern = None;
-        state.find_scope = None;
-        state.search_hit_count = 0;
-        state.search_current_hit = 0;
-        state.needs_redraw = true;
-        return true;
-    }
-
     // Exit goto_line mode
     if state.goto_line_active {
         state.goto_line_active = false;
@@ -477,6 +931,34 @@ fn handle_first_esc(state: &mut FileViewerState) -> bool {
         return true;
     }
 
+    // Clear selection, multi-cursors and search highlights in one press
+    // when Esc is the clear_modes key, one at a time otherwise
+    if state.settings.keybindings.clear_modes_matches(&key_event.code, &key_event.modifiers) {
+        if state.clear_modes() {
+            return true;
+        }
+    } else if clear_one_mode(state) {
+        return true;
+    }
+
+    // In normal mode - Esc should open menu
+    // Let it pass through to handle_key_event which will call handle_menu_key
+    false
+}
+
+/// Esc without a clear_modes binding: drop search highlights, then
+/// multi-cursors, then the selection, one per press
+fn clear_one_mode(state: &mut FileViewerState) -> bool {
+    // Clear search highlights (after exiting find mode with Enter)
+    if state.last_search_pattern.is_some() {
+        state.last_search_pattern = None;
+        state.find_scope = None;
+        state.search_hit_count = 0;
+        state.search_current_hit = 0;
+        state.needs_redraw = true;
+        return true;
+    }
+
     // Clear multi-cursors
     if state.has_multi_cursors() {
         state.clear_multi_cursors();
@@ -498,8 +980,6 @@ fn handle_first_esc(state: &mut FileViewerState) -> bool {
         return true;
     }
 
-    // In normal mode - Esc should open menu
-    // Let it pass through to handle_key_event which will call handle_menu_key
     false
 }
 
@@ -514,8 +994,17 @@ fn view_help_file(
         Ok(c) => c,
         Err(_) => return Ok(()), // silently ignore if the file doesn't exist
     };
+    view_markdown(help_path, &content, settings)
+}
 
-    let mut stdout = io::stdout();
+/// Show markdown `content` in the read-only viewer used for help, with
+/// `title` in the header. Returns when the user presses ESC or F1.
+fn view_markdown(
+    title: &str,
+    content: &str,
+    settings: &Settings,
+) -> std::io::Result<()> {
+    let mut stdout = crate::no_color::stdout();
     let (term_width, term_height) = terminal::size()?;
 
     // Build a minimal FileViewerState for the viewer (no undo, read-only, markdown rendered)
@@ -543,7 +1032,7 @@ fn view_help_file(
 
     loop {
         if state.needs_redraw {
-            render_screen(&mut stdout, help_path, &lines, &state, visible_lines)?;
+            render_screen(&mut stdout, title, &lines, &state, visible_lines)?;
             state.needs_redraw = false;
         }
 
@@ -661,16 +1150,244 @@ fn view_help_file(
     }
 }
 
-fn editing_session(
+/// Put the cursor on the last line and scroll so the end of the file is at the bottom
+fn scroll_to_end(state: &mut FileViewerState, lines: &[String], visible_lines: usize) {
+    let last = lines.len().saturating_sub(1);
+    state.top_line = lines.len().saturating_sub(visible_lines);
+    state.top_line_visual_offset = 0;
+    state.cursor_line = last - state.top_line;
+    state.cursor_col = 0;
+    state.desired_cursor_col = 0;
+    state.ensure_cursor_visible(visible_lines, lines);
+}
+
+/// Read data appended to a followed file. The view sticks to the end while
+/// the last line is on screen; scrolling away from it pauses auto-scroll.
+fn poll_followed_file(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
+    file: &str,
+    visible_lines: usize,
+) {
+    let Some(follow) = state.follow.as_mut() else {
+        return;
+    };
+    let at_end = state.top_line + visible_lines >= lines.len();
+    if follow.paused == at_end {
+        follow.paused = !at_end;
+        state.needs_footer_redraw = true;
+    }
+    let paused = follow.paused;
+    match follow.poll(file, state.encoding, lines) {
+        Ok(crate::follow::FollowUpdate::Unchanged) | Err(_) => {}
+        Ok(update) => {
+            if update == crate::follow::FollowUpdate::Reloaded {
+                state.status_message = Some("File was truncated: reloaded".to_string());
+            }
+            if !paused || update == crate::follow::FollowUpdate::Reloaded {
+                scroll_to_end(state, lines, visible_lines);
+            }
+            state.needs_redraw = true;
+        }
+    }
+}
+
+/// Notice another program writing the file. A buffer without unsaved changes
+/// is reloaded; otherwise the user chooses between reloading, keeping their
+/// version (the next save overwrites the disk) and looking at the differences.
+fn check_file_changed_on_disk(
+    state: &mut FileViewerState,
+    lines: &mut Vec<String>,
     file: &str,
+    settings: &Settings,
+    visible_lines: usize,
+) -> std::io::Result<()> {
+    use crate::event_handlers::{pick_external_change_action, reload_buffer_as, ExternalChangeAction};
+
+    if state.follow.is_some() || state.large_file || state.display_name.is_some() {
+        return Ok(());
+    }
+    let Some(known) = state.disk_stamp else {
+        return Ok(());
+    };
+    let current = crate::file_watch::DiskStamp::read(file);
+    if current == Some(known) {
+        return Ok(());
+    }
+    state.disk_stamp = current;
+    let Ok(bytes) = fs::read(file) else {
+        state.status_message = Some("File was removed from disk: saving writes it again".to_string());
+        state.needs_footer_redraw = true;
+        return Ok(());
+    };
+    // Touched, or written by another instance with the same content
+    let on_disk = crate::line_ending::split_lines(&state.encoding.decode_as(&bytes));
+    if on_disk == *lines || (on_disk.is_empty() && lines.len() == 1 && lines[0].is_empty()) {
+        return Ok(());
+    }
+
+    let encoding = state.encoding;
+    if !state.modified {
+        match reload_buffer_as(state, lines, file, encoding, visible_lines) {
+            Ok(()) => state.status_message = Some("File changed on disk: reloaded".to_string()),
+            Err(e) => state.status_message = Some(format!("Cannot reload: {}", e)),
+        }
+        state.needs_redraw = true;
+        return Ok(());
+    }
+    loop {
+        match pick_external_change_action(settings)? {
+            ExternalChangeAction::Reload => {
+                match reload_buffer_as(state, lines, file, encoding, visible_lines) {
+                    Ok(()) => state.status_message = Some("Reloaded from disk".to_string()),
+                    Err(e) => state.status_message = Some(format!("Cannot reload: {}", e)),
+                }
+            }
+            ExternalChangeAction::KeepMine => {
+                state.status_message = Some("Kept your changes: saving overwrites the file on disk".to_string());
+            }
+            ExternalChangeAction::ShowDiff => {
+                let diff = crate::diff::unified(&on_disk, lines, 3);
+                let content = format!(
+                    "# Changes since the file changed on disk\n\n\
+                     Lines starting with `-` are only on disk, lines starting with `+` only in your buffer.\n\n\
+                     ```diff\n{}\n```\n",
+                    diff.join("\n")
+                );
+                view_markdown(file, &content, settings)?;
+                continue;
+            }
+        }
+        break;
+    }
+    state.needs_redraw = true;
+    Ok(())
+}
+
+/// Where a file given on the command line opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartPosition {
+    /// Whole lines `first..=last` selected (1-based, inclusive)
+    Lines(usize, usize),
+    /// Cursor at a 1-based line and column
+    At(usize, usize),
+}
+
+/// Split a `file:10-25`, `file:120` or `file:120:5` argument into the path and
+/// where to open it. A trailing colon, as in compiler messages, is ignored. A
+/// file whose name really ends like that is left alone.
+pub fn split_start_position(arg: &str) -> (String, Option<StartPosition>) {
+    if std::path::Path::new(arg).exists() {
+        return (arg.to_string(), None);
+    }
+    let trimmed = arg.strip_suffix(':').unwrap_or(arg);
+    let number = |text: &str| -> Option<usize> {
+        text.bytes().all(|b| b.is_ascii_digit()).then(|| text.parse().ok()).flatten()
+    };
+    let parsed = trimmed.rsplit_once(':').and_then(|(path, last)| {
+        if let Some((first, end)) = last.split_once('-') {
+            let (first, end) = (number(first)?, number(end)?);
+            return Some((path, StartPosition::Lines(first.min(end), first.max(end))));
+        }
+        let last = number(last)?;
+        match path.rsplit_once(':').and_then(|(file, line)| Some((file, number(line)?))) {
+            Some((file, line)) => Some((file, StartPosition::At(line, last))),
+            None => Some((path, StartPosition::At(last, 1))),
+        }
+    });
+    match parsed {
+        Some((path, position)) if !path.is_empty() => (path.to_string(), Some(position)),
+        _ => (arg.to_string(), None),
+    }
+}
+
+/// Line of a `+120` argument, which applies to the file following it
+pub fn parse_plus_line(arg: &str) -> Option<usize> {
+    let line = arg.strip_prefix('+')?;
+    if line.is_empty() || !line.bytes().all(|b| b.is_ascii_digit()) || std::path::Path::new(arg).exists() {
+        return None;
+    }
+    line.parse().ok()
+}
+
+/// Put the cursor where `position` asks, clamped to the buffer, with a few
+/// lines of context above it
+fn apply_start_position(state: &mut FileViewerState, lines: &[String], position: StartPosition, visible_lines: usize) {
+    match position {
+        StartPosition::Lines(first, last) => select_line_range(state, lines, (first, last), visible_lines),
+        StartPosition::At(line, col) => {
+            let line = line.clamp(1, lines.len().max(1)) - 1;
+            state.top_line = line.saturating_sub(CURSOR_CONTEXT_LINES);
+            state.set_cursor_position(line, col.max(1) - 1, lines, visible_lines);
+            state.desired_cursor_col = state.cursor_col;
+        }
+    }
+}
+
+/// Select whole lines `first..=last` (1-based, clamped to the buffer) and put
+/// the cursor on the first of them
+fn select_line_range(
+    state: &mut FileViewerState,
+    lines: &[String],
+    (first, last): (usize, usize),
+    visible_lines: usize,
+) {
+    let max = lines.len().max(1);
+    let first = first.clamp(1, max) - 1;
+    let last = last.clamp(1, max) - 1;
+    let last_len = lines.get(last).map_or(0, |l| l.chars().count());
+    state.top_line = first.saturating_sub(CURSOR_CONTEXT_LINES);
+    state.set_cursor_position(first, 0, lines, visible_lines);
+    state.selection_start = Some((last, last_len));
+    state.selection_end = Some((first, 0));
+}
+
+/// Size in bytes of `file` when it exceeds `threshold_mb` (0 disables the check)
+fn large_file_size(file: &str, threshold_mb: u64) -> Option<u64> {
+    if threshold_mb == 0 || is_scratch_buffer(file) || is_untitled(file) {
+        return None;
+    }
+    let size = fs::metadata(file).ok()?.len();
+    (size > threshold_mb.saturating_mul(BYTES_PER_MB)).then_some(size)
+}
+
+/// `untitled` marks a buffer without a file on disk. `restricted` opens a large
+/// file read-only, without undo persistence and without document-wide search
+/// statistics. The cursor starts at `start_position` when given.
+/// `buffers` are all open files for the header tabs, this one at `buffer_index`.
+fn editing_session(
+    (file, untitled): (&str, bool),
     content: String,
+    encoding: FileEncoding,
     settings: &Settings,
+    restricted: bool,
+    start_position: Option<StartPosition>,
+    (buffers, buffer_index): (&[(String, bool)], usize),
 ) -> std::io::Result<(bool, Option<String>, bool, bool)> {
     // Set the current file for syntax highlighting
     crate::syntax::set_current_file(file);
 
-    let mut stdout = io::stdout();
-    let mut undo_history = UndoHistory::load(file).unwrap_or_else(|_| UndoHistory::new());
+    let mut stdout = crate::no_color::stdout();
+    let is_scratch = is_scratch_buffer(file);
+    // A followed log is shown as it is on disk, never with a previous session's edits
+    let follow = FOLLOW_MODE.load(std::sync::atomic::Ordering::Relaxed)
+        && !is_scratch
+        && !untitled
+        && std::path::Path::new(file).is_file();
+    let mut undo_history = if is_scratch || follow || restricted {
+        UndoHistory::new()
+    } else {
+        let mut history = UndoHistory::load(file, untitled).unwrap_or_else(|_| UndoHistory::new());
+        history.untitled = untitled;
+        // Without a history (e.g. deleted on clean close) a restored session
+        // still knows where the view was
+        if !UndoHistory::history_path_for(file, untitled).is_ok_and(|p| p.exists())
+            && let Some(view) = crate::session::take_restored_view(file)
+        {
+            history.update_cursor(view.scroll_top, view.cursor_line, view.cursor_col);
+        }
+        history
+    };
 
     // Validate undo file against current file modification time
     let validation_result = undo_history.validate(file);
@@ -680,7 +1397,7 @@ fn editing_session(
         }
         ValidationResult::ModifiedNoUnsaved => {
             // File was modified externally and no unsaved changes - delete stale undo file and quit
-            let _ = crate::editing::delete_file_history(file);
+            let _ = crate::editing::delete_file_history(file, untitled);
             return Ok((false, None, true, false)); // quit
         }
         ValidationResult::ModifiedWithUnsaved => {
@@ -690,16 +1407,36 @@ fn editing_session(
                 return Ok((false, None, true, false)); // quit
             } else {
                 // User pressed Enter (Yes) - open file anyway with unsaved changes
-                // Update the timestamp to current file time so future validations pass
-                update_undo_timestamp(&mut undo_history, file);
+                // and don't ask again until the file changes once more
+                undo_history.acknowledge_external_change(file);
+                let _ = undo_history.save(file);
             }
         }
     };
 
-    let mut lines: Vec<String> = if let Some(saved) = &undo_history.file_content {
+    // A file that does not exist yet starts from the template for its extension
+    let template = if !is_scratch
+        && undo_history.file_content.is_none()
+        && !untitled
+        && !std::path::Path::new(file).exists()
+    {
+        crate::template::template_for(file).map(|t| crate::template::expand(&t))
+    } else {
+        None
+    };
+
+    let mut lines: Vec<String> = if is_scratch {
+        let mut l = load_scratch_lines();
+        if l.is_empty() {
+            l.push(String::new());
+        }
+        l
+    } else if let Some(saved) = &undo_history.file_content {
         saved.clone()
+    } else if let Some((template_lines, _)) = &template {
+        template_lines.clone()
     } else {
-        let mut l: Vec<String> = content.lines().map(String::from).collect();
+        let mut l: Vec<String> = crate::line_ending::split_lines(&content);
         // Ensure at least one empty line for empty files
         if l.is_empty() {
             l.push(String::new());
@@ -708,40 +1445,89 @@ fn editing_session(
         l
     };
 
+    // Edits of a session that crashed survive in its swap file
+    let mut recovered = false;
+    if !is_scratch && !follow && !restricted
+        && let Some(swapped) = crate::swap::orphaned(file)
+    {
+        if swapped != lines
+            && crate::event_handlers::confirm_in_footer(
+                "Found unsaved changes of a session that crashed. Recover them? [Enter=Yes, Esc=No]",
+                settings,
+            )?
+        {
+            lines = swapped;
+            recovered = true;
+        }
+        crate::swap::remove(file);
+    }
+
     // For files whose extension is not recognised (e.g. `NuGet.config`,
     // `~/.config/zshrc/00-init`), fall back to content-based detection using the
     // `detect|` patterns embedded in each syntax definition.
     crate::syntax::maybe_detect_syntax_from_content(&lines);
+    if let Some(ext) = &undo_history.syntax_override {
+        crate::syntax::set_base_syntax(ext);
+    }
 
     let (term_width, term_height) = size()?;
 
+    let gutter_settings = GutterSettings::new(settings);
     let mut state = FileViewerState::new(term_width, undo_history.clone(), settings);
+    state.encoding = encoding;
+    state.line_ending = crate::line_ending::LineEnding::detect(&content);
+    state.disk_stamp = crate::file_watch::DiskStamp::read(file);
+    state.buffers = buffers.to_vec();
+    state.buffer_index = buffer_index;
+    state.zen_mode = ZEN_MODE.load(std::sync::atomic::Ordering::Relaxed);
+    state.sidebar_open = crate::sidebar::is_open();
+    state.settings = gutter_settings.for_state(&state);
+    state.show_invisibles = SHOW_INVISIBLES.load(std::sync::atomic::Ordering::Relaxed);
     state.modified = state.undo_history.modified;
+    if recovered {
+        // The undo steps belong to the content before the crash, not to the recovered one
+        state.undo_history.discard_unsaved_state();
+        state.undo_history.update_state(0, 0, 0, lines.clone());
+        state.undo_history.modified = true;
+        state.modified = true;
+        state.status_message = Some("Recovered unsaved changes".to_string());
+    }
     state.top_line = undo_history.scroll_top.min(lines.len());
     state.find_history = undo_history.find_history.clone(); // Restore find history
+    state.find_options_default = undo_history.find_options.unwrap_or_default();
+    state.reset_find_options();
     state.replace_history = undo_history.replace_history.clone(); // Restore replace history
     state.rendered_top_line = undo_history.rendered_scroll_top; // Restore rendered scroll position
 
-    // Check if this is an untitled file (filename starts with "untitled" and doesn't exist on disk)
-    let filename_lower = std::path::Path::new(file)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-    state.is_untitled = filename_lower.starts_with("untitled") && !std::path::Path::new(file).exists();
+    state.is_untitled = untitled;
+    state.is_scratch = is_scratch;
+    state.display_name = (state.is_untitled || is_scratch).then(|| file.to_string());
+    state.is_new = state.display_name.is_none() && !std::path::Path::new(file).exists();
 
     // Check if this file is read-only by attempting to open it for writing.
     // We use OpenOptions with write(true) but without truncate/create so we can test
     // write permission without modifying the file. permissions().readonly() is not
     // sufficient on Unix because it only checks if all write bits are cleared, not
     // whether the current user actually has write access (ownership matters).
-    state.is_read_only = !state.is_untitled && std::path::Path::new(file).exists() && {
+    state.is_read_only = !state.is_untitled && !is_scratch && std::path::Path::new(file).exists() && {
         std::fs::OpenOptions::new()
             .write(true)
             .open(file)
             .is_err()
     };
 
+    if restricted {
+        state.is_read_only = true;
+        state.large_file = true;
+        state.undo_history.ephemeral = true;
+        state.status_message = Some("Large file: read-only, no undo history, no search statistics".to_string());
+    }
+    if follow {
+        state.is_read_only = true;
+        let len = std::fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+        state.follow = Some(crate::follow::FollowState::new(len, &content));
+    }
+
     // Detect if running with elevated privileges (sudo or direct root login).
     // SUDO_USER is set by sudo when it elevates privileges.
     // USER == "root" covers direct root login without sudo.
@@ -749,6 +1535,18 @@ fn editing_session(
         || std::env::var("USER").as_deref() == Ok("root")
         || std::env::var("LOGNAME").as_deref() == Ok("root");
 
+    // Wrapping math on a minified file's one huge line makes every redraw crawl
+    if state.is_line_wrapping_enabled()
+        && let Some((line, len)) = first_long_line(&lines, settings.long_line_threshold)
+    {
+        state.line_wrapping_override = Some(false);
+        state.status_message = Some(format!(
+            "Line {} has {} characters: line wrapping turned off for this file",
+            line + 1,
+            len
+        ));
+    }
+
     // Update menu bar settings from configuration
     state.menu_bar.update_max_visible_files(settings.max_menu_files);
     // Update file menu with current recent files
@@ -771,6 +1569,21 @@ fn editing_session(
         }
     }
     let mut visible_lines = (term_height as usize).saturating_sub(STATUS_LINE_HEIGHT);
+    if let Some((_, (line, col))) = template {
+        state.top_line = 0;
+        state.cursor_line = line;
+        state.cursor_col = col;
+        state.desired_cursor_col = col;
+        state.ensure_cursor_visible(visible_lines, &lines);
+    }
+    if let Some(position) = start_position {
+        apply_start_position(&mut state, &lines, position, visible_lines);
+    } else if let Some(location) = crate::project_find::take_pending_jump(file) {
+        state.top_line = location.0.saturating_sub(CURSOR_CONTEXT_LINES);
+        crate::project_find::select_match(&mut state, &lines, location, visible_lines);
+    } else if state.follow.is_some() {
+        scroll_to_end(&mut state, &lines, visible_lines);
+    }
     state.needs_redraw = true;
 
     // Track last Esc press time for double-press detection
@@ -778,9 +1591,18 @@ fn editing_session(
 
     // File watching state for multi-instance synchronization
     let mut last_undo_check = Instant::now();
-    let mut last_known_undo_mtime = UndoHistory::get_undo_file_mtime(file);
+    let mut last_known_undo_mtime = UndoHistory::get_undo_file_mtime(file, untitled);
+    let mut last_autosave = Instant::now();
+    let mut last_swap_flush = Instant::now();
 
     loop {
+        // Zen mode, the blame gutter and the sidebar each have settings with their gutter
+        let gutter = gutter_settings.for_state(&state);
+        if !std::ptr::eq(state.settings, gutter) {
+            state.settings = gutter;
+            state.needs_redraw = true;
+        }
+
         if state.needs_redraw {
             // Update menu checkable states if menu is active (for both help and editor modes)
             if state.menu_bar.active {
@@ -800,7 +1622,15 @@ fn editing_session(
                     crate::menu::MenuAction::ViewMarkdownRendered,
                     crate::menu::is_markdown_file(file),
                 );
+                state.menu_bar.update_checkable(crate::menu::MenuAction::ViewSidebar, state.sidebar_open);
+            }
+
+            // Every edit triggers a redraw, so this keeps the in-memory scratch content current
+            if state.is_scratch {
+                store_scratch_lines(&lines);
             }
+            state.clear_word_highlight_if_edited();
+            state.update_diff_marks(&lines, file);
 
             // help_active is no longer used for rendering; help is now shown via view_help_file.
             render_screen(&mut stdout, file, &lines, &state, visible_lines)?;
@@ -828,6 +1658,7 @@ fn editing_session(
                 crate::menu::MenuAction::ViewMarkdownRendered,
                 crate::menu::is_markdown_file(file),
             );
+            state.menu_bar.update_checkable(crate::menu::MenuAction::ViewSidebar, state.sidebar_open);
 
             // Menu is open and needs redraw - render the dropdown menu overlay
             crate::menu::render_dropdown_menu(&mut stdout, &state.menu_bar, &state, &lines, state.effective_theme_bg())?;
@@ -848,12 +1679,43 @@ fn editing_session(
                 visible_lines,
             );
             last_known_undo_mtime = new_mtime;
+            poll_followed_file(&mut state, &mut lines, file, visible_lines);
+            check_file_changed_on_disk(&mut state, &mut lines, file, settings, visible_lines)?;
+
+            if now.duration_since(last_swap_flush) >= crate::swap::SWAP_FLUSH_INTERVAL {
+                last_swap_flush = now;
+                if state.modified && !state.is_scratch && !state.is_read_only {
+                    let _ = crate::swap::write(file, &lines);
+                } else {
+                    crate::swap::remove(file);
+                }
+            }
+
+            if settings.autosave_seconds > 0
+                && now.duration_since(last_autosave) >= Duration::from_secs(settings.autosave_seconds)
+            {
+                last_autosave = now;
+                match crate::editing::autosave(&mut state, &lines, file) {
+                    Ok(true) => state.needs_redraw = true,
+                    Ok(false) => {}
+                    Err(e) => {
+                        state.status_message = Some(format!("Autosave failed: {}", e));
+                        state.needs_footer_redraw = true;
+                    }
+                }
+            }
         }
 
         // Use poll with timeout for file check interval
         let file_check_timeout = Duration::from_millis(UNDO_FILE_CHECK_INTERVAL_MS);
         let timeout = file_check_timeout;
 
+        // A visual bell lasts until the next tick, or until the next event
+        let flash_pending = std::mem::take(&mut state.bell_flash);
+        if flash_pending {
+            state.needs_redraw = true;
+        }
+
         if !event::poll(timeout)? {
             // Handle continuous horizontal auto-scroll during mouse drag
             if crate::mouse_handlers::handle_continuous_auto_scroll(&mut state, &lines, visible_lines) {
@@ -881,7 +1743,7 @@ fn editing_session(
                     }
                     EscResult::First => {
                         // First Esc - handle based on current mode
-                        let handled = handle_first_esc(&mut state);
+                        let handled = handle_first_esc(&mut state, &key_event);
                         if handled {
                             continue; // Wait for second Esc or timeout
                         }
@@ -935,6 +1797,59 @@ fn editing_session(
                     continue;
                 }
 
+                // Handle zen mode toggle; the settings with a blank gutter margin are
+                // swapped in before the next redraw
+                if state.zen_toggle_requested {
+                    state.zen_toggle_requested = false;
+                    state.zen_mode = !state.zen_mode;
+                    ZEN_MODE.store(state.zen_mode, std::sync::atomic::Ordering::Relaxed);
+                    state.needs_redraw = true;
+                }
+
+                // Handle blame gutter toggle the same way, widening the line-number gutter
+                if state.blame_toggle_requested {
+                    state.blame_toggle_requested = false;
+                    if state.blame.is_some() {
+                        state.blame = None;
+                    } else if let Some(blame) = crate::git::blame(file, &lines, state.encoding) {
+                        state.blame = Some(blame);
+                    } else {
+                        state.status_message = Some("Not tracked by git".to_string());
+                    }
+                    state.needs_redraw = true;
+                }
+
+                // Handle scratch buffer toggle: jump to the scratch buffer, or back to the
+                // most recent file when already there
+                if state.scratch_toggle_requested {
+                    state.scratch_toggle_requested = false;
+                    if state.is_scratch {
+                        store_scratch_lines(&lines);
+                        let back = crate::recent::get_recent_files()
+                            .unwrap_or_default()
+                            .first()
+                            .map(|p| p.to_string_lossy().to_string())
+                            .unwrap_or_else(generate_untitled_filename);
+                        return Ok((false, Some(back), false, false));
+                    }
+                    persist_editor_state(&mut state, file);
+                    return Ok((state.modified, Some(SCRATCH_BUFFER_NAME.to_string()), false, false));
+                }
+
+                // Handle buffer switch: this buffer's state lives on in its undo history
+                let switch_to = match state.buffer_switch_requested.take() {
+                    Some(target) => state.buffers.get(target).map(|(path, _)| path.clone()),
+                    None => state.open_file_requested.take(),
+                };
+                if let Some(next) = switch_to {
+                    if state.is_scratch {
+                        store_scratch_lines(&lines);
+                    } else {
+                        persist_editor_state(&mut state, file);
+                    }
+                    return Ok((state.modified, Some(next), false, false));
+                }
+
                 // Handle close all confirmation
                 if state.close_all_confirmed {
                     state.close_all_confirmed = false;
@@ -956,7 +1871,8 @@ fn editing_session(
 
                     // Close saved files
                     for file_path in &saved_files {
-                        let _ = crate::editing::delete_file_history(&file_path.to_string_lossy());
+                        let name = file_path.to_string_lossy();
+                        let _ = crate::editing::delete_file_history(&name, is_untitled(&name));
                     }
 
                     // Always show status message
@@ -1026,70 +1942,20 @@ fn editing_session(
                                 return Ok(result);
                             }
                         }
-                        crate::menu::MenuAction::FileSave => {
+                        crate::menu::MenuAction::FileSave if state.is_untitled => {
                             // This is an untitled file - show save-as dialog
-                            if state.is_untitled {
-                                // Exit raw mode temporarily for the dialog
-                                execute!(stdout, Show, DisableMouseCapture, LeaveAlternateScreen)?;
-                                terminal::disable_raw_mode()?;
-
-                                // Show open dialog to choose save location
-                                terminal::enable_raw_mode()?;
-                                execute!(stdout, EnterAlternateScreen, EnableMouseCapture, Hide)?;
-
-                                match crate::open_dialog::run_open_dialog(Some(file), settings, crate::open_dialog::DialogMode::SaveAs)? {
-                                    crate::open_dialog::OpenDialogResult::Selected(path) => {
-                                        let target_path = path.to_str().unwrap_or(file);
-
-                                        // Check if target file already exists and ask for confirmation
-                                        if std::path::Path::new(target_path).exists() {
-                                            use crate::event_handlers::show_overwrite_confirmation;
-                                            // Show overwrite confirmation in footer
-                                            if !show_overwrite_confirmation(target_path, settings)? {
-                                                // User declined - redraw and continue editing
-                                                state.needs_redraw = true;
-                                                continue; // Go back to event loop
-                                            }
-                                        }
-
-                                        // User selected a path - save the file there
-                                        use crate::editing::{save_file, delete_file_history};
-
-                                        match save_file(target_path, &lines) {
-                                            Err(e) => {
-                                                // Show error (e.g. permission denied) and continue editing
-                                                let _ = crate::event_handlers::show_save_error(target_path, &e);
-                                                state.needs_redraw = true;
-                                            }
-                                            Ok(()) => {
-                                                // Delete the old untitled undo file and remove from recent files
-                                                let _ = delete_file_history(file);
-
-                                                state.modified = false;
-                                                state.undo_history.clear_unsaved_state();
-                                                let abs = state.absolute_line();
-                                                state.undo_history.update_cursor(state.top_line, abs, state.cursor_col);
-                                                state.undo_history.find_history = state.find_history.clone();
-                                                state.undo_history.replace_history = state.replace_history.clone();
-
-                                                // Save undo history to the NEW file location
-                                                let _ = state.undo_history.save(target_path);
-                                                state.last_save_time = Some(Instant::now());
-
-                                                // Switch to the new filename - don't persist to old file, it's deleted
-                                                return Ok((false, Some(path.to_string_lossy().to_string()), false, false));
-                                            }
-                                        }
-                                    }
-                                    crate::open_dialog::OpenDialogResult::Cancelled => {
-                                        // User cancelled - just redraw
-                                        state.needs_redraw = true;
-                                    }
-                                    crate::open_dialog::OpenDialogResult::Quit => {
-                                        // User wants to quit
-                                        return Ok((state.modified, None, true, false));
-                                    }
-                                }
+                            if let Some(result) = save_as_in_loop(file, &mut state, &lines, settings)? {
+                                return Ok(result);
+                            }
+                        }
+                        crate::menu::MenuAction::FileSaveAs => {
+                            if let Some(result) = save_as_in_loop(file, &mut state, &lines, settings)? {
+                                return Ok(result);
+                            }
+                        }
+                        crate::menu::MenuAction::FileRename => {
+                            if let Some(result) = rename_in_loop(file, &mut state, settings)? {
+                                return Ok(result);
                             }
                         }
                         crate::menu::MenuAction::FileCloseAll => {
@@ -1104,6 +1970,22 @@ fn editing_session(
                     }
                 }
             }
+            Event::Paste(text) => {
+                let (should_quit, should_close) = crate::event_handlers::handle_paste_event(
+                    &mut state,
+                    &mut lines,
+                    &text,
+                    settings,
+                    visible_lines,
+                    file,
+                )?;
+                if should_quit {
+                    return Ok((state.modified, None, true, false));
+                }
+                if should_close {
+                    return Ok((state.modified, None, false, true));
+                }
+            }
             Event::Resize(w, h) => {
                 let absolute_cursor_line = state.absolute_line();
                 let cursor_col = state.cursor_col;
@@ -1131,6 +2013,18 @@ fn editing_session(
             Event::Mouse(mouse_event) => {
                 handle_mouse_event(&mut state, &mut lines, mouse_event, visible_lines);
 
+                // A file clicked in the sidebar
+                if let Some(next) = state.open_file_requested.take()
+                    && !crate::project_find::same_file(std::path::Path::new(&next), std::path::Path::new(file))
+                {
+                    if state.is_scratch {
+                        store_scratch_lines(&lines);
+                    } else {
+                        persist_editor_state(&mut state, file);
+                    }
+                    return Ok((state.modified, Some(next), false, false));
+                }
+
                 // Process pending menu actions from mouse clicks
                 if let Some(action) = state.pending_menu_action.take() {
                     // Execute the menu action (same logic as keyboard menu actions in event_handlers.rs)
@@ -1171,73 +2065,24 @@ fn editing_session(
                             // This case is here for exhaustiveness but should not be reached
                         }
                         MenuAction::FileSave => {
+                            if state.is_scratch {
+                                state.status_message = Some(crate::event_handlers::SCRATCH_NOT_SAVED_MSG.to_string());
+                                continue;
+                            }
                             // If this is an untitled file, show save-as dialog
                             if state.is_untitled {
-                                // Exit raw mode temporarily for the dialog
-                                execute!(stdout, Show, DisableMouseCapture, LeaveAlternateScreen)?;
-                                terminal::disable_raw_mode()?;
-
-                                // Show open dialog to choose save location
-                                terminal::enable_raw_mode()?;
-                                execute!(stdout, EnterAlternateScreen, EnableMouseCapture, Hide)?;
-
-                                match crate::open_dialog::run_open_dialog(Some(file), settings, crate::open_dialog::DialogMode::SaveAs)? {
-                                    crate::open_dialog::OpenDialogResult::Selected(path) => {
-                                        let target_path = path.to_str().unwrap_or(file);
-
-                                        // Check if target file already exists and ask for confirmation
-                                        if std::path::Path::new(target_path).exists() {
-                                            use crate::event_handlers::show_overwrite_confirmation;
-                                            // Show overwrite confirmation in footer
-                                            if !show_overwrite_confirmation(target_path, settings)? {
-                                                // User declined - redraw and continue editing
-                                                state.needs_redraw = true;
-                                                continue; // Go back to event loop
-                                            }
-                                        }
-
-                                        // User selected a path - save the file there
-                                        use crate::editing::delete_file_history;
-
-                                        match save_file(target_path, &lines) {
-                                            Err(e) => {
-                                                // Show error (e.g. permission denied) and continue editing
-                                                let _ = crate::event_handlers::show_save_error(target_path, &e);
-                                                state.needs_redraw = true;
-                                            }
-                                            Ok(()) => {
-                                                // Delete the old untitled undo file and remove from recent files
-                                                let _ = delete_file_history(file);
-
-                                                state.modified = false;
-                                                state.undo_history.clear_unsaved_state();
-                                                let abs = state.absolute_line();
-                                                state.undo_history.update_cursor(state.top_line, abs, state.cursor_col);
-                                                state.undo_history.find_history = state.find_history.clone();
-                                                state.undo_history.replace_history = state.replace_history.clone();
-
-                                                // Save undo history to the NEW file location
-                                                let _ = state.undo_history.save(target_path);
-                                                state.last_save_time = Some(Instant::now());
-
-                                                // Switch to the new filename - don't persist to old file, it's deleted
-                                                return Ok((false, Some(path.to_string_lossy().to_string()), false, false));
-                                            }
-                                        }
-                                    }
-                                    crate::open_dialog::OpenDialogResult::Cancelled => {
-                                        // User cancelled - just redraw
-                                        state.needs_redraw = true;
-                                    }
-                                    crate::open_dialog::OpenDialogResult::Quit => {
-                                        // User wants to quit
-                                        return Ok((state.modified, None, true, false));
-                                    }
+                                if let Some(result) = save_as_in_loop(file, &mut state, &lines, settings)? {
+                                    return Ok(result);
                                 }
                             } else {
                                 // Normal file - just save
-                                save_file(file, &lines)?;
+                                if !crate::event_handlers::prepare_parent_dir(&mut state, file, settings)? {
+                                    continue;
+                                }
+                                save_file(file, &lines, state.encoding, state.line_ending)?;
+                                state.disk_stamp = crate::file_watch::DiskStamp::read(file);
                                 state.modified = false;
+                                state.is_new = false;
                                 state.undo_history.clear_unsaved_state();
                                 let abs = state.absolute_line();
                                 state.undo_history.update_cursor(state.top_line, abs, state.cursor_col);
@@ -1247,8 +2092,18 @@ fn editing_session(
                                 state.last_save_time = Some(Instant::now());
                             }
                         }
+                        MenuAction::FileSaveAs => {
+                            if let Some(result) = save_as_in_loop(file, &mut state, &lines, settings)? {
+                                return Ok(result);
+                            }
+                        }
+                        MenuAction::FileRename => {
+                            if let Some(result) = rename_in_loop(file, &mut state, settings)? {
+                                return Ok(result);
+                            }
+                        }
                         MenuAction::FileClose => {
-                            if state.modified {
+                            if state.modified && !state.is_scratch {
                                 let _ = crossterm::execute!(std::io::stdout(), crossterm::cursor::Show);
                                 // Show simple yes/no prompt
                                 let _ = crossterm::terminal::disable_raw_mode();
@@ -1259,11 +2114,11 @@ fn editing_session(
                                 let _ = crossterm::terminal::enable_raw_mode();
                                 let confirmed = input.trim().eq_ignore_ascii_case("y");
                                 if confirmed {
-                                    let _ = delete_file_history(file);
+                                    let _ = delete_file_history(file, state.is_untitled);
                                     return Ok((state.modified, None, false, true));
                                 }
                             } else {
-                                let _ = delete_file_history(file);
+                                let _ = delete_file_history(file, state.is_untitled);
                                 return Ok((state.modified, None, false, true));
                             }
                         }
@@ -1291,6 +2146,9 @@ fn editing_session(
                         MenuAction::EditPaste => {
                             handle_paste(&mut state, &mut lines, file);
                         }
+                        MenuAction::EditGitCommit => {
+                            crate::event_handlers::commit_current_file(&mut state, file, settings)?;
+                        }
                         MenuAction::EditFind => {
                             state.saved_search_pattern = state.last_search_pattern.clone();
                             if let (Some(start), Some(end)) = (state.selection_start, state.selection_end) {
@@ -1304,6 +2162,7 @@ fn editing_session(
                                 state.find_scope = None;
                             }
                             state.find_active = true;
+                            state.reset_find_options();
                             state.find_pattern.clear();
                             state.find_cursor_pos = 0;
                         }
@@ -1316,6 +2175,10 @@ fn editing_session(
                                 );
                             }
                         }
+                        MenuAction::ViewSidebar => {
+                            crate::sidebar::toggle(&mut state, file, true);
+                            state.menu_bar.update_checkable(MenuAction::ViewSidebar, state.sidebar_open);
+                        }
                         MenuAction::ViewMarkdownRendered => {
                             if crate::menu::is_markdown_file(file) {
                                 state.markdown_rendered = !state.markdown_rendered;
@@ -1446,7 +2309,7 @@ pub fn print_keys_mode() -> std::io::Result<()> {
 }
 
 /// Format and print a key event with all its details
-fn print_key_event(key: &crossterm::event::KeyEvent) -> std::io::Result<()> {
+fn print_key_event(key: &KeyEvent) -> std::io::Result<()> {
     use crossterm::event::{KeyCode, KeyModifiers};
 
     let mut parts = Vec::new();
@@ -1540,4 +2403,195 @@ mod tests {
         assert!(s.contains("[?25h"), "expected cursor show sequence in output: {}", s);
         assert!(s.contains("[?1049l"), "expected leave alt-screen sequence in output: {}", s);
     }
+
+    #[test]
+    fn untitled_flag_is_set_on_creation_and_dropped_on_close() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let name = generate_untitled_filename();
+        assert!(is_untitled(&name));
+        assert!(!is_untitled("untitled-9"), "a name that looks untitled is not enough");
+        assert_ne!(generate_untitled_filename(), name, "names in use are not handed out again");
+
+        crate::editing::delete_file_history(&name, true).unwrap();
+        assert!(!is_untitled(&name));
+    }
+
+    #[test]
+    fn untitled_buffers_are_known_by_the_flag_in_their_history() {
+        let (tmp, _guard) = crate::env::set_temp_home();
+        let files = tmp.path().join("data").join("files");
+        std::fs::create_dir_all(&files).unwrap();
+        std::fs::write(files.join("untitled-4.ue"), "{}").unwrap();
+        assert!(!is_untitled("untitled-4"), "a history without the flag is not enough");
+
+        let mut history = UndoHistory::new();
+        history.untitled = true;
+        history.save("untitled-4").unwrap();
+        assert!(is_untitled("untitled-4"));
+        assert!(!is_untitled("./untitled-4"), "a path is always a file");
+    }
+
+    #[test]
+    fn untitled_buffers_are_never_read_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("untitled-1");
+        std::fs::write(&real, "on disk").unwrap();
+
+        assert_eq!(read_buffer(real.to_str().unwrap(), false).unwrap().0, "on disk");
+        let err = read_buffer("untitled-1", true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn first_long_line_counts_characters_not_bytes() {
+        let lines = vec!["short".to_string(), "ä".repeat(6), "x".repeat(7)];
+        // "ää…" is 12 bytes but only 6 characters
+        assert_eq!(first_long_line(&lines, 6), Some((2, 7)));
+        assert_eq!(first_long_line(&lines, 7), None);
+        assert_eq!(first_long_line(&lines, 0), None, "0 disables the check");
+    }
+
+    #[test]
+    fn clean_close_deletes_only_histories_without_unsaved_changes() {
+        let (tmp, _guard) = crate::env::set_temp_home();
+        let clean = tmp.path().join("clean.txt").to_string_lossy().to_string();
+        let dirty = tmp.path().join("dirty.txt").to_string_lossy().to_string();
+        std::fs::write(&clean, "a").unwrap();
+        std::fs::write(&dirty, "b").unwrap();
+
+        UndoHistory::new().save(&clean).unwrap();
+        let mut unsaved = UndoHistory::new();
+        unsaved.modified = true;
+        unsaved.save(&dirty).unwrap();
+
+        delete_clean_file_histories(&[clean.clone(), dirty.clone()]);
+
+        assert!(!UndoHistory::history_path_for(&clean, false).unwrap().exists());
+        assert!(UndoHistory::history_path_for(&dirty, false).unwrap().exists());
+    }
+
+    #[test]
+    fn large_file_size_applies_threshold() {
+        let (tmp, _guard) = crate::env::set_temp_home();
+        let file = tmp.path().join("big.log").to_string_lossy().to_string();
+        std::fs::write(&file, vec![b'x'; BYTES_PER_MB as usize + 1]).unwrap();
+
+        assert_eq!(large_file_size(&file, 1), Some(BYTES_PER_MB + 1));
+        assert_eq!(large_file_size(&file, 2), None);
+        assert_eq!(large_file_size(&file, 0), None, "0 turns the check off");
+        assert_eq!(large_file_size("untitled-3", 1), None);
+    }
+
+    #[test]
+    fn ephemeral_history_is_never_written() {
+        let (tmp, _guard) = crate::env::set_temp_home();
+        let file = tmp.path().join("big.log").to_string_lossy().to_string();
+        std::fs::write(&file, "x").unwrap();
+        let mut history = UndoHistory::new();
+        history.ephemeral = true;
+        history.save(&file).unwrap();
+        assert!(!UndoHistory::history_path_for(&file, false).unwrap().exists());
+    }
+
+    #[test]
+    fn line_range_argument_selects_clamped_lines() {
+        let (tmp, _guard) = crate::env::set_temp_home();
+        let lines_at = |first, last| Some(StartPosition::Lines(first, last));
+        assert_eq!(split_start_position("src/main.rs:10-25"), ("src/main.rs".to_string(), lines_at(10, 25)));
+        assert_eq!(split_start_position("a.txt:25-10"), ("a.txt".to_string(), lines_at(10, 25)));
+        assert_eq!(split_start_position("a.txt:x-3"), ("a.txt:x-3".to_string(), None));
+        let literal = tmp.path().join("odd:1-2").to_string_lossy().to_string();
+        std::fs::write(&literal, "").unwrap();
+        assert_eq!(split_start_position(&literal), (literal.clone(), None));
+
+        let settings = Settings::default();
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        let lines: Vec<String> = (1..=40).map(|n| format!("line {}", n)).collect();
+        select_line_range(&mut state, &lines, (10, 25), 20);
+        assert_eq!(state.current_position(), (9, 0));
+        assert_eq!(state.selection_range(), Some(((9, 0), (24, 7))));
+
+        select_line_range(&mut state, &lines, (35, 99), 20);
+        assert_eq!(state.current_position(), (34, 0));
+        assert_eq!(state.selection_range(), Some(((34, 0), (39, 7))));
+    }
+
+    #[test]
+    fn line_and_column_arguments_place_the_cursor() {
+        let at = |line, col| Some(StartPosition::At(line, col));
+        assert_eq!(split_start_position("src/main.rs:120:5"), ("src/main.rs".to_string(), at(120, 5)));
+        assert_eq!(split_start_position("src/main.rs:120:5:"), ("src/main.rs".to_string(), at(120, 5)));
+        assert_eq!(split_start_position("a.txt:10"), ("a.txt".to_string(), at(10, 1)));
+        assert_eq!(split_start_position("a.txt:3:x"), ("a.txt:3:x".to_string(), None));
+        assert_eq!(split_start_position(":10"), (":10".to_string(), None));
+        assert_eq!(parse_plus_line("+120"), Some(120));
+        assert_eq!(parse_plus_line("+"), None);
+        assert_eq!(parse_plus_line("+1a"), None);
+        assert_eq!(parse_plus_line("120"), None);
+
+        let settings = Settings::default();
+        let mut state = FileViewerState::new(80, UndoHistory::new(), &settings);
+        let lines: Vec<String> = (1..=40).map(|n| format!("line {}", n)).collect();
+        apply_start_position(&mut state, &lines, StartPosition::At(30, 6), 20);
+        assert_eq!(state.current_position(), (29, 5));
+        assert_eq!(state.selection_range(), None);
+
+        // Past the end of the file or the line lands on its last character
+        apply_start_position(&mut state, &lines, StartPosition::At(99, 99), 20);
+        assert_eq!(state.current_position(), (39, 7));
+        apply_start_position(&mut state, &lines, StartPosition::At(0, 0), 20);
+        assert_eq!(state.current_position(), (0, 0));
+    }
+
+    #[test]
+    fn piped_input_becomes_an_unsaved_untitled_buffer() {
+        let (_tmp, _guard) = crate::env::set_temp_home();
+        let name = create_untitled_buffer(b"one\r\ntwo\n").unwrap();
+        assert!(is_untitled(&name));
+        let history = UndoHistory::load(&name, true).unwrap();
+        assert!(history.modified);
+        assert_eq!(history.file_content, Some(vec!["one".to_string(), "two".to_string()]));
+        assert_eq!(final_buffer_text(&name), "one\ntwo\n");
+    }
+
+    #[test]
+    fn final_buffer_text_prefers_unsaved_content() {
+        let (tmp, _guard) = crate::env::set_temp_home();
+        let file = tmp.path().join("notes.txt").to_string_lossy().to_string();
+        std::fs::write(&file, "on disk\n").unwrap();
+        assert_eq!(final_buffer_text(&file), "on disk\n");
+
+        let mut history = UndoHistory::new();
+        history.update_state(0, 0, 0, vec!["edited".to_string(), "twice".to_string()]);
+        history.save(&file).unwrap();
+        assert_eq!(final_buffer_text(&file), "edited\ntwice\n");
+
+        // An untitled buffer is emitted from its history without ever being saved
+        history.untitled = true;
+        history.update_state(0, 0, 0, vec![String::new()]);
+        history.save("untitled-7").unwrap();
+        assert_eq!(final_buffer_text("untitled-7"), "");
+        assert_eq!(final_buffer_text("untitled-8"), "");
+    }
+
+    #[test]
+    fn esc_clears_one_mode_per_press_when_not_the_clear_modes_key() {
+        let mut settings = Settings::default();
+        settings.keybindings.clear_modes = "Ctrl+l".into();
+        let mut state = FileViewerState::new(80, crate::undo::UndoHistory::new(), &settings);
+        let esc = KeyEvent::new(KeyCode::Esc, crossterm::event::KeyModifiers::empty());
+        state.selection_start = Some((1, 0));
+        state.selection_end = Some((3, 2));
+        state.multi_cursors = vec![(4, 0)];
+        state.last_search_pattern = Some("Line".to_string());
+
+        assert!(handle_first_esc(&mut state, &esc));
+        assert!(state.last_search_pattern.is_none());
+        assert!(state.has_multi_cursors() && state.has_selection());
+        assert!(handle_first_esc(&mut state, &esc));
+        assert!(!state.has_multi_cursors() && state.has_selection());
+        assert!(handle_first_esc(&mut state, &esc));
+        assert!(!state.has_selection());
+        assert!(!handle_first_esc(&mut state, &esc), "nothing left: Esc opens the menu");
+    }
 }
@@ -1,5 +1,12 @@
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf, time::SystemTime};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Characters typed or deleted closer together than this are undone as one step
+const TYPING_GROUP_WINDOW: Duration = Duration::from_millis(1000);
 
 /// Cursor snapshot for undo restoration: `(line, col, multi_cursors)`.
 pub(crate) type CursorState = Option<(usize, usize, Vec<(usize, usize)>)>;
@@ -69,6 +76,11 @@ pub enum Edit {
         dest: (usize, usize),
         copy: bool,
     },
+    MoveLines {
+        start: usize,
+        len: usize,
+        up: bool,
+    },
     ReplaceLine {
         line: usize,
         old_content: String,
@@ -101,7 +113,9 @@ pub struct UndoHistory {
     #[serde(default)]
     pub scroll_top: usize, // persisted top_line (first visible logical line)
     #[serde(default, with = "optional_systemtime")]
-    pub file_timestamp: Option<u64>, // UNIX epoch timestamp of file when undo was saved
+    pub file_timestamp: Option<u64>, // UNIX epoch timestamp of the file version the buffer is based on
+    #[serde(default)]
+    pub acknowledged_timestamp: Option<u64>, // External change the user chose to keep unsaved changes over
     #[serde(default)]
     pub saved_at: usize, // Edit position where the file was last saved
     #[serde(default)]
@@ -109,7 +123,21 @@ pub struct UndoHistory {
     #[serde(default)]
     pub replace_history: Vec<String>, // Persisted replace history
     #[serde(default)]
+    pub find_options: Option<crate::find::FindOptions>, // Saved default find options
+    #[serde(default)]
+    pub find_history_options: std::collections::HashMap<String, crate::find::FindOptions>, // Options each find_history entry was searched with
+    #[serde(default)]
     pub rendered_scroll_top: usize, // last scroll position used in rendered markdown mode
+    #[serde(default)]
+    pub syntax_override: Option<String>, // Syntax chosen by the user instead of the detected one
+    #[serde(default)]
+    pub edit_times: Vec<u64>, // UNIX epoch seconds each edit was made; 0 for edits recorded before times were kept
+    #[serde(skip)]
+    pub ephemeral: bool, // never written to disk (large files opened in restricted mode)
+    #[serde(default)]
+    pub untitled: bool, // history of an untitled buffer, kept under its name in the data files root
+    #[serde(skip)]
+    last_typed: Option<Instant>, // when the last step recorded by push_typing was added
 }
 
 impl Default for UndoHistory {
@@ -129,18 +157,28 @@ impl UndoHistory {
             modified: false,
             scroll_top: 0,
             file_timestamp: None,
+            acknowledged_timestamp: None,
             saved_at: 0,
             find_history: Vec::new(),
             replace_history: Vec::new(),
+            find_options: None,
+            find_history_options: std::collections::HashMap::new(),
             rendered_scroll_top: 0,
+            syntax_override: None,
+            edit_times: Vec::new(),
+            ephemeral: false,
+            untitled: false,
+            last_typed: None,
         }
     }
 
     pub fn push(&mut self, edit: Edit) {
+        self.last_typed = None;
         // Remove any edits after current position (they were undone)
         self.edits.truncate(self.current);
         self.edits.push(edit);
         self.current = self.edits.len();
+        self.stamp_last_edit();
     }
 
     pub fn push_composite(
@@ -152,6 +190,7 @@ impl UndoHistory {
         if edits.is_empty() {
             return;
         }
+        self.last_typed = None;
         // Remove any edits after current position (they were undone)
         self.edits.truncate(self.current);
         if edits.len() == 1 && undo_cursor.is_none() && pre_cursor.is_none() {
@@ -160,6 +199,67 @@ impl UndoHistory {
             self.edits.push(Edit::CompositeEdit { edits, undo_cursor, pre_cursor });
         }
         self.current = self.edits.len();
+        self.stamp_last_edit();
+    }
+
+    /// Record the current time for the newest edit, keeping `edit_times` as long as `edits`
+    fn stamp_last_edit(&mut self) {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.edit_times.resize(self.edits.len(), 0);
+        if let Some(last) = self.edit_times.last_mut() {
+            *last = now;
+        }
+    }
+
+    /// Undo position (number of applied edits) of the buffer as it was at
+    /// `time` (UNIX epoch seconds): every edit made at or before it is applied
+    pub fn position_at_time(&self, time: u64) -> usize {
+        (0..self.edits.len())
+            .take_while(|&i| self.edit_times.get(i).copied().unwrap_or(0) <= time)
+            .count()
+    }
+
+    /// When the buffer reached the current undo position: the time of the last
+    /// applied edit, or just before the first one when everything is undone
+    pub fn current_time(&self) -> u64 {
+        match self.current {
+            0 => self.edit_times.first().map_or(0, |t| t.saturating_sub(1)),
+            n => self.edit_times.get(n - 1).copied().unwrap_or(0),
+        }
+    }
+
+    /// Record a typed or deleted character. While the user keeps typing (or
+    /// deleting) at the same spot, characters join the previous undo step as a
+    /// composite edit, so one undo removes a word rather than a keystroke. A
+    /// pause, moving elsewhere, starting a new word or saving begins a new step.
+    pub fn push_typing(&mut self, edit: Edit) {
+        let now = Instant::now();
+        let recent = self
+            .last_typed
+            .is_some_and(|t| now.duration_since(t) < TYPING_GROUP_WINDOW);
+        if recent && self.current == self.edits.len() && self.current > self.saved_at {
+            let previous = &mut self.edits[self.current - 1];
+            let last = match &*previous {
+                Edit::CompositeEdit { edits, undo_cursor: None, pre_cursor: None } => edits.last(),
+                single => Some(single),
+            };
+            if last.is_some_and(|last| continues_typing(last, &edit)) {
+                match previous {
+                    Edit::CompositeEdit { edits, .. } => edits.push(edit),
+                    single => {
+                        let first = single.clone();
+                        *single = Edit::CompositeEdit { edits: vec![first, edit], undo_cursor: None, pre_cursor: None };
+                    }
+                }
+                self.stamp_last_edit();
+                self.last_typed = Some(now);
+                return;
+            }
+        }
+        self.push(edit);
+        self.last_typed = Some(now);
     }
 
     // Update cursor, scroll position, and unsaved file content (marks modified)
@@ -188,10 +288,22 @@ impl UndoHistory {
     pub fn clear_unsaved_state(&mut self) {
         self.file_content = None;
         self.modified = false;
+        // Our own version is on disk now; earlier conflicts no longer apply
+        self.acknowledged_timestamp = None;
         // Mark current position as the saved baseline
         self.saved_at = self.current;
     }
 
+    /// Drop all edits and unsaved content so the file on disk becomes the new baseline
+    pub fn discard_unsaved_state(&mut self) {
+        self.edits.clear();
+        self.edit_times.clear();
+        self.current = 0;
+        self.saved_at = 0;
+        self.file_content = None;
+        self.modified = false;
+    }
+
     pub fn can_undo(&self) -> bool {
         self.current > 0
     }
@@ -200,6 +312,7 @@ impl UndoHistory {
     }
 
     pub fn undo(&mut self) -> Option<Edit> {
+        self.last_typed = None;
         if self.can_undo() {
             self.current -= 1;
             self.edits.get(self.current).cloned()
@@ -208,6 +321,7 @@ impl UndoHistory {
         }
     }
     pub fn redo(&mut self) -> Option<Edit> {
+        self.last_typed = None;
         if self.can_redo() {
             let edit = self.edits.get(self.current).cloned();
             self.current += 1;
@@ -218,7 +332,11 @@ impl UndoHistory {
     }
 
     pub fn save(&self, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let history_path = Self::history_path(file_path)?;
+        // The scratch buffer lives only in memory
+        if self.ephemeral || crate::ui::is_scratch_buffer(file_path) {
+            return Ok(());
+        }
+        let history_path = Self::history_path(file_path, self.untitled)?;
         // Create parent directories if they don't exist
         if let Some(parent) = history_path.parent() {
             fs::create_dir_all(parent)?;
@@ -226,8 +344,12 @@ impl UndoHistory {
 
         // Create a copy with updated timestamp
         let mut history_to_save = self.clone();
-        // Capture current file modification time
-        if let Ok(metadata) = fs::metadata(file_path)
+        // Capture current file modification time while the buffer matches the file.
+        // With unsaved changes the timestamp of the version they are based on is kept,
+        // so a later external change is still detected as a conflict.
+        if (!self.modified || self.file_timestamp.is_none())
+            && !self.untitled
+            && let Ok(metadata) = fs::metadata(file_path)
             && let Ok(modified) = metadata.modified()
             && let Ok(duration) = modified.duration_since(SystemTime::UNIX_EPOCH)
         {
@@ -238,15 +360,18 @@ impl UndoHistory {
         fs::write(&history_path, serialized)?;
         Ok(())
     }
-    pub fn load(file_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let history_path = Self::history_path(file_path)?;
-        if history_path.exists() {
+    /// Load the history of `file_path`, or of the untitled buffer of that name.
+    /// The returned history is saved back to the same place.
+    pub fn load(file_path: &str, untitled: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let history_path = Self::history_path(file_path, untitled)?;
+        let mut history = if history_path.exists() {
             let content = fs::read_to_string(&history_path)?;
-            let history: UndoHistory = serde_json::from_str(&content)?;
-            Ok(history)
+            serde_json::from_str::<UndoHistory>(&content)?
         } else {
-            Ok(Self::new())
-        }
+            Self::new()
+        };
+        history.untitled = untitled;
+        Ok(history)
     }
 
     /// Validate undo history against current file modification timestamp
@@ -270,8 +395,8 @@ impl UndoHistory {
             Err(_) => return ValidationResult::Valid, // File doesn't exist or can't read, treat as valid
         };
 
-        // Compare timestamps
-        if current_timestamp != stored_timestamp {
+        // Compare timestamps; a change the user already decided on is not reported again
+        if current_timestamp != stored_timestamp && self.acknowledged_timestamp != Some(current_timestamp) {
             // File was modified externally
             if self.modified {
                 ValidationResult::ModifiedWithUnsaved
@@ -283,38 +408,53 @@ impl UndoHistory {
         }
     }
 
-    pub(crate) fn history_path_for(file_path: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        Self::history_path(file_path)
+    /// Remember the file's current on-disk version as an acknowledged conflict:
+    /// the user keeps their unsaved changes and is only asked again once the
+    /// file changes to a newer state
+    pub fn acknowledge_external_change(&mut self, file_path: &str) {
+        if let Ok(metadata) = fs::metadata(file_path)
+            && let Ok(modified) = metadata.modified()
+            && let Ok(duration) = modified.duration_since(SystemTime::UNIX_EPOCH)
+        {
+            self.acknowledged_timestamp = Some(duration.as_secs());
+        }
+    }
+
+    /// Whether the history kept for the untitled buffer `name` marks it as one
+    pub(crate) fn records_untitled(name: &str) -> bool {
+        Self::history_path(name, true)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<UndoHistory>(&content).ok())
+            .is_some_and(|history| history.untitled)
+    }
+
+    pub(crate) fn history_path_for(file_path: &str, untitled: bool) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Self::history_path(file_path, untitled)
     }
 
     /// Get modification time of undo history file in seconds since UNIX epoch
     /// Returns None if file doesn't exist or can't be read
-    pub(crate) fn get_undo_file_mtime(file_path: &str) -> Option<u128> {
-        let history_path = Self::history_path(file_path).ok()?;
+    pub(crate) fn get_undo_file_mtime(file_path: &str, untitled: bool) -> Option<u128> {
+        let history_path = Self::history_path(file_path, untitled).ok()?;
         let metadata = fs::metadata(&history_path).ok()?;
         let modified = metadata.modified().ok()?;
         let duration = modified.duration_since(SystemTime::UNIX_EPOCH).ok()?;
         Some(duration.as_nanos())
     }
 
-    fn history_path(file_path: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    fn history_path(file_path: &str, untitled: bool) -> Result<PathBuf, Box<dyn std::error::Error>> {
         let data_dir = crate::env::resolve_data_dir()?;
 
-        // Check if this is an untitled file.
-        // Untitled files are stored directly in the data files root without subdirectories.
-        // They are identified by:
-        // 1. Filename starts with "untitled" (case-insensitive)
-        // 2. It's not an absolute path (no '/' on Unix or drive letter on Windows)
-        // 3. It's just a simple filename (no path separators)
+        // Untitled buffers are stored directly in the data files root without subdirectories.
+        // The caller knows whether the buffer is one, so a real file such as
+        // "untitled-1.txt" or even "untitled-1" is treated like any other file.
         let path_buf = PathBuf::from(file_path);
         let filename = path_buf.file_name()
             .and_then(|n| n.to_str())
             .ok_or("Invalid filename")?;
 
-        let is_simple_filename = !file_path.contains('/') && !file_path.contains('\\');
-        let is_untitled = filename.to_lowercase().starts_with("untitled") && is_simple_filename;
-
-        if is_untitled {
+        if untitled {
             // Store untitled files in the data files root (no subdirectories)
             let ue_filename = format!("{}.ue", filename);
             return Ok(data_dir.join("files").join(ue_filename));
@@ -360,11 +500,83 @@ impl UndoHistory {
     }
 }
 
+/// Whether `next` continues typing (or deleting) right where `previous` left off
+fn continues_typing(previous: &Edit, next: &Edit) -> bool {
+    // Whitespace followed by anything else is where a new word begins
+    let same_word = |p: &char, n: &char| !p.is_whitespace() || n.is_whitespace();
+    match (previous, next) {
+        (
+            Edit::InsertChar { line: l1, col: c1, ch: p },
+            Edit::InsertChar { line: l2, col: c2, ch: n },
+        ) => l1 == l2 && *c2 == c1 + 1 && same_word(p, n),
+        (
+            Edit::DeleteChar { line: l1, col: c1, ch: p },
+            Edit::DeleteChar { line: l2, col: c2, ch: n },
+        ) => {
+            // Backspace moves left, Delete stays in place
+            l1 == l2 && (c2 + 1 == *c1 || c2 == c1) && same_word(p, n)
+        }
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::env::set_temp_home;
 
+    #[test]
+    fn push_typing_groups_until_a_jump_or_save() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut h = UndoHistory::new();
+        h.push_typing(Edit::InsertChar { line: 0, col: 0, ch: 'a' });
+        h.push_typing(Edit::InsertChar { line: 0, col: 1, ch: 'b' });
+        assert_eq!(h.edits.len(), 1);
+
+        // Typing somewhere else starts a new step
+        h.push_typing(Edit::InsertChar { line: 3, col: 0, ch: 'c' });
+        assert_eq!(h.edits.len(), 2);
+
+        // So does saving in between
+        h.clear_unsaved_state();
+        h.push_typing(Edit::InsertChar { line: 3, col: 1, ch: 'd' });
+        assert_eq!(h.edits.len(), 3);
+
+        // And any other kind of edit
+        h.push(Edit::InsertLine { line: 4, content: String::new() });
+        h.push_typing(Edit::InsertChar { line: 3, col: 2, ch: 'e' });
+        assert_eq!(h.edits.len(), 5);
+
+        // Grouped steps are plain composite edits in the saved format
+        let json = serde_json::to_string(&h).unwrap();
+        let loaded: UndoHistory = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.edits, h.edits);
+    }
+
+    #[test]
+    fn position_at_time_counts_edits_made_by_then() {
+        let (_tmp, _guard) = set_temp_home();
+        let mut h = UndoHistory::new();
+        for col in 0..3 {
+            h.push(Edit::InsertLine { line: col, content: String::new() });
+        }
+        assert_eq!(h.edit_times.len(), 3);
+        h.edit_times = vec![0, 100, 200]; // the first edit predates recorded times
+
+        assert_eq!(h.position_at_time(50), 1);
+        assert_eq!(h.position_at_time(100), 2);
+        assert_eq!(h.position_at_time(1000), 3);
+        assert_eq!(h.current_time(), 200);
+        h.current = 0;
+        assert_eq!(h.current_time(), 0);
+
+        // Undone edits are replaced together with their times
+        h.current = 1;
+        h.push(Edit::InsertLine { line: 9, content: String::new() });
+        assert_eq!(h.edit_times.len(), 2);
+        assert_eq!(h.edit_times[0], 0);
+    }
+
     #[test]
     fn push_and_undo_redo_cycle() {
         let (_tmp, _guard) = set_temp_home();
@@ -446,7 +658,7 @@ mod tests {
         });
         h.update_state(0, 0, 1, vec!["a".into()]);
         h.save(&file_str).expect("save");
-        let loaded = UndoHistory::load(&file_str).expect("load");
+        let loaded = UndoHistory::load(&file_str, false).expect("load");
         assert_eq!(loaded.edits.len(), 1);
         assert_eq!(loaded.cursor_col, 1);
         assert_eq!(
@@ -455,6 +667,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn scratch_buffer_never_writes_history() {
+        let (_tmp, _guard) = set_temp_home();
+        let name = crate::ui::SCRATCH_BUFFER_NAME;
+        let mut h = UndoHistory::new();
+        h.push(Edit::InsertChar { line: 0, col: 0, ch: 'a' });
+        h.update_state(0, 0, 1, vec!["a".into()]);
+        h.save(name).expect("save");
+        assert!(UndoHistory::get_undo_file_mtime(name, false).is_none());
+        assert!(UndoHistory::load(name, false).expect("load").edits.is_empty());
+    }
+
     #[test]
     fn undo_redo_round_trip_persistence() {
         let (_tmp, _guard) = set_temp_home();
@@ -476,7 +700,7 @@ mod tests {
         h.update_state(0, 0, 2, vec!["ab".into()]);
         h.save(&file_str).unwrap();
 
-        let mut loaded = UndoHistory::load(&file_str).unwrap();
+        let mut loaded = UndoHistory::load(&file_str, false).unwrap();
         assert_eq!(loaded.edits.len(), 2);
         assert_eq!(loaded.cursor_col, 2);
 
@@ -547,7 +771,7 @@ mod tests {
     #[test]
     fn load_nonexistent_file_returns_new_history() {
         let (_tmp, _guard) = set_temp_home();
-        let result = UndoHistory::load("/nonexistent/file.txt");
+        let result = UndoHistory::load("/nonexistent/file.txt", false);
         assert!(result.is_ok());
         let h = result.unwrap();
         assert_eq!(h.edits.len(), 0);
@@ -571,18 +795,18 @@ mod tests {
         h.save(&file_str).expect("save");
 
         // Now corrupt the history file
-        let history_path = UndoHistory::history_path(&file_str).unwrap();
+        let history_path = UndoHistory::history_path(&file_str, false).unwrap();
         fs::write(&history_path, "{ this is not valid json ").expect("write corrupted");
 
         // Loading should fail
-        let result = UndoHistory::load(&file_str);
+        let result = UndoHistory::load(&file_str, false);
         assert!(result.is_err());
     }
 
     #[test]
     fn history_path_handles_absolute_paths() {
         let (_tmp, _guard) = set_temp_home();
-        let result = UndoHistory::history_path("/home/user/test.txt");
+        let result = UndoHistory::history_path("/home/user/test.txt", false);
         assert!(result.is_ok());
         let path = result.unwrap();
         // Should be data/files/home/user/test.txt.ue (no leading dot before filename)
@@ -595,7 +819,7 @@ mod tests {
     #[test]
     fn history_path_handles_relative_paths() {
         let (_tmp, _guard) = set_temp_home();
-        let result = UndoHistory::history_path("documents/test.txt");
+        let result = UndoHistory::history_path("documents/test.txt", false);
         assert!(result.is_ok());
         let path = result.unwrap();
         let path_str = path.to_string_lossy();
@@ -603,6 +827,16 @@ mod tests {
         assert!(path_str.ends_with("documents/test.txt.ue"));
     }
 
+    #[test]
+    fn history_path_keeps_real_untitled_named_files_out_of_untitled_root() {
+        let (_tmp, _guard) = set_temp_home();
+        let untitled = UndoHistory::history_path("untitled-1", true).unwrap();
+        let real = UndoHistory::history_path("untitled-1", false).unwrap();
+        assert!(untitled.ends_with("files/untitled-1.ue"));
+        assert!(!real.ends_with("files/untitled-1.ue"));
+        assert!(real.to_string_lossy().ends_with("untitled-1.ue"));
+    }
+
     #[test]
     fn modified_flag_resets_when_all_changes_undone() {
         let (_tmp, _guard) = set_temp_home();
@@ -646,7 +880,7 @@ mod tests {
     #[test]
     fn history_path_handles_hidden_filename() {
         let (_tmp, _guard) = set_temp_home();
-        let path = UndoHistory::history_path(".env").unwrap();
+        let path = UndoHistory::history_path(".env", false).unwrap();
         let s = path.to_string_lossy();
         assert!(s.ends_with(".env.ue")); // hidden original remains with dot
     }
@@ -654,7 +888,7 @@ mod tests {
     #[test]
     fn history_path_handles_no_extension() {
         let (_tmp, _guard) = set_temp_home();
-        let path = UndoHistory::history_path("LICENSE").unwrap();
+        let path = UndoHistory::history_path("LICENSE", false).unwrap();
         let s = path.to_string_lossy();
         assert!(s.ends_with("LICENSE.ue"));
     }
@@ -662,7 +896,7 @@ mod tests {
     #[test]
     fn history_path_handles_unicode_filename() {
         let (_tmp, _guard) = set_temp_home();
-        let path = UndoHistory::history_path("übergröße.txt").unwrap();
+        let path = UndoHistory::history_path("übergröße.txt", false).unwrap();
         let s = path.to_string_lossy();
         assert!(s.ends_with("übergröße.txt.ue"));
     }
@@ -671,7 +905,7 @@ mod tests {
     fn history_path_handles_dot_slash_relative() {
         let (_tmp, _guard) = set_temp_home();
         let rel = "./docs/readme.md";
-        let path = UndoHistory::history_path(rel).unwrap();
+        let path = UndoHistory::history_path(rel, false).unwrap();
         let s = path.to_string_lossy();
         assert!(s.contains("data/files"));
         assert!(s.ends_with("docs/readme.md.ue"));
@@ -708,7 +942,7 @@ mod tests {
         });
         h.save(&file_str).unwrap();
 
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         let result = loaded.validate(&file_str);
         assert_eq!(result, ValidationResult::Valid);
     }
@@ -732,7 +966,7 @@ mod tests {
         fs::write(&file, "modified content").unwrap();
 
         // Load and validate
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         let result = loaded.validate(&file_str);
 
         // This test may not work on all filesystems - mark as ignored
@@ -764,7 +998,7 @@ mod tests {
         fs::write(&file, "modified content").unwrap();
 
         // Load and validate
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         assert!(loaded.modified);
         let result = loaded.validate(&file_str);
 
@@ -772,6 +1006,34 @@ mod tests {
         assert_eq!(result, ValidationResult::ModifiedWithUnsaved);
     }
 
+    #[test]
+    fn acknowledged_external_change_is_not_reported_again() {
+        let (tmp, _guard) = set_temp_home();
+        let file = tmp.path().join("test.txt");
+        fs::write(&file, "changed on disk").unwrap();
+        let file_str = file.to_string_lossy();
+
+        let mut h = UndoHistory::new();
+        h.push(Edit::InsertChar { line: 0, col: 0, ch: 'a' });
+        h.update_state(0, 0, 1, vec!["a".to_string()]);
+        h.file_timestamp = Some(1); // based on a much older version
+        assert_eq!(h.validate(&file_str), ValidationResult::ModifiedWithUnsaved);
+
+        h.acknowledge_external_change(&file_str);
+        h.save(&file_str).unwrap();
+        let mut loaded = UndoHistory::load(&file_str, false).unwrap();
+        assert_eq!(loaded.file_timestamp, Some(1), "unsaved changes keep their base version");
+        assert_eq!(loaded.validate(&file_str), ValidationResult::Valid);
+
+        // A newer external change than the acknowledged one asks again
+        loaded.acknowledged_timestamp = loaded.acknowledged_timestamp.map(|ts| ts - 1);
+        assert_eq!(loaded.validate(&file_str), ValidationResult::ModifiedWithUnsaved);
+
+        // Saving our own version clears the acknowledgment
+        loaded.clear_unsaved_state();
+        assert_eq!(loaded.acknowledged_timestamp, None);
+    }
+
     #[test]
     fn save_captures_file_timestamp() {
         let (tmp, _guard) = set_temp_home();
@@ -782,7 +1044,7 @@ mod tests {
         let h = UndoHistory::new();
         h.save(&file_str).unwrap();
 
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         assert!(loaded.file_timestamp.is_some());
     }
 
@@ -797,7 +1059,7 @@ mod tests {
         let h = UndoHistory::new();
         h.save(&file_str).unwrap();
 
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         assert!(!loaded.modified);
         assert!(loaded.file_content.is_none());
         assert_eq!(loaded.edits.len(), 0);
@@ -820,7 +1082,7 @@ mod tests {
         h.modified = false;
         h.save(&file_str).unwrap();
 
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         assert!(!loaded.modified);
         assert!(loaded.file_content.is_some());
 
@@ -847,7 +1109,7 @@ mod tests {
         assert!(h.modified);
         h.save(&file_str).unwrap();
 
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         assert!(loaded.modified);
         assert_eq!(loaded.edits.len(), 1);
         assert!(loaded.file_content.is_some());
@@ -891,7 +1153,7 @@ mod tests {
         assert_eq!(h.edits.len(), 3); // 3 total edits (can redo)
         h.save(&file_str).unwrap();
 
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         assert!(loaded.modified);
         assert_eq!(loaded.current, 2);
         assert_eq!(loaded.edits.len(), 3);
@@ -975,7 +1237,7 @@ mod tests {
         h.file_timestamp = None;
 
         // Manually save as JSON without timestamp
-        let history_path = UndoHistory::history_path_for(&file_str).unwrap();
+        let history_path = UndoHistory::history_path_for(&file_str, false).unwrap();
         if let Some(parent) = history_path.parent() {
             fs::create_dir_all(parent).unwrap();
         }
@@ -983,7 +1245,7 @@ mod tests {
         fs::write(&history_path, serialized).unwrap();
 
         // Load and validate
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         assert!(loaded.file_timestamp.is_none());
         assert!(loaded.modified);
 
@@ -1007,7 +1269,7 @@ mod tests {
         h.save(&file_str).unwrap();
 
         // Verify undo file exists
-        let history_path = UndoHistory::history_path_for(&file_str).unwrap();
+        let history_path = UndoHistory::history_path_for(&file_str, false).unwrap();
         assert!(history_path.exists());
 
         // Modify file externally
@@ -1015,7 +1277,7 @@ mod tests {
         fs::write(&file, "modified content").unwrap();
 
         // Load and validate
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         let _result = loaded.validate(&file_str);
 
         // Validation should detect modification (or Valid if timestamps don't differ)
@@ -1053,7 +1315,7 @@ mod tests {
         h.save(&file_str).unwrap();
 
         // Verify undo file exists
-        let history_path = UndoHistory::history_path_for(&file_str).unwrap();
+        let history_path = UndoHistory::history_path_for(&file_str, false).unwrap();
         assert!(history_path.exists());
 
         // Modify file externally
@@ -1061,7 +1323,7 @@ mod tests {
         fs::write(&file, "completely different").unwrap();
 
         // Load and validate
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
         assert!(loaded.modified);
         assert_eq!(loaded.edits.len(), 2);
 
@@ -1075,7 +1337,7 @@ mod tests {
         );
 
         // Reload and verify edits are preserved
-        let reloaded = UndoHistory::load(&file_str).unwrap();
+        let reloaded = UndoHistory::load(&file_str, false).unwrap();
         assert_eq!(reloaded.edits.len(), 2);
         assert!(reloaded.modified);
         assert!(reloaded.file_content.is_some());
@@ -1087,7 +1349,7 @@ mod tests {
         let file_str = "/tmp/nonexistent_file_for_mtime_test.txt";
 
         // Undo file doesn't exist yet
-        let mtime = UndoHistory::get_undo_file_mtime(file_str);
+        let mtime = UndoHistory::get_undo_file_mtime(file_str, false);
         assert!(
             mtime.is_none(),
             "Should return None for nonexistent undo file"
@@ -1109,7 +1371,7 @@ mod tests {
         h.save(file_str).unwrap();
 
         // Should now return a timestamp
-        let mtime1 = UndoHistory::get_undo_file_mtime(file_str);
+        let mtime1 = UndoHistory::get_undo_file_mtime(file_str, false);
         assert!(mtime1.is_some(), "Should return timestamp after save");
 
         // Wait a bit and save again
@@ -1122,7 +1384,7 @@ mod tests {
         h.save(file_str).unwrap();
 
         // Timestamp should be different (or at least not None)
-        let mtime2 = UndoHistory::get_undo_file_mtime(file_str);
+        let mtime2 = UndoHistory::get_undo_file_mtime(file_str, false);
         assert!(
             mtime2.is_some(),
             "Should return timestamp after second save"
@@ -1152,14 +1414,14 @@ mod tests {
         h1.update_state(0, 0, 1, vec!["aoriginal".to_string()]);
         h1.save(&file_str).unwrap();
 
-        let mtime1 = UndoHistory::get_undo_file_mtime(&file_str);
+        let mtime1 = UndoHistory::get_undo_file_mtime(&file_str, false);
         assert!(mtime1.is_some());
 
         // Wait to ensure mtime changes
         thread::sleep(Duration::from_millis(10));
 
         // Instance 2: Make different changes and save
-        let mut h2 = UndoHistory::load(&file_str).unwrap();
+        let mut h2 = UndoHistory::load(&file_str, false).unwrap();
         h2.push(Edit::InsertChar {
             line: 0,
             col: 1,
@@ -1168,14 +1430,14 @@ mod tests {
         h2.update_state(0, 0, 2, vec!["aboriginal".to_string()]);
         h2.save(&file_str).unwrap();
 
-        let mtime2 = UndoHistory::get_undo_file_mtime(&file_str);
+        let mtime2 = UndoHistory::get_undo_file_mtime(&file_str, false);
         assert!(mtime2.is_some());
 
         // mtimes should be different
         assert_ne!(mtime1, mtime2);
 
         // Instance 1: Reload and verify it sees instance 2's changes
-        let h1_reloaded = UndoHistory::load(&file_str).unwrap();
+        let h1_reloaded = UndoHistory::load(&file_str, false).unwrap();
         assert_eq!(h1_reloaded.edits.len(), 2);
         assert_eq!(h1_reloaded.cursor_col, 2);
         assert_eq!(
@@ -1211,7 +1473,7 @@ mod tests {
         h1.save(&file_str).unwrap();
 
         // Instance 2: Load and verify cursor restoration
-        let h2 = UndoHistory::load(&file_str).unwrap();
+        let h2 = UndoHistory::load(&file_str, false).unwrap();
         assert_eq!(h2.scroll_top, 1);
         assert_eq!(h2.cursor_line, 2);
         assert_eq!(h2.cursor_col, 3);
@@ -1236,7 +1498,7 @@ mod tests {
         h1.save(&file_str).unwrap();
 
         // Instance 2: Load and verify modified=true
-        let h2 = UndoHistory::load(&file_str).unwrap();
+        let h2 = UndoHistory::load(&file_str, false).unwrap();
         assert!(h2.modified);
         assert_eq!(h2.edits.len(), 1);
 
@@ -1247,7 +1509,7 @@ mod tests {
         h1.save(&file_str).unwrap();
 
         // Instance 2: Reload and verify modified flag is now false
-        let h2_reloaded = UndoHistory::load(&file_str).unwrap();
+        let h2_reloaded = UndoHistory::load(&file_str, false).unwrap();
         assert!(!h2_reloaded.modified);
     }
 
@@ -1263,7 +1525,7 @@ mod tests {
         h_base.save(&file_str).unwrap();
 
         // Instance 1: Add edit 'x'
-        let mut h1 = UndoHistory::load(&file_str).unwrap();
+        let mut h1 = UndoHistory::load(&file_str, false).unwrap();
         h1.push(Edit::InsertChar {
             line: 0,
             col: 0,
@@ -1273,7 +1535,7 @@ mod tests {
         h1.save(&file_str).unwrap();
 
         // Instance 2: Add edit 'y' (loads instance 1's state first)
-        let mut h2 = UndoHistory::load(&file_str).unwrap();
+        let mut h2 = UndoHistory::load(&file_str, false).unwrap();
         h2.push(Edit::InsertChar {
             line: 0,
             col: 0,
@@ -1283,7 +1545,7 @@ mod tests {
         h2.save(&file_str).unwrap();
 
         // Final state should be instance 2's changes (which includes instance 1's edit)
-        let h_final = UndoHistory::load(&file_str).unwrap();
+        let h_final = UndoHistory::load(&file_str, false).unwrap();
         // h2 loaded h1's state (with 'x') then added 'y', so we have 2 edits
         assert_eq!(h_final.edits.len(), 2);
     }
@@ -1319,7 +1581,7 @@ mod tests {
         h1.save(&file_str).unwrap();
 
         // Instance 2: Load and verify redo chain preserved
-        let h2 = UndoHistory::load(&file_str).unwrap();
+        let h2 = UndoHistory::load(&file_str, false).unwrap();
         assert_eq!(h2.current, 2);
         assert_eq!(h2.edits.len(), 3);
         assert!(h2.can_redo());
@@ -1339,12 +1601,12 @@ mod tests {
         // Initial save
         let h1 = UndoHistory::new();
         h1.save(&file_str).unwrap();
-        let mtime1 = UndoHistory::get_undo_file_mtime(&file_str);
+        let mtime1 = UndoHistory::get_undo_file_mtime(&file_str, false);
         assert!(mtime1.is_some());
 
         // Wait and modify
         thread::sleep(Duration::from_millis(10));
-        let mut h2 = UndoHistory::load(&file_str).unwrap();
+        let mut h2 = UndoHistory::load(&file_str, false).unwrap();
         h2.push(Edit::InsertChar {
             line: 0,
             col: 0,
@@ -1352,7 +1614,7 @@ mod tests {
         });
         h2.save(&file_str).unwrap();
 
-        let mtime2 = UndoHistory::get_undo_file_mtime(&file_str);
+        let mtime2 = UndoHistory::get_undo_file_mtime(&file_str, false);
         assert!(mtime2.is_some());
 
         // mtimes should differ (though on fast systems with low-res fs, may be equal)
@@ -1457,7 +1719,7 @@ mod tests {
         h1.save(&file_str).unwrap();
 
         // Instance 2: Load and verify modified=true
-        let h2 = UndoHistory::load(&file_str).unwrap();
+        let h2 = UndoHistory::load(&file_str, false).unwrap();
         assert!(h2.modified);
         assert_eq!(h2.current, 1);
         assert_eq!(h2.saved_at, 0);
@@ -1469,7 +1731,7 @@ mod tests {
         h1.save(&file_str).unwrap();
 
         // Instance 2: Reload and verify modified=false
-        let h2_reloaded = UndoHistory::load(&file_str).unwrap();
+        let h2_reloaded = UndoHistory::load(&file_str, false).unwrap();
         assert!(
             !h2_reloaded.modified,
             "Modified flag should propagate from save in other instance"
@@ -1511,7 +1773,7 @@ mod tests {
         h1.save(&file_str).unwrap();
 
         // Get the undo file mtime after first save
-        let mtime1 = UndoHistory::get_undo_file_mtime(&file_str).unwrap();
+        let mtime1 = UndoHistory::get_undo_file_mtime(&file_str, false).unwrap();
 
         // Simulate: User then quickly moves cursor down (CursorDown) to line 1, col 0
         // In this scenario, we would check if the content changed before restoring cursor
@@ -1527,11 +1789,11 @@ mod tests {
         h2.save(&file_str).unwrap();
 
         // Get mtime after second save
-        let mtime2 = UndoHistory::get_undo_file_mtime(&file_str).unwrap();
+        let mtime2 = UndoHistory::get_undo_file_mtime(&file_str, false).unwrap();
         assert!(mtime2 > mtime1, "Undo file should have been modified");
 
         // Load the history - this simulates the reload mechanism detecting mtime change
-        let loaded = UndoHistory::load(&file_str).unwrap();
+        let loaded = UndoHistory::load(&file_str, false).unwrap();
 
         // Verify the content is the same
         assert_eq!(
@@ -0,0 +1,267 @@
+//! Named workspaces (`ue --workspace <name>`): each keeps its own last session
+//! and recent files list in a directory of its own below the data directory,
+//! and is bound to the project directory it was first opened in, which becomes
+//! the working directory whenever it is opened again. Without a name a selector
+//! lists the existing workspaces.
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute, queue,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File in a workspace directory holding its project directory
+const PROJECT_FILE: &str = "project";
+
+/// Most rows the selector takes
+const MAX_SELECTOR_ROWS: usize = 15;
+
+/// Name of the workspace in use, None for the global session
+static ACTIVE: Mutex<Option<String>> = Mutex::new(None);
+
+/// A named workspace and the project directory it works in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub name: String,
+    pub project: PathBuf,
+}
+
+fn workspaces_dir() -> io::Result<PathBuf> {
+    let data_dir = crate::env::resolve_data_dir()
+        .map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+    Ok(data_dir.join("workspaces"))
+}
+
+/// Directory the session and recent files are kept in: the active workspace's,
+/// otherwise the data directory itself
+pub(crate) fn data_dir() -> io::Result<PathBuf> {
+    match ACTIVE.lock().ok().and_then(|active| active.clone()) {
+        Some(name) => Ok(workspaces_dir()?.join(name)),
+        None => crate::env::resolve_data_dir().map_err(|e| io::Error::new(io::ErrorKind::NotFound, e)),
+    }
+}
+
+/// Name of the workspace in use
+pub(crate) fn active() -> Option<String> {
+    ACTIVE.lock().ok().and_then(|active| active.clone())
+}
+
+/// Names become directory names: no separators, not hidden, not empty
+fn is_valid_name(name: &str) -> bool {
+    !name.trim().is_empty() && !name.starts_with('.') && !name.contains(['/', '\\'])
+}
+
+/// Use the workspace `name` for this run, creating it for the working directory
+/// if it does not exist yet. Returns its project directory.
+pub fn open(name: &str) -> io::Result<PathBuf> {
+    if !is_valid_name(name) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid workspace name '{}'", name)));
+    }
+    let dir = workspaces_dir()?.join(name);
+    let project_file = dir.join(PROJECT_FILE);
+    let project = match std::fs::read_to_string(&project_file) {
+        Ok(path) => PathBuf::from(path.trim()),
+        Err(_) => {
+            let project = std::env::current_dir()?;
+            std::fs::create_dir_all(&dir)?;
+            std::fs::write(&project_file, project.to_string_lossy().as_bytes())?;
+            project
+        }
+    };
+    if let Ok(mut active) = ACTIVE.lock() {
+        *active = Some(name.to_string());
+    }
+    Ok(project)
+}
+
+/// Existing workspaces, most recently used first
+pub fn list() -> io::Result<Vec<Workspace>> {
+    let entries = match std::fs::read_dir(workspaces_dir()?) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut found: Vec<(std::time::SystemTime, Workspace)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let dir = e.path();
+            let project = std::fs::read_to_string(dir.join(PROJECT_FILE)).ok()?;
+            let used = last_used(&dir);
+            let name = e.file_name().to_string_lossy().to_string();
+            Some((used, Workspace { name, project: PathBuf::from(project.trim()) }))
+        })
+        .collect();
+    found.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.name.cmp(&b.1.name)));
+    Ok(found.into_iter().map(|(_, w)| w).collect())
+}
+
+/// When the workspace in `dir` was last used: its session is written on every switch
+fn last_used(dir: &Path) -> std::time::SystemTime {
+    ["last_session", PROJECT_FILE]
+        .iter()
+        .filter_map(|f| std::fs::metadata(dir.join(f)).and_then(|m| m.modified()).ok())
+        .max()
+        .unwrap_or(std::time::UNIX_EPOCH)
+}
+
+/// Workspaces whose name or project directory fuzzy-matches `query`, best first
+fn filter_workspaces<'a>(workspaces: &'a [Workspace], query: &str) -> Vec<&'a Workspace> {
+    let mut scored: Vec<(i64, usize, &Workspace)> = workspaces
+        .iter()
+        .enumerate()
+        .filter_map(|(i, w)| {
+            let text = format!("{} {}", w.name, w.project.display());
+            crate::outline::fuzzy_score(query, &text).map(|score| (score, i, w))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, _, w)| w).collect()
+}
+
+/// Let the user choose a workspace before the editor starts: type to filter,
+/// Enter opens the highlighted one, or creates a workspace named as typed for
+/// the working directory when nothing matches. None on Esc.
+pub fn pick() -> io::Result<Option<String>> {
+    let settings = crate::settings::Settings::load().map_err(|e| io::Error::other(e.to_string()))?;
+    let workspaces = list()?;
+    let mut stdout = crate::no_color::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen, Clear(ClearType::All))?;
+
+    let mut query = String::new();
+    let mut selected = 0usize;
+    let mut scroll = 0usize;
+    let result = loop {
+        let (width, height) = terminal::size()?;
+        let matches = filter_workspaces(&workspaces, &query);
+        selected = selected.min(matches.len().saturating_sub(1));
+        let rows = MAX_SELECTOR_ROWS.min((height as usize).saturating_sub(4)).max(1);
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + rows {
+            scroll = selected + 1 - rows;
+        }
+        render_selector(&mut stdout, &query, &matches, selected, scroll, rows, width, &settings.theme)?;
+
+        if let Event::Key(key) = event::read()? {
+            let key = crate::event_handlers::normalize_key_event(key, &settings);
+            match key.code {
+                KeyCode::Esc => break None,
+                KeyCode::Enter => match matches.get(selected) {
+                    Some(workspace) => break Some(workspace.name.clone()),
+                    None if is_valid_name(&query) => break Some(query.trim().to_string()),
+                    None => {}
+                },
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down => selected += 1,
+                KeyCode::PageUp => selected = selected.saturating_sub(rows),
+                KeyCode::PageDown => selected += rows,
+                KeyCode::Backspace => {
+                    query.pop();
+                    selected = 0;
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    query.push(c);
+                    selected = 0;
+                }
+                _ => {}
+            }
+        }
+    };
+
+    execute!(stdout, Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    Ok(result)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_selector(
+    stdout: &mut impl Write,
+    query: &str,
+    matches: &[&Workspace],
+    selected: usize,
+    scroll: usize,
+    rows: usize,
+    term_width: u16,
+    theme: &crate::theme::Theme,
+) -> io::Result<()> {
+    let width = (term_width as usize).saturating_sub(4).clamp(10, 90);
+    let x = ((term_width as usize).saturating_sub(width) / 2) as u16;
+    let bg = theme.chrome_bg;
+    let fit = |text: String| -> String {
+        let text = crate::rendering::truncate_to_width(&text, width);
+        let pad = width.saturating_sub(crate::coordinates::visual_width(&text, 4));
+        format!("{}{}", text, " ".repeat(pad))
+    };
+
+    queue!(stdout, Hide, MoveTo(x, 1), SetBackgroundColor(bg), SetForegroundColor(Color::Yellow))?;
+    queue!(stdout, Print(fit(format!(" Workspace: {}", query))), ResetColor)?;
+    for row in 0..rows {
+        let idx = scroll + row;
+        let text = match matches.get(idx) {
+            Some(workspace) => format!(" {}  {}", workspace.name, workspace.project.display()),
+            None if idx == 0 && is_valid_name(query) => {
+                let cwd = std::env::current_dir().unwrap_or_default();
+                format!(" Enter: new workspace '{}' for {}", query.trim(), cwd.display())
+            }
+            None if idx == 0 => " (type a name for a new workspace)".to_string(),
+            None => String::new(),
+        };
+        queue!(stdout, MoveTo(x, 2 + row as u16))?;
+        if idx == selected && idx < matches.len() {
+            queue!(stdout, SetBackgroundColor(theme.menu_selection_bg), SetForegroundColor(Color::White))?;
+        } else {
+            queue!(stdout, SetBackgroundColor(bg), SetForegroundColor(Color::White))?;
+        }
+        queue!(stdout, Print(fit(text)), ResetColor)?;
+    }
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::set_temp_home;
+
+    #[test]
+    fn workspaces_keep_their_own_session_and_recent_files() {
+        let (tmp, _guard) = set_temp_home();
+        let file = tmp.path().join("notes.txt");
+        std::fs::write(&file, "x").unwrap();
+        let file = file.to_string_lossy().to_string();
+        crate::session::save_editor_session(&file).unwrap();
+        crate::recent::update_recent_file(&file).unwrap();
+
+        let project = open("web").unwrap();
+        assert_eq!(project, std::env::current_dir().unwrap());
+        assert_eq!(active().as_deref(), Some("web"));
+        assert!(crate::session::load_last_session().unwrap().is_none());
+        assert!(crate::recent::get_recent_files().unwrap().is_empty());
+        crate::session::save_selector_session().unwrap();
+        *ACTIVE.lock().unwrap() = None;
+
+        // The global session is untouched
+        let global = crate::session::load_last_session().unwrap().unwrap();
+        assert_eq!(global.file, Some(PathBuf::from(&file)));
+        assert_eq!(crate::recent::get_recent_files().unwrap().len(), 1);
+
+        let listed = list().unwrap();
+        assert_eq!(listed, vec![Workspace { name: "web".to_string(), project }]);
+        assert_eq!(filter_workspaces(&listed, "wb").len(), 1);
+        assert!(filter_workspaces(&listed, "xyz").is_empty());
+    }
+
+    #[test]
+    fn workspace_names_are_plain_directory_names() {
+        assert!(is_valid_name("my-project"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name(".hidden"));
+        assert!(!is_valid_name("a/b"));
+        assert!(open("../escape").is_err());
+    }
+}
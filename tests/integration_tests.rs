@@ -176,7 +176,7 @@ fn test_delete_file_history_cleans_empty_dirs() {
     assert!(ue_leaf_dir.exists(), "history dir should exist before deletion");
 
     // Delete history
-    ue::editing::delete_file_history(&file.to_string_lossy()).unwrap();
+    ue::editing::delete_file_history(&file.to_string_lossy(), false).unwrap();
 
     // The .ue file should be gone
     let ue_file = ue_leaf_dir.join("doc.txt.ue");
@@ -228,7 +228,7 @@ fn test_close_file_from_selector() {
     assert_eq!(recent.len(), 2, "Should have 2 recent files");
     
     // Remove file1 using delete_file_history
-    ue::editing::delete_file_history(&file1.to_string_lossy()).unwrap();
+    ue::editing::delete_file_history(&file1.to_string_lossy(), false).unwrap();
     
     // Should still have file2 in recent (delete_file_history removes undo but not recent entry)
     // So we need to manually update recent list for this test
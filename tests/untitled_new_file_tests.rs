@@ -19,7 +19,8 @@ fn test_untitled_file_is_created_on_disk() {
     let home_dir = temp_home.path();
     
     // Simulate creating an untitled file by saving undo history for "untitled"
-    let undo_history = ue::undo::UndoHistory::new();
+    let mut undo_history = ue::undo::UndoHistory::new();
+    undo_history.untitled = true;
     undo_history.save("untitled").unwrap();
     
     // Verify the undo file was created in the correct location
@@ -35,6 +36,7 @@ fn test_untitled_cleanup_after_save() {
     
     // Create an untitled undo file
     let mut undo_history = ue::undo::UndoHistory::new();
+    undo_history.untitled = true;
     undo_history.push(ue::undo::Edit::InsertChar { line: 0, col: 0, ch: 'a' });
     undo_history.save("untitled").unwrap();
     
@@ -46,12 +48,13 @@ fn test_untitled_cleanup_after_save() {
     fs::write(&real_file, "test content").unwrap();
     
     // Delete the untitled history
-    ue::editing::delete_file_history("untitled").unwrap();
+    ue::editing::delete_file_history("untitled", true).unwrap();
     
     // Verify untitled.ue was deleted
     assert!(!untitled_path.exists(), "Untitled undo file should be deleted after save");
     
     // Create undo file for the real filename
+    undo_history.untitled = false;
     undo_history.save(real_file.to_str().unwrap()).unwrap();
     
     // Verify the real file's undo history exists
@@ -66,13 +69,16 @@ fn test_multiple_untitled_files() {
     let home_dir = temp_home.path();
     
     // Create multiple untitled files
-    let undo1 = ue::undo::UndoHistory::new();
+    let mut undo1 = ue::undo::UndoHistory::new();
+    undo1.untitled = true;
     undo1.save("untitled").unwrap();
     
-    let undo2 = ue::undo::UndoHistory::new();
+    let mut undo2 = ue::undo::UndoHistory::new();
+    undo2.untitled = true;
     undo2.save("untitled-2").unwrap();
     
-    let undo3 = ue::undo::UndoHistory::new();
+    let mut undo3 = ue::undo::UndoHistory::new();
+    undo3.untitled = true;
     undo3.save("untitled-3").unwrap();
     
     // Verify all were created
@@ -81,7 +87,7 @@ fn test_multiple_untitled_files() {
     assert!(home_dir.join("data/files/untitled-3.ue").exists());
     
     // Cleanup one of them
-    ue::editing::delete_file_history("untitled-2").unwrap();
+    ue::editing::delete_file_history("untitled-2", true).unwrap();
     
     // Verify only the specified one was deleted
     assert!(home_dir.join("data/files/untitled.ue").exists(), "untitled should still exist");
@@ -96,7 +102,8 @@ fn test_untitled_not_saved_to_subdirectory() {
     let home_dir = temp_home.path();
     
     // Save an untitled file
-    let undo = ue::undo::UndoHistory::new();
+    let mut undo = ue::undo::UndoHistory::new();
+    undo.untitled = true;
     undo.save("untitled").unwrap();
     
     // Verify it's in the root of data/files/, not in a subdirectory
@@ -115,7 +122,8 @@ fn test_untitled_case_insensitive() {
     let home_dir = temp_home.path();
     
     // Test that UNTITLED (uppercase) is also treated as untitled
-    let undo = ue::undo::UndoHistory::new();
+    let mut undo = ue::undo::UndoHistory::new();
+    undo.untitled = true;
     undo.save("UNTITLED").unwrap();
     
     let expected_path = home_dir.join("data/files/UNTITLED.ue");